@@ -8,6 +8,7 @@ use crate::{
     api::{auth, routes::*, state::AppState},
     file::MAX_UPLOAD_REQUEST_SIZE,
     sse,
+    utils::fonts::MAX_FONT_SIZE,
 };
 
 fn auth_routes() -> Router<AppState> {
@@ -33,6 +34,21 @@ pub fn routes() -> Router<AppState> {
                 .route("/control/{id}/text", post(send_text_message))
                 .route("/control/{id}/playout", post(control_playout))
                 .route("/control/{id}/audio", put(update_audio_effects))
+                .route("/control/{id}/layers", get(list_layers))
+                .route(
+                    "/control/{id}/layers/{layer}",
+                    put(set_layer).patch(update_layer).delete(remove_layer),
+                )
+                .route(
+                    "/control/{id}/pip",
+                    get(get_pip).put(show_pip).delete(hide_pip),
+                )
+                .route("/control/{id}/ingest", get(get_ingest).post(control_ingest))
+                .route("/control/{id}/ingest/preview", get(ingest_preview))
+                .route(
+                    "/control/{id}/ticker",
+                    put(start_ticker).delete(stop_ticker),
+                )
                 .route("/control/{id}/media/current", get(media_current))
                 .route("/control/{id}/process", post(process_control))
                 .route("/file/{id}/browse", post(file_browser))
@@ -59,12 +75,40 @@ pub fn routes() -> Router<AppState> {
                 .route("/playout/outputs/{id}", get(get_playout_outputs))
                 .route("/playout/codecs/{id}", get(get_playout_codecs))
                 .route("/text/fonts", get(get_font_families))
+                .route(
+                    "/text/{id}/fonts",
+                    get(get_channel_fonts)
+                        .put(upload_font)
+                        .layer(DefaultBodyLimit::max(MAX_FONT_SIZE)),
+                )
+                .route("/text/{id}/fonts/{name}", delete(delete_font))
                 .route("/presets/{id}", get(get_presets))
                 .route(
                     "/presets/{channel}/{id}",
                     put(update_preset).delete(delete_preset),
                 )
                 .route("/presets/{id}", post(add_preset))
+                .route(
+                    "/branding/{id}",
+                    get(get_branding_rules).post(add_branding_rule),
+                )
+                .route(
+                    "/branding/{channel}/{id}",
+                    put(update_branding_rule).delete(delete_branding_rule),
+                )
+                .route(
+                    "/stream-keys/{id}",
+                    get(get_stream_keys).post(add_stream_key),
+                )
+                .route("/stream-keys/{channel}/{id}", delete(delete_stream_key))
+                .route(
+                    "/recording-schedules/{id}",
+                    get(get_recording_schedules).post(add_recording_schedule),
+                )
+                .route(
+                    "/recording-schedules/{channel}/{id}",
+                    delete(delete_recording_schedule),
+                )
                 .route("/program/{id}", get(get_program))
                 .route("/setup", get(get_setup_status).post(complete_setup))
                 .route("/system/{id}", get(get_system_stat))
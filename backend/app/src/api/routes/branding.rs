@@ -0,0 +1,123 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use protect_axum::authorities::AuthDetails;
+
+use crate::{
+    api::{
+        routes::{AuthUser, ensure_any_authority, presets::channel_storage},
+        state::AppState,
+    },
+    db::{
+        handles,
+        models::{BrandingRule, Role},
+    },
+    utils::errors::ServiceError,
+};
+
+/// #### Branding Rules
+///
+/// Branding rules hide or replace the channel logo and add icons for the
+/// clips of a playlist category, or for nodes that list the rule name in
+/// `branding`. Changed rules apply from the next clip on.
+///
+/// **Get all Rules**
+///
+/// ```BASH
+/// curl -X GET http://127.0.0.1:8787/api/branding/1 -H 'Authorization: Bearer <TOKEN>'
+/// ```
+pub async fn get_branding_rules(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+) -> Result<Json<Vec<BrandingRule>>, ServiceError> {
+    ensure_any_authority(
+        &details,
+        &[&Role::GlobalAdmin, &Role::ChannelAdmin, &Role::User],
+    )?;
+    user.ensure_channel_or_admin(id)?;
+
+    Ok(Json(handles::select_branding_rules(&state.pool, id).await?))
+}
+
+/// **Add new Rule**
+///
+/// ```BASH
+/// curl -X POST http://127.0.0.1:8787/api/branding/1 -H 'Content-Type: application/json' \
+/// -d '{ "name": "fsk12", "icon": "00-assets/fsk12.png", "icon_position": "12:12", "icon_seconds": 10 }' \
+/// -H 'Authorization: Bearer <TOKEN>'
+/// ```
+pub async fn add_branding_rule(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+    Json(mut data): Json<BrandingRule>,
+) -> Result<&'static str, ServiceError> {
+    ensure_any_authority(&details, &[&Role::GlobalAdmin, &Role::ChannelAdmin])?;
+    user.ensure_channel_or_admin(id)?;
+    data.channel_id = id;
+    data.validate().map_err(ServiceError::BadRequest)?;
+    data.normalize_paths(&channel_storage(&state, id).await?)?;
+
+    handles::insert_branding_rule(&state.pool, data).await?;
+    reload_rules(&state, id).await;
+
+    Ok("Add branding rule Success")
+}
+
+/// **Update Rule**
+///
+/// ```BASH
+/// curl -X PUT http://127.0.0.1:8787/api/branding/1/2 -H 'Content-Type: application/json' \
+/// -d '{ "name": "no-bug", "category": "advertisement", "hide_logo": true }' \
+/// -H 'Authorization: Bearer <TOKEN>'
+/// ```
+pub async fn update_branding_rule(
+    State(state): State<AppState>,
+    Path((channel, id)): Path<(i32, i32)>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+    Json(mut data): Json<BrandingRule>,
+) -> Result<&'static str, ServiceError> {
+    ensure_any_authority(&details, &[&Role::GlobalAdmin, &Role::ChannelAdmin])?;
+    user.ensure_channel_or_admin(channel)?;
+    data.channel_id = channel;
+    data.validate().map_err(ServiceError::BadRequest)?;
+    data.normalize_paths(&channel_storage(&state, channel).await?)?;
+
+    handles::update_branding_rule(&state.pool, channel, id, data).await?;
+    reload_rules(&state, channel).await;
+
+    Ok("Update Success")
+}
+
+/// **Delete Rule**
+///
+/// ```BASH
+/// curl -X DELETE http://127.0.0.1:8787/api/branding/1/2 -H 'Authorization: Bearer <TOKEN>'
+/// ```
+pub async fn delete_branding_rule(
+    State(state): State<AppState>,
+    Path((channel, id)): Path<(i32, i32)>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+) -> Result<&'static str, ServiceError> {
+    ensure_any_authority(&details, &[&Role::GlobalAdmin, &Role::ChannelAdmin])?;
+    user.ensure_channel_or_admin(channel)?;
+
+    handles::delete_branding_rule(&state.pool, channel, id).await?;
+    reload_rules(&state, channel).await;
+
+    Ok("Delete branding rule Success")
+}
+
+/// Let the playout of the channel read its rules again.
+async fn reload_rules(state: &AppState, channel: i32) {
+    let manager = state.controller.read().await.get(channel);
+    if let Some(manager) = manager {
+        manager.reload_branding_rules().await;
+    }
+}
@@ -3,9 +3,13 @@ use std::sync::atomic::Ordering;
 use axum::{
     Json,
     extract::{Path, State},
+    http::header::{CACHE_CONTROL, CONTENT_TYPE},
+    response::{IntoResponse, Response},
 };
+use ff_engine::LiveTakeState;
 use protect_axum::authorities::AuthDetails;
 use serde::Deserialize;
+use serde_json::json;
 
 use crate::{
     api::{
@@ -20,6 +24,9 @@ use crate::{
     utils::{
         control::{ControlParams, Process, ProcessCtl, control_state, send_message},
         errors::ServiceError,
+        layers::{LayerChanges, LayerInfo, LayerParams},
+        pip::PipParams,
+        ticker::TickerParams,
     },
 };
 
@@ -94,12 +101,458 @@ pub async fn send_text_message(
     }
     .ok_or_else(|| ServiceError::BadRequest(format!("Channel {id} not found!")))?;
 
+    if manager.ticker_running().await {
+        return Err(ServiceError::Conflict(
+            "A news ticker is on the text overlay, stop it first".to_string(),
+        ));
+    }
+
     match send_message(manager, data).await {
         Ok(res) => Ok(Json(res)),
         Err(e) => Err(e),
     }
 }
 
+/// **Graphic Layers**
+///
+/// Stack of images and texts drawn over the program, ordered by `z_index`.
+/// Layers can be added, changed and removed while the channel is running.
+///
+/// ```BASH
+/// curl -X GET http://127.0.0.1:8787/api/control/1/layers -H 'Authorization: Bearer <TOKEN>'
+///
+/// curl -X PUT http://127.0.0.1:8787/api/control/1/layers/live \
+/// -H 'Content-Type: application/json' -H 'Authorization: Bearer <TOKEN>' \
+/// -d '{"z_index": 10, "fade_in_seconds": 0.5, "fade_out_seconds": 0.5, "content": {"type": "image", "path": "graphics/live.png", "position": "12:12"}}'
+///
+/// curl -X PATCH http://127.0.0.1:8787/api/control/1/layers/live \
+/// -H 'Content-Type: application/json' -H 'Authorization: Bearer <TOKEN>' \
+/// -d '{"visible": false}'
+///
+/// curl -X DELETE http://127.0.0.1:8787/api/control/1/layers/live -H 'Authorization: Bearer <TOKEN>'
+/// ```
+pub async fn list_layers(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+) -> Result<Json<Vec<LayerInfo>>, ServiceError> {
+    ensure_any_authority(
+        &details,
+        &[&Role::GlobalAdmin, &Role::ChannelAdmin, &Role::User],
+    )?;
+    user.ensure_channel_or_admin(id)?;
+
+    let manager = {
+        let guard = state.controller.read().await;
+        guard.get(id)
+    }
+    .ok_or_else(|| ServiceError::BadRequest(format!("Channel {id} not found!")))?;
+
+    Ok(Json(
+        manager
+            .graphics_layers
+            .layers()
+            .into_iter()
+            .map(LayerInfo::from)
+            .collect(),
+    ))
+}
+
+pub async fn set_layer(
+    State(state): State<AppState>,
+    Path((id, layer)): Path<(i32, String)>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+    Json(params): Json<LayerParams>,
+) -> Result<Json<&'static str>, ServiceError> {
+    ensure_any_authority(
+        &details,
+        &[&Role::GlobalAdmin, &Role::ChannelAdmin, &Role::User],
+    )?;
+    user.ensure_channel_or_admin(id)?;
+
+    let manager = {
+        let guard = state.controller.read().await;
+        guard.get(id)
+    }
+    .ok_or_else(|| ServiceError::BadRequest(format!("Channel {id} not found!")))?;
+
+    let storage = manager.config.read().await.channel.storage.clone();
+    manager
+        .graphics_layers
+        .set(params.engine_layer(layer, &storage)?)
+        .map_err(|error| ServiceError::BadRequest(error.to_string()))?;
+
+    Ok(Json("Success"))
+}
+
+pub async fn update_layer(
+    State(state): State<AppState>,
+    Path((id, layer)): Path<(i32, String)>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+    Json(changes): Json<LayerChanges>,
+) -> Result<Json<&'static str>, ServiceError> {
+    ensure_any_authority(
+        &details,
+        &[&Role::GlobalAdmin, &Role::ChannelAdmin, &Role::User],
+    )?;
+    user.ensure_channel_or_admin(id)?;
+
+    let manager = {
+        let guard = state.controller.read().await;
+        guard.get(id)
+    }
+    .ok_or_else(|| ServiceError::BadRequest(format!("Channel {id} not found!")))?;
+
+    let found = manager
+        .graphics_layers
+        .update(&layer, changes.into())
+        .map_err(|error| ServiceError::BadRequest(error.to_string()))?;
+    if !found {
+        return Err(ServiceError::NotFound(format!("Layer {layer} not found")));
+    }
+
+    Ok(Json("Success"))
+}
+
+pub async fn remove_layer(
+    State(state): State<AppState>,
+    Path((id, layer)): Path<(i32, String)>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+) -> Result<Json<&'static str>, ServiceError> {
+    ensure_any_authority(
+        &details,
+        &[&Role::GlobalAdmin, &Role::ChannelAdmin, &Role::User],
+    )?;
+    user.ensure_channel_or_admin(id)?;
+
+    let manager = {
+        let guard = state.controller.read().await;
+        guard.get(id)
+    }
+    .ok_or_else(|| ServiceError::BadRequest(format!("Channel {id} not found!")))?;
+
+    if !manager.graphics_layers.remove(&layer) {
+        return Err(ServiceError::NotFound(format!("Layer {layer} not found")));
+    }
+
+    Ok(Json("Success"))
+}
+
+/// **Picture-in-Picture**
+///
+/// Show a second source in a window over the program, or squeeze the
+/// program into a window and show the second source around it. The source
+/// is a file from the channel storage, a stream URL, a `generator://` source
+/// or `ingest` for the live ingest of the channel. Sending new settings
+/// while it is shown moves it there.
+///
+/// ```BASH
+/// curl -X GET http://127.0.0.1:8787/api/control/1/pip -H 'Authorization: Bearer <TOKEN>'
+///
+/// curl -X PUT http://127.0.0.1:8787/api/control/1/pip \
+/// -H 'Content-Type: application/json' -H 'Authorization: Bearer <TOKEN>' \
+/// -d '{"source": "ingest", "layout": "window", "rect": {"x": 0.62, "y": 0.06, "width": 0.33, "height": 0.33}, "transition_seconds": 0.5}'
+///
+/// curl -X DELETE http://127.0.0.1:8787/api/control/1/pip -H 'Authorization: Bearer <TOKEN>'
+/// ```
+pub async fn get_pip(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+) -> Result<Json<Option<PipParams>>, ServiceError> {
+    ensure_any_authority(
+        &details,
+        &[&Role::GlobalAdmin, &Role::ChannelAdmin, &Role::User],
+    )?;
+    user.ensure_channel_or_admin(id)?;
+
+    let manager = {
+        let guard = state.controller.read().await;
+        guard.get(id)
+    }
+    .ok_or_else(|| ServiceError::BadRequest(format!("Channel {id} not found!")))?;
+
+    Ok(Json(
+        manager.picture_in_picture.config().map(PipParams::from),
+    ))
+}
+
+pub async fn show_pip(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+    Json(params): Json<PipParams>,
+) -> Result<Json<&'static str>, ServiceError> {
+    ensure_any_authority(
+        &details,
+        &[&Role::GlobalAdmin, &Role::ChannelAdmin, &Role::User],
+    )?;
+    user.ensure_channel_or_admin(id)?;
+
+    let manager = {
+        let guard = state.controller.read().await;
+        guard.get(id)
+    }
+    .ok_or_else(|| ServiceError::BadRequest(format!("Channel {id} not found!")))?;
+
+    let (storage, ingest_enabled) = {
+        let config = manager.config.read().await;
+        (config.channel.storage.clone(), config.ingest.enable)
+    };
+    manager
+        .picture_in_picture
+        .show(params.engine_config(&storage, ingest_enabled)?)
+        .map_err(|error| ServiceError::BadRequest(error.to_string()))?;
+
+    Ok(Json("Success"))
+}
+
+pub async fn hide_pip(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+) -> Result<Json<&'static str>, ServiceError> {
+    ensure_any_authority(
+        &details,
+        &[&Role::GlobalAdmin, &Role::ChannelAdmin, &Role::User],
+    )?;
+    user.ensure_channel_or_admin(id)?;
+
+    let manager = {
+        let guard = state.controller.read().await;
+        guard.get(id)
+    }
+    .ok_or_else(|| ServiceError::BadRequest(format!("Channel {id} not found!")))?;
+
+    if !manager.picture_in_picture.hide() {
+        return Err(ServiceError::NotFound(
+            "No picture-in-picture is shown".to_string(),
+        ));
+    }
+
+    Ok(Json("Success"))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IngestCommand {
+    Take,
+    Release,
+    Dump,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IngestParams {
+    command: IngestCommand,
+}
+
+/// **Manual Take**
+///
+/// With `manual` take in the ingest config, an incoming feed is armed: it is
+/// received and can be previewed, but only goes on air with `take`. `release`
+/// returns to the playlist at the current time. The state is `automatic`,
+/// `idle`, `armed` or `on_air`; with backup ingests, `endpoint` is the
+/// position of the one on air, `0` for the primary.
+///
+/// With a broadcast `delay`, `dump` skips what is still in the delay; the
+/// delay then builds up again. `delay` reports the configured and the
+/// current delay in seconds.
+///
+/// ```BASH
+/// curl -X GET http://127.0.0.1:8787/api/control/1/ingest -H 'Authorization: Bearer <TOKEN>'
+///
+/// curl -X POST http://127.0.0.1:8787/api/control/1/ingest \
+/// -H 'Content-Type: application/json' -H 'Authorization: Bearer <TOKEN>' \
+/// -d '{"command": "take"}'
+///
+/// curl -X POST http://127.0.0.1:8787/api/control/1/ingest \
+/// -H 'Content-Type: application/json' -H 'Authorization: Bearer <TOKEN>' \
+/// -d '{"command": "dump"}'
+///
+/// curl -X GET http://127.0.0.1:8787/api/control/1/ingest/preview \
+/// -H 'Authorization: Bearer <TOKEN>' --output preview.jpg
+/// ```
+pub async fn get_ingest(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    ensure_any_authority(
+        &details,
+        &[&Role::GlobalAdmin, &Role::ChannelAdmin, &Role::User],
+    )?;
+    user.ensure_channel_or_admin(id)?;
+
+    let manager = {
+        let guard = state.controller.read().await;
+        guard.get(id)
+    }
+    .ok_or_else(|| ServiceError::BadRequest(format!("Channel {id} not found!")))?;
+
+    let take_state = match manager.live_take.state() {
+        LiveTakeState::Automatic => "automatic",
+        LiveTakeState::Idle => "idle",
+        LiveTakeState::Armed => "armed",
+        LiveTakeState::OnAir => "on_air",
+    };
+
+    let delay = manager.live_delay.status();
+
+    Ok(Json(json!({
+        "state": take_state,
+        "endpoint": manager.ingest_failover.on_air(),
+        "delay": {
+            "target": delay.target.as_secs_f64(),
+            "current": delay.current.as_secs_f64(),
+            "rebuilding": delay.rebuilding,
+        },
+    })))
+}
+
+pub async fn control_ingest(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+    Json(params): Json<IngestParams>,
+) -> Result<Json<&'static str>, ServiceError> {
+    ensure_any_authority(
+        &details,
+        &[&Role::GlobalAdmin, &Role::ChannelAdmin, &Role::User],
+    )?;
+    user.ensure_channel_or_admin(id)?;
+
+    let manager = {
+        let guard = state.controller.read().await;
+        guard.get(id)
+    }
+    .ok_or_else(|| ServiceError::BadRequest(format!("Channel {id} not found!")))?;
+
+    match params.command {
+        IngestCommand::Take => manager
+            .live_take
+            .take()
+            .map_err(|error| ServiceError::Conflict(error.to_string()))?,
+        IngestCommand::Release => {
+            if !manager.live_take.release() {
+                return Err(ServiceError::Conflict(
+                    "The live ingest is not on air".to_string(),
+                ));
+            }
+        }
+        IngestCommand::Dump => manager
+            .live_delay
+            .dump()
+            .map_err(|error| ServiceError::Conflict(error.to_string()))?,
+    }
+
+    Ok(Json("Success"))
+}
+
+pub async fn ingest_preview(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+) -> Result<Response, ServiceError> {
+    ensure_any_authority(
+        &details,
+        &[&Role::GlobalAdmin, &Role::ChannelAdmin, &Role::User],
+    )?;
+    user.ensure_channel_or_admin(id)?;
+
+    let manager = {
+        let guard = state.controller.read().await;
+        guard.get(id)
+    }
+    .ok_or_else(|| ServiceError::BadRequest(format!("Channel {id} not found!")))?;
+
+    let jpeg = manager
+        .live_take
+        .preview_jpeg()
+        .map_err(|error| ServiceError::ServiceUnavailable(error.to_string()))?
+        .ok_or_else(|| ServiceError::NotFound("No live feed is connected".to_string()))?;
+
+    Ok((
+        [(CONTENT_TYPE, "image/jpeg"), (CACHE_CONTROL, "no-store")],
+        jpeg,
+    )
+        .into_response())
+}
+
+/// **News Ticker**
+///
+/// Crawl the items of an RSS/Atom feed, a JSON or a text file on the text
+/// overlay. The source is read again every `interval_seconds`. Only admins
+/// may start a ticker from a URL, since the server fetches it.
+///
+/// ```BASH
+/// curl -X PUT http://127.0.0.1:8787/api/control/1/ticker \
+/// -H 'Content-Type: application/json' -H 'Authorization: Bearer <TOKEN>' \
+/// -d '{"source": "https://example.org/news.rss", "interval_seconds": 120, "style": {"font_size": 28, "scroll_direction": "right_to_left", "scroll_speed": 120}}'
+///
+/// curl -X DELETE http://127.0.0.1:8787/api/control/1/ticker -H 'Authorization: Bearer <TOKEN>'
+/// ```
+pub async fn start_ticker(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+    Json(params): Json<TickerParams>,
+) -> Result<Json<&'static str>, ServiceError> {
+    ensure_any_authority(
+        &details,
+        &[&Role::GlobalAdmin, &Role::ChannelAdmin, &Role::User],
+    )?;
+    user.ensure_channel_or_admin(id)?;
+
+    let manager = {
+        let guard = state.controller.read().await;
+        guard.get(id)
+    }
+    .ok_or_else(|| ServiceError::BadRequest(format!("Channel {id} not found!")))?;
+
+    let storage = manager.config.read().await.channel.storage.clone();
+    let ticker = params.ticker(&storage)?;
+    if ticker.is_url() {
+        ensure_any_authority(&details, &[&Role::GlobalAdmin, &Role::ChannelAdmin])?;
+    }
+    manager.spawn_ticker(ticker).await;
+
+    Ok(Json("Success"))
+}
+
+pub async fn stop_ticker(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+) -> Result<Json<&'static str>, ServiceError> {
+    ensure_any_authority(
+        &details,
+        &[&Role::GlobalAdmin, &Role::ChannelAdmin, &Role::User],
+    )?;
+    user.ensure_channel_or_admin(id)?;
+
+    let manager = {
+        let guard = state.controller.read().await;
+        guard.get(id)
+    }
+    .ok_or_else(|| ServiceError::BadRequest(format!("Channel {id} not found!")))?;
+
+    manager.stop_ticker().await;
+
+    Ok(Json("Success"))
+}
+
 /// **Control Playout**
 ///
 /// - next
@@ -31,6 +31,7 @@ use crate::db::models::Role;
 use crate::utils::mail::MailQueue;
 use crate::utils::{config::Template, errors::ServiceError, optional_naive_date_time_from_str};
 
+mod branding;
 mod channel;
 mod control;
 mod file;
@@ -41,10 +42,13 @@ mod playout_config;
 mod presets;
 mod program;
 mod public;
+mod recording;
 mod setup;
+mod stream_key;
 mod system;
 mod user;
 
+pub use branding::*;
 pub use channel::*;
 pub use control::*;
 pub use file::*;
@@ -55,7 +59,9 @@ pub use playout_config::*;
 pub use presets::*;
 pub use program::*;
 pub use public::*;
+pub use recording::*;
 pub use setup::*;
+pub use stream_key::*;
 pub use system::*;
 pub use user::*;
 
@@ -18,7 +18,7 @@ use crate::{
     },
     file::norm_abs_path,
     utils::{
-        config::{OutputMode, PlayoutConfig, get_config, parse_rtmp_ingest_port},
+        config::{OutputMode, PlayoutConfig, get_config, parse_ingest_port},
         errors::ServiceError,
     },
 };
@@ -69,6 +69,7 @@ pub struct PlayoutCodecOptions {
     pub rtmp: OutputCodecOptions,
     pub srt: OutputCodecOptions,
     pub udp: OutputCodecOptions,
+    pub icecast: OutputCodecOptions,
     pub custom: OutputCodecOptions,
 }
 
@@ -258,13 +259,15 @@ pub async fn update_playout_config(
     data.processing
         .hls_subtitle()
         .map_err(ServiceError::BadRequest)?;
-    if data.ingest.enable {
-        let ingest_port =
-            parse_rtmp_ingest_port(&data.ingest.ingest_url).map_err(ServiceError::BadRequest)?;
-        if handles::ingest_port_in_use(&state.pool, id, ingest_port).await? {
-            return Err(ServiceError::BadRequest(format!(
-                "ingest port {ingest_port} is already assigned to another channel"
-            )));
+    data.ingest.validate().map_err(ServiceError::BadRequest)?;
+    if data.ingest.enable && !data.ingest.pull {
+        for url in data.ingest.urls() {
+            let ingest_port = parse_ingest_port(&url).map_err(ServiceError::BadRequest)?;
+            if handles::ingest_port_in_use(&state.pool, id, ingest_port).await? {
+                return Err(ServiceError::BadRequest(format!(
+                    "ingest port {ingest_port} is already assigned to another channel"
+                )));
+            }
         }
     }
     ff_engine::AudioEffectsControl::new(data.processing.volume)
@@ -285,6 +288,7 @@ pub async fn update_playout_config(
             crate::utils::config::StreamType::Rtmp => "rtmp",
             crate::utils::config::StreamType::Srt => "srt",
             crate::utils::config::StreamType::Udp => "udp",
+            crate::utils::config::StreamType::Icecast => "icecast",
             crate::utils::config::StreamType::Custom => "custom",
         }),
         (data.output.mode == OutputMode::Stream
@@ -294,6 +298,7 @@ pub async fn update_playout_config(
         is_hls.then_some(i64::from(data.output.hls_segment_duration)),
         is_hls.then_some(i64::from(data.output.hls_list_size)),
         data.output.desktop_fullscreen,
+        is_encoded && data.output.clip_metadata,
         i64::from(data.output.width),
         i64::from(data.output.height),
         data.output.fps,
@@ -377,6 +382,7 @@ pub async fn get_playout_codecs(
         rtmp: output_codec_options(ff_engine::FfmpegOutputTarget::Rtmp),
         srt: output_codec_options(ff_engine::FfmpegOutputTarget::Srt),
         udp: output_codec_options(ff_engine::FfmpegOutputTarget::Udp),
+        icecast: output_codec_options(ff_engine::FfmpegOutputTarget::Icecast),
         custom: custom_output_codec_options(),
     }))
 }
@@ -1,6 +1,8 @@
+use std::path::PathBuf;
+
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Multipart, Path, State},
 };
 use protect_axum::authorities::AuthDetails;
 
@@ -13,7 +15,10 @@ use crate::{
         handles,
         models::{Role, TextPreset},
     },
-    utils::errors::ServiceError,
+    utils::{
+        errors::ServiceError,
+        fonts::{FontFile, channel_fonts, remove_font, save_font},
+    },
 };
 
 pub async fn get_font_families(
@@ -30,6 +35,101 @@ pub async fn get_font_families(
     Ok(Json(families))
 }
 
+/// #### Fonts
+///
+/// Fonts of a channel are stored in `00-assets/fonts` of its storage and can
+/// be used by the texts of all channels.
+///
+/// **Get Channel Fonts**
+///
+/// ```BASH
+/// curl -X GET http://127.0.0.1:8787/api/text/1/fonts -H 'Authorization: Bearer <TOKEN>'
+/// ```
+pub async fn get_channel_fonts(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+) -> Result<Json<Vec<FontFile>>, ServiceError> {
+    ensure_any_authority(
+        &details,
+        &[&Role::GlobalAdmin, &Role::ChannelAdmin, &Role::User],
+    )?;
+    user.ensure_channel_or_admin(id)?;
+    let storage = channel_storage(&state, id).await?;
+
+    Ok(Json(channel_fonts(&storage).await))
+}
+
+/// **Upload Font**
+///
+/// A TTF, OTF or TTC file in the multipart field `file`. A font with the same
+/// file name is replaced.
+///
+/// ```BASH
+/// curl -X PUT http://127.0.0.1:8787/api/text/1/fonts -H 'Authorization: Bearer <TOKEN>' \
+/// -F "file=@BrandSans-Bold.ttf"
+/// ```
+pub async fn upload_font(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+    mut payload: Multipart,
+) -> Result<Json<FontFile>, ServiceError> {
+    ensure_any_authority(
+        &details,
+        &[&Role::GlobalAdmin, &Role::ChannelAdmin, &Role::User],
+    )?;
+    user.ensure_channel_or_admin(id)?;
+    let storage = channel_storage(&state, id).await?;
+
+    while let Some(field) = payload.next_field().await? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let name = field.file_name().unwrap_or_default().to_string();
+        let data = field.bytes().await?;
+
+        return Ok(Json(save_font(&storage, &name, &data).await?));
+    }
+
+    Err(ServiceError::BadRequest("Missing font file".to_string()))
+}
+
+/// **Delete Font**
+///
+/// ```BASH
+/// curl -X DELETE http://127.0.0.1:8787/api/text/1/fonts/BrandSans-Bold.ttf \
+/// -H 'Authorization: Bearer <TOKEN>'
+/// ```
+pub async fn delete_font(
+    State(state): State<AppState>,
+    Path((id, name)): Path<(i32, String)>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+) -> Result<&'static str, ServiceError> {
+    ensure_any_authority(
+        &details,
+        &[&Role::GlobalAdmin, &Role::ChannelAdmin, &Role::User],
+    )?;
+    user.ensure_channel_or_admin(id)?;
+    let storage = channel_storage(&state, id).await?;
+    remove_font(&storage, &name).await?;
+
+    Ok("Delete font Success")
+}
+
+pub(super) async fn channel_storage(state: &AppState, id: i32) -> Result<PathBuf, ServiceError> {
+    let manager = {
+        let guard = state.controller.read().await;
+        guard.get(id)
+    }
+    .ok_or_else(|| ServiceError::BadRequest(format!("Channel {id} not found!")))?;
+
+    Ok(manager.config.read().await.channel.storage.clone())
+}
+
 /// #### Text Presets
 ///
 /// Text presets are made for sending text messages to the ffplayout engine, to overlay them as a lower third.
@@ -0,0 +1,97 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use chrono::Utc;
+use protect_axum::authorities::AuthDetails;
+
+use crate::{
+    api::{
+        routes::{AuthUser, ensure_any_authority},
+        state::AppState,
+    },
+    db::{
+        handles,
+        models::{RecordingSchedule, Role},
+    },
+    utils::errors::ServiceError,
+};
+
+/// #### Recording Schedules
+///
+/// A recording schedule records an external stream every day in its window,
+/// into a folder of the channel storage. Finished recordings are probed and
+/// can go into the next day's playlist on their own.
+///
+/// **Get all Schedules**
+///
+/// ```BASH
+/// curl -X GET http://127.0.0.1:8787/api/recording-schedules/1 -H 'Authorization: Bearer <TOKEN>'
+/// ```
+pub async fn get_recording_schedules(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+) -> Result<Json<Vec<RecordingSchedule>>, ServiceError> {
+    ensure_any_authority(&details, &[&Role::GlobalAdmin, &Role::ChannelAdmin])?;
+    user.ensure_channel_or_admin(id)?;
+
+    Ok(Json(
+        handles::select_recording_schedules(&state.pool, id).await?,
+    ))
+}
+
+/// **Add new Schedule**
+///
+/// Times are in the channel's time zone. Without `folder`, recordings go to
+/// `recordings`; without `insert_at`, they are only recorded.
+///
+/// ```BASH
+/// curl -X POST http://127.0.0.1:8787/api/recording-schedules/1 -H 'Content-Type: application/json' \
+/// -d '{ "label": "Partner News", "url": "https://partner.example/live/index.m3u8",
+/// "start_time": "20:00", "end_time": "21:00", "folder": "partner", "insert_at": "18:00" }' \
+/// -H 'Authorization: Bearer <TOKEN>'
+/// ```
+pub async fn add_recording_schedule(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+    Json(mut data): Json<RecordingSchedule>,
+) -> Result<Json<RecordingSchedule>, ServiceError> {
+    ensure_any_authority(&details, &[&Role::GlobalAdmin, &Role::ChannelAdmin])?;
+    user.ensure_channel_or_admin(id)?;
+    data.channel_id = id;
+    data.created_at = Utc::now().timestamp();
+    if data.folder.trim().is_empty() {
+        data.folder = "recordings".to_string();
+    }
+    data.validate().map_err(ServiceError::BadRequest)?;
+
+    let result = handles::insert_recording_schedule(&state.pool, data.clone()).await?;
+    data.id = result.last_insert_rowid() as i32;
+
+    Ok(Json(data))
+}
+
+/// **Delete Schedule**
+///
+/// A recording that runs stops and is saved as it is.
+///
+/// ```BASH
+/// curl -X DELETE http://127.0.0.1:8787/api/recording-schedules/1/2 -H 'Authorization: Bearer <TOKEN>'
+/// ```
+pub async fn delete_recording_schedule(
+    State(state): State<AppState>,
+    Path((channel, id)): Path<(i32, i32)>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+) -> Result<&'static str, ServiceError> {
+    ensure_any_authority(&details, &[&Role::GlobalAdmin, &Role::ChannelAdmin])?;
+    user.ensure_channel_or_admin(channel)?;
+
+    handles::delete_recording_schedule(&state.pool, channel, id).await?;
+
+    Ok("Delete recording schedule Success")
+}
@@ -0,0 +1,97 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use chrono::Utc;
+use protect_axum::authorities::AuthDetails;
+
+use crate::{
+    api::{
+        routes::{AuthUser, ensure_any_authority},
+        state::AppState,
+    },
+    db::{
+        handles,
+        models::{Role, StreamKey},
+    },
+    utils::errors::ServiceError,
+};
+
+/// #### Stream Keys
+///
+/// With stream keys, publishers send one of the channel's keys as RTMP stream
+/// key or SRT stream id, and the key in the ingest URL is only a
+/// placeholder. Keys are checked when a publisher connects; the label of the
+/// key is logged when it goes on air.
+///
+/// **Get all Keys**
+///
+/// ```BASH
+/// curl -X GET http://127.0.0.1:8787/api/stream-keys/1 -H 'Authorization: Bearer <TOKEN>'
+/// ```
+pub async fn get_stream_keys(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+) -> Result<Json<Vec<StreamKey>>, ServiceError> {
+    ensure_any_authority(&details, &[&Role::GlobalAdmin, &Role::ChannelAdmin])?;
+    user.ensure_channel_or_admin(id)?;
+
+    Ok(Json(handles::select_stream_keys(&state.pool, id).await?))
+}
+
+/// **Add new Key**
+///
+/// Without `key`, a random one is generated. `expires_at` is a unix
+/// timestamp and can be left out.
+///
+/// ```BASH
+/// curl -X POST http://127.0.0.1:8787/api/stream-keys/1 -H 'Content-Type: application/json' \
+/// -d '{ "label": "Reporter 1", "expires_at": 1767225600 }' \
+/// -H 'Authorization: Bearer <TOKEN>'
+/// ```
+pub async fn add_stream_key(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+    Json(mut data): Json<StreamKey>,
+) -> Result<Json<StreamKey>, ServiceError> {
+    ensure_any_authority(&details, &[&Role::GlobalAdmin, &Role::ChannelAdmin])?;
+    user.ensure_channel_or_admin(id)?;
+    let now = Utc::now().timestamp();
+    data.channel_id = id;
+    data.created_at = now;
+    if data.key.is_empty() {
+        data.key = uuid::Uuid::new_v4().simple().to_string();
+    }
+    data.validate(now).map_err(ServiceError::BadRequest)?;
+
+    let result = handles::insert_stream_key(&state.pool, data.clone()).await?;
+    data.id = result.last_insert_rowid() as i32;
+
+    Ok(Json(data))
+}
+
+/// **Delete Key**
+///
+/// A publisher that is on air stays on; the key only stops working for the
+/// next connection.
+///
+/// ```BASH
+/// curl -X DELETE http://127.0.0.1:8787/api/stream-keys/1/2 -H 'Authorization: Bearer <TOKEN>'
+/// ```
+pub async fn delete_stream_key(
+    State(state): State<AppState>,
+    Path((channel, id)): Path<(i32, i32)>,
+    user: AuthUser,
+    details: AuthDetails<Role>,
+) -> Result<&'static str, ServiceError> {
+    ensure_any_authority(&details, &[&Role::GlobalAdmin, &Role::ChannelAdmin])?;
+    user.ensure_channel_or_admin(channel)?;
+
+    handles::delete_stream_key(&state.pool, channel, id).await?;
+
+    Ok("Delete stream key Success")
+}
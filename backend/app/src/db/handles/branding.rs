@@ -0,0 +1,94 @@
+use sqlx::sqlite::{SqlitePool, SqliteQueryResult};
+
+use crate::{db::models::BrandingRule, utils::errors::ProcessError};
+
+pub async fn select_branding_rules(
+    pool: &SqlitePool,
+    channel_id: i32,
+) -> Result<Vec<BrandingRule>, ProcessError> {
+    const QUERY: &str = "SELECT * FROM branding_rules WHERE channel_id = $1 ORDER BY id";
+
+    let result = sqlx::query_as(QUERY)
+        .bind(channel_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(result)
+}
+
+pub async fn insert_branding_rule(
+    pool: &SqlitePool,
+    rule: BrandingRule,
+) -> Result<SqliteQueryResult, ProcessError> {
+    const QUERY: &str = "INSERT INTO branding_rules (
+        channel_id, name, category, hide_logo, logo, logo_scale, logo_opacity, logo_position,
+        icon, icon_scale, icon_opacity, icon_position, icon_seconds
+    ) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)";
+
+    let result = sqlx::query(QUERY)
+        .bind(rule.channel_id)
+        .bind(rule.name)
+        .bind(rule.category)
+        .bind(rule.hide_logo)
+        .bind(rule.logo)
+        .bind(rule.logo_scale)
+        .bind(rule.logo_opacity)
+        .bind(rule.logo_position)
+        .bind(rule.icon)
+        .bind(rule.icon_scale)
+        .bind(rule.icon_opacity)
+        .bind(rule.icon_position)
+        .bind(rule.icon_seconds)
+        .execute(pool)
+        .await?;
+
+    Ok(result)
+}
+
+pub async fn update_branding_rule(
+    pool: &SqlitePool,
+    channel_id: i32,
+    id: i32,
+    rule: BrandingRule,
+) -> Result<SqliteQueryResult, ProcessError> {
+    const QUERY: &str = "UPDATE branding_rules SET name = $1, category = $2, hide_logo = $3,
+        logo = $4, logo_scale = $5, logo_opacity = $6, logo_position = $7, icon = $8,
+        icon_scale = $9, icon_opacity = $10, icon_position = $11, icon_seconds = $12
+        WHERE id = $13 AND channel_id = $14";
+
+    let result = sqlx::query(QUERY)
+        .bind(rule.name)
+        .bind(rule.category)
+        .bind(rule.hide_logo)
+        .bind(rule.logo)
+        .bind(rule.logo_scale)
+        .bind(rule.logo_opacity)
+        .bind(rule.logo_position)
+        .bind(rule.icon)
+        .bind(rule.icon_scale)
+        .bind(rule.icon_opacity)
+        .bind(rule.icon_position)
+        .bind(rule.icon_seconds)
+        .bind(id)
+        .bind(channel_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result)
+}
+
+pub async fn delete_branding_rule(
+    pool: &SqlitePool,
+    channel_id: i32,
+    id: i32,
+) -> Result<SqliteQueryResult, ProcessError> {
+    const QUERY: &str = "DELETE FROM branding_rules WHERE id = $1 AND channel_id = $2;";
+
+    let result = sqlx::query(QUERY)
+        .bind(id)
+        .bind(channel_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result)
+}
@@ -6,7 +6,7 @@ use sqlx::{
 use crate::{
     db::models::Configuration,
     utils::{
-        config::{PlayoutConfig, parse_rtmp_ingest_port},
+        config::{PlayoutConfig, backup_urls, parse_ingest_port},
         errors::ProcessError,
     },
 };
@@ -49,17 +49,17 @@ pub async fn ingest_port_in_use(
     channel_id: i32,
     port: u16,
 ) -> Result<bool, ProcessError> {
-    const QUERY: &str =
-        "SELECT ingest_url FROM configurations WHERE channel_id != $1 AND ingest_enable = 1";
+    const QUERY: &str = "SELECT ingest_url, ingest_backup_urls FROM configurations WHERE channel_id != $1 AND ingest_enable = 1 AND ingest_pull = 0";
 
-    let urls = sqlx::query_scalar::<_, String>(QUERY)
+    let urls = sqlx::query_as::<_, (String, String)>(QUERY)
         .bind(channel_id)
         .fetch_all(pool)
         .await?;
 
     Ok(urls
         .iter()
-        .filter_map(|url| parse_rtmp_ingest_port(url).ok())
+        .flat_map(|(url, backups)| std::iter::once(url.as_str()).chain(backup_urls(backups)))
+        .filter_map(|url| parse_ingest_port(url).ok())
         .any(|configured_port| configured_port == port))
 }
 
@@ -68,7 +68,7 @@ pub async fn update_configuration(
     id: i32,
     config: PlayoutConfig,
 ) -> Result<SqliteQueryResult, ProcessError> {
-    const QUERY: &str = "UPDATE configurations SET general_stop_threshold = $2, mail_subject = $3, mail_recipient = $4, mail_level = $5, mail_interval = $6, logging_ffmpeg_level = $7, logging_ingest_level = $8, logging_detect_silence = $9, logging_ignore = $10, processing_mode = $11, processing_add_logo = $12, processing_logo = $13, processing_logo_scale = $14, processing_logo_opacity = $15, processing_logo_position = $16, processing_volume = $17, processing_vtt_enable = $18, processing_vtt_dummy = $19, processing_vtt_name = $20, processing_vtt_language = $21, processing_vtt_default = $22, ingest_enable = $23, ingest_url = $24, playlist_day_start = $25, playlist_length = $26, playlist_infinit = $27, storage_filler = $28, storage_extensions = $29, storage_shuffle = $30, text_preset_id = $31, task_enable = $32, task_path = $33, output_id = $34 WHERE id = $1";
+    const QUERY: &str = "UPDATE configurations SET general_stop_threshold = $2, mail_subject = $3, mail_recipient = $4, mail_level = $5, mail_interval = $6, logging_ffmpeg_level = $7, logging_ingest_level = $8, logging_detect_silence = $9, logging_ignore = $10, processing_mode = $11, processing_add_logo = $12, processing_logo = $13, processing_logo_scale = $14, processing_logo_opacity = $15, processing_logo_position = $16, processing_volume = $17, processing_vtt_enable = $18, processing_vtt_dummy = $19, processing_vtt_name = $20, processing_vtt_language = $21, processing_vtt_default = $22, ingest_enable = $23, ingest_url = $24, playlist_day_start = $25, playlist_length = $26, playlist_infinit = $27, storage_filler = $28, storage_extensions = $29, storage_shuffle = $30, text_preset_id = $31, task_enable = $32, task_path = $33, output_id = $34, processing_slate_enable = $35, processing_slate = $36, processing_slate_text = $37, processing_slate_tone = $38, ingest_pull = $39, ingest_schedule = $40, ingest_manual = $41, ingest_backup_urls = $42, ingest_failover_seconds = $43, ingest_on_publish = $44, ingest_record = $45, ingest_record_folder = $46, ingest_delay = $47, ingest_signal_failover = $48 WHERE id = $1";
 
     let result = sqlx::query(QUERY)
         .bind(id)
@@ -105,6 +105,20 @@ pub async fn update_configuration(
         .bind(config.task.enable)
         .bind(config.task.path.to_string_lossy().to_string())
         .bind(config.output.id)
+        .bind(config.processing.slate_enable)
+        .bind(config.processing.slate)
+        .bind(config.processing.slate_text)
+        .bind(config.processing.slate_tone)
+        .bind(config.ingest.pull)
+        .bind(config.ingest.schedule)
+        .bind(config.ingest.manual)
+        .bind(config.ingest.backup_urls)
+        .bind(config.ingest.failover_seconds)
+        .bind(config.ingest.on_publish)
+        .bind(config.ingest.record)
+        .bind(config.ingest.record_folder)
+        .bind(config.ingest.delay)
+        .bind(config.ingest.signal_failover)
         .execute(pool)
         .await?;
 
@@ -1,17 +1,23 @@
+pub mod branding;
 pub mod channel;
 pub mod configuration;
 pub mod global;
 pub mod init;
 pub mod output;
 pub mod preset;
+pub mod recording;
 pub mod refresh_token;
+pub mod stream_key;
 pub mod user;
 
+pub use branding::*;
 pub use channel::*;
 pub use configuration::*;
 pub use global::*;
 pub use init::*;
 pub use output::*;
 pub use preset::*;
+pub use recording::*;
 pub use refresh_token::*;
+pub use stream_key::*;
 pub use user::*;
@@ -21,7 +21,7 @@ pub async fn insert_output<'e, E>(
 where
     E: Executor<'e, Database = Sqlite>,
 {
-    const QUERY: &str = "INSERT INTO outputs (channel_id, name, hls_variants, stream_url, stream_type, stream_format, hls_playlist_name, hls_segment_duration, hls_list_size, desktop_fullscreen, width, height, fps, video_codec, video_options, audio_codec, audio_bitrate) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17) RETURNING id";
+    const QUERY: &str = "INSERT INTO outputs (channel_id, name, hls_variants, stream_url, stream_type, stream_format, hls_playlist_name, hls_segment_duration, hls_list_size, desktop_fullscreen, clip_metadata, width, height, fps, video_codec, video_options, audio_codec, audio_bitrate) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18) RETURNING id";
 
     let output_id = sqlx::query(QUERY)
         .bind(channel_id)
@@ -34,6 +34,7 @@ where
         .bind(output.hls_segment_duration)
         .bind(output.hls_list_size)
         .bind(output.desktop_fullscreen)
+        .bind(output.clip_metadata)
         .bind(output.width)
         .bind(output.height)
         .bind(output.fps)
@@ -61,6 +62,7 @@ pub async fn update_output(
     hls_segment_duration: Option<i64>,
     hls_list_size: Option<i64>,
     desktop_fullscreen: bool,
+    clip_metadata: bool,
     width: i64,
     height: i64,
     fps: f64,
@@ -69,7 +71,7 @@ pub async fn update_output(
     audio_codec: Option<&str>,
     audio_bitrate: Option<i64>,
 ) -> Result<SqliteQueryResult, ProcessError> {
-    const QUERY: &str = "UPDATE outputs SET hls_variants = $3, stream_url = $4, stream_type = $5, stream_format = $6, hls_playlist_name = $7, hls_segment_duration = $8, hls_list_size = $9, desktop_fullscreen = $10, width = $11, height = $12, fps = $13, video_codec = $14, video_options = $15, audio_codec = $16, audio_bitrate = $17 WHERE id = $1 AND channel_id = $2";
+    const QUERY: &str = "UPDATE outputs SET hls_variants = $3, stream_url = $4, stream_type = $5, stream_format = $6, hls_playlist_name = $7, hls_segment_duration = $8, hls_list_size = $9, desktop_fullscreen = $10, clip_metadata = $11, width = $12, height = $13, fps = $14, video_codec = $15, video_options = $16, audio_codec = $17, audio_bitrate = $18 WHERE id = $1 AND channel_id = $2";
 
     let result = sqlx::query(QUERY)
         .bind(id)
@@ -82,6 +84,7 @@ pub async fn update_output(
         .bind(hls_segment_duration)
         .bind(hls_list_size)
         .bind(desktop_fullscreen)
+        .bind(clip_metadata)
         .bind(width)
         .bind(height)
         .bind(fps)
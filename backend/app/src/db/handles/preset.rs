@@ -39,7 +39,11 @@ pub async fn update_preset(
         text_opacity = $12, background_enabled = $13, background_color = $14,
         background_opacity = $15, background_padding = $16, opacity = $17,
         scroll_direction = $18, scroll_speed = $19, scroll_repeat = $20,
-        fade_in_seconds = $21, fade_out_seconds = $22 WHERE id = $23 AND channel_id = $24";
+        fade_in_seconds = $21, fade_out_seconds = $22, reveal = $23, reveal_speed = $24,
+        slide_in = $25, slide_out = $26, slide_seconds = $27, easing = $28, markup = $29,
+        outline_width = $30, outline_color = $31, shadow_enabled = $32, shadow_color = $33,
+        shadow_opacity = $34, shadow_offset_x = $35, shadow_offset_y = $36, shadow_blur = $37
+        WHERE id = $38 AND channel_id = $39";
 
     let result = sqlx::query(QUERY)
         .bind(preset.name)
@@ -64,6 +68,21 @@ pub async fn update_preset(
         .bind(preset.scroll_repeat)
         .bind(preset.fade_in_seconds)
         .bind(preset.fade_out_seconds)
+        .bind(preset.reveal)
+        .bind(preset.reveal_speed)
+        .bind(preset.slide_in)
+        .bind(preset.slide_out)
+        .bind(preset.slide_seconds)
+        .bind(preset.easing)
+        .bind(preset.markup)
+        .bind(preset.outline_width)
+        .bind(preset.outline_color)
+        .bind(preset.shadow_enabled)
+        .bind(preset.shadow_color)
+        .bind(preset.shadow_opacity)
+        .bind(preset.shadow_offset_x)
+        .bind(preset.shadow_offset_y)
+        .bind(preset.shadow_blur)
         .bind(id)
         .bind(channel_id)
         .execute(pool)
@@ -80,10 +99,14 @@ pub async fn insert_preset(
         channel_id, name, text, use_filename, font_family, font_weight, filename_regex,
         position_x, position_y, font_size, line_spacing, text_color, text_opacity,
         background_enabled, background_color, background_opacity, background_padding,
-        opacity, scroll_direction, scroll_speed, scroll_repeat, fade_in_seconds, fade_out_seconds
+        opacity, scroll_direction, scroll_speed, scroll_repeat, fade_in_seconds, fade_out_seconds,
+        reveal, reveal_speed, slide_in, slide_out, slide_seconds, easing, markup, outline_width,
+        outline_color, shadow_enabled, shadow_color, shadow_opacity, shadow_offset_x,
+        shadow_offset_y, shadow_blur
     ) VALUES(
         $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15,
-        $16, $17, $18, $19, $20, $21, $22, $23
+        $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29,
+        $30, $31, $32, $33, $34, $35, $36, $37, $38
     )";
 
     let result = sqlx::query(QUERY)
@@ -110,6 +133,21 @@ pub async fn insert_preset(
         .bind(preset.scroll_repeat)
         .bind(preset.fade_in_seconds)
         .bind(preset.fade_out_seconds)
+        .bind(preset.reveal)
+        .bind(preset.reveal_speed)
+        .bind(preset.slide_in)
+        .bind(preset.slide_out)
+        .bind(preset.slide_seconds)
+        .bind(preset.easing)
+        .bind(preset.markup)
+        .bind(preset.outline_width)
+        .bind(preset.outline_color)
+        .bind(preset.shadow_enabled)
+        .bind(preset.shadow_color)
+        .bind(preset.shadow_opacity)
+        .bind(preset.shadow_offset_x)
+        .bind(preset.shadow_offset_y)
+        .bind(preset.shadow_blur)
         .execute(pool)
         .await?;
 
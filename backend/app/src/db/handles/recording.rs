@@ -0,0 +1,55 @@
+use sqlx::sqlite::{SqlitePool, SqliteQueryResult};
+
+use crate::{db::models::RecordingSchedule, utils::errors::ProcessError};
+
+pub async fn select_recording_schedules(
+    pool: &SqlitePool,
+    channel_id: i32,
+) -> Result<Vec<RecordingSchedule>, ProcessError> {
+    const QUERY: &str = "SELECT * FROM recording_schedules WHERE channel_id = $1 ORDER BY id";
+
+    let result = sqlx::query_as(QUERY)
+        .bind(channel_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(result)
+}
+
+pub async fn insert_recording_schedule(
+    pool: &SqlitePool,
+    schedule: RecordingSchedule,
+) -> Result<SqliteQueryResult, ProcessError> {
+    const QUERY: &str = "INSERT INTO recording_schedules (channel_id, label, url, start_time,
+        end_time, folder, insert_at, created_at) VALUES($1, $2, $3, $4, $5, $6, $7, $8)";
+
+    let result = sqlx::query(QUERY)
+        .bind(schedule.channel_id)
+        .bind(schedule.label)
+        .bind(schedule.url)
+        .bind(schedule.start_time)
+        .bind(schedule.end_time)
+        .bind(schedule.folder)
+        .bind(schedule.insert_at)
+        .bind(schedule.created_at)
+        .execute(pool)
+        .await?;
+
+    Ok(result)
+}
+
+pub async fn delete_recording_schedule(
+    pool: &SqlitePool,
+    channel_id: i32,
+    id: i32,
+) -> Result<SqliteQueryResult, ProcessError> {
+    const QUERY: &str = "DELETE FROM recording_schedules WHERE id = $1 AND channel_id = $2;";
+
+    let result = sqlx::query(QUERY)
+        .bind(id)
+        .bind(channel_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result)
+}
@@ -0,0 +1,88 @@
+use sqlx::sqlite::{SqlitePool, SqliteQueryResult};
+
+use crate::{db::models::StreamKey, utils::errors::ProcessError};
+
+pub async fn select_stream_keys(
+    pool: &SqlitePool,
+    channel_id: i32,
+) -> Result<Vec<StreamKey>, ProcessError> {
+    const QUERY: &str = "SELECT * FROM stream_keys WHERE channel_id = $1 ORDER BY id";
+
+    let result = sqlx::query_as(QUERY)
+        .bind(channel_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(result)
+}
+
+/// Key of the channel that did not expire by `now`.
+pub async fn select_valid_stream_key(
+    pool: &SqlitePool,
+    channel_id: i32,
+    key: &str,
+    now: i64,
+) -> Result<Option<StreamKey>, ProcessError> {
+    const QUERY: &str = "SELECT * FROM stream_keys WHERE channel_id = $1 AND key = $2
+        AND (expires_at IS NULL OR expires_at > $3)";
+
+    let result = sqlx::query_as(QUERY)
+        .bind(channel_id)
+        .bind(key)
+        .bind(now)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(result)
+}
+
+pub async fn count_stream_keys(pool: &SqlitePool, channel_id: i32) -> Result<i64, ProcessError> {
+    const QUERY: &str = "SELECT COUNT(*) FROM stream_keys WHERE channel_id = $1";
+
+    let result = sqlx::query_scalar(QUERY)
+        .bind(channel_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(result)
+}
+
+pub async fn insert_stream_key(
+    pool: &SqlitePool,
+    key: StreamKey,
+) -> Result<SqliteQueryResult, ProcessError> {
+    const QUERY: &str = "INSERT INTO stream_keys (channel_id, label, key, expires_at, created_at)
+        VALUES($1, $2, $3, $4, $5)";
+
+    let result = sqlx::query(QUERY)
+        .bind(key.channel_id)
+        .bind(key.label)
+        .bind(key.key)
+        .bind(key.expires_at)
+        .bind(key.created_at)
+        .execute(pool)
+        .await;
+
+    match result {
+        Err(sqlx::Error::Database(error)) if error.is_unique_violation() => Err(
+            ProcessError::Custom("stream key is already in use on this channel".to_string()),
+        ),
+        result => Ok(result?),
+    }
+}
+
+pub async fn delete_stream_key(
+    pool: &SqlitePool,
+    channel_id: i32,
+    id: i32,
+) -> Result<SqliteQueryResult, ProcessError> {
+    const QUERY: &str = "DELETE FROM stream_keys WHERE id = $1 AND channel_id = $2;";
+
+    let result = sqlx::query(QUERY)
+        .bind(id)
+        .bind(channel_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result)
+}
@@ -7,7 +7,7 @@ use sqlx::{FromRow, Pool, Row, Sqlite, sqlite::SqliteRow};
 
 use crate::{
     db::handles,
-    utils::config::{OutputMode, PlayoutConfig},
+    utils::config::{OutputMode, PlayoutConfig, clock_seconds, is_storage_folder},
 };
 
 #[derive(Clone, Default, Debug, Deserialize, Serialize, sqlx::FromRow)]
@@ -247,6 +247,21 @@ pub struct TextPreset {
     pub scroll_repeat: i32,
     pub fade_in_seconds: f64,
     pub fade_out_seconds: f64,
+    pub reveal: String,
+    pub reveal_speed: f64,
+    pub slide_in: String,
+    pub slide_out: String,
+    pub slide_seconds: f64,
+    pub easing: String,
+    pub markup: bool,
+    pub outline_width: u32,
+    pub outline_color: String,
+    pub shadow_enabled: bool,
+    pub shadow_color: String,
+    pub shadow_opacity: f64,
+    pub shadow_offset_x: i32,
+    pub shadow_offset_y: i32,
+    pub shadow_blur: u32,
 }
 
 impl Default for TextPreset {
@@ -276,6 +291,21 @@ impl Default for TextPreset {
             scroll_repeat: -1,
             fade_in_seconds: 0.0,
             fade_out_seconds: 0.0,
+            reveal: "none".to_string(),
+            reveal_speed: 20.0,
+            slide_in: "none".to_string(),
+            slide_out: "none".to_string(),
+            slide_seconds: 0.5,
+            easing: "ease_out".to_string(),
+            markup: false,
+            outline_width: 0,
+            outline_color: "#000000".to_string(),
+            shadow_enabled: false,
+            shadow_color: "#000000".to_string(),
+            shadow_opacity: 0.6,
+            shadow_offset_x: 2,
+            shadow_offset_y: 2,
+            shadow_blur: 2,
         }
     }
 }
@@ -300,6 +330,7 @@ impl TextPreset {
         for (name, value) in [
             ("text opacity", self.text_opacity),
             ("background opacity", self.background_opacity),
+            ("shadow opacity", self.shadow_opacity),
             ("opacity", self.opacity),
         ] {
             if !value.is_finite() || !(0.0..=1.0).contains(&value) {
@@ -314,7 +345,7 @@ impl TextPreset {
         }
         if !matches!(
             self.scroll_direction.as_str(),
-            "none" | "left_to_right" | "right_to_left"
+            "none" | "left_to_right" | "right_to_left" | "bottom_to_top" | "top_to_bottom"
         ) {
             return Err("invalid text scroll direction".to_string());
         }
@@ -324,6 +355,35 @@ impl TextPreset {
         if self.scroll_repeat < -1 {
             return Err("text scroll repeat must be -1 or greater".to_string());
         }
+        if !matches!(self.reveal.as_str(), "none" | "typewriter") {
+            return Err("invalid text reveal".to_string());
+        }
+        if self.reveal != "none" && !(self.reveal_speed.is_finite() && self.reveal_speed > 0.0) {
+            return Err("text reveal speed must be greater than zero".to_string());
+        }
+        for (name, edge) in [("slide-in", &self.slide_in), ("slide-out", &self.slide_out)] {
+            if !matches!(edge.as_str(), "none" | "left" | "right" | "top" | "bottom") {
+                return Err(format!("invalid text {name} edge"));
+            }
+        }
+        if !self.slide_seconds.is_finite() || self.slide_seconds < 0.0 {
+            return Err("slide duration must not be negative".to_string());
+        }
+        if !matches!(
+            self.easing.as_str(),
+            "linear" | "ease_in" | "ease_out" | "ease_in_out"
+        ) {
+            return Err("invalid text easing".to_string());
+        }
+        if self.outline_width > 32 {
+            return Err("text outline width must be 32 or less".to_string());
+        }
+        if self.shadow_offset_x.unsigned_abs() > 64 || self.shadow_offset_y.unsigned_abs() > 64 {
+            return Err("text shadow offset must be between -64 and 64".to_string());
+        }
+        if self.shadow_blur > 32 {
+            return Err("text shadow blur must be 32 or less".to_string());
+        }
         if self.use_filename {
             Regex::new(&self.filename_regex)
                 .map_err(|error| format!("invalid filename regex: {error}"))?;
@@ -332,6 +392,184 @@ impl TextPreset {
     }
 }
 
+/// Logo changes for the clips of a category or the playlist nodes that name
+/// the rule.
+#[derive(Debug, Deserialize, Serialize, Clone, sqlx::FromRow)]
+#[serde(default)]
+pub struct BrandingRule {
+    #[sqlx(default)]
+    #[serde(skip_deserializing)]
+    pub id: i32,
+    pub channel_id: i32,
+    pub name: String,
+    /// Playlist category the rule applies to; empty only applies the rule to
+    /// nodes that name it.
+    pub category: String,
+    pub hide_logo: bool,
+    /// Logo shown instead of the channel logo; empty keeps the channel logo.
+    pub logo: String,
+    pub logo_scale: String,
+    pub logo_opacity: f64,
+    pub logo_position: String,
+    /// Extra icon, like an age rating; empty adds none.
+    pub icon: String,
+    pub icon_scale: String,
+    pub icon_opacity: f64,
+    pub icon_position: String,
+    /// Seconds from the start of the clip the icon stays; 0 keeps it for the
+    /// whole clip.
+    pub icon_seconds: f64,
+}
+
+impl Default for BrandingRule {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            channel_id: 1,
+            name: String::new(),
+            category: String::new(),
+            hide_logo: false,
+            logo: String::new(),
+            logo_scale: String::new(),
+            logo_opacity: 0.7,
+            logo_position: "W-w-12:12".to_string(),
+            icon: String::new(),
+            icon_scale: String::new(),
+            icon_opacity: 1.0,
+            icon_position: "12:12".to_string(),
+            icon_seconds: 0.0,
+        }
+    }
+}
+
+impl BrandingRule {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("branding rule name must not be empty".to_string());
+        }
+        for (name, value) in [
+            ("logo opacity", self.logo_opacity),
+            ("icon opacity", self.icon_opacity),
+        ] {
+            if !value.is_finite() || !(0.0..=1.0).contains(&value) {
+                return Err(format!("{name} must be between 0 and 1"));
+            }
+        }
+        if !self.icon_seconds.is_finite() || self.icon_seconds < 0.0 {
+            return Err("icon duration must not be negative".to_string());
+        }
+        if self.hide_logo && !self.logo.trim().is_empty() {
+            return Err("a branding rule can not hide and replace the logo".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Key a publisher sends as RTMP stream key or SRT stream id to go on the
+/// channel's ingest.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, sqlx::FromRow)]
+#[serde(default)]
+pub struct StreamKey {
+    #[sqlx(default)]
+    #[serde(skip_deserializing)]
+    pub id: i32,
+    pub channel_id: i32,
+    /// Name of the publisher, logged when the key goes on air.
+    pub label: String,
+    /// Generated when empty.
+    pub key: String,
+    /// Unix time the key stops working; `None` keeps it.
+    pub expires_at: Option<i64>,
+    #[serde(skip_deserializing)]
+    pub created_at: i64,
+}
+
+impl StreamKey {
+    pub fn validate(&self, now: i64) -> Result<(), String> {
+        if self.label.trim().is_empty() {
+            return Err("stream key label must not be empty".to_string());
+        }
+        if !(8..=128).contains(&self.key.len())
+            || !self
+                .key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            return Err(
+                "stream key must have 8 to 128 letters, digits, '-', '_' or '.'".to_string(),
+            );
+        }
+        if self.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err("stream key expiry must be in the future".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Daily recording of an external stream into the channel storage, like a
+/// partner show that airs later.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, sqlx::FromRow)]
+#[serde(default)]
+pub struct RecordingSchedule {
+    #[sqlx(default)]
+    #[serde(skip_deserializing)]
+    pub id: i32,
+    pub channel_id: i32,
+    /// Name of the recording, it starts the file names.
+    pub label: String,
+    /// Stream to record, any URL a pull ingest takes.
+    pub url: String,
+    /// Daily window in the channel's time zone, as `HH:MM` or `HH:MM:SS`.
+    /// A window that ends before it starts runs over midnight.
+    pub start_time: String,
+    pub end_time: String,
+    /// Folder in the channel storage; `recordings` when empty.
+    pub folder: String,
+    /// Time of day the recording goes into the next day's playlist;
+    /// `None` only records.
+    pub insert_at: Option<String>,
+    #[serde(skip_deserializing)]
+    pub created_at: i64,
+}
+
+impl RecordingSchedule {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.label.trim().is_empty() {
+            return Err("recording label must not be empty".to_string());
+        }
+        if !ff_engine::is_pull_url(&self.url) {
+            return Err(
+                "recording URL must be an rtmp, rtmps, srt, http(s), udp, rtp or tcp URL"
+                    .to_string(),
+            );
+        }
+        let start = clock_seconds(&self.start_time)
+            .ok_or_else(|| format!("invalid recording start time {}", self.start_time))?;
+        let end = clock_seconds(&self.end_time)
+            .ok_or_else(|| format!("invalid recording end time {}", self.end_time))?;
+        if start == end {
+            return Err("recording window is empty".to_string());
+        }
+        if !is_storage_folder(&self.folder) {
+            return Err("recording folder must be a folder inside the channel storage".to_string());
+        }
+        if let Some(time) = &self.insert_at
+            && clock_seconds(time).is_none()
+        {
+            return Err(format!("invalid playlist time {time}"));
+        }
+        Ok(())
+    }
+
+    /// Start and end as seconds of the day.
+    pub fn window(&self) -> Option<(u32, u32)> {
+        Some((
+            clock_seconds(&self.start_time)?,
+            clock_seconds(&self.end_time)?,
+        ))
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, sqlx::FromRow)]
 pub struct Configuration {
     pub id: i32,
@@ -366,9 +604,37 @@ pub struct Configuration {
     pub processing_vtt_language: String,
     #[serde(default)]
     pub processing_vtt_default: bool,
+    #[serde(default)]
+    pub processing_slate_enable: bool,
+    #[serde(default)]
+    pub processing_slate: String,
+    #[serde(default)]
+    pub processing_slate_text: String,
+    #[serde(default)]
+    pub processing_slate_tone: bool,
 
     pub ingest_enable: bool,
     pub ingest_url: String,
+    #[serde(default)]
+    pub ingest_pull: bool,
+    #[serde(default)]
+    pub ingest_schedule: String,
+    #[serde(default)]
+    pub ingest_manual: bool,
+    #[serde(default)]
+    pub ingest_backup_urls: String,
+    #[serde(default = "default_failover_seconds")]
+    pub ingest_failover_seconds: f64,
+    #[serde(default)]
+    pub ingest_on_publish: String,
+    #[serde(default)]
+    pub ingest_record: bool,
+    #[serde(default = "default_record_folder")]
+    pub ingest_record_folder: String,
+    #[serde(default)]
+    pub ingest_delay: f64,
+    #[serde(default)]
+    pub ingest_signal_failover: f64,
 
     pub playlist_day_start: String,
     pub playlist_length: String,
@@ -412,8 +678,22 @@ impl Configuration {
             processing_vtt_name: config.processing.vtt_name,
             processing_vtt_language: config.processing.vtt_language,
             processing_vtt_default: config.processing.vtt_default,
+            processing_slate_enable: config.processing.slate_enable,
+            processing_slate: config.processing.slate,
+            processing_slate_text: config.processing.slate_text,
+            processing_slate_tone: config.processing.slate_tone,
             ingest_enable: config.ingest.enable,
             ingest_url: config.ingest.ingest_url,
+            ingest_pull: config.ingest.pull,
+            ingest_schedule: config.ingest.schedule,
+            ingest_manual: config.ingest.manual,
+            ingest_backup_urls: config.ingest.backup_urls,
+            ingest_failover_seconds: config.ingest.failover_seconds,
+            ingest_on_publish: config.ingest.on_publish,
+            ingest_record: config.ingest.record,
+            ingest_record_folder: config.ingest.record_folder,
+            ingest_delay: config.ingest.delay,
+            ingest_signal_failover: config.ingest.signal_failover,
             playlist_day_start: config.playlist.day_start,
             playlist_length: config.playlist.length,
             playlist_infinit: config.playlist.infinit,
@@ -443,6 +723,9 @@ pub struct Output {
     #[sqlx(default)]
     #[serde(default)]
     pub desktop_fullscreen: bool,
+    #[sqlx(default)]
+    #[serde(default)]
+    pub clip_metadata: bool,
     pub width: i64,
     pub height: i64,
     pub fps: f64,
@@ -476,6 +759,7 @@ impl Output {
             hls_segment_duration,
             hls_list_size,
             desktop_fullscreen: false,
+            clip_metadata: false,
             width: 1280,
             height: 720,
             fps: 25.0,
@@ -491,6 +775,14 @@ impl Output {
     }
 }
 
+fn default_record_folder() -> String {
+    "recordings".to_string()
+}
+
+fn default_failover_seconds() -> f64 {
+    5.0
+}
+
 fn default_vtt_name() -> String {
     "Subtitles".to_string()
 }
@@ -498,3 +790,49 @@ fn default_vtt_name() -> String {
 fn default_vtt_language() -> String {
     "und".to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_key_is_validated() {
+        let key = StreamKey {
+            label: "Reporter 1".to_string(),
+            key: "reporter-key_1.a".to_string(),
+            expires_at: Some(1_000),
+            ..StreamKey::default()
+        };
+        assert!(key.validate(999).is_ok());
+        assert!(
+            StreamKey {
+                expires_at: None,
+                ..key.clone()
+            }
+            .validate(5_000)
+            .is_ok()
+        );
+
+        assert!(key.validate(1_000).is_err());
+        assert!(
+            StreamKey {
+                label: " ".to_string(),
+                ..key.clone()
+            }
+            .validate(0)
+            .is_err()
+        );
+        for invalid in ["short12", "reporter key", "reporter/key", &"k".repeat(129)] {
+            let key = StreamKey {
+                key: invalid.to_string(),
+                ..key.clone()
+            };
+            assert!(key.validate(0).is_err(), "{invalid} was taken");
+        }
+        let longest = StreamKey {
+            key: "k".repeat(128),
+            ..key.clone()
+        };
+        assert!(longest.validate(0).is_ok());
+    }
+}
@@ -198,6 +198,7 @@ async fn async_main() -> Result<(), ProcessError> {
                 app_state.controller.write().await.add(manager.clone());
                 app_state.mail_queues.lock().await.push(m_queue);
 
+                manager.spawn_recordings().await;
                 manager.foreground_start(index).await?;
             } else if ARGS.generate.is_some() {
                 // Run a simple playlist generator and save it to disk.
@@ -259,6 +260,7 @@ async fn async_main() -> Result<(), ProcessError> {
         manager.channel.lock().await.active = false;
         manager.stop_all(false).await;
         manager.stop_supervisor().await;
+        manager.stop_recordings().await;
     }
 
     pool.close().await;
@@ -7,7 +7,10 @@ use std::{
     },
 };
 
-use ff_engine::{AudioEffectsControl, AudioLevel, PlaybackControl, TextOverlayState};
+use ff_engine::{
+    AudioEffectsControl, AudioLevel, GraphicsLayers, IngestFailover, LiveDelay, LiveRecorder,
+    LiveTake, PictureInPicture, PlaybackControl, SignalMonitor, TextOverlayState, TextPlaceholders,
+};
 use log::*;
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Sqlite};
@@ -21,10 +24,16 @@ use tokio_util::sync::CancellationToken;
 
 use crate::{
     ARGS,
-    db::{handles, models::Channel},
+    db::{
+        handles,
+        models::{BrandingRule, Channel},
+    },
     file::{init_storage, local::LocalStorage},
     player::{output::player, utils::Media},
-    utils::{config::PlayoutConfig, errors::ServiceError, logging::Target, system::SystemStat},
+    utils::{
+        config::PlayoutConfig, errors::ServiceError, logging::Target, system::SystemStat,
+        ticker::Ticker,
+    },
 };
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -77,14 +86,28 @@ pub struct ChannelManager {
     pub validation_handle: Arc<Mutex<Option<JoinHandle<()>>>>,
     pub metrics_handle: Arc<Mutex<Option<JoinHandle<()>>>>,
     pub task_runner_handle: Arc<Mutex<Option<JoinHandle<()>>>>,
+    pub ticker_handle: Arc<Mutex<Option<JoinHandle<()>>>>,
+    pub recording_handle: Arc<Mutex<Option<JoinHandle<()>>>>,
     pub supervisor_token: Arc<Mutex<Option<CancellationToken>>>,
     pub validation_token: Arc<Mutex<Option<CancellationToken>>>,
     pub metrics_token: Arc<Mutex<Option<CancellationToken>>>,
     pub task_runner_token: Arc<Mutex<Option<CancellationToken>>>,
+    pub ticker_token: Arc<Mutex<Option<CancellationToken>>>,
+    pub recording_token: Arc<Mutex<Option<CancellationToken>>>,
     pub task_generation: Arc<AtomicUsize>,
     pub audio_effects: AudioEffectsControl,
     pub audio_level: Arc<StdMutex<Option<AudioLevel>>>,
     pub text_overlay: TextOverlayState,
+    pub graphics_layers: GraphicsLayers,
+    pub picture_in_picture: PictureInPicture,
+    pub live_take: LiveTake,
+    pub ingest_failover: IngestFailover,
+    pub live_recorder: LiveRecorder,
+    pub live_delay: LiveDelay,
+    pub signal_monitor: SignalMonitor,
+    pub text_placeholders: TextPlaceholders,
+    /// Branding rules, read on the first clip and after they changed.
+    pub branding_rules: Arc<Mutex<Option<Arc<Vec<BrandingRule>>>>>,
     pub playback_control: Arc<Mutex<PlaybackControl>>,
     pub shutdown: CancellationToken,
     pub system: SystemStat,
@@ -136,14 +159,27 @@ impl ChannelManager {
             validation_handle: Arc::new(Mutex::new(None)),
             metrics_handle: Arc::new(Mutex::new(None)),
             task_runner_handle: Arc::new(Mutex::new(None)),
+            ticker_handle: Arc::new(Mutex::new(None)),
+            recording_handle: Arc::new(Mutex::new(None)),
             supervisor_token: Arc::new(Mutex::new(None)),
             validation_token: Arc::new(Mutex::new(None)),
             metrics_token: Arc::new(Mutex::new(None)),
             task_runner_token: Arc::new(Mutex::new(None)),
+            ticker_token: Arc::new(Mutex::new(None)),
+            recording_token: Arc::new(Mutex::new(None)),
             task_generation: Arc::new(AtomicUsize::new(0)),
             audio_effects,
             audio_level: Arc::new(StdMutex::new(None)),
             text_overlay,
+            graphics_layers: GraphicsLayers::default(),
+            picture_in_picture: PictureInPicture::default(),
+            live_take: LiveTake::default(),
+            ingest_failover: IngestFailover::default(),
+            live_recorder: LiveRecorder::default(),
+            live_delay: LiveDelay::default(),
+            signal_monitor: SignalMonitor::default(),
+            text_placeholders: TextPlaceholders::default(),
+            branding_rules: Arc::new(Mutex::new(None)),
             playback_control: Arc::new(Mutex::new(PlaybackControl::default())),
             shutdown,
             system,
@@ -391,6 +427,63 @@ impl ChannelManager {
         *self.task_runner_handle.lock().await = Some(handle);
     }
 
+    /// Run `ticker` on the text overlay, replacing a running one.
+    pub async fn spawn_ticker(&self, ticker: Ticker) {
+        self.stop_ticker().await;
+
+        let token = Self::replace_token(&self.ticker_token).await;
+        let manager = self.clone();
+        let handle = tokio::spawn(ticker.run(manager, token));
+        *self.ticker_handle.lock().await = Some(handle);
+    }
+
+    /// Stop the ticker, its text leaves the overlay with it.
+    pub async fn stop_ticker(&self) {
+        self.stop_task("ticker", &self.ticker_handle, &self.ticker_token)
+            .await;
+    }
+
+    /// Branding rules of the channel, read from the database once and kept
+    /// until [`Self::reload_branding_rules`].
+    pub async fn branding_rules(&self) -> Result<Arc<Vec<BrandingRule>>, ServiceError> {
+        let mut rules = self.branding_rules.lock().await;
+        if let Some(rules) = &*rules {
+            return Ok(rules.clone());
+        }
+
+        let loaded = Arc::new(handles::select_branding_rules(&self.db_pool, self.id).await?);
+        *rules = Some(loaded.clone());
+        Ok(loaded)
+    }
+
+    /// Read the branding rules again with the next clip.
+    pub async fn reload_branding_rules(&self) {
+        *self.branding_rules.lock().await = None;
+    }
+
+    pub async fn ticker_running(&self) -> bool {
+        self.ticker_token.lock().await.is_some()
+    }
+
+    /// Run the recording schedules of the channel, apart from its playout,
+    /// which can start and stop without cutting them. A running scheduler
+    /// is kept.
+    pub async fn spawn_recordings(&self) {
+        let mut handle = self.recording_handle.lock().await;
+        if handle.as_ref().is_some_and(|task| !task.is_finished()) {
+            return;
+        }
+
+        let token = Self::replace_token(&self.recording_token).await;
+        let manager = self.clone();
+        *handle = Some(tokio::spawn(crate::utils::recording::run(manager, token)));
+    }
+
+    pub async fn stop_recordings(&self) {
+        self.stop_task("recordings", &self.recording_handle, &self.recording_token)
+            .await;
+    }
+
     pub async fn spawn_validation(
         &self,
         config: PlayoutConfig,
@@ -497,6 +590,7 @@ impl ChannelManager {
 
             self.stop_validation().await;
             self.stop_dev_metrics_snapshot().await;
+            self.stop_ticker().await;
         } else {
             debug!(target: Target::All.as_str(), channel = channel_id; "Stop all child processes from channel: <span class=\"log-number\">{channel_id}</span>");
         }
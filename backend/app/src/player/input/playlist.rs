@@ -337,8 +337,9 @@ impl CurrentProgram {
 
             trace!("Clip from init: {}", node_clone.source);
 
-            node_clone.seek += time_sec
+            node_clone.resume = time_sec
                 - (node_clone.begin.unwrap() - self.manager.channel.lock().await.time_shift);
+            node_clone.seek += node_clone.resume;
 
             self.last_next_ad(&mut node_clone).await;
 
@@ -561,7 +562,10 @@ impl CurrentProgram {
 
         trace!("Clip length: {duration}, duration: {}", node.duration);
 
+        // Live events have nothing to probe, the engine fills in for a
+        // missing feed.
         if node.probe.is_none()
+            && node.live.is_none()
             && !node.source.is_empty()
             && let Err(e) = node.add_probe(true).await
         {
@@ -569,7 +573,7 @@ impl CurrentProgram {
         };
 
         // separate if condition, because of node.add_probe() in last condition
-        if node.probe.is_none() {
+        if node.probe.is_none() && node.live.is_none() {
             trace!("clip index: {node_index} | last index: {last_index}");
 
             if node_index < last_index {
@@ -5,26 +5,39 @@ use std::{
     time::Duration,
 };
 
+use chrono::Utc;
 use ff_engine::{
-    AsyncPlayout, AudioLevelCallback, ClipResult, HlsHealth, LogLevel, LogoConfig, LogoFade,
-    OutputConfig, TextOverlayState,
+    AsyncPlayout, AudioLevelCallback, ClipMetadata, ClipResult, GraphicsLayers, HlsHealth,
+    IngestSwitchCallback, LogLevel, LogoConfig, LogoFade, OutputConfig, PlaceholderValues,
+    PullControl, TextOverlayState, TextPlaceholders,
 };
 use log::*;
-use tokio::time::sleep;
+use tokio::{task::JoinHandle, time::sleep};
 use tokio_util::sync::CancellationToken;
 
+use super::icecast;
 use crate::{
     file::norm_abs_path,
     player::{
         controller::ChannelManager,
         input::source_generator,
-        utils::{Media, get_delta, sec_to_time},
+        utils::{
+            Media,
+            events::{engine_events, spawn_webhooks},
+            get_delta,
+            live::engine_live_block,
+            publish::publish_authorizer,
+            record::live_recorder,
+            sec_to_time, time_in_seconds,
+        },
     },
     utils::{
-        config::{OutputMode, PlayoutConfig},
+        branding::clip_branding,
+        config::{OutputMode, PlayoutConfig, url_address},
         control::{PlayerCtl, control_state},
         errors::ServiceError,
         text::text_config,
+        time_machine::time_now,
     },
 };
 
@@ -33,6 +46,7 @@ const HLS_RATE_CORRECTION_MAX_SECONDS: f64 = 2.0;
 const HLS_RATE_CORRECTION_MIN_RATE: f64 = 0.98;
 const HLS_RATE_CORRECTION_MAX_RATE: f64 = 1.02;
 const HLS_RATE_CORRECTION_MAX_DELTA_FACTOR: f64 = 1.0;
+const PULL_SCHEDULE_INTERVAL: Duration = Duration::from_secs(1);
 
 pub async fn player(manager: ChannelManager) -> Result<(), ServiceError> {
     let config = manager.config.read().await.clone();
@@ -50,8 +64,32 @@ pub async fn player(manager: ChannelManager) -> Result<(), ServiceError> {
         manager.audio_effects.clone(),
         manager.audio_level.clone(),
         manager.text_overlay.clone(),
+        manager.graphics_layers.clone(),
+        manager.text_placeholders.clone(),
         desktop_control_callback(manager.clone()),
-    )?;
+    )?
+    .with_picture_in_picture(manager.picture_in_picture.clone())
+    .with_live_take(manager.live_take.clone())
+    .with_ingest_failover(manager.ingest_failover.clone())
+    .with_publish_authorizer(publish_authorizer(manager.db_pool.clone(), &config))
+    .with_live_recorder(manager.live_recorder.clone())
+    .with_live_delay(manager.live_delay.clone())
+    .with_signal_monitor(manager.signal_monitor.clone());
+    live_recorder(&manager.live_recorder, &config);
+    manager.live_take.set_manual(config.ingest.manual);
+    manager
+        .live_delay
+        .set_delay(Duration::from_secs_f64(config.ingest.delay));
+    manager.signal_monitor.set_failover_after(
+        (config.ingest.signal_failover > 0.0)
+            .then(|| Duration::from_secs_f64(config.ingest.signal_failover)),
+    );
+    manager
+        .ingest_failover
+        .set_switch_after(Duration::from_secs_f64(config.ingest.failover_seconds));
+    manager
+        .ingest_failover
+        .set_callback(ingest_switch_callback(&manager, &config));
     let playout = open_playout(&config, output_config.clone()).await?;
     *manager.playback_control.lock().await = playout.playback_control();
     if config.output.mode == OutputMode::Desktop {
@@ -65,27 +103,104 @@ pub async fn player(manager: ChannelManager) -> Result<(), ServiceError> {
         );
     }
 
-    if config.ingest.enable {
-        let url = config.ingest.ingest_url.clone();
-        playout
-            .start_rtmp_live(url.clone(), output_config)
-            .await
-            .map_err(engine_error)?;
-        info!(channel = config.general.channel_id;
-            "Start ingest server, listening on: <span class=\"log-addr\">{url}</span>"
-        );
-    }
+    let pull_schedule = if config.ingest.enable {
+        start_ingest(&config, &playout, output_config).await?
+    } else {
+        None
+    };
 
     let result = match config.output.mode {
         OutputMode::HLS => play_hls(manager.clone(), &config, &playout).await,
         _ => play_loop(manager.clone(), &config, &playout, None).await,
     };
+    if let Some(pull_schedule) = pull_schedule {
+        pull_schedule.abort();
+    }
 
     let finish_result = playout.finish().await.map_err(engine_error);
     result?;
     finish_result
 }
 
+/// Starts the live ingest. A scheduled pull gets a task that arms and
+/// disarms it, which the caller aborts when playout ends.
+async fn start_ingest(
+    config: &PlayoutConfig,
+    playout: &AsyncPlayout,
+    output_config: OutputConfig,
+) -> Result<Option<JoinHandle<()>>, ServiceError> {
+    let id = config.general.channel_id;
+    let urls = config.ingest.urls();
+    let address = urls
+        .iter()
+        .map(|url| url_address(url))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if !config.ingest.pull {
+        playout
+            .start_live_failover(urls, output_config, None)
+            .await
+            .map_err(engine_error)?;
+        info!(channel = id;
+            "Start ingest server, listening on: <span class=\"log-addr\">{address}</span>"
+        );
+        return Ok(None);
+    }
+
+    let ingest = config.ingest.clone();
+    let timezone = config.channel.timezone;
+    let control = PullControl::new(ingest.armed_at(time_in_seconds(&timezone)));
+    playout
+        .start_live_failover(urls, output_config, Some(control.clone()))
+        .await
+        .map_err(engine_error)?;
+    info!(channel = id; "Start pull ingest from: <span class=\"log-addr\">{address}</span>");
+    if ingest.schedule.trim().is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(tokio::spawn(async move {
+        loop {
+            sleep(PULL_SCHEDULE_INTERVAL).await;
+            let armed = ingest.armed_at(time_in_seconds(&timezone));
+            if armed != control.is_armed() {
+                info!(channel = id; "Pull ingest {}", if armed { "armed" } else { "disarmed" });
+                control.set_armed(armed);
+            }
+        }
+    })))
+}
+
+/// Logs ingest failovers and keeps `ingest_is_alive` up to date.
+fn ingest_switch_callback(
+    manager: &ChannelManager,
+    config: &PlayoutConfig,
+) -> IngestSwitchCallback {
+    let id = config.general.channel_id;
+    let is_alive = manager.ingest_is_alive.clone();
+    let addresses = config
+        .ingest
+        .urls()
+        .iter()
+        .map(|url| url_address(url).to_string())
+        .collect::<Vec<_>>();
+
+    IngestSwitchCallback::new(move |on_air| {
+        is_alive.store(on_air.is_some(), Ordering::SeqCst);
+        match on_air {
+            Some(0) => info!(channel = id;
+                "Primary ingest <span class=\"log-addr\">{}</span> on air", addresses[0]
+            ),
+            Some(index) => warn!(channel = id;
+                "Failover to backup ingest <span class=\"log-addr\">{}</span>",
+                addresses.get(index).map_or("", String::as_str)
+            ),
+            None => info!(channel = id; "No ingest endpoint connected"),
+        }
+    })
+}
+
 async fn play_hls(
     manager: ChannelManager,
     config: &PlayoutConfig,
@@ -116,6 +231,30 @@ async fn play_hls(
     result
 }
 
+/// Queues the branding rules matching the node. A broken rule is logged and
+/// leaves the clip with the channel logo.
+async fn set_clip_branding(
+    manager: &ChannelManager,
+    config: &PlayoutConfig,
+    node: &Media,
+    playout: &AsyncPlayout,
+) -> Result<(), ServiceError> {
+    let id = config.general.channel_id;
+    let branding = manager
+        .branding_rules()
+        .await
+        .and_then(|rules| clip_branding(&rules, node, &config.channel.storage));
+
+    match branding {
+        Ok(Some(branding)) => playout.set_clip_branding(branding).map_err(engine_error),
+        Ok(None) => Ok(()),
+        Err(error) => {
+            warn!(channel = id; "Branding rules not applied: {error}");
+            Ok(())
+        }
+    }
+}
+
 async fn hls_watchdog(
     channel_id: i32,
     hls_health: HlsHealth,
@@ -184,13 +323,31 @@ async fn play_loop(
             continue;
         }
 
-        validate_supported_node(config, &node.source, &node.audio)?;
+        if let Some(live) = node.live {
+            // The query can carry an SRT passphrase or access tokens.
+            info!(channel = id;
+                "Live event for <span class=\"log-number\">{}</span>: <span class=\"log-addr\">{}</span>, {:?} while absent",
+                sec_to_time(node.out - node.seek),
+                url_address(&node.source),
+                live.absent
+            );
+        } else {
+            validate_supported_node(config, &node.source, &node.audio)?;
 
-        info!(channel = id;
-            "Play for <span class=\"log-number\">{}</span>: <span class=\"log-addr\">{}</span>",
-            sec_to_time(node.out - node.seek),
-            node.source
-        );
+            info!(channel = id;
+                "Play for <span class=\"log-number\">{}</span>: <span class=\"log-addr\">{}</span>",
+                sec_to_time(node.out - node.seek),
+                node.source
+            );
+        }
+
+        if config.output.has_icecast_titles() {
+            tokio::spawn(icecast::update_now_playing(
+                id,
+                config.output.stream_url.clone(),
+                icecast::now_playing_title(&node),
+            ));
+        }
 
         if config.task.enable {
             if config.task.path.is_file() {
@@ -210,21 +367,55 @@ async fn play_loop(
             1.0
         };
         let is_ad = node.category == "advertisement";
-        match playout
-            .play_with_timing_logo_fade_and_rate(
-                node.source.clone(),
-                (node.seek > 0.0).then_some(node.seek),
-                duration,
-                subtitle_media_path(config, &node.source),
-                LogoFade {
-                    fade_in: !is_ad && node.last_ad,
-                    fade_out: !is_ad && node.next_ad,
-                },
-                playout_rate,
+        set_text_placeholders(&manager, config, &node, duration).await;
+        playout
+            .set_clip_metadata(
+                ClipMetadata::new(icecast::now_playing_title(&node))
+                    .with_category(node.category.clone()),
             )
-            .await
-            .map_err(engine_error)?
-        {
+            .map_err(engine_error)?;
+        if let Some(image) = &node.image {
+            playout
+                .set_image_options(image.engine_options(&config.storage.path))
+                .map_err(engine_error)?;
+        }
+        if !config.output.is_audio_only() {
+            set_clip_branding(&manager, config, &node, playout).await?;
+            if !node.events.is_empty() {
+                playout
+                    .set_clip_events(engine_events(&manager.db_pool, id, &node).await)
+                    .map_err(engine_error)?;
+            }
+        }
+        let webhooks = spawn_webhooks(id, &node, duration);
+        let result = match node.live {
+            Some(live) => {
+                playout
+                    .play_live_block(engine_live_block(&manager, config, &node, live).await)
+                    .await
+            }
+            None => {
+                playout
+                    .play_with_timing_logo_fade_and_rate(
+                        node.source.clone(),
+                        (node.seek > 0.0).then_some(node.seek),
+                        duration,
+                        subtitle_media_path(config, &node.source),
+                        LogoFade {
+                            fade_in: !is_ad && node.last_ad,
+                            fade_out: !is_ad && node.next_ad,
+                        },
+                        playout_rate,
+                    )
+                    .await
+            }
+        };
+        if let Some(webhooks) = webhooks {
+            webhooks.abort();
+        }
+        match result.map_err(engine_error)? {
+            // Also the end of a live event that was on air; the playlist
+            // goes on from the current time, so it stays in sync.
             ClipResult::LiveEnded => {
                 info!(channel = id;
                     "Live input ended; reinitialize playlist at current time"
@@ -277,6 +468,32 @@ async fn play_loop(
     Ok(())
 }
 
+/// Update the values for `{title}`, `{next_start}` and the other text
+/// placeholders right before `node` starts.
+async fn set_text_placeholders(
+    manager: &ChannelManager,
+    config: &PlayoutConfig,
+    node: &Media,
+    duration: Option<f64>,
+) {
+    let next_title = {
+        let list = manager.current_list.lock().await;
+        node.index
+            .and_then(|index| list.get(index + 1))
+            .map(icecast::now_playing_title)
+    };
+    // The engine clock runs on the system time and resolves the channel
+    // timezone itself, so the offset follows daylight saving time.
+    let now = Utc::now().timestamp_millis() as f64 / 1000.0;
+
+    manager.text_placeholders.set(PlaceholderValues {
+        title: icecast::now_playing_title(node),
+        next_title: next_title.unwrap_or_default(),
+        next_start: duration.map(|duration| now + duration),
+        timezone: Some(time_now(&config.channel.timezone).timezone()),
+    });
+}
+
 async fn hls_playout_rate(
     config: &PlayoutConfig,
     manager: &ChannelManager,
@@ -397,6 +614,8 @@ fn engine_output_config(
     audio_effects: ff_engine::AudioEffectsControl,
     audio_level: std::sync::Arc<std::sync::Mutex<Option<ff_engine::AudioLevel>>>,
     text_overlay_state: TextOverlayState,
+    graphics_layers: GraphicsLayers,
+    text_placeholders: TextPlaceholders,
     desktop_control_callback: ff_engine::DesktopControlCallback,
 ) -> Result<OutputConfig, ServiceError> {
     let width = config.output.width;
@@ -413,7 +632,8 @@ fn engine_output_config(
     }
     let fps = config.output.fps.round() as u32;
 
-    let logo = config.processing.add_logo.then(|| LogoConfig {
+    let audio_only = config.output.is_audio_only();
+    let logo = (config.processing.add_logo && !audio_only).then(|| LogoConfig {
         path: config.processing.logo_path.clone(),
         scale: (!config.processing.logo_scale.trim().is_empty())
             .then(|| config.processing.logo_scale.clone()),
@@ -424,7 +644,7 @@ fn engine_output_config(
         .text
         .preset
         .as_ref()
-        .filter(|preset| preset.use_filename)
+        .filter(|preset| preset.use_filename && !audio_only)
         .map(|preset| text_config(preset, None, true));
 
     let ffmpeg_log_level = config
@@ -448,6 +668,9 @@ fn engine_output_config(
         .with_logo(logo)
         .with_text(text)
         .with_text_overlay_state(text_overlay_state)
+        .with_graphics_layers(graphics_layers)
+        .with_text_placeholders(text_placeholders)
+        .with_slate(config.processing.slate())
         .with_desktop_fullscreen(config.output.desktop_fullscreen)
         .with_desktop_control_callback(desktop_control_callback)
         .with_logging(ffmpeg_log_level, ingest_log_level)
@@ -455,6 +678,8 @@ fn engine_output_config(
         .with_channel_id(config.general.channel_id)
         .with_stream_type(config.output.stream_type.engine_stream_type())
         .with_stream_format(config.output.stream_format.clone())
+        .with_clip_metadata(config.output.clip_metadata)
+        .with_audio_only(audio_only)
         .with_encoding(
             config.output.video_codec.clone(),
             config.output.video_options.clone(),
@@ -0,0 +1,370 @@
+use std::time::Duration;
+
+use ff_engine::{ClipEvent, ClipEventAction, ClipOffset};
+use log::*;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sqlx::{Pool, Sqlite};
+use tokio::{
+    task::JoinHandle,
+    time::{Instant, sleep_until},
+};
+
+use crate::{
+    db::handles,
+    player::utils::{Media, sec_to_time},
+    utils::text::text_config,
+};
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Secondary event of a playlist node, like a lower third five seconds into
+/// the clip or a webhook three seconds before it ends.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SecondaryEvent {
+    /// `+ss`, `+mm:ss` or `+hh:mm:ss` after the clip start, `-` before its
+    /// end.
+    pub at: String,
+    /// Seconds a text or logo change lasts; without it the change lasts until
+    /// the clip ends.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    #[serde(flatten)]
+    pub action: SecondaryAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum SecondaryAction {
+    /// Show a text preset, optionally with another text.
+    Text {
+        preset: i32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text: Option<String>,
+    },
+    Logo {
+        visible: bool,
+    },
+    /// POST the clip details as JSON to the URL.
+    Webhook {
+        url: String,
+    },
+}
+
+impl SecondaryEvent {
+    pub fn offset(&self) -> Result<ClipOffset, String> {
+        parse_offset(&self.at)
+    }
+
+    /// Checks the event against the length of its clip.
+    pub fn validate(&self, clip_length: f64) -> Result<(), String> {
+        let seconds = match self.offset()? {
+            ClipOffset::Start(seconds) | ClipOffset::End(seconds) => seconds,
+        };
+        if clip_length > 0.0 && seconds > clip_length {
+            return Err(format!(
+                "event at {} is outside of the clip length {}",
+                self.at,
+                sec_to_time(clip_length)
+            ));
+        }
+        if self
+            .duration
+            .is_some_and(|duration| !duration.is_finite() || duration <= 0.0)
+        {
+            return Err(format!("event at {} needs a positive duration", self.at));
+        }
+        if let SecondaryAction::Webhook { url } = &self.action {
+            let parsed =
+                Url::parse(url).map_err(|error| format!("invalid webhook {url}: {error}"))?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err(format!("webhook {url} must use http or https"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses `+5`, `+00:05`, `-00:00:03.5`; without a sign the time counts from
+/// the clip start.
+pub fn parse_offset(value: &str) -> Result<ClipOffset, String> {
+    let value = value.trim();
+    let (from_end, time) = match value.strip_prefix('-') {
+        Some(time) => (true, time),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let parts = time.split(':').collect::<Vec<_>>();
+    if parts.len() > 3 {
+        return Err(format!("invalid event time {value}"));
+    }
+
+    let mut seconds = 0.0;
+    for part in parts {
+        let number = part
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|number| number.is_finite() && *number >= 0.0)
+            .ok_or_else(|| format!("invalid event time {value}"))?;
+        seconds = seconds * 60.0 + number;
+    }
+
+    Ok(if from_end {
+        ClipOffset::End(seconds)
+    } else {
+        ClipOffset::Start(seconds)
+    })
+}
+
+/// Engine events of a node: its texts and logo changes, which are drawn on
+/// the video. Events that are invalid or can't be built are logged and left
+/// out; times from the start follow a clip joined in the middle.
+pub async fn engine_events(pool: &Pool<Sqlite>, channel_id: i32, node: &Media) -> Vec<ClipEvent> {
+    let mut events = vec![];
+
+    for event in &node.events {
+        if matches!(event.action, SecondaryAction::Webhook { .. }) {
+            continue;
+        }
+        let at = match checked_offset(channel_id, node, event) {
+            Some(ClipOffset::Start(seconds)) => ClipOffset::Start(seconds - node.resume),
+            Some(offset) => offset,
+            None => continue,
+        };
+        let action = match &event.action {
+            SecondaryAction::Text { preset, text } => {
+                match handles::select_preset(pool, channel_id, *preset).await {
+                    Ok(preset) => ClipEventAction::Text(Box::new(text_config(
+                        &preset,
+                        text.clone(),
+                        preset.use_filename,
+                    ))),
+                    Err(error) => {
+                        error!(channel = channel_id;
+                            "Secondary event of <span class=\"log-addr\">{}</span>: text preset {preset}: {error}",
+                            node.source
+                        );
+                        continue;
+                    }
+                }
+            }
+            SecondaryAction::Logo { visible } => ClipEventAction::Logo { visible: *visible },
+            SecondaryAction::Webhook { .. } => continue,
+        };
+
+        events.push(ClipEvent {
+            at,
+            duration: event.duration,
+            action,
+        });
+    }
+
+    events
+}
+
+/// Sends the webhooks of a node, timed from now, when the clip starts. They
+/// don't depend on the picture, so they run on every output, also for
+/// audio-only outputs, generators and live events. `duration` is the time
+/// the clip plays; the task is aborted when the clip ends.
+pub fn spawn_webhooks(
+    channel_id: i32,
+    node: &Media,
+    duration: Option<f64>,
+) -> Option<JoinHandle<()>> {
+    let webhooks = due_webhooks(channel_id, node, duration);
+    if webhooks.is_empty() {
+        return None;
+    }
+
+    let start = Instant::now();
+    let webhooks = webhooks
+        .into_iter()
+        .map(|(delay, url, at)| (start + delay, url, webhook_payload(channel_id, node, at)))
+        .collect::<Vec<_>>();
+
+    Some(tokio::spawn(async move {
+        for (due, url, payload) in webhooks {
+            sleep_until(due).await;
+            // Sent on its own, so the end of the clip doesn't cut it off.
+            tokio::spawn(send_webhook(channel_id, url, payload));
+        }
+    }))
+}
+
+/// Webhooks of a node with their delay from the clip start, in order.
+/// Webhooks that were due before playout joined the clip are left out.
+fn due_webhooks<'a>(
+    channel_id: i32,
+    node: &'a Media,
+    duration: Option<f64>,
+) -> Vec<(Duration, String, &'a str)> {
+    let mut webhooks = vec![];
+
+    for event in &node.events {
+        let SecondaryAction::Webhook { url } = &event.action else {
+            continue;
+        };
+        let delay = match checked_offset(channel_id, node, event) {
+            Some(ClipOffset::Start(seconds)) => seconds - node.resume,
+            Some(ClipOffset::End(seconds)) => match duration {
+                Some(duration) => duration - seconds,
+                None => continue,
+            },
+            None => continue,
+        };
+        if delay >= 0.0 {
+            webhooks.push((
+                Duration::from_secs_f64(delay),
+                url.clone(),
+                event.at.as_str(),
+            ));
+        }
+    }
+    webhooks.sort_by_key(|(delay, _, _)| *delay);
+
+    webhooks
+}
+
+/// Offset of a valid event; an invalid one is logged.
+fn checked_offset(channel_id: i32, node: &Media, event: &SecondaryEvent) -> Option<ClipOffset> {
+    let clip_length = node.out - node.seek + node.resume;
+
+    match event.validate(clip_length).and_then(|()| event.offset()) {
+        Ok(offset) => Some(offset),
+        Err(error) => {
+            error!(channel = channel_id; "Secondary event of <span class=\"log-addr\">{}</span>: {error}", node.source);
+            None
+        }
+    }
+}
+
+fn webhook_payload(channel_id: i32, node: &Media, at: &str) -> Value {
+    json!({
+        "channel": channel_id,
+        "at": at,
+        "source": node.source,
+        "title": node.title,
+        "category": node.category,
+    })
+}
+
+async fn send_webhook(channel_id: i32, url: String, payload: Value) {
+    let response = reqwest::Client::new()
+        .post(&url)
+        .json(&payload)
+        .timeout(WEBHOOK_TIMEOUT)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status);
+
+    match response {
+        Ok(_) => {
+            debug!(channel = channel_id; "Webhook sent to <span class=\"log-addr\">{url}</span>")
+        }
+        Err(error) => {
+            warn!(channel = channel_id; "Webhook to <span class=\"log-addr\">{url}</span> failed: {error}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ff_engine::ClipOffset;
+
+    use super::{SecondaryAction, SecondaryEvent, due_webhooks, parse_offset};
+    use crate::player::utils::Media;
+
+    #[test]
+    fn parses_offsets_from_start_and_end() {
+        assert_eq!(parse_offset("+00:05"), Ok(ClipOffset::Start(5.0)));
+        assert_eq!(parse_offset("1.5"), Ok(ClipOffset::Start(1.5)));
+        assert_eq!(parse_offset("-00:00:03.5"), Ok(ClipOffset::End(3.5)));
+        assert_eq!(parse_offset("+01:00:00"), Ok(ClipOffset::Start(3600.0)));
+        assert!(parse_offset("+1:2:3:4").is_err());
+        assert!(parse_offset("soon").is_err());
+        assert!(parse_offset("+-5").is_err());
+    }
+
+    #[test]
+    fn events_round_trip_and_validate() {
+        let events: Vec<SecondaryEvent> = serde_json::from_str(
+            r#"[
+                {"at": "+00:05", "duration": 10, "action": "text", "preset": 3},
+                {"at": "-00:03", "action": "logo", "visible": false},
+                {"at": "+0", "action": "webhook", "url": "https://example.org/hook"}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            events[0].action,
+            SecondaryAction::Text {
+                preset: 3,
+                text: None
+            }
+        );
+        assert!(events.iter().all(|event| event.validate(60.0).is_ok()));
+        assert!(events[0].validate(4.0).is_err());
+
+        let json = serde_json::to_value(&events[1]).unwrap();
+        assert_eq!(json["action"], "logo");
+        assert!(json.get("duration").is_none());
+
+        let ftp = SecondaryEvent {
+            action: SecondaryAction::Webhook {
+                url: "ftp://example.org".to_string(),
+            },
+            ..events[2].clone()
+        };
+        assert!(ftp.validate(60.0).is_err());
+    }
+
+    #[test]
+    fn webhooks_are_timed_from_the_clip_start() {
+        let hook = |at: &str, url: &str| SecondaryEvent {
+            at: at.to_string(),
+            duration: None,
+            action: SecondaryAction::Webhook {
+                url: url.to_string(),
+            },
+        };
+        let mut node = Media {
+            seek: 20.0,
+            out: 60.0,
+            resume: 20.0,
+            events: vec![
+                hook("-00:05", "https://example.org/end"),
+                hook("+00:30", "https://example.org/middle"),
+                hook("+00:10", "https://example.org/missed"),
+                hook("+02:00", "https://example.org/outside"),
+                hook("+00:40", "ftp://example.org/invalid"),
+            ],
+            ..Media::default()
+        };
+
+        // Joined 20 seconds in, 40 seconds are left.
+        let due = due_webhooks(1, &node, Some(40.0));
+        assert_eq!(
+            due,
+            [
+                (
+                    Duration::from_secs(10),
+                    "https://example.org/middle".to_string(),
+                    "+00:30"
+                ),
+                (
+                    Duration::from_secs(35),
+                    "https://example.org/end".to_string(),
+                    "-00:05"
+                ),
+            ]
+        );
+
+        node.events.truncate(1);
+        assert!(due_webhooks(1, &node, None).is_empty());
+    }
+}
@@ -83,7 +83,7 @@ pub fn set_defaults(config: &PlayoutConfig, playlist: &mut JsonPlaylist) {
         item.skip = false;
 
         let source_path = Path::new(&item.source);
-        if source_path.is_relative() {
+        if item.live.is_none() && source_path.is_relative() {
             let new_path = config.storage.path.join(source_path);
             item.source = new_path.to_string_lossy().to_string();
         }
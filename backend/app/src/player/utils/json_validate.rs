@@ -7,6 +7,7 @@ use std::{
     time::Instant,
 };
 
+use ff_engine::{is_generator_source, validate_generator_source};
 use log::*;
 use tokio::{
     fs::File,
@@ -54,6 +55,16 @@ async fn check_media(
         error_list.push("Engine probe returned no media metadata".to_string());
     }
 
+    if let Some(bed) = node
+        .image
+        .as_ref()
+        .and_then(|image| image.audio_bed_path(&config.storage.path))
+        && !is_remote(&bed)
+        && !Path::new(&bed).is_file()
+    {
+        error_list.push(format!("Audio bed not found: {bed}"));
+    }
+
     if !node.audio.is_empty() && node.probe_audio.is_none() {
         error_list.push(format!(
             "Engine probe returned no metadata for external audio: {}",
@@ -149,6 +160,7 @@ async fn check_vtt(source: &str, duration: f64, channel_id: i32) -> Result<(), P
 ///
 /// - the source files are existing
 /// - file can be read by the engine probe and metadata exists
+/// - secondary events fit into their clip
 /// - total playtime fits target length from config
 ///
 /// This function we run in a thread, to don't block the main function.
@@ -188,7 +200,35 @@ pub async fn validate_playlist(
             continue;
         }
 
-        if !is_remote(&item.source) {
+        for event in &item.events {
+            if let Err(e) = event.validate(item.out - item.seek) {
+                error!(channel = id;
+                    "<span class=\"log-gray\">[Validation]</span> Secondary event on position <span class=\"log-number\">{pos:0>3}</span> - <span class=\"log-number\">{}</span>: {e}",
+                    sec_to_time(begin)
+                );
+            }
+        }
+
+        if let Some(live) = &item.live {
+            if let Err(e) = live.validate(&item.source, item.out - item.seek, &config) {
+                error!(channel = id;
+                    "<span class=\"log-gray\">[Validation]</span> Live event on position <span class=\"log-number\">{pos:0>3}</span> - <span class=\"log-number\">{}</span>: {e}",
+                    sec_to_time(begin)
+                );
+            }
+
+            begin += item.out - item.seek;
+            continue;
+        }
+
+        if is_generator_source(&item.source) {
+            if let Err(e) = validate_generator_source(&item.source) {
+                error!(channel = id;
+                    "<span class=\"log-gray\">[Validation]</span> Error on position <span class=\"log-number\">{pos:0>3}</span> - <span class=\"log-number\">{}</span>: {e}",
+                    sec_to_time(begin)
+                );
+            }
+        } else if !is_remote(&item.source) {
             let source_path = Path::new(&item.source);
             if source_path.is_relative() {
                 let new_path = config.storage.path.join(source_path);
@@ -0,0 +1,109 @@
+use std::sync::atomic::Ordering;
+
+use ff_engine::{LiveBlock, is_pull_url};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    player::{controller::ChannelManager, utils::Media},
+    utils::config::PlayoutConfig,
+};
+
+/// Source of a live node that takes the channel's own ingest.
+pub const INGEST_SOURCE: &str = "ingest";
+const SLATE_SOURCE: &str = "generator://slate";
+
+/// Makes a playlist node a live event: from its begin on, the live input of
+/// the node source is on air for the node length.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LiveSettings {
+    #[serde(default)]
+    pub absent: LiveAbsent,
+}
+
+/// What plays while the live feed is missing.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LiveAbsent {
+    /// The slate, the live block keeps its time slot.
+    #[default]
+    Slate,
+    /// The filler, so the program goes on until the feed shows up.
+    Continue,
+    /// The slate, and the node length counts from the moment the feed shows
+    /// up.
+    Wait,
+}
+
+impl LiveSettings {
+    pub fn validate(
+        &self,
+        source: &str,
+        length: f64,
+        config: &PlayoutConfig,
+    ) -> Result<(), String> {
+        if length <= 0.0 {
+            return Err(format!("live event {source} needs a length"));
+        }
+        if source == INGEST_SOURCE {
+            if !config.ingest.enable {
+                return Err("live event takes the ingest, but the ingest is off".to_string());
+            }
+        } else if !is_pull_url(source) {
+            return Err(format!(
+                "live event source {source} must be '{INGEST_SOURCE}' or a stream URL"
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Engine block of a live node. Blocks that join in the middle only get the
+/// rest of their slot.
+pub async fn engine_live_block(
+    manager: &ChannelManager,
+    config: &PlayoutConfig,
+    node: &Media,
+    live: LiveSettings,
+) -> LiveBlock {
+    let standby = match live.absent {
+        LiveAbsent::Continue => filler_source(manager, config).await,
+        LiveAbsent::Slate | LiveAbsent::Wait => None,
+    };
+
+    LiveBlock {
+        url: (node.source != INGEST_SOURCE).then(|| node.source.clone()),
+        standby: standby.unwrap_or_else(|| SLATE_SOURCE.to_string()),
+        duration_seconds: node.out - node.seek,
+        wait: live.absent == LiveAbsent::Wait,
+    }
+}
+
+async fn filler_source(manager: &ChannelManager, config: &PlayoutConfig) -> Option<String> {
+    if config.storage.filler_path.is_file() {
+        return Some(config.storage.filler_path.to_string_lossy().to_string());
+    }
+
+    let fillers = manager.filler_list.lock().await;
+    if fillers.is_empty() {
+        return None;
+    }
+    let index = manager.filler_index.fetch_add(1, Ordering::SeqCst) % fillers.len();
+
+    Some(fillers[index].source.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LiveAbsent, LiveSettings};
+
+    #[test]
+    fn absent_behavior_defaults_to_the_slate() {
+        let live: LiveSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(live.absent, LiveAbsent::Slate);
+
+        let live: LiveSettings = serde_json::from_str(r#"{"absent": "wait"}"#).unwrap();
+        assert_eq!(live.absent, LiveAbsent::Wait);
+        assert!(serde_json::from_str::<LiveSettings>(r#"{"absent": "loop"}"#).is_err());
+    }
+}
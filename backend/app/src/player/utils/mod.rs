@@ -10,6 +10,7 @@ use std::{
 
 use chrono::{TimeDelta, prelude::*};
 use chrono_tz::Tz;
+use ff_engine::is_generator_source;
 use log::*;
 use regex::Regex;
 use reqwest::header;
@@ -20,9 +21,13 @@ use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
 };
 
+pub mod events;
 pub mod import;
 pub mod json_serializer;
 pub mod json_validate;
+pub mod live;
+pub mod publish;
+pub mod record;
 
 use crate::{
     player::controller::{
@@ -35,7 +40,9 @@ use crate::{
         time_machine::time_now,
     },
 };
+pub use events::SecondaryEvent;
 pub use json_serializer::{JsonPlaylist, read_json};
+pub use live::LiveSettings;
 
 pub type MediaProbe = ff_engine::EngineMediaProbe;
 pub type SilenceDetection = ff_engine::SilenceDetection;
@@ -135,6 +142,22 @@ pub async fn get_data_map(manager: &ChannelManager) -> Map<String, Value> {
             }),
         );
     }
+    // Signal of the connected feed; with backups, of the one on air.
+    let endpoint = manager.ingest_failover.on_air().unwrap_or(0);
+    if let Some(signal) = manager.signal_monitor.report(endpoint) {
+        data_map.insert(
+            "signal".to_string(),
+            json!({
+                "black": (signal.black * 10.0).round() / 10.0,
+                "frozen": (signal.frozen * 10.0).round() / 10.0,
+                "silent": (signal.silent * 10.0).round() / 10.0,
+                "clipping": (signal.clipping * 10_000.0).round() / 10_000.0,
+                "dropped_frames": signal.dropped_frames,
+                "bitrate": signal.bitrate,
+                "failed": signal.failed,
+            }),
+        );
+    }
 
     data_map
 }
@@ -157,6 +180,11 @@ pub struct Media {
     #[serde(skip_serializing, skip_deserializing)]
     pub duration_audio: f64,
 
+    /// Seconds the clip starts past its `in` point, when playout joins it
+    /// in the middle.
+    #[serde(skip_serializing, skip_deserializing)]
+    pub resume: f64,
+
     #[serde(
         default,
         deserialize_with = "null_string",
@@ -176,6 +204,21 @@ pub struct Media {
     #[serde(default, skip_serializing_if = "is_empty_string")]
     pub custom_filter: String,
 
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<ImageSettings>,
+
+    /// Names of branding rules applied on top of the category rules.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub branding: Vec<String>,
+
+    /// Texts, logo changes and webhooks timed against the clip.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<SecondaryEvent>,
+
+    /// Makes the node a live event, its source is `ingest` or a stream URL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub live: Option<LiveSettings>,
+
     #[serde(skip_serializing, skip_deserializing)]
     pub probe: Option<MediaProbe>,
 
@@ -198,13 +241,105 @@ pub struct Media {
     pub unit: ProcessUnit,
 }
 
+/// Per-node settings for still images: a Ken Burns move and an audio bed.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ImageSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<ImageRect>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<ImageRect>,
+    #[serde(default)]
+    pub easing: ImageEasing,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_bed: Option<String>,
+}
+
+/// Image region as fractions of the image size.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ImageRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageEasing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl From<ImageEasing> for ff_engine::MotionEasing {
+    fn from(easing: ImageEasing) -> Self {
+        match easing {
+            ImageEasing::Linear => Self::Linear,
+            ImageEasing::EaseIn => Self::EaseIn,
+            ImageEasing::EaseOut => Self::EaseOut,
+            ImageEasing::EaseInOut => Self::EaseInOut,
+        }
+    }
+}
+
+impl From<ff_engine::MotionEasing> for ImageEasing {
+    fn from(easing: ff_engine::MotionEasing) -> Self {
+        match easing {
+            ff_engine::MotionEasing::Linear => Self::Linear,
+            ff_engine::MotionEasing::EaseIn => Self::EaseIn,
+            ff_engine::MotionEasing::EaseOut => Self::EaseOut,
+            ff_engine::MotionEasing::EaseInOut => Self::EaseInOut,
+        }
+    }
+}
+
+impl ImageSettings {
+    /// Audio bed path, relative paths are resolved against the storage.
+    pub fn audio_bed_path(&self, storage: &Path) -> Option<String> {
+        let bed = self.audio_bed.as_deref()?.trim();
+        if bed.is_empty() {
+            None
+        } else if is_remote(bed) || Path::new(bed).is_absolute() {
+            Some(bed.to_string())
+        } else {
+            Some(storage.join(bed).to_string_lossy().to_string())
+        }
+    }
+
+    pub fn engine_options(&self, storage: &Path) -> ff_engine::ImageOptions {
+        let motion = (self.start.is_some() || self.end.is_some()).then(|| ff_engine::ImageMotion {
+            start: self.start.map(ImageRect::engine_rect).unwrap_or_default(),
+            end: self.end.map(ImageRect::engine_rect).unwrap_or_default(),
+            easing: self.easing.into(),
+        });
+
+        ff_engine::ImageOptions {
+            motion,
+            audio_bed: self.audio_bed_path(storage),
+        }
+    }
+}
+
+impl ImageRect {
+    fn engine_rect(self) -> ff_engine::MotionRect {
+        ff_engine::MotionRect {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
 impl Media {
     pub async fn new(index: usize, src: &str, do_probe: bool) -> Self {
         let mut duration = 0.0;
         let mut probe = None;
 
         if do_probe
-            && (is_remote(src) || Path::new(src).is_file())
+            && (is_remote(src) || is_generator_source(src) || Path::new(src).is_file())
             && let Ok(p) = probe_media(src).await
         {
             probe = Some(p.clone());
@@ -220,10 +355,15 @@ impl Media {
             out: duration,
             duration,
             duration_audio: 0.0,
+            resume: 0.0,
             category: String::new(),
             source: src.to_string(),
             audio: String::new(),
             custom_filter: String::new(),
+            image: None,
+            branding: vec![],
+            events: vec![],
+            live: None,
             probe,
             probe_audio: None,
             last_ad: false,
@@ -289,10 +429,15 @@ impl Default for Media {
             out: 0.0,
             duration: 0.0,
             duration_audio: 0.0,
+            resume: 0.0,
             category: String::new(),
             source: String::new(),
             audio: String::new(),
             custom_filter: String::new(),
+            image: None,
+            branding: vec![],
+            events: vec![],
+            live: None,
             probe: None,
             probe_audio: None,
             last_ad: false,
@@ -642,7 +787,9 @@ pub fn custom_format<T: fmt::Display>(template: &str, args: &[T]) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::custom_format;
+    use std::path::Path;
+
+    use super::{ImageEasing, ImageSettings, Media, custom_format};
 
     #[test]
     fn custom_format_keeps_escaped_braces_without_args() {
@@ -675,4 +822,37 @@ mod tests {
         assert_eq!(custom_format("{0", &["foo"]), "{0");
         assert_eq!(custom_format("}", &["foo"]), "}");
     }
+
+    #[test]
+    fn image_settings_map_to_engine_options() {
+        let node: Media = serde_json::from_str(
+            r#"{
+                "in": 0,
+                "out": 10,
+                "duration": 10,
+                "source": "photos/harbor.jpg",
+                "image": {
+                    "end": { "x": 0.25, "y": 0.25, "width": 0.5, "height": 0.5 },
+                    "easing": "ease_in_out",
+                    "audio_bed": "music/bed.mp3"
+                }
+            }"#,
+        )
+        .unwrap();
+        let image = node.image.unwrap();
+        assert_eq!(image.easing, ImageEasing::EaseInOut);
+
+        let options = image.engine_options(Path::new("/storage"));
+        let motion = options.motion.unwrap();
+        assert_eq!(motion.start, ff_engine::MotionRect::default());
+        assert_eq!(motion.end.width, 0.5);
+        assert_eq!(options.audio_bed.as_deref(), Some("/storage/music/bed.mp3"));
+
+        assert!(
+            ImageSettings::default()
+                .engine_options(Path::new("/storage"))
+                .motion
+                .is_none()
+        );
+    }
 }
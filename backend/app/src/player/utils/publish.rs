@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use ff_engine::{PublishAuthorizer, Publisher};
+use log::*;
+use serde_json::{Value, json};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+    db::handles,
+    utils::config::{PlayoutConfig, url_address},
+};
+
+const ON_PUBLISH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Checks publishers on the ingest listeners against the channel's stream
+/// keys, then asks the `on_publish` URL. A channel without both only takes
+/// the key of its ingest URL, like the listeners do on their own. The label
+/// of the key the publisher sent is logged, and again when it goes on air.
+pub fn publish_authorizer(pool: Pool<Sqlite>, config: &PlayoutConfig) -> Option<PublishAuthorizer> {
+    if config.ingest.pull {
+        return None;
+    }
+
+    let runtime = tokio::runtime::Handle::current();
+    let id = config.general.channel_id;
+    let on_publish = config.ingest.on_publish.clone();
+
+    // Listeners wait for publishers on their own threads, outside the
+    // runtime, so blocking there is fine.
+    Some(PublishAuthorizer::new(move |publisher| {
+        let result = runtime.block_on(authorize(&pool, id, &on_publish, publisher));
+        if let Err(reason) = &result {
+            warn!(channel = id;
+                "Publisher on <span class=\"log-addr\">{}</span> rejected: {reason}",
+                url_address(&publisher.listener)
+            );
+        }
+
+        result
+    }))
+}
+
+async fn authorize(
+    pool: &Pool<Sqlite>,
+    id: i32,
+    on_publish: &str,
+    publisher: &Publisher,
+) -> Result<Option<String>, String> {
+    if let Some(key) = &publisher.key {
+        let stream_key = handles::select_valid_stream_key(pool, id, key, Utc::now().timestamp())
+            .await
+            .map_err(|error| format!("stream keys can not be read: {error}"))?;
+        if let Some(stream_key) = stream_key {
+            info!(channel = id;
+                "Stream key <b>{}</b> connected on <span class=\"log-addr\">{}</span>",
+                stream_key.label,
+                url_address(&publisher.listener)
+            );
+            return Ok(Some(stream_key.label));
+        }
+    }
+
+    if !on_publish.is_empty() {
+        return ask_on_publish(id, on_publish, publisher).await;
+    }
+
+    let keys = handles::count_stream_keys(pool, id)
+        .await
+        .map_err(|error| format!("stream keys can not be read: {error}"))?;
+    if keys > 0 {
+        return Err("unknown or expired stream key".to_string());
+    }
+
+    match (&publisher.key, &publisher.expected_key) {
+        (Some(key), Some(expected)) if key != expected => {
+            Err("stream key does not match the ingest URL".to_string())
+        }
+        (None, Some(_)) => Err("publisher sent no stream key".to_string()),
+        _ => Ok(None),
+    }
+}
+
+/// Any 2xx answer lets the publisher on. A JSON body with `label` names it
+/// in the logs.
+async fn ask_on_publish(
+    id: i32,
+    url: &str,
+    publisher: &Publisher,
+) -> Result<Option<String>, String> {
+    let response = reqwest::Client::new()
+        .post(url)
+        .json(&json!({
+            "channel": id,
+            "protocol": publisher.protocol,
+            "listener": url_address(&publisher.listener),
+            "key": publisher.key,
+        }))
+        .timeout(ON_PUBLISH_TIMEOUT)
+        .send()
+        .await
+        .map_err(|error| format!("on_publish request failed: {error}"))?;
+
+    if !response.status().is_success() {
+        return Err(format!("on_publish answered {}", response.status()));
+    }
+
+    let label = response
+        .json::<Value>()
+        .await
+        .ok()
+        .and_then(|body| body.get("label")?.as_str().map(str::to_string));
+    info!(channel = id;
+        "Publisher <b>{}</b> authorized by on_publish on <span class=\"log-addr\">{}</span>",
+        label.as_deref().unwrap_or_default(),
+        url_address(&publisher.listener)
+    );
+
+    Ok(label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::StreamKey;
+
+    fn publisher(key: Option<&str>, expected_key: Option<&str>) -> Publisher {
+        Publisher {
+            protocol: "rtmp",
+            listener: "rtmp://127.0.0.1:1936/live/stream".to_string(),
+            key: key.map(str::to_string),
+            expected_key: expected_key.map(str::to_string),
+        }
+    }
+
+    async fn memory_pool() -> Pool<Sqlite> {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        handles::db_migrate(&pool).await.unwrap();
+
+        pool
+    }
+
+    #[tokio::test]
+    async fn ingest_url_key_is_required_without_stream_keys() {
+        let pool = memory_pool().await;
+        let check = |key, expected| {
+            let pool = pool.clone();
+            async move { authorize(&pool, 1, "", &publisher(key, expected)).await }
+        };
+
+        assert_eq!(check(Some("stream"), Some("stream")).await, Ok(None));
+        assert_eq!(check(None, None).await, Ok(None));
+        assert!(check(Some("other"), Some("stream")).await.is_err());
+        assert!(check(None, Some("stream")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn stream_keys_replace_the_ingest_url_key() {
+        let pool = memory_pool().await;
+        let now = Utc::now().timestamp();
+        for (label, key, expires_at) in [
+            ("Reporter 1", "reporter-key-1", None),
+            ("Reporter 2", "reporter-key-2", Some(now - 60)),
+        ] {
+            let key = StreamKey {
+                channel_id: 1,
+                label: label.to_string(),
+                key: key.to_string(),
+                expires_at,
+                created_at: now,
+                ..StreamKey::default()
+            };
+            handles::insert_stream_key(&pool, key).await.unwrap();
+        }
+        let check = |key| {
+            let pool = pool.clone();
+            async move { authorize(&pool, 1, "", &publisher(key, Some("stream"))).await }
+        };
+
+        assert_eq!(
+            check(Some("reporter-key-1")).await,
+            Ok(Some("Reporter 1".to_string()))
+        );
+        assert!(check(Some("reporter-key-2")).await.is_err());
+        assert!(check(Some("stream")).await.is_err());
+        assert!(check(None).await.is_err());
+        assert!(
+            authorize(&pool, 2, "", &publisher(Some("reporter-key-1"), None))
+                .await
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use std::path::{Path, PathBuf};
+
+use ff_engine::{LiveRecorder, RecordingCallback, RecordingPath};
+use log::*;
+use tokio::fs;
+
+use crate::{
+    player::utils::probe_media,
+    utils::{config::PlayoutConfig, time_machine::time_now},
+};
+
+/// Sets up recording of the ingest into the record folder of the channel
+/// storage, with the start time in the channel's time zone as name.
+///
+/// A recording is written as `.mkv.part` and only renamed once it probes
+/// fine, so the folder watcher and the playlist generator see finished
+/// files only.
+pub fn live_recorder(recorder: &LiveRecorder, config: &PlayoutConfig) {
+    if !config.ingest.enable || !config.ingest.record {
+        recorder.set_path(None);
+        return;
+    }
+
+    let id = config.general.channel_id;
+    let folder = config.channel.storage.join(&config.ingest.record_folder);
+    let timezone = config.channel.timezone;
+    let runtime = tokio::runtime::Handle::current();
+
+    recorder.set_path(Some(RecordingPath::new(move || {
+        if let Err(error) = std::fs::create_dir_all(&folder) {
+            error!(channel = id;
+                "Record folder <span class=\"log-addr\">{}</span> can not be created: {error}",
+                folder.display()
+            );
+            return None;
+        }
+
+        let name = time_now(&timezone).format("live_%Y-%m-%d_%H-%M-%S");
+        Some(recording_path(&folder, &name.to_string()))
+    })));
+    recorder.set_callback(RecordingCallback::new(move |path| {
+        runtime.spawn(async move {
+            finish_recording(id, path).await;
+        });
+    }));
+}
+
+/// Part file for `name`, numbered when a recording started in the same
+/// second.
+pub(crate) fn recording_path(folder: &Path, name: &str) -> PathBuf {
+    (1..)
+        .map(|number| match number {
+            1 => folder.join(format!("{name}.mkv.part")),
+            _ => folder.join(format!("{name}_{number}.mkv.part")),
+        })
+        .find(|path| !path.exists() && !path.with_extension("").exists())
+        .unwrap_or_else(|| folder.join(format!("{name}.mkv.part")))
+}
+
+/// Probes a part file and renames it, returns the recording and its length
+/// in seconds.
+pub(crate) async fn finish_recording(id: i32, path: PathBuf) -> Option<(PathBuf, f64)> {
+    let finished = path.with_extension("");
+    let duration = match probe_media(&path).await {
+        Ok(probe) => probe.format.duration.unwrap_or_default(),
+        Err(error) => {
+            warn!(channel = id;
+                "Recording <span class=\"log-addr\">{}</span> can not be probed, it stays a part file: {error}",
+                path.display()
+            );
+            return None;
+        }
+    };
+
+    if duration <= 0.0 {
+        warn!(channel = id;
+            "Recording <span class=\"log-addr\">{}</span> is empty, delete it",
+            path.display()
+        );
+        if let Err(error) = fs::remove_file(&path).await {
+            error!(channel = id; "Empty recording can not be deleted: {error}");
+        }
+        return None;
+    }
+
+    if let Err(error) = fs::rename(&path, &finished).await {
+        error!(channel = id;
+            "Recording <span class=\"log-addr\">{}</span> can not be renamed: {error}",
+            path.display()
+        );
+        return None;
+    }
+
+    info!(channel = id;
+        "Recording <span class=\"log-addr\">{}</span> saved, <span class=\"log-number\">{duration:.2}</span> seconds",
+        finished.display()
+    );
+
+    Some((finished, duration))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::recording_path;
+
+    #[test]
+    fn recordings_of_the_same_second_are_numbered() {
+        let folder = std::env::temp_dir().join(format!("record-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&folder).unwrap();
+
+        let first = recording_path(&folder, "live_2026-10-19_18-00-00");
+        assert_eq!(first, folder.join("live_2026-10-19_18-00-00.mkv.part"));
+        fs::write(first.with_extension(""), b"").unwrap();
+
+        let second = recording_path(&folder, "live_2026-10-19_18-00-00");
+        assert_eq!(second, folder.join("live_2026-10-19_18-00-00_2.mkv.part"));
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
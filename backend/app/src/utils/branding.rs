@@ -0,0 +1,162 @@
+use std::path::Path;
+
+use ff_engine::{BrandingIcon, ClipBranding, LogoConfig};
+
+use crate::{
+    db::models::BrandingRule, file::norm_abs_path, player::utils::Media,
+    utils::errors::ServiceError,
+};
+
+impl BrandingRule {
+    /// Stores the logo and icon relative to the channel storage and checks
+    /// that they exist.
+    pub fn normalize_paths(&mut self, storage: &Path) -> Result<(), ServiceError> {
+        for path in [&mut self.logo, &mut self.icon] {
+            if path.trim().is_empty() {
+                path.clear();
+                continue;
+            }
+            let (file, _, relative) = norm_abs_path(storage, path)?;
+            if !file.is_file() {
+                return Err(ServiceError::BadRequest(format!(
+                    "Branding image {path} not found"
+                )));
+            }
+            *path = relative;
+        }
+
+        Ok(())
+    }
+}
+
+/// Engine branding for a playlist node. Rules of its category apply before
+/// the rules the node names, so a named rule's logo wins.
+pub fn clip_branding(
+    rules: &[BrandingRule],
+    node: &Media,
+    storage: &Path,
+) -> Result<Option<ClipBranding>, ServiceError> {
+    let mut matched: Vec<&BrandingRule> = rules
+        .iter()
+        .filter(|rule| !rule.category.is_empty() && rule.category == node.category)
+        .collect();
+    for name in &node.branding {
+        if let Some(rule) = rules.iter().find(|rule| &rule.name == name)
+            && !matched.iter().any(|matched| matched.id == rule.id)
+        {
+            matched.push(rule);
+        }
+    }
+    if matched.is_empty() {
+        return Ok(None);
+    }
+
+    let mut branding = ClipBranding::default();
+    for rule in matched {
+        if rule.hide_logo {
+            branding.hide_logo = true;
+        }
+        if !rule.logo.is_empty() {
+            branding.logo = Some(LogoConfig {
+                path: storage_path(storage, &rule.logo)?,
+                scale: (!rule.logo_scale.trim().is_empty()).then(|| rule.logo_scale.clone()),
+                opacity: rule.logo_opacity,
+                position: rule.logo_position.clone(),
+            });
+        }
+        if !rule.icon.is_empty() {
+            branding.icons.push(BrandingIcon {
+                image: LogoConfig {
+                    path: storage_path(storage, &rule.icon)?,
+                    scale: (!rule.icon_scale.trim().is_empty()).then(|| rule.icon_scale.clone()),
+                    opacity: rule.icon_opacity,
+                    position: rule.icon_position.clone(),
+                },
+                seconds: (rule.icon_seconds > 0.0).then_some(rule.icon_seconds - node.resume),
+            });
+        }
+    }
+
+    Ok(Some(branding))
+}
+
+fn storage_path(storage: &Path, path: &str) -> Result<String, ServiceError> {
+    let (file, _, _) = norm_abs_path(storage, path)?;
+
+    Ok(file.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::clip_branding;
+    use crate::{db::models::BrandingRule, player::utils::Media};
+
+    fn rule(id: i32, name: &str, category: &str) -> BrandingRule {
+        BrandingRule {
+            id,
+            name: name.to_string(),
+            category: category.to_string(),
+            ..BrandingRule::default()
+        }
+    }
+
+    #[test]
+    fn category_rules_apply_before_named_rules() {
+        let rules = vec![
+            BrandingRule {
+                hide_logo: true,
+                ..rule(1, "no-bug", "advertisement")
+            },
+            BrandingRule {
+                logo: "00-assets/kids.png".to_string(),
+                ..rule(2, "kids", "kids")
+            },
+            BrandingRule {
+                icon: "00-assets/fsk12.png".to_string(),
+                icon_seconds: 10.0,
+                ..rule(3, "fsk12", "")
+            },
+        ];
+        let storage = Path::new("/tv/storage");
+        let node = Media {
+            category: "kids".to_string(),
+            branding: vec!["fsk12".to_string(), "kids".to_string()],
+            ..Media::default()
+        };
+
+        let branding = clip_branding(&rules, &node, storage).unwrap().unwrap();
+
+        assert!(!branding.hide_logo);
+        assert_eq!(
+            branding.logo.unwrap().path,
+            "/tv/storage/00-assets/kids.png"
+        );
+        assert_eq!(branding.icons.len(), 1);
+        assert_eq!(branding.icons[0].seconds, Some(10.0));
+
+        let resumed = Media {
+            resume: 4.0,
+            ..node
+        };
+        let branding = clip_branding(&rules, &resumed, storage).unwrap().unwrap();
+        assert_eq!(branding.icons[0].seconds, Some(6.0));
+
+        let ad = Media {
+            category: "advertisement".to_string(),
+            ..Media::default()
+        };
+        assert!(
+            clip_branding(&rules, &ad, storage)
+                .unwrap()
+                .unwrap()
+                .hide_logo
+        );
+        assert!(
+            clip_branding(&rules, &Media::default(), storage)
+                .unwrap()
+                .is_none()
+        );
+    }
+}
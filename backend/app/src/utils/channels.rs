@@ -10,10 +10,13 @@ use crate::{
         models::{self, Channel},
     },
     player::controller::{ChannelController, ChannelManager},
-    utils::{config::get_config, errors::ServiceError, mail::MailQueue, system::SystemStat},
+    utils::{
+        config::get_config, errors::ServiceError, fonts::load_channel_fonts, mail::MailQueue,
+        system::SystemStat,
+    },
 };
 
-use super::config::{DEFAULT_INGEST_PORT, OutputMode, parse_rtmp_ingest_port};
+use super::config::{DEFAULT_INGEST_PORT, OutputMode, backup_urls, parse_ingest_port};
 
 async fn map_global_admins(conn: &Pool<Sqlite>) -> Result<(), ServiceError> {
     sqlx::query(
@@ -56,7 +59,10 @@ pub async fn initialize_channels(
             warn!("Could not copy initial storage assets: {error}");
         }
 
+        let storage = manager.config.read().await.channel.storage.clone();
+        load_channel_fonts(manager.id, &storage).await;
         queue.lock().await.push(mail_queue);
+        manager.spawn_recordings().await;
 
         if active {
             manager.start().await?;
@@ -100,6 +106,7 @@ pub async fn create_channel(
         error!("{e}");
     };
 
+    manager.spawn_recordings().await;
     controllers.write().await.add(manager);
     queue.lock().await.push(m_queue);
 
@@ -117,10 +124,18 @@ async fn create_channel_records(
     target_channel: Channel,
 ) -> Result<Channel, ServiceError> {
     let mut transaction = conn.begin().await?;
-    let configured_ingest_urls =
-        sqlx::query_scalar::<_, String>("SELECT ingest_url FROM configurations")
-            .fetch_all(&mut *transaction)
-            .await?;
+    let configured_ingest_urls = sqlx::query_as::<_, (String, String)>(
+        "SELECT ingest_url, ingest_backup_urls FROM configurations WHERE ingest_pull = 0",
+    )
+    .fetch_all(&mut *transaction)
+    .await?
+    .into_iter()
+    .flat_map(|(url, backups)| {
+        let mut urls = vec![url];
+        urls.extend(backup_urls(&backups).map(str::to_string));
+        urls
+    })
+    .collect::<Vec<_>>();
     let ingest_url = default_ingest_url(next_available_ingest_port(&configured_ingest_urls)?);
     let channel = handles::insert_channel(&mut *transaction, target_channel).await?;
     let outputs = [
@@ -161,7 +176,7 @@ fn default_ingest_url(port: u16) -> String {
 fn next_available_ingest_port(configured_urls: &[String]) -> Result<u16, ServiceError> {
     let used_ports = configured_urls
         .iter()
-        .filter_map(|url| parse_rtmp_ingest_port(url).ok())
+        .filter_map(|url| parse_ingest_port(url).ok())
         .collect::<HashSet<_>>();
 
     (DEFAULT_INGEST_PORT..=u16::MAX)
@@ -187,6 +202,7 @@ pub async fn delete_channel(
         manager.channel.lock().await.active = false;
         manager.stop_all(false).await;
         manager.stop_supervisor().await;
+        manager.stop_recordings().await;
     }
 
     handles::delete_channel(conn, &channel.id).await?;
@@ -0,0 +1,176 @@
+use std::path::{Path, PathBuf};
+
+use log::*;
+use serde::Serialize;
+use tokio::{fs, task::spawn_blocking};
+
+use crate::utils::errors::ServiceError;
+
+/// Folder in the channel storage with the fonts of the channel.
+pub const FONT_FOLDER: &str = "00-assets/fonts";
+pub const MAX_FONT_SIZE: usize = 32 * 1024 * 1024;
+const FONT_EXTENSIONS: [&str; 4] = ["ttf", "otf", "ttc", "otc"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FontFile {
+    pub name: String,
+    /// Font families in the file, empty when it could not be loaded.
+    pub families: Vec<String>,
+}
+
+/// Load the fonts from the storage of a channel.
+pub async fn load_channel_fonts(channel_id: i32, storage: &Path) {
+    for path in font_files(storage).await {
+        let file = path.clone();
+        match spawn_blocking(move || ff_engine::load_font_file(&file)).await {
+            Ok(Ok(families)) => {
+                debug!(channel = channel_id; "Loaded font {}: {}", path.display(), families.join(", "));
+            }
+            Ok(Err(error)) => warn!(channel = channel_id; "{error:#}"),
+            Err(error) => warn!(channel = channel_id; "Font loading stopped: {error}"),
+        }
+    }
+}
+
+pub async fn channel_fonts(storage: &Path) -> Vec<FontFile> {
+    let mut fonts = Vec::new();
+    for path in font_files(storage).await {
+        let file = path.clone();
+        let families = spawn_blocking(move || ff_engine::loaded_font_families(&file))
+            .await
+            .unwrap_or_default();
+        fonts.push(FontFile {
+            name: file_name(&path),
+            families,
+        });
+    }
+
+    fonts
+}
+
+/// Store a font in the channel storage and load it. A file with the same
+/// name is replaced; data that holds no usable font leaves it untouched.
+pub async fn save_font(storage: &Path, name: &str, data: &[u8]) -> Result<FontFile, ServiceError> {
+    let name = font_name(name)?;
+    let upload = data.to_vec();
+    spawn_blocking(move || ff_engine::font_data_families(&upload))
+        .await?
+        .map_err(|error| ServiceError::BadRequest(format!("Font {name}: {error:#}")))?;
+
+    let folder = storage.join(FONT_FOLDER);
+    fs::create_dir_all(&folder).await?;
+    let path = folder.join(&name);
+    // Loaded fonts can be mapped in memory, so the old file is replaced
+    // and not overwritten.
+    let upload = folder.join(format!(".{name}.upload"));
+    fs::write(&upload, data).await?;
+    fs::rename(&upload, &path).await?;
+
+    let file = path.clone();
+    match spawn_blocking(move || ff_engine::load_font_file(&file)).await? {
+        Ok(families) => {
+            info!("Font {name} added: {}", families.join(", "));
+            Ok(FontFile { name, families })
+        }
+        Err(error) => {
+            if let Err(error) = fs::remove_file(&path).await {
+                warn!("Could not remove unusable font {}: {error}", path.display());
+            }
+            Err(ServiceError::BadRequest(format!("{error:#}")))
+        }
+    }
+}
+
+pub async fn remove_font(storage: &Path, name: &str) -> Result<(), ServiceError> {
+    let path = storage.join(FONT_FOLDER).join(font_name(name)?);
+    if !path.is_file() {
+        return Err(ServiceError::NotFound(format!("Font {name} not found")));
+    }
+
+    let file = path.clone();
+    spawn_blocking(move || ff_engine::unload_font_file(&file)).await?;
+    fs::remove_file(&path).await?;
+
+    Ok(())
+}
+
+async fn font_files(storage: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let Ok(mut entries) = fs::read_dir(storage.join(FONT_FOLDER)).await else {
+        return files;
+    };
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if path.is_file() && font_name(&file_name(&path)).is_ok() {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    files
+}
+
+/// File name of a font, without folders and with a font extension.
+fn font_name(name: &str) -> Result<String, ServiceError> {
+    let name = sanitize_filename::sanitize(name.trim());
+    let extension = Path::new(&name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+
+    if !FONT_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(ServiceError::BadRequest(format!(
+            "Font must be a {} file",
+            FONT_EXTENSIONS.join(", ")
+        )));
+    }
+
+    Ok(name)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BRAND_FONT: &[u8] = include_bytes!("../../../../tests/assets/fonts/brand.ttf");
+
+    #[test]
+    fn font_names_are_sanitized() {
+        assert_eq!(font_name("Brand Bold.TTF").unwrap(), "Brand Bold.TTF");
+        assert!(!font_name("../../etc/brand.otf").unwrap().contains('/'));
+        assert!(font_name("logo.png").is_err());
+        assert!(font_name("fonts").is_err());
+    }
+
+    #[tokio::test]
+    async fn broken_upload_keeps_the_loaded_font() {
+        let storage = std::env::temp_dir().join(format!(
+            "ffplayout-font-test-{}-{}",
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        let path = storage.join(FONT_FOLDER).join("brand.ttf");
+
+        let saved = save_font(&storage, "brand.ttf", BRAND_FONT).await.unwrap();
+        assert_eq!(saved.families, ["Channel Brand"]);
+
+        assert!(
+            save_font(&storage, "brand.ttf", b"not a font")
+                .await
+                .is_err()
+        );
+        assert_eq!(fs::read(&path).await.unwrap(), BRAND_FONT);
+        assert_eq!(ff_engine::loaded_font_families(&path), ["Channel Brand"]);
+
+        remove_font(&storage, "brand.ttf").await.unwrap();
+        fs::remove_dir_all(&storage).await.unwrap();
+    }
+}
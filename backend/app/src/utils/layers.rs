@@ -0,0 +1,220 @@
+use std::path::Path;
+
+use ff_engine::{
+    AnimationConfig, GraphicLayer, GraphicLayerContent, GraphicLayerUpdate, LogoConfig,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::models::TextPreset,
+    file::norm_abs_path,
+    utils::{errors::ServiceError, text::text_config},
+};
+
+/// Graphic layer as sent to the control API.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LayerParams {
+    #[serde(default)]
+    pub z_index: i32,
+    #[serde(default = "default_opacity")]
+    pub opacity: f64,
+    #[serde(default = "default_visible")]
+    pub visible: bool,
+    #[serde(default)]
+    pub fade_in_seconds: f64,
+    #[serde(default)]
+    pub fade_out_seconds: f64,
+    pub content: LayerContent,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LayerContent {
+    /// Image from the channel storage, scaled and placed like the logo.
+    Image {
+        path: String,
+        #[serde(default)]
+        scale: String,
+        #[serde(default = "default_position")]
+        position: String,
+    },
+    /// GIF, APNG or alpha video from the channel storage.
+    Animation {
+        path: String,
+        #[serde(default)]
+        scale: String,
+        #[serde(default = "default_position")]
+        position: String,
+        #[serde(rename = "loop", default = "default_loop")]
+        looping: bool,
+    },
+    Text(TextPreset),
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LayerChanges {
+    pub z_index: Option<i32>,
+    pub opacity: Option<f64>,
+    pub visible: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LayerInfo {
+    pub id: String,
+    pub z_index: i32,
+    pub opacity: f64,
+    pub visible: bool,
+    pub fade_in_seconds: f64,
+    pub fade_out_seconds: f64,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// Source file or text of the layer.
+    pub source: String,
+}
+
+fn default_opacity() -> f64 {
+    1.0
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+fn default_loop() -> bool {
+    true
+}
+
+fn default_position() -> String {
+    "W-w-12:12".to_string()
+}
+
+impl LayerParams {
+    pub fn engine_layer(self, id: String, storage: &Path) -> Result<GraphicLayer, ServiceError> {
+        let content = match self.content {
+            LayerContent::Image {
+                path,
+                scale,
+                position,
+            } => GraphicLayerContent::Image(LogoConfig {
+                path: storage_file(storage, &path)?,
+                scale: (!scale.trim().is_empty()).then_some(scale),
+                opacity: 1.0,
+                position,
+            }),
+            LayerContent::Animation {
+                path,
+                scale,
+                position,
+                looping,
+            } => GraphicLayerContent::Animation(AnimationConfig {
+                path: storage_file(storage, &path)?,
+                scale: (!scale.trim().is_empty()).then_some(scale),
+                position,
+                looping,
+            }),
+            LayerContent::Text(preset) => {
+                let text = (!preset.text.trim().is_empty()).then(|| preset.text.clone());
+                if text.is_none() && !preset.use_filename {
+                    return Err(ServiceError::BadRequest(
+                        "Text layer needs a text or use_filename".to_string(),
+                    ));
+                }
+
+                GraphicLayerContent::Text(text_config(&preset, text, preset.use_filename))
+            }
+        };
+
+        Ok(GraphicLayer {
+            id,
+            z_index: self.z_index,
+            opacity: self.opacity,
+            visible: self.visible,
+            fade_in_seconds: self.fade_in_seconds,
+            fade_out_seconds: self.fade_out_seconds,
+            content,
+        })
+    }
+}
+
+fn storage_file(storage: &Path, path: &str) -> Result<String, ServiceError> {
+    let (file, _, _) = norm_abs_path(storage, path)?;
+    if !file.is_file() {
+        return Err(ServiceError::BadRequest(format!(
+            "Layer source {path} not found"
+        )));
+    }
+
+    Ok(file.to_string_lossy().to_string())
+}
+
+impl From<LayerChanges> for GraphicLayerUpdate {
+    fn from(changes: LayerChanges) -> Self {
+        Self {
+            z_index: changes.z_index,
+            opacity: changes.opacity,
+            visible: changes.visible,
+        }
+    }
+}
+
+impl From<GraphicLayer> for LayerInfo {
+    fn from(layer: GraphicLayer) -> Self {
+        let (kind, source) = match layer.content {
+            GraphicLayerContent::Image(image) => ("image", image.path),
+            GraphicLayerContent::Animation(animation) => ("animation", animation.path),
+            GraphicLayerContent::Text(text) => ("text", text.text.unwrap_or_default()),
+        };
+
+        Self {
+            id: layer.id,
+            z_index: layer.z_index,
+            opacity: layer.opacity,
+            visible: layer.visible,
+            fade_in_seconds: layer.fade_in_seconds,
+            fade_out_seconds: layer.fade_out_seconds,
+            kind,
+            source,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use ff_engine::GraphicLayerContent;
+
+    use super::LayerParams;
+
+    #[test]
+    fn text_layer_uses_defaults_and_preset_fields() {
+        let params: LayerParams = serde_json::from_str(
+            r#"{"z_index": 3, "content": {"type": "text", "text": "LIVE", "font_size": 30}}"#,
+        )
+        .unwrap();
+        let layer = params
+            .engine_layer("live".to_string(), Path::new("/tmp"))
+            .unwrap();
+
+        assert_eq!(layer.z_index, 3);
+        assert_eq!(layer.opacity, 1.0);
+        assert!(layer.visible);
+        let GraphicLayerContent::Text(text) = layer.content else {
+            panic!("expected a text layer");
+        };
+        assert_eq!(text.text.as_deref(), Some("LIVE"));
+        assert_eq!(text.font_size, 30.0);
+    }
+
+    #[test]
+    fn empty_text_layer_is_rejected() {
+        let params: LayerParams =
+            serde_json::from_str(r#"{"content": {"type": "text", "text": " "}}"#).unwrap();
+
+        assert!(
+            params
+                .engine_layer("empty".to_string(), Path::new("/tmp"))
+                .is_err()
+        );
+    }
+}
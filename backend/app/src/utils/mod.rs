@@ -12,17 +12,23 @@ use tokio::fs;
 use serde::{Deserialize, Deserializer, de};
 
 pub mod args_parse;
+pub mod branding;
 pub mod channels;
 pub mod config;
 pub mod control;
 pub mod errors;
+pub mod fonts;
 pub mod generator;
+pub mod layers;
 pub mod logging;
 pub mod mail;
+pub mod pip;
 pub mod playlist;
+pub mod recording;
 pub mod system;
 pub mod task_runner;
 pub mod text;
+pub mod ticker;
 pub mod time_machine;
 
 use crate::{
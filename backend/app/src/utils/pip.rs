@@ -0,0 +1,169 @@
+use std::path::Path;
+
+use ff_engine::{PictureInPictureConfig, PipLayout, PipRect, PipSource, is_generator_source};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    file::norm_abs_path,
+    player::utils::{ImageEasing, is_remote},
+    utils::errors::ServiceError,
+};
+
+/// Source name of the live ingest of the channel.
+pub const INGEST_SOURCE: &str = "ingest";
+
+/// Picture-in-picture as sent to and returned by the control API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipParams {
+    /// File in the channel storage, stream URL, `generator://` source or
+    /// `ingest`.
+    pub source: String,
+    #[serde(default)]
+    pub layout: PipLayoutParam,
+    #[serde(default = "default_rect")]
+    pub rect: PipRectParams,
+    #[serde(default = "default_transition")]
+    pub transition_seconds: f64,
+    #[serde(default = "default_easing")]
+    pub easing: ImageEasing,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PipLayoutParam {
+    #[default]
+    Window,
+    SqueezeBack,
+}
+
+/// Output region as fractions of the output size.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct PipRectParams {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+fn default_rect() -> PipRectParams {
+    PipRectParams {
+        x: 0.62,
+        y: 0.06,
+        width: 0.33,
+        height: 0.33,
+    }
+}
+
+fn default_transition() -> f64 {
+    0.5
+}
+
+fn default_easing() -> ImageEasing {
+    ImageEasing::EaseInOut
+}
+
+impl PipParams {
+    /// Engine settings; files are looked up in the channel storage.
+    pub fn engine_config(
+        &self,
+        storage: &Path,
+        ingest_enabled: bool,
+    ) -> Result<PictureInPictureConfig, ServiceError> {
+        let source = self.source.trim();
+        let source = if source == INGEST_SOURCE {
+            if !ingest_enabled {
+                return Err(ServiceError::BadRequest(
+                    "Live ingest is not enabled for this channel".to_string(),
+                ));
+            }
+            PipSource::Ingest
+        } else if is_generator_source(source) || is_remote(source) {
+            PipSource::Media(source.to_string())
+        } else {
+            let (file, _, _) = norm_abs_path(storage, source)?;
+            if !file.is_file() {
+                return Err(ServiceError::BadRequest(format!(
+                    "Picture-in-picture source {source} not found"
+                )));
+            }
+            PipSource::Media(file.to_string_lossy().to_string())
+        };
+
+        Ok(PictureInPictureConfig {
+            source,
+            layout: match self.layout {
+                PipLayoutParam::Window => PipLayout::Window,
+                PipLayoutParam::SqueezeBack => PipLayout::SqueezeBack,
+            },
+            rect: PipRect {
+                x: self.rect.x,
+                y: self.rect.y,
+                width: self.rect.width,
+                height: self.rect.height,
+            },
+            transition_seconds: self.transition_seconds,
+            easing: self.easing.into(),
+        })
+    }
+}
+
+impl From<PictureInPictureConfig> for PipParams {
+    fn from(config: PictureInPictureConfig) -> Self {
+        Self {
+            source: match config.source {
+                PipSource::Media(source) => source,
+                PipSource::Ingest => INGEST_SOURCE.to_string(),
+            },
+            layout: match config.layout {
+                PipLayout::Window => PipLayoutParam::Window,
+                PipLayout::SqueezeBack => PipLayoutParam::SqueezeBack,
+            },
+            rect: PipRectParams {
+                x: config.rect.x,
+                y: config.rect.y,
+                width: config.rect.width,
+                height: config.rect.height,
+            },
+            transition_seconds: config.transition_seconds,
+            easing: config.easing.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use ff_engine::{PipLayout, PipSource};
+
+    use super::PipParams;
+
+    #[test]
+    fn ingest_window_uses_defaults() {
+        let params: PipParams = serde_json::from_str(r#"{"source": "ingest"}"#).unwrap();
+        let config = params.engine_config(Path::new("/tmp"), true).unwrap();
+
+        assert_eq!(config.source, PipSource::Ingest);
+        assert_eq!(config.layout, PipLayout::Window);
+        assert_eq!(config.rect.width, 0.33);
+        assert_eq!(config.transition_seconds, 0.5);
+        assert!(params.engine_config(Path::new("/tmp"), false).is_err());
+    }
+
+    #[test]
+    fn squeeze_back_with_generator() {
+        let params: PipParams = serde_json::from_str(
+            r#"{"source": "generator://smpte-bars", "layout": "squeeze_back",
+                "rect": {"x": 0, "y": 0, "width": 0.75, "height": 0.75}}"#,
+        )
+        .unwrap();
+        let config = params.engine_config(Path::new("/tmp"), false).unwrap();
+
+        assert_eq!(
+            config.source,
+            PipSource::Media("generator://smpte-bars".to_string())
+        );
+        assert_eq!(config.layout, PipLayout::SqueezeBack);
+        assert_eq!(config.rect.height, 0.75);
+    }
+}
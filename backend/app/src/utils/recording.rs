@@ -0,0 +1,433 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use chrono::TimeDelta;
+use ff_engine::{RecordingCallback, RecordingPath, record_stream};
+use log::*;
+use tokio::{
+    sync::mpsc::{UnboundedReceiver, unbounded_channel},
+    task::JoinHandle,
+    time::sleep,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    db::{handles, models::RecordingSchedule},
+    file::norm_abs_path,
+    player::{
+        controller::ChannelManager,
+        utils::{
+            Media,
+            record::{finish_recording, recording_path},
+            sec_to_time, time_in_seconds,
+        },
+    },
+    utils::{
+        config::{PlayoutConfig, clock_seconds, url_address, window_contains},
+        errors::ServiceError,
+        playlist::{read_playlist, write_playlist},
+        time_machine::time_now,
+    },
+};
+
+const SCHEDULE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Recording of a schedule whose window is open.
+struct WindowRecording {
+    label: String,
+    stop: Arc<AtomicBool>,
+    task: JoinHandle<()>,
+    /// Files as they are finished, in the order they were recorded.
+    files: UnboundedReceiver<JoinHandle<Option<(PathBuf, f64)>>>,
+    /// Day whose playlist gets the files at `insert_at`.
+    date: String,
+    insert_at: Option<u32>,
+}
+
+impl WindowRecording {
+    /// Stops the recording and waits for its files. A reconnect splits a
+    /// window into several files; with `insert_at` they go into the
+    /// playlist together, in the order they were recorded.
+    async fn finish(mut self, manager: &ChannelManager) {
+        self.stop.store(true, Ordering::SeqCst);
+        let _ = self.task.await;
+
+        let mut files = vec![];
+        while let Some(file) = self.files.recv().await {
+            if let Ok(Some(file)) = file.await {
+                files.push(file);
+            }
+        }
+
+        if let Some(time) = self.insert_at
+            && !files.is_empty()
+        {
+            save_recordings(manager, &files, &self.date, time).await;
+        }
+    }
+}
+
+/// Records the channel's schedules in their windows. Schedules are read
+/// every second, so new and deleted ones take effect right away. It runs
+/// from the creation of the channel on, whether its playout runs or not.
+pub async fn run(manager: ChannelManager, cancel: CancellationToken) {
+    let id = manager.id;
+    // A window is recorded once, also when the recording fails early.
+    let mut recordings = HashMap::<i32, WindowRecording>::new();
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = sleep(SCHEDULE_INTERVAL) => {}
+        }
+
+        let schedules = match handles::select_recording_schedules(&manager.db_pool, id).await {
+            Ok(schedules) => schedules,
+            Err(error) => {
+                error!(channel = id; "Recording schedules can not be read: {error}");
+                continue;
+            }
+        };
+        let config = manager.config.read().await.clone();
+        let now = time_in_seconds(&config.channel.timezone);
+        let due = schedules
+            .into_iter()
+            .filter(|schedule| {
+                schedule
+                    .window()
+                    .is_some_and(|(start, end)| window_contains(start, end, now))
+            })
+            .collect::<Vec<_>>();
+
+        let closed = recordings
+            .keys()
+            .filter(|schedule_id| !due.iter().any(|schedule| schedule.id == **schedule_id))
+            .copied()
+            .collect::<Vec<_>>();
+        for schedule_id in closed {
+            if let Some(recording) = recordings.remove(&schedule_id) {
+                info!(channel = id; "Stop recording <span class=\"log-addr\">{}</span>", recording.label);
+                let manager = manager.clone();
+                tokio::spawn(async move { recording.finish(&manager).await });
+            }
+        }
+
+        for schedule in due {
+            if recordings.contains_key(&schedule.id) {
+                continue;
+            }
+            match start_recording(&manager, &config, &schedule) {
+                Ok(recording) => {
+                    recordings.insert(schedule.id, recording);
+                }
+                Err(error) => {
+                    error!(channel = id; "Recording <span class=\"log-addr\">{}</span> can not start: {error}", schedule.label);
+                }
+            }
+        }
+    }
+
+    for recording in recordings.into_values() {
+        recording.finish(&manager).await;
+    }
+}
+
+fn start_recording(
+    manager: &ChannelManager,
+    config: &PlayoutConfig,
+    schedule: &RecordingSchedule,
+) -> Result<WindowRecording, ServiceError> {
+    let id = manager.id;
+    let (folder, _, _) = norm_abs_path(&config.channel.storage, &schedule.folder)?;
+    let timezone = config.channel.timezone;
+    let name = file_label(&schedule.label);
+    let date = (time_now(&timezone).date_naive() + TimeDelta::days(1))
+        .format("%Y-%m-%d")
+        .to_string();
+    let insert_at = schedule.insert_at.as_deref().and_then(clock_seconds);
+
+    let path = RecordingPath::new(move || {
+        if let Err(error) = std::fs::create_dir_all(&folder) {
+            error!(channel = id;
+                "Record folder <span class=\"log-addr\">{}</span> can not be created: {error}",
+                folder.display()
+            );
+            return None;
+        }
+
+        let time = time_now(&timezone).format("%Y-%m-%d_%H-%M-%S");
+        Some(recording_path(&folder, &format!("{name}_{time}")))
+    });
+    let runtime = tokio::runtime::Handle::current();
+    let (files_tx, files) = unbounded_channel();
+    let callback = RecordingCallback::new(move |path| {
+        let _ = files_tx.send(runtime.spawn(finish_recording(id, path)));
+    });
+
+    let url = schedule.url.clone();
+    let label = schedule.label.clone();
+    let stop = Arc::new(AtomicBool::new(false));
+    let task_stop = stop.clone();
+    info!(channel = id;
+        "Start recording <span class=\"log-addr\">{label}</span> from <span class=\"log-addr\">{}</span>",
+        url_address(&url)
+    );
+    let task = tokio::task::spawn_blocking(move || {
+        if let Err(error) = record_stream(&url, path, callback, task_stop) {
+            error!(channel = id; "Recording <span class=\"log-addr\">{label}</span> failed: {error:#}");
+        }
+    });
+
+    Ok(WindowRecording {
+        label: schedule.label.clone(),
+        stop,
+        task,
+        files,
+        date,
+        insert_at,
+    })
+}
+
+/// Label as part of a file name.
+fn file_label(label: &str) -> String {
+    label
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+async fn save_recordings(
+    manager: &ChannelManager,
+    files: &[(PathBuf, f64)],
+    date: &str,
+    time: u32,
+) {
+    let names = files
+        .iter()
+        .map(|(path, _)| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    match insert_recordings(manager, files, date, time).await {
+        Ok(begin) => info!(channel = manager.id;
+            "Recording <span class=\"log-addr\">{names}</span> added to the playlist of {date} at {}",
+            sec_to_time(begin)
+        ),
+        Err(ServiceError::NoContent()) => warn!(channel = manager.id;
+            "No playlist for {date}, recording <span class=\"log-addr\">{names}</span> is not added"
+        ),
+        Err(error) => error!(channel = manager.id;
+            "Recording <span class=\"log-addr\">{names}</span> can not be added to the playlist of {date}: {error}"
+        ),
+    }
+}
+
+/// Puts the recordings into the playlist of `date`, and returns the time of
+/// day they start at. Clips are not cut, so when the playlist would get
+/// longer than its day, it is left as it is and an error returned.
+async fn insert_recordings(
+    manager: &ChannelManager,
+    files: &[(PathBuf, f64)],
+    date: &str,
+    time: u32,
+) -> Result<f64, ServiceError> {
+    let config = manager.config.read().await.clone();
+    let mut playlist = read_playlist(&config, date.to_string()).await?;
+    let day_start = config.playlist.start_sec.unwrap_or_default();
+
+    let mut recordings = Vec::with_capacity(files.len());
+    for (path, duration) in files {
+        let mut media = Media::new(0, &path.to_string_lossy(), false).await;
+        media.out = *duration;
+        media.duration = *duration;
+        recordings.push(media);
+    }
+    let begin = insert_files(
+        &mut playlist.program,
+        recordings,
+        day_start,
+        f64::from(time),
+    );
+    fits_day(
+        &playlist.program,
+        config.playlist.length_sec.unwrap_or(86400.0),
+    )?;
+    write_playlist(&config, playlist).await?;
+
+    Ok((day_start + begin) % 86400.0)
+}
+
+/// Puts `files` into `program` one after the other at the first clip
+/// boundary from `time` of the day on, and returns their start in seconds
+/// after `day_start`.
+fn insert_files(program: &mut Vec<Media>, mut files: Vec<Media>, day_start: f64, time: f64) -> f64 {
+    let (index, begin) = insert_position(program, day_start, time);
+    for (offset, media) in files.iter_mut().enumerate() {
+        media.index = Some(index + offset);
+    }
+    program.splice(index..index, files);
+
+    begin
+}
+
+/// Refuses a program that runs past the end of its day, which would not be
+/// played.
+fn fits_day(program: &[Media], day: f64) -> Result<(), ServiceError> {
+    let length = program
+        .iter()
+        .map(|media| media.out - media.seek)
+        .sum::<f64>();
+
+    if length > day {
+        return Err(ServiceError::Conflict(format!(
+            "the playlist would run {} past the end of its day",
+            sec_to_time(length - day)
+        )));
+    }
+
+    Ok(())
+}
+
+/// Index of the first clip that starts at `time` of the day or later, and
+/// its start in seconds after `day_start`. Clips are not cut, so the
+/// recording starts at the next clip boundary.
+fn insert_position(program: &[Media], day_start: f64, time: f64) -> (usize, f64) {
+    let target = (time - day_start).rem_euclid(86400.0);
+    let mut begin = 0.0;
+
+    for (index, media) in program.iter().enumerate() {
+        if begin >= target {
+            return (index, begin);
+        }
+        begin += media.out - media.seek;
+    }
+
+    (program.len(), begin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{file_label, fits_day, insert_files, insert_position};
+    use crate::{db::models::RecordingSchedule, player::utils::Media};
+
+    #[tokio::test]
+    async fn recordings_go_in_at_the_next_clip_boundary() {
+        let mut program = vec![];
+        for index in 0..3 {
+            let mut media = Media::new(index, "clip.mp4", false).await;
+            media.out = 3600.0;
+            program.push(media);
+        }
+
+        // The playlist starts at 06:00, so 07:30 falls into the second clip.
+        assert_eq!(insert_position(&program, 21600.0, 27000.0), (2, 7200.0));
+        assert_eq!(insert_position(&program, 21600.0, 21600.0), (0, 0.0));
+        assert_eq!(insert_position(&program, 21600.0, 18000.0), (3, 10800.0));
+    }
+
+    #[tokio::test]
+    async fn fragments_of_a_window_keep_their_order() {
+        let mut program = vec![];
+        for index in 0..3 {
+            let mut media = Media::new(index, "clip.mp4", false).await;
+            media.out = 3600.0;
+            program.push(media);
+        }
+        let mut fragments = vec![];
+        for (index, name) in ["news_20-00-00.mkv", "news_20-12-30.mkv"]
+            .into_iter()
+            .enumerate()
+        {
+            let mut media = Media::new(0, name, false).await;
+            media.out = 600.0 + index as f64 * 300.0;
+            fragments.push(media);
+        }
+
+        assert_eq!(
+            insert_files(&mut program, fragments, 21600.0, 27000.0),
+            7200.0
+        );
+        let sources = program
+            .iter()
+            .map(|media| media.source.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sources,
+            [
+                "clip.mp4",
+                "clip.mp4",
+                "news_20-00-00.mkv",
+                "news_20-12-30.mkv",
+                "clip.mp4"
+            ]
+        );
+        assert_eq!(program[3].index, Some(3));
+    }
+
+    #[tokio::test]
+    async fn recordings_that_overflow_the_day_are_refused() {
+        let mut program = vec![];
+        for index in 0..3 {
+            let mut media = Media::new(index, "clip.mp4", false).await;
+            media.out = 28800.0;
+            program.push(media);
+        }
+        assert!(fits_day(&program, 86400.0).is_ok());
+
+        // A full day has no room for another half hour.
+        let mut recording = Media::new(0, "news_20-00-00.mkv", false).await;
+        recording.out = 1800.0;
+        insert_files(&mut program, vec![recording], 21600.0, 27000.0);
+        assert!(fits_day(&program, 86400.0).is_err());
+        assert!(fits_day(&program, 88200.0).is_ok());
+    }
+
+    #[test]
+    fn labels_become_file_names() {
+        assert_eq!(file_label("Partner News 20:00"), "Partner_News_20_00");
+        assert_eq!(file_label(" ../Sport "), "___Sport");
+    }
+
+    #[test]
+    fn schedules_need_a_stream_and_a_window() {
+        let mut schedule = RecordingSchedule {
+            label: "Partner News".to_string(),
+            url: "https://partner.example/live/index.m3u8".to_string(),
+            start_time: "23:30".to_string(),
+            end_time: "00:15".to_string(),
+            folder: "partner".to_string(),
+            insert_at: Some("18:00".to_string()),
+            ..RecordingSchedule::default()
+        };
+        assert!(schedule.validate().is_ok());
+        assert_eq!(schedule.window(), Some((84600, 900)));
+
+        schedule.url = "/media/partner.mp4".to_string();
+        assert!(schedule.validate().is_err());
+
+        schedule.url = "srt://partner.example:9000".to_string();
+        schedule.end_time = "23:30:00".to_string();
+        assert!(schedule.validate().is_err());
+
+        schedule.end_time = "24:00".to_string();
+        assert!(schedule.validate().is_err());
+
+        schedule.end_time = "00:15".to_string();
+        schedule.folder = "../partner".to_string();
+        assert!(schedule.validate().is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use ff_engine::{
-    RgbaColor, TextBackgroundConfig, TextConfig, TextPosition, TextScroll, TextWeight,
+    MotionEasing, RgbaColor, TextAnimation, TextBackgroundConfig, TextConfig, TextOutline,
+    TextPosition, TextReveal, TextScroll, TextShadow, TextSlide, TextWeight,
 };
 
 use crate::db::models::TextPreset;
@@ -25,6 +26,22 @@ pub fn text_config(preset: &TextPreset, text: Option<String>, use_filename: bool
         }
     });
 
+    let outline = (preset.outline_width > 0).then(|| TextOutline {
+        color: parse_color(&preset.outline_color, RgbaColor::opaque(0, 0, 0)),
+        width: preset.outline_width,
+    });
+
+    let shadow = preset.shadow_enabled.then(|| {
+        let mut color = parse_color(&preset.shadow_color, RgbaColor::opaque(0, 0, 0));
+        color.a = opacity_to_alpha(preset.shadow_opacity);
+        TextShadow {
+            color,
+            offset_x: preset.shadow_offset_x,
+            offset_y: preset.shadow_offset_y,
+            blur: preset.shadow_blur,
+        }
+    });
+
     TextConfig {
         text,
         use_filename,
@@ -43,6 +60,9 @@ pub fn text_config(preset: &TextPreset, text: Option<String>, use_filename: bool
         position_x: parse_position(&preset.position_x),
         position_y: parse_position(&preset.position_y),
         background,
+        markup: preset.markup,
+        outline,
+        shadow,
         scroll: match preset.scroll_direction.as_str() {
             "left_to_right" => TextScroll::LeftToRight {
                 pixels_per_second: preset.scroll_speed,
@@ -50,11 +70,44 @@ pub fn text_config(preset: &TextPreset, text: Option<String>, use_filename: bool
             "right_to_left" => TextScroll::RightToLeft {
                 pixels_per_second: preset.scroll_speed,
             },
+            "bottom_to_top" => TextScroll::BottomToTop {
+                pixels_per_second: preset.scroll_speed,
+            },
+            "top_to_bottom" => TextScroll::TopToBottom {
+                pixels_per_second: preset.scroll_speed,
+            },
             _ => TextScroll::None,
         },
         scroll_repeat: preset.scroll_repeat,
         fade_in_seconds: preset.fade_in_seconds,
         fade_out_seconds: preset.fade_out_seconds,
+        animation: TextAnimation {
+            reveal: match preset.reveal.as_str() {
+                "typewriter" => TextReveal::Typewriter {
+                    characters_per_second: preset.reveal_speed,
+                },
+                _ => TextReveal::None,
+            },
+            slide_in: parse_slide(&preset.slide_in),
+            slide_out: parse_slide(&preset.slide_out),
+            slide_seconds: preset.slide_seconds,
+            easing: match preset.easing.as_str() {
+                "ease_in" => MotionEasing::EaseIn,
+                "ease_out" => MotionEasing::EaseOut,
+                "ease_in_out" => MotionEasing::EaseInOut,
+                _ => MotionEasing::Linear,
+            },
+        },
+    }
+}
+
+fn parse_slide(value: &str) -> TextSlide {
+    match value {
+        "left" => TextSlide::Left,
+        "right" => TextSlide::Right,
+        "top" => TextSlide::Top,
+        "bottom" => TextSlide::Bottom,
+        _ => TextSlide::None,
     }
 }
 
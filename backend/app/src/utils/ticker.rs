@@ -0,0 +1,292 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::Duration,
+};
+
+use log::*;
+use regex::Regex;
+use roxmltree::{Document, Node, ParsingOptions};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    db::models::TextPreset,
+    file::norm_abs_path,
+    player::controller::ChannelManager,
+    utils::{errors::ServiceError, text::text_config},
+};
+
+const MIN_INTERVAL_SECONDS: u64 = 10;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Markup in a title, like the escaped HTML of an Atom `type="html"` title.
+static MARKUP: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").expect("valid regex"));
+
+/// News ticker as sent to the control API.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TickerParams {
+    /// `http(s)://` URL or file in the channel storage.
+    pub source: String,
+    #[serde(default = "default_interval")]
+    pub interval_seconds: u64,
+    #[serde(default = "default_separator")]
+    pub separator: String,
+    /// Look and crawl of the ticker; its `text` is ignored.
+    #[serde(default = "default_style")]
+    pub style: TextPreset,
+}
+
+#[derive(Debug, Clone)]
+enum TickerSource {
+    Url(String),
+    File(PathBuf),
+}
+
+/// Running ticker, created from validated [`TickerParams`].
+#[derive(Debug, Clone)]
+pub struct Ticker {
+    params: TickerParams,
+    source: TickerSource,
+}
+
+fn default_interval() -> u64 {
+    300
+}
+
+fn default_separator() -> String {
+    "  •  ".to_string()
+}
+
+fn default_style() -> TextPreset {
+    TextPreset {
+        position_x: "0".to_string(),
+        position_y: "end:24".to_string(),
+        background_enabled: true,
+        scroll_direction: "right_to_left".to_string(),
+        ..TextPreset::default()
+    }
+}
+
+impl TickerParams {
+    pub fn ticker(self, storage: &Path) -> Result<Ticker, ServiceError> {
+        let source = self.source.trim();
+        let source = if source.starts_with("http://") || source.starts_with("https://") {
+            TickerSource::Url(source.to_string())
+        } else {
+            let (file, _, _) = norm_abs_path(storage, source)?;
+            if !file.is_file() {
+                return Err(ServiceError::BadRequest(format!(
+                    "Ticker source {source} not found"
+                )));
+            }
+            TickerSource::File(file)
+        };
+
+        Ok(Ticker {
+            params: self,
+            source,
+        })
+    }
+}
+
+impl Ticker {
+    /// Whether the server fetches the source over the network.
+    pub fn is_url(&self) -> bool {
+        matches!(self.source, TickerSource::Url(_))
+    }
+
+    /// Read the source every interval and put its items on the text
+    /// overlay. Later updates replace only the text, so the crawl goes on.
+    ///
+    /// When cancelled, the overlay is cleared unless another text took
+    /// its place.
+    pub async fn run(self, manager: ChannelManager, cancel: CancellationToken) {
+        let id = manager.id;
+        let interval = Duration::from_secs(self.params.interval_seconds.max(MIN_INTERVAL_SECONDS));
+        let mut owner = None;
+
+        loop {
+            let body = tokio::select! {
+                _ = cancel.cancelled() => break,
+                body = self.read() => body,
+            };
+
+            match body.and_then(|body| ticker_items(&body)) {
+                Ok(items) if items.is_empty() => {
+                    warn!(channel = id; "Ticker source {} has no items", self.params.source);
+                }
+                Ok(items) => {
+                    let text = items.join(&self.params.separator);
+                    if !owner
+                        .is_some_and(|owner| manager.text_overlay.set_text(owner, text.clone()))
+                    {
+                        owner = Some(manager.text_overlay.set(Some(text_config(
+                            &self.params.style,
+                            Some(text),
+                            false,
+                        ))));
+                    }
+                }
+                Err(error) => {
+                    warn!(channel = id;
+                        "Ticker source {} could not be read: {error}", self.params.source
+                    );
+                }
+            }
+
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = sleep(interval) => {}
+            }
+        }
+
+        if let Some(owner) = owner {
+            manager.text_overlay.clear_own(owner);
+        }
+    }
+
+    async fn read(&self) -> Result<String, String> {
+        match &self.source {
+            TickerSource::Url(url) => reqwest::Client::new()
+                .get(url)
+                .timeout(FETCH_TIMEOUT)
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .map_err(|error| error.to_string())?
+                .text()
+                .await
+                .map_err(|error| error.to_string()),
+            TickerSource::File(path) => tokio::fs::read_to_string(path)
+                .await
+                .map_err(|error| error.to_string()),
+        }
+    }
+}
+
+/// Items of an RSS or Atom feed, a JSON document or a text file with one
+/// item per line.
+///
+/// JSON can be a list of strings or of objects with a `title` or `text`,
+/// either on its own or under `items`.
+pub fn ticker_items(body: &str) -> Result<Vec<String>, String> {
+    let body = body.trim_start_matches('\u{feff}').trim();
+    let items = if body.starts_with('<') {
+        feed_items(body)?
+    } else if body.starts_with('[') || body.starts_with('{') {
+        let value = serde_json::from_str::<Value>(body).map_err(|error| error.to_string())?;
+        json_items(&value)
+    } else {
+        body.lines().map(str::to_string).collect()
+    };
+
+    Ok(items
+        .into_iter()
+        .map(|item| item.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|item| !item.is_empty())
+        .collect())
+}
+
+/// Titles of the items of an RSS feed or the entries of an Atom feed.
+fn feed_items(body: &str) -> Result<Vec<String>, String> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let document =
+        Document::parse_with_options(body, options).map_err(|error| error.to_string())?;
+
+    Ok(document
+        .descendants()
+        .filter(|node| node.has_tag_name("item") || node.has_tag_name("entry"))
+        .filter_map(|item| item.children().find(|node| node.has_tag_name("title")))
+        .map(title_text)
+        .collect())
+}
+
+fn title_text(title: Node) -> String {
+    let text = title
+        .descendants()
+        .filter(Node::is_text)
+        .filter_map(|node| node.text())
+        .collect::<String>();
+
+    MARKUP.replace_all(&text, "").into_owned()
+}
+
+fn json_items(value: &Value) -> Vec<String> {
+    let list = match value {
+        Value::Array(list) => list,
+        Value::Object(map) => match map.get("items") {
+            Some(Value::Array(list)) => list,
+            _ => return Vec::new(),
+        },
+        _ => return Vec::new(),
+    };
+
+    list.iter()
+        .filter_map(|item| match item {
+            Value::String(text) => Some(text.clone()),
+            Value::Object(map) => ["title", "text"]
+                .iter()
+                .find_map(|key| map.get(*key).and_then(Value::as_str))
+                .map(str::to_string),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ticker_items;
+
+    #[test]
+    fn feed_titles_are_decoded() {
+        let rss = r#"<?xml version="1.0"?>
+            <rss><channel><title>News</title>
+            <item><title>Rain &amp; wind</title><link>x</link></item>
+            <item><title><![CDATA[Match <b>won</b>]]></title></item>
+            </channel></rss>"#;
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>Feed</title>
+            <entry><title type="html">Caf&#xE9; &lt;i&gt;opens&lt;/i&gt;</title></entry>
+            </feed>"#;
+
+        assert_eq!(ticker_items(rss).unwrap(), ["Rain & wind", "Match won"]);
+        assert_eq!(ticker_items(atom).unwrap(), ["Café opens"]);
+    }
+
+    #[test]
+    fn json_and_text_items() {
+        assert_eq!(
+            ticker_items(r#"["One", {"title": "Two"}, {"text": " Three "}, 4]"#).unwrap(),
+            ["One", "Two", "Three"]
+        );
+        assert_eq!(ticker_items(r#"{"items": ["Only"]}"#).unwrap(), ["Only"]);
+        assert_eq!(
+            ticker_items("First\n\n  Second  line\n").unwrap(),
+            ["First", "Second line"]
+        );
+    }
+
+    #[test]
+    fn feed_markup_is_parsed() {
+        // A title in an attribute or a comment is no item, the item title
+        // is not taken from nested elements.
+        let rss = r#"<rss><channel>
+            <!-- <item><title>Commented</title></item> -->
+            <item><source url="x"><title>Source</title></source>
+            <title alt="&lt;title&gt;">Headline</title></item>
+            </channel></rss>"#;
+        let xhtml = r#"<feed xmlns="http://www.w3.org/2005/Atom"><entry>
+            <title type="xhtml"><div xmlns="http://www.w3.org/1999/xhtml">Big <b>news</b></div></title>
+            </entry></feed>"#;
+
+        assert_eq!(ticker_items(rss).unwrap(), ["Headline"]);
+        assert_eq!(ticker_items(xhtml).unwrap(), ["Big news"]);
+        assert!(ticker_items("<rss><item><title>Open</rss>").is_err());
+        assert!(ticker_items(r#"["unclosed"#).is_err());
+    }
+}
@@ -12,7 +12,7 @@ use glob::glob;
 
 use ff_engine::{
     ClipResult, HlsSubtitle, HlsVariant, LogoConfig, OutputConfig, OutputSize, Playout,
-    print_media_info, spawn_rtmp_listener,
+    print_media_info, spawn_live_listener,
 };
 
 #[derive(Parser, Debug)]
@@ -112,7 +112,8 @@ struct Args {
     #[arg(long, default_value = "W-w-20:H-h-20")]
     logo_position: String,
 
-    /// RTMP listen URL for live override, e.g. rtmp://0.0.0.0:1935/live/input
+    /// RTMP or SRT listen URL for live override, e.g. rtmp://0.0.0.0:1935/live/input
+    /// or srt://0.0.0.0:9000?latency=800
     #[arg(long, value_name = "URL")]
     rtmp_live: Option<String>,
 
@@ -226,8 +227,9 @@ fn main() -> Result<()> {
 
     let mut live = args
         .rtmp_live
-        .clone()
-        .map(|url| spawn_rtmp_listener(url, live_config));
+        .as_deref()
+        .map(|url| spawn_live_listener(url, live_config))
+        .transpose()?;
 
     for (index, path) in inputs.iter().enumerate() {
         print_media_info(path);
@@ -1 +1,2 @@
 pub mod audio_level;
+pub mod signal;
@@ -0,0 +1,442 @@
+use std::{
+    fmt,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use ffmpeg_next::{
+    Packet, Rational,
+    format::{self, Sample, sample::Type as SampleType},
+    frame, media,
+};
+use log::{info, warn};
+
+use crate::input::failover::{has_luma, is_black, is_silent};
+
+/// Reports cover this stretch of the feed.
+const SIGNAL_WINDOW: Duration = Duration::from_secs(1);
+/// Mean luma change, out of 255, below which a picture counts as frozen.
+const FROZEN_DIFFERENCE: f64 = 0.5;
+/// Every n-th pixel of every n-th row is compared for a frozen picture.
+const FROZEN_SAMPLE_STEP: usize = 8;
+/// -0.1 dBFS.
+const CLIPPING_PEAK: f32 = 0.989;
+/// Longer gaps in the video timestamps are a jump of the publisher's
+/// clock, not dropped frames.
+const MAX_DROPPED_GAP_SECONDS: f64 = 5.0;
+
+/// Signal quality of a connected live feed.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SignalReport {
+    /// Seconds the picture has been black, 0 while it isn't.
+    pub black: f64,
+    /// Seconds the picture hasn't changed.
+    pub frozen: f64,
+    /// Seconds the sound has stayed below -60 dBFS.
+    pub silent: f64,
+    /// Share of the audio samples at full scale.
+    pub clipping: f64,
+    /// Frames missing in the video since the feed connected.
+    pub dropped_frames: u64,
+    /// Kbit/s of the feed as it comes in.
+    pub bitrate: u64,
+    /// The signal keeps the feed off air.
+    pub failed: bool,
+}
+
+/// Watches the signal of the live feeds: black frames, frozen picture,
+/// silence, clipping, dropped frames and bitrate. With a failover time, a
+/// feed that stays black, frozen or silent that long goes off air and the
+/// program returns to the playlist, until the feed is fine for as long.
+#[derive(Clone, Default)]
+pub struct SignalMonitor {
+    inner: Arc<Mutex<MonitorInner>>,
+}
+
+impl fmt::Debug for SignalMonitor {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("SignalMonitor(..)")
+    }
+}
+
+#[derive(Default)]
+struct MonitorInner {
+    failover_after: Option<Duration>,
+    /// Latest report by ingest endpoint.
+    reports: Vec<Option<SignalReport>>,
+}
+
+impl SignalMonitor {
+    /// `None` only reports on the signal.
+    pub fn set_failover_after(&self, failover_after: Option<Duration>) {
+        self.lock().failover_after = failover_after;
+    }
+
+    /// Report of the feed on an ingest endpoint, by its position in the URL
+    /// list; `None` while it isn't connected.
+    pub fn report(&self, endpoint: usize) -> Option<SignalReport> {
+        self.lock().reports.get(endpoint).copied().flatten()
+    }
+
+    /// Meter for one connection of an endpoint.
+    pub(crate) fn meter(&self, endpoint: usize) -> SignalMeter {
+        SignalMeter {
+            monitor: self.clone(),
+            endpoint,
+            counters: Arc::default(),
+            window_start: Instant::now(),
+            window_bytes: 0,
+            samples: 0,
+            clipped: 0,
+            luma: Vec::new(),
+            black_since: None,
+            frozen_since: None,
+            silent_since: None,
+            clean_since: None,
+            report: SignalReport::default(),
+        }
+    }
+
+    fn publish(&self, endpoint: usize, report: Option<SignalReport>) {
+        let mut inner = self.lock();
+        if inner.reports.len() <= endpoint {
+            inner.reports.resize(endpoint + 1, None);
+        }
+        inner.reports[endpoint] = report;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MonitorInner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[derive(Default)]
+struct PacketCounters {
+    bytes: AtomicU64,
+    dropped: AtomicU64,
+}
+
+/// Analyses the decoded pictures and samples of one connection.
+pub(crate) struct SignalMeter {
+    monitor: SignalMonitor,
+    endpoint: usize,
+    counters: Arc<PacketCounters>,
+    window_start: Instant,
+    window_bytes: u64,
+    samples: u64,
+    clipped: u64,
+    /// Sampled luma of the last picture.
+    luma: Vec<u8>,
+    black_since: Option<Instant>,
+    frozen_since: Option<Instant>,
+    silent_since: Option<Instant>,
+    /// Since when a failed signal is fine again.
+    clean_since: Option<Instant>,
+    report: SignalReport,
+}
+
+impl SignalMeter {
+    /// Meter for the packets the input reads, which the decoded frames
+    /// don't show.
+    pub(crate) fn packet_meter(&self, ictx: &format::context::Input) -> PacketMeter {
+        let video = ictx.streams().best(media::Type::Video);
+        let frame_ticks = video.as_ref().and_then(|stream| {
+            let rate = match stream.avg_frame_rate() {
+                rate if rate.numerator() > 0 => rate,
+                _ => stream.rate(),
+            };
+            let time_base = stream.time_base();
+            let ticks = f64::from(rate.denominator()) * f64::from(time_base.denominator())
+                / (f64::from(rate.numerator()) * f64::from(time_base.numerator()));
+
+            (ticks.is_finite() && ticks >= 1.0).then_some(ticks)
+        });
+
+        PacketMeter {
+            video_index: video.map(|stream| stream.index()),
+            frame_ticks,
+            next_dts: None,
+            counters: Arc::clone(&self.counters),
+        }
+    }
+
+    /// Whether the signal keeps the feed off air.
+    pub(crate) fn failed(&self) -> bool {
+        self.report.failed
+    }
+
+    pub(crate) fn observe_video(&mut self, picture: &frame::Video) {
+        let now = Instant::now();
+        let black = is_black(picture);
+        let frozen = self.is_frozen(picture);
+        self.black_since = black.then(|| self.black_since.unwrap_or(now));
+        self.frozen_since = frozen.then(|| self.frozen_since.unwrap_or(now));
+        self.update(now);
+    }
+
+    pub(crate) fn observe_audio(&mut self, samples: &frame::Audio) {
+        let now = Instant::now();
+        let silent = is_silent(samples);
+        self.silent_since = silent.then(|| self.silent_since.unwrap_or(now));
+        if samples.format() == Sample::F32(SampleType::Planar) {
+            for plane in 0..samples.planes() {
+                let plane = samples.plane::<f32>(plane);
+                self.samples += plane.len() as u64;
+                self.clipped += plane
+                    .iter()
+                    .filter(|sample| sample.abs() >= CLIPPING_PEAK)
+                    .count() as u64;
+            }
+        }
+        self.update(now);
+    }
+
+    /// Compares a sample of the luma with the last picture.
+    fn is_frozen(&mut self, picture: &frame::Video) -> bool {
+        if !has_luma(picture) {
+            self.luma.clear();
+            return false;
+        }
+
+        let stride = picture.stride(0);
+        let data = picture.data(0);
+        let width = picture.width() as usize;
+        let luma = (0..picture.height() as usize)
+            .step_by(FROZEN_SAMPLE_STEP)
+            .filter_map(|row| data.get(row * stride..row * stride + width))
+            .flat_map(|line| line.iter().step_by(FROZEN_SAMPLE_STEP).copied())
+            .collect::<Vec<_>>();
+        let frozen = !luma.is_empty() && luma.len() == self.luma.len() && {
+            let difference = luma
+                .iter()
+                .zip(&self.luma)
+                .map(|(current, last)| f64::from(current.abs_diff(*last)))
+                .sum::<f64>();
+            difference / (luma.len() as f64) < FROZEN_DIFFERENCE
+        };
+        self.luma = luma;
+
+        frozen
+    }
+
+    fn update(&mut self, now: Instant) {
+        let lasted = |since: Option<Instant>| {
+            since.map_or(0.0, |since| now.duration_since(since).as_secs_f64())
+        };
+        self.report.black = lasted(self.black_since);
+        self.report.frozen = lasted(self.frozen_since);
+        self.report.silent = lasted(self.silent_since);
+
+        let failover_after = self.monitor.lock().failover_after;
+        let failed = match failover_after {
+            Some(after) => self.follow_failover(now, after.as_secs_f64()),
+            None => false,
+        };
+        let changed = failed != self.report.failed;
+        self.report.failed = failed;
+
+        let elapsed = now.duration_since(self.window_start);
+        if elapsed >= SIGNAL_WINDOW {
+            let bytes = self.counters.bytes.load(Ordering::Relaxed);
+            self.report.bitrate =
+                ((bytes - self.window_bytes) as f64 * 8.0 / elapsed.as_secs_f64() / 1000.0).round()
+                    as u64;
+            self.report.clipping = match self.samples {
+                0 => 0.0,
+                samples => self.clipped as f64 / samples as f64,
+            };
+            self.report.dropped_frames = self.counters.dropped.load(Ordering::Relaxed);
+            self.window_start = now;
+            self.window_bytes = bytes;
+            self.samples = 0;
+            self.clipped = 0;
+        } else if !changed {
+            return;
+        }
+
+        self.monitor.publish(self.endpoint, Some(self.report));
+    }
+
+    /// A black, frozen or silent signal fails once it lasts `after` seconds,
+    /// and recovers when it is fine for as long.
+    fn follow_failover(&mut self, now: Instant, after: f64) -> bool {
+        let problem = [
+            ("black", self.report.black),
+            ("frozen", self.report.frozen),
+            ("silent", self.report.silent),
+        ]
+        .into_iter()
+        .filter(|(_, seconds)| *seconds > 0.0)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        if !self.report.failed {
+            if let Some((problem, seconds)) = problem
+                && seconds >= after
+            {
+                warn!("live signal {problem} for {seconds:.1} s; going back to the playlist");
+                self.clean_since = None;
+                return true;
+            }
+            return false;
+        }
+
+        if problem.is_some() {
+            self.clean_since = None;
+            return true;
+        }
+        let clean_since = *self.clean_since.get_or_insert(now);
+        if now.duration_since(clean_since).as_secs_f64() >= after {
+            info!("live signal recovered");
+            return false;
+        }
+
+        true
+    }
+}
+
+impl Drop for SignalMeter {
+    fn drop(&mut self) {
+        self.monitor.publish(self.endpoint, None);
+    }
+}
+
+/// Counts the bytes and the video frames missing in the packets of one
+/// connection.
+pub(crate) struct PacketMeter {
+    video_index: Option<usize>,
+    /// Length of a video frame in ticks of its stream.
+    frame_ticks: Option<f64>,
+    next_dts: Option<f64>,
+    counters: Arc<PacketCounters>,
+}
+
+impl PacketMeter {
+    pub(crate) fn observe(&mut self, stream: &format::stream::Stream, packet: &Packet) {
+        self.count(stream.index(), stream.time_base(), packet);
+    }
+
+    /// Counts a packet of the stream at `index`, whose timestamps are in
+    /// `time_base`.
+    fn count(&mut self, index: usize, time_base: Rational, packet: &Packet) {
+        self.counters
+            .bytes
+            .fetch_add(packet.size() as u64, Ordering::Relaxed);
+        if Some(index) != self.video_index {
+            return;
+        }
+        let (Some(frame_ticks), Some(dts)) = (self.frame_ticks, packet.dts().or(packet.pts()))
+        else {
+            return;
+        };
+
+        let dts = dts as f64;
+        if let Some(expected) = self.next_dts {
+            let missing = ((dts - expected) / frame_ticks).round();
+            let max_missing = MAX_DROPPED_GAP_SECONDS * f64::from(time_base.denominator())
+                / f64::from(time_base.numerator().max(1))
+                / frame_ticks;
+            if (1.0..=max_missing).contains(&missing) {
+                self.counters
+                    .dropped
+                    .fetch_add(missing as u64, Ordering::Relaxed);
+            }
+        }
+        self.next_dts = Some(dts + frame_ticks);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use std::sync::Arc;
+
+    use ffmpeg_next::{Packet, Rational, frame, util::format::pixel::Pixel};
+
+    use super::{PacketMeter, SignalMonitor};
+
+    #[test]
+    fn frozen_picture_is_detected() {
+        let monitor = SignalMonitor::default();
+        let mut meter = monitor.meter(0);
+        let mut picture = frame::Video::new(Pixel::YUV420P, 64, 36);
+        picture.data_mut(0).fill(120);
+
+        assert!(!meter.is_frozen(&picture));
+        assert!(meter.is_frozen(&picture));
+        picture.data_mut(0).fill(60);
+        assert!(!meter.is_frozen(&picture));
+    }
+
+    #[test]
+    fn failed_signal_recovers_after_the_same_time() {
+        let monitor = SignalMonitor::default();
+        monitor.set_failover_after(Some(Duration::from_secs(3)));
+        let mut meter = monitor.meter(1);
+        let start = Instant::now();
+
+        meter.black_since = Some(start);
+        meter.update(start + Duration::from_secs(2));
+        assert!(!meter.failed());
+        meter.update(start + Duration::from_secs(3));
+        assert!(meter.failed());
+        assert!(monitor.report(1).is_some_and(|report| report.failed));
+
+        meter.black_since = None;
+        meter.update(start + Duration::from_secs(4));
+        assert!(meter.failed());
+        meter.update(start + Duration::from_secs(7));
+        assert!(!meter.failed());
+
+        drop(meter);
+        assert_eq!(monitor.report(1), None);
+    }
+
+    #[test]
+    fn packets_give_dropped_frames_and_bitrate() {
+        let monitor = SignalMonitor::default();
+        let mut meter = monitor.meter(0);
+        // 25 fps video in 90 kHz ticks on stream 0, audio on stream 1.
+        let mut packets = PacketMeter {
+            video_index: Some(0),
+            frame_ticks: Some(3600.0),
+            next_dts: None,
+            counters: Arc::clone(&meter.counters),
+        };
+        let time_base = Rational(1, 90_000);
+        let mut count = |index, size, dts| {
+            let mut packet = Packet::new(size);
+            packet.set_dts(dts);
+            packets.count(index, time_base, &packet);
+        };
+
+        count(0, 10_000, Some(0));
+        count(1, 500, Some(0));
+        count(0, 10_000, Some(3600));
+        // Two frames missing.
+        count(0, 10_000, Some(14_400));
+        count(1, 500, None);
+        // A repeated timestamp, a packet without any and a jump of the
+        // publisher's clock are no drops.
+        count(0, 10_000, Some(14_400));
+        count(0, 10_000, None);
+        count(0, 10_000, Some(90_000 * 60));
+        count(0, 10_000, Some(90_000 * 60 + 3600));
+
+        let start = Instant::now();
+        meter.window_start = start;
+        meter.update(start + Duration::from_secs(1));
+        let report = monitor.report(0).unwrap();
+        assert_eq!(report.dropped_frames, 2);
+        assert_eq!(report.bitrate, 568);
+
+        count(0, 5_000, Some(90_000 * 60 + 7200));
+        meter.update(start + Duration::from_secs(3));
+        let report = monitor.report(0).unwrap();
+        assert_eq!(report.dropped_frames, 2);
+        assert_eq!(report.bitrate, 20);
+    }
+}
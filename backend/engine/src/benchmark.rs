@@ -8,6 +8,7 @@ pub(crate) enum Stage {
     LogoOverlay,
     TextStatic,
     TextRuntime,
+    GraphicsLayers,
     Vtt,
     EncodeMux,
     #[cfg(feature = "desktop-base")]
@@ -30,6 +31,7 @@ impl Stage {
         Self::LogoOverlay,
         Self::TextStatic,
         Self::TextRuntime,
+        Self::GraphicsLayers,
         Self::Vtt,
         Self::EncodeMux,
         #[cfg(feature = "desktop-base")]
@@ -53,14 +55,15 @@ impl Stage {
             Self::LogoOverlay => 5,
             Self::TextStatic => 6,
             Self::TextRuntime => 7,
-            Self::Vtt => 8,
-            Self::EncodeMux => 9,
+            Self::GraphicsLayers => 8,
+            Self::Vtt => 9,
+            Self::EncodeMux => 10,
             #[cfg(feature = "desktop-base")]
-            Self::DesktopSend => 10,
+            Self::DesktopSend => 11,
             #[cfg(feature = "desktop-base")]
-            Self::DesktopConvert => 11,
+            Self::DesktopConvert => 12,
             #[cfg(feature = "desktop-base")]
-            Self::DesktopPresent => 12,
+            Self::DesktopPresent => 13,
             Self::LiveQueue => Self::COUNT - 1,
         }
     }
@@ -75,6 +78,7 @@ impl Stage {
             Self::LogoOverlay => "logo",
             Self::TextStatic => "text_static",
             Self::TextRuntime => "text_runtime",
+            Self::GraphicsLayers => "graphics_layers",
             Self::Vtt => "vtt",
             Self::EncodeMux => "encode_mux",
             #[cfg(feature = "desktop-base")]
@@ -0,0 +1,553 @@
+use std::{
+    fmt,
+    sync::{
+        Arc, Mutex, PoisonError,
+        mpsc::{self, Receiver, Sender, SyncSender, TryRecvError},
+    },
+    thread,
+};
+
+use anyhow::{Context, Result, anyhow};
+use ffmpeg_next::{
+    Packet, Rational, codec, format, frame, media, software::scaling, util::format::pixel::Pixel,
+};
+use log::warn;
+
+use crate::{
+    compositor::{
+        logo::{logo_dimensions, logo_position},
+        overlay::{OverlayFrame, blend_overlay, chroma_alpha},
+    },
+    utils::{
+        config::AnimationConfig,
+        helper::{even, open_media_input},
+    },
+};
+
+/// Frames decoded ahead of playback.
+const QUEUE_FRAMES: usize = 4;
+/// Used when the stream reports no frame rate.
+const DEFAULT_FRAME_INTERVAL: f64 = 0.04;
+
+/// Animated overlay decoded on its own thread, shared by every renderer of
+/// the layer that shows it.
+#[derive(Clone)]
+pub(crate) struct SharedAnimation(Arc<Mutex<AnimatedOverlay>>);
+
+impl fmt::Debug for SharedAnimation {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("SharedAnimation(..)")
+    }
+}
+
+struct AnimatedOverlay {
+    frames: Receiver<AnimationFrame>,
+    commands: Sender<Command>,
+    current: Option<AnimationFrame>,
+    next: Option<AnimationFrame>,
+    /// Frames of an earlier run still in the queue are skipped.
+    generation: u64,
+    /// Output frames shown since the animation started.
+    elapsed: i64,
+    output_width: u32,
+    output_height: u32,
+    fps: u32,
+}
+
+struct AnimationFrame {
+    overlay: OverlayFrame,
+    /// Presentation time since the start of the run.
+    seconds: f64,
+    generation: u64,
+}
+
+enum Command {
+    Restart,
+}
+
+enum PassEnd {
+    /// The file ended at this time since the start of the run.
+    Finished(f64),
+    Restarted,
+    Closed,
+}
+
+impl SharedAnimation {
+    pub(crate) fn spawn(
+        config: &AnimationConfig,
+        output_width: u32,
+        output_height: u32,
+        fps: u32,
+    ) -> Self {
+        let (frame_sender, frames) = mpsc::sync_channel(QUEUE_FRAMES);
+        let (commands, command_receiver) = mpsc::channel();
+        let config = config.clone();
+
+        thread::spawn(move || {
+            if let Err(error) = run_decoder(
+                &config,
+                output_width,
+                output_height,
+                &frame_sender,
+                &command_receiver,
+            ) {
+                warn!("animated overlay {} stopped: {error:#}", config.path);
+            }
+        });
+
+        Self::from_channels(frames, commands, output_width, output_height, fps)
+    }
+
+    fn from_channels(
+        frames: Receiver<AnimationFrame>,
+        commands: Sender<Command>,
+        output_width: u32,
+        output_height: u32,
+        fps: u32,
+    ) -> Self {
+        Self(Arc::new(Mutex::new(AnimatedOverlay {
+            frames,
+            commands,
+            current: None,
+            next: None,
+            generation: 0,
+            elapsed: 0,
+            output_width,
+            output_height,
+            fps,
+        })))
+    }
+
+    /// Whether the animation was decoded for this output format.
+    pub(crate) fn matches(&self, output_width: u32, output_height: u32, fps: u32) -> bool {
+        let animation = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        animation.output_width == output_width
+            && animation.output_height == output_height
+            && animation.fps == fps
+    }
+
+    pub(crate) fn restart(&self) {
+        let mut animation = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        // A decoder that already stopped has nothing left to restart.
+        let _ = animation.commands.send(Command::Restart);
+        animation.generation += 1;
+        animation.elapsed = 0;
+        animation.current = None;
+        animation.next = None;
+    }
+
+    /// Advance by one output frame and blend the current animation frame.
+    pub(crate) fn blend(&self, target: &mut frame::Video, opacity_factor: f64) {
+        let mut animation = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(overlay) = animation.advance() {
+            blend_overlay(target, overlay.as_ref(), opacity_factor);
+        }
+    }
+
+    /// Advance by one output frame and copy the current animation frame.
+    pub(crate) fn next_picture(&self) -> Option<frame::Video> {
+        let mut animation = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        animation.advance().map(|overlay| overlay.frame.clone())
+    }
+}
+
+impl AnimatedOverlay {
+    /// Frames the decoder hasn't delivered in time are skipped, so a slow
+    /// decoder drops frames instead of slowing the animation down.
+    fn advance(&mut self) -> Option<&OverlayFrame> {
+        let seconds = self.elapsed as f64 / f64::from(self.fps.max(1));
+        self.elapsed += 1;
+
+        loop {
+            if self.next.is_none() {
+                match self.frames.try_recv() {
+                    Ok(frame) if frame.generation == self.generation => self.next = Some(frame),
+                    Ok(_) => continue,
+                    Err(_) => break,
+                }
+            }
+            if self
+                .next
+                .as_ref()
+                .is_some_and(|next| next.seconds <= seconds)
+            {
+                self.current = self.next.take();
+            } else {
+                break;
+            }
+        }
+
+        self.current.as_ref().map(|current| &current.overlay)
+    }
+}
+
+fn run_decoder(
+    config: &AnimationConfig,
+    output_width: u32,
+    output_height: u32,
+    frames: &SyncSender<AnimationFrame>,
+    commands: &Receiver<Command>,
+) -> Result<()> {
+    let mut generation = 0;
+    let mut offset = 0.0;
+
+    loop {
+        let mut pass = AnimationPass::open(config, output_width, output_height)?;
+        match pass.run(frames, commands, generation, offset)? {
+            // A still image is held instead of being decoded over and over.
+            PassEnd::Finished(end) if config.looping && pass.decoded > 1 => offset = end,
+            PassEnd::Finished(_) => match commands.recv() {
+                Ok(Command::Restart) => {
+                    generation += 1;
+                    offset = 0.0;
+                }
+                Err(_) => return Ok(()),
+            },
+            PassEnd::Restarted => {
+                generation += 1;
+                offset = 0.0;
+            }
+            PassEnd::Closed => return Ok(()),
+        }
+    }
+}
+
+/// One pass through the animation file.
+struct AnimationPass {
+    path: String,
+    ictx: format::context::Input,
+    stream_index: usize,
+    decoder: codec::decoder::Video,
+    time_base: Rational,
+    frame_interval: f64,
+    scaler: Option<(scaling::Context, (Pixel, u32, u32))>,
+    width: u32,
+    height: u32,
+    x: i32,
+    y: i32,
+    first_timestamp: Option<i64>,
+    last_seconds: Option<f64>,
+    decoded: usize,
+}
+
+impl AnimationPass {
+    fn open(config: &AnimationConfig, output_width: u32, output_height: u32) -> Result<Self> {
+        let ictx = open_media_input(&config.path)
+            .with_context(|| format!("failed to open animation {}", config.path))?;
+        let (stream_index, time_base, frame_rate, decoder) = {
+            let stream = ictx
+                .streams()
+                .best(media::Type::Video)
+                .ok_or_else(|| anyhow!("animation {} contains no video stream", config.path))?;
+            (
+                stream.index(),
+                stream.time_base(),
+                f64::from(stream.avg_frame_rate()),
+                open_alpha_decoder(&stream)?,
+            )
+        };
+        let (width, height) = logo_dimensions(
+            config.scale.as_deref(),
+            decoder.width(),
+            decoder.height(),
+            output_width,
+            output_height,
+        )?;
+        let (x, y) = logo_position(&config.position, output_width, output_height, width, height)?;
+
+        Ok(Self {
+            path: config.path.clone(),
+            ictx,
+            stream_index,
+            decoder,
+            time_base,
+            frame_interval: if frame_rate.is_finite() && frame_rate > 0.0 {
+                1.0 / frame_rate
+            } else {
+                DEFAULT_FRAME_INTERVAL
+            },
+            scaler: None,
+            width,
+            height,
+            x: even(x) as i32,
+            y: even(y) as i32,
+            first_timestamp: None,
+            last_seconds: None,
+            decoded: 0,
+        })
+    }
+
+    fn run(
+        &mut self,
+        frames: &SyncSender<AnimationFrame>,
+        commands: &Receiver<Command>,
+        generation: u64,
+        offset: f64,
+    ) -> Result<PassEnd> {
+        loop {
+            let mut packet = Packet::empty();
+            match packet.read(&mut self.ictx) {
+                Ok(()) if packet.stream() != self.stream_index => continue,
+                Ok(()) => self.decoder.send_packet(&packet)?,
+                Err(ffmpeg_next::Error::Eof) => break,
+                Err(error) => return Err(error.into()),
+            }
+            if let Some(end) = self.receive_frames(frames, commands, generation, offset)? {
+                return Ok(end);
+            }
+        }
+
+        self.decoder.send_eof()?;
+        if let Some(end) = self.receive_frames(frames, commands, generation, offset)? {
+            return Ok(end);
+        }
+
+        let last = self
+            .last_seconds
+            .ok_or_else(|| anyhow!("animation {} produced no frames", self.path))?;
+        Ok(PassEnd::Finished(offset + last + self.frame_interval))
+    }
+
+    fn receive_frames(
+        &mut self,
+        frames: &SyncSender<AnimationFrame>,
+        commands: &Receiver<Command>,
+        generation: u64,
+        offset: f64,
+    ) -> Result<Option<PassEnd>> {
+        let mut decoded = frame::Video::empty();
+        while self.decoder.receive_frame(&mut decoded).is_ok() {
+            match commands.try_recv() {
+                Ok(Command::Restart) => return Ok(Some(PassEnd::Restarted)),
+                Err(TryRecvError::Disconnected) => return Ok(Some(PassEnd::Closed)),
+                Err(TryRecvError::Empty) => {}
+            }
+
+            let seconds = self.seconds(decoded.timestamp());
+            let overlay = self.convert(&decoded)?;
+            let frame = AnimationFrame {
+                overlay,
+                seconds: offset + seconds,
+                generation,
+            };
+            if frames.send(frame).is_err() {
+                return Ok(Some(PassEnd::Closed));
+            }
+            self.last_seconds = Some(seconds);
+            self.decoded += 1;
+        }
+        Ok(None)
+    }
+
+    /// Frame time since the start of the file. Missing or non-increasing
+    /// timestamps fall back to the stream frame rate.
+    fn seconds(&mut self, timestamp: Option<i64>) -> f64 {
+        let fallback = self
+            .last_seconds
+            .map_or(0.0, |last| last + self.frame_interval);
+        let Some(timestamp) = timestamp else {
+            return fallback;
+        };
+        let first = *self.first_timestamp.get_or_insert(timestamp);
+        let seconds = (timestamp - first) as f64 * f64::from(self.time_base);
+
+        if self.last_seconds.is_some_and(|last| seconds <= last) {
+            fallback
+        } else {
+            seconds
+        }
+    }
+
+    fn convert(&mut self, decoded: &frame::Video) -> Result<OverlayFrame> {
+        let mut yuva = frame::Video::empty();
+        self.scaler(decoded)?.run(decoded, &mut yuva)?;
+
+        Ok(OverlayFrame {
+            chroma_alpha: chroma_alpha(&yuva, self.width, self.height),
+            frame: yuva,
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+            opacity: 255,
+        })
+    }
+
+    /// GIF palettes and some codecs may change the frame format mid-stream.
+    fn scaler(&mut self, decoded: &frame::Video) -> Result<&mut scaling::Context> {
+        let source = (decoded.format(), decoded.width(), decoded.height());
+        if self
+            .scaler
+            .as_ref()
+            .is_none_or(|(_, current)| *current != source)
+        {
+            let scaler = scaling::Context::get(
+                source.0,
+                source.1,
+                source.2,
+                Pixel::YUVA420P,
+                self.width,
+                self.height,
+                scaling::flag::Flags::BILINEAR,
+            )?;
+            self.scaler = Some((scaler, source));
+        }
+
+        self.scaler
+            .as_mut()
+            .map(|(scaler, _)| scaler)
+            .ok_or_else(|| anyhow!("animation scaler is missing"))
+    }
+}
+
+/// The native VP8 and VP9 decoders drop the alpha channel that WebM stores
+/// next to the picture, the libvpx decoders keep it.
+fn open_alpha_decoder(stream: &format::stream::Stream) -> Result<codec::decoder::Video> {
+    let ctx = codec::context::Context::from_parameters(stream.parameters())?;
+    let alpha_decoder = match ctx.id() {
+        codec::Id::VP8 => codec::decoder::find_by_name("libvpx"),
+        codec::Id::VP9 => codec::decoder::find_by_name("libvpx-vp9"),
+        _ => None,
+    };
+
+    Ok(match alpha_decoder {
+        Some(codec) => ctx.decoder().open_as(codec)?.video()?,
+        None => ctx.decoder().video()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, sync::mpsc};
+
+    use ffmpeg_next::{codec, frame, util::format::pixel::Pixel};
+
+    use super::{AnimationFrame, AnimationPass, Command, PassEnd, SharedAnimation};
+    use crate::{compositor::overlay::OverlayFrame, utils::config::AnimationConfig};
+
+    fn animation_frame(seconds: f64, generation: u64, opacity: u8) -> AnimationFrame {
+        AnimationFrame {
+            overlay: OverlayFrame {
+                frame: frame::Video::new(Pixel::YUVA420P, 2, 2),
+                chroma_alpha: vec![255],
+                x: 0,
+                y: 0,
+                width: 2,
+                height: 2,
+                opacity,
+            },
+            seconds,
+            generation,
+        }
+    }
+
+    fn shown_opacity(animation: &SharedAnimation) -> Option<u8> {
+        let mut animation = animation.0.lock().unwrap();
+        animation.advance().map(|overlay| overlay.opacity)
+    }
+
+    #[test]
+    fn frames_follow_the_output_clock() {
+        let (frames, receiver) = mpsc::sync_channel(8);
+        let (commands, _command_receiver) = mpsc::channel::<Command>();
+        let animation = SharedAnimation::from_channels(receiver, commands, 1920, 1080, 25);
+
+        // A 10 fps animation on a 25 fps output.
+        for index in 0..3 {
+            frames
+                .send(animation_frame(f64::from(index) * 0.1, 0, index as u8))
+                .unwrap();
+        }
+
+        let shown = (0..7)
+            .map(|_| shown_opacity(&animation))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            shown,
+            [
+                Some(0),
+                Some(0),
+                Some(0),
+                Some(1),
+                Some(1),
+                Some(2),
+                Some(2)
+            ]
+        );
+    }
+
+    #[test]
+    fn restart_skips_frames_of_the_previous_run() {
+        let (frames, receiver) = mpsc::sync_channel(8);
+        let (commands, command_receiver) = mpsc::channel();
+        let animation = SharedAnimation::from_channels(receiver, commands, 1920, 1080, 25);
+
+        frames.send(animation_frame(0.0, 0, 1)).unwrap();
+        assert_eq!(shown_opacity(&animation), Some(1));
+
+        frames.send(animation_frame(0.04, 0, 2)).unwrap();
+        animation.restart();
+        assert!(matches!(command_receiver.try_recv(), Ok(Command::Restart)));
+        assert_eq!(shown_opacity(&animation), None);
+
+        frames.send(animation_frame(0.0, 1, 3)).unwrap();
+        assert_eq!(shown_opacity(&animation), Some(3));
+    }
+
+    /// Frames of one pass through a fixture in tests/assets/animation.
+    fn decode_fixture(name: &str) -> Vec<OverlayFrame> {
+        ffmpeg_next::init().ok();
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../tests/assets/animation")
+            .join(name);
+        let config = AnimationConfig {
+            path: path.to_string_lossy().to_string(),
+            scale: None,
+            position: "0:0".to_string(),
+            looping: false,
+        };
+        let mut pass = AnimationPass::open(&config, 64, 36).unwrap();
+        let (frames, receiver) = mpsc::sync_channel(8);
+        let (_commands, command_receiver) = mpsc::channel();
+
+        let end = pass.run(&frames, &command_receiver, 0, 0.0).unwrap();
+        assert!(matches!(end, PassEnd::Finished(_)));
+        receiver.try_iter().map(|frame| frame.overlay).collect()
+    }
+
+    fn alpha(overlay: &OverlayFrame, x: usize, y: usize) -> u8 {
+        overlay.frame.data(3)[y * overlay.frame.stride(3) + x]
+    }
+
+    #[test]
+    fn gif_and_apng_keep_frames_and_transparency() {
+        for name in ["bug.gif", "bug.apng"] {
+            let frames = decode_fixture(name);
+
+            assert_eq!(frames.len(), 3, "{name}");
+            for overlay in &frames {
+                assert_eq!((overlay.width, overlay.height), (16, 16), "{name}");
+                // The left half of the bug is transparent.
+                assert_eq!(alpha(overlay, 0, 8), 0, "{name}");
+                assert_eq!(alpha(overlay, 15, 8), 255, "{name}");
+            }
+        }
+    }
+
+    #[test]
+    fn webm_alpha_channel_is_kept() {
+        // Only the libvpx decoder reads the alpha stream.
+        if codec::decoder::find_by_name("libvpx-vp9").is_none() {
+            return;
+        }
+        let frames = decode_fixture("bug_alpha.webm");
+
+        assert_eq!(frames.len(), 3);
+        for overlay in &frames {
+            assert_eq!((overlay.width, overlay.height), (8, 8));
+            // The alpha plane is mid gray, like the picture.
+            assert_eq!(alpha(overlay, 0, 0), 128);
+            assert_eq!(alpha(overlay, 7, 7), 128);
+        }
+    }
+}
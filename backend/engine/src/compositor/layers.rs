@@ -0,0 +1,636 @@
+use std::sync::{Arc, PoisonError, RwLock};
+
+use anyhow::{Result, anyhow};
+use ffmpeg_next::frame;
+use log::warn;
+
+use crate::{
+    compositor::{
+        animation::SharedAnimation,
+        logo::{LogoOverlay, blend_logo},
+        text::TextOverlay,
+    },
+    utils::config::{GraphicLayer, GraphicLayerContent, GraphicLayerUpdate, OutputConfig},
+};
+
+/// Ordered stack of graphic layers that can be changed while the channel
+/// is running.
+///
+/// Fades run on the layer clock, the continuous frame counter that also
+/// drives scrolling text, so a fade carries on across clip changes.
+#[derive(Debug, Clone, Default)]
+pub struct GraphicsLayers {
+    inner: Arc<RwLock<LayersInner>>,
+}
+
+#[derive(Debug, Default)]
+struct LayersInner {
+    revision: u64,
+    /// Sorted by z-index; layers with the same index keep insertion order.
+    entries: Vec<LayerEntry>,
+}
+
+#[derive(Debug)]
+struct LayerEntry {
+    layer: GraphicLayer,
+    /// Changes whenever the content has to be rendered again.
+    revision: u64,
+    /// Layer clock when the content was set, stamped by the next frame.
+    content_pts: Option<i64>,
+    fade: Fade,
+    /// Visibility waiting for the next frame to start its fade.
+    pending: Option<bool>,
+    /// Decoder of animation content, started by the first renderer.
+    animation: Option<SharedAnimation>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Fade {
+    visible: bool,
+    start_pts: Option<i64>,
+    from: f64,
+}
+
+/// Layer state for one output frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct LayerFrame {
+    pub revision: u64,
+    pub content_pts: i64,
+    pub opacity: f64,
+}
+
+impl GraphicsLayers {
+    /// Add a layer, or replace the one with the same id.
+    pub fn set(&self, layer: GraphicLayer) -> Result<()> {
+        validate_layer(&layer)?;
+
+        let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        inner.revision = inner.revision.wrapping_add(1);
+        let revision = inner.revision;
+
+        match inner
+            .entries
+            .iter()
+            .position(|entry| entry.layer.id == layer.id)
+        {
+            Some(index) => {
+                let entry = &mut inner.entries[index];
+                if entry.layer.content != layer.content {
+                    entry.revision = revision;
+                    entry.content_pts = None;
+                    entry.animation = None;
+                }
+                let visible = layer.visible;
+                entry.layer = layer;
+                entry.show(visible);
+            }
+            None => inner.entries.push(LayerEntry::new(layer, revision)),
+        }
+        inner.sort();
+        Ok(())
+    }
+
+    /// Change z-index, opacity or visibility. Returns `false` when no layer
+    /// with `id` exists.
+    pub fn update(&self, id: &str, update: GraphicLayerUpdate) -> Result<bool> {
+        if let Some(opacity) = update.opacity {
+            validate_opacity(opacity)?;
+        }
+
+        let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        let Some(entry) = inner.entries.iter_mut().find(|entry| entry.layer.id == id) else {
+            return Ok(false);
+        };
+        if let Some(z_index) = update.z_index {
+            entry.layer.z_index = z_index;
+        }
+        if let Some(opacity) = update.opacity {
+            entry.layer.opacity = opacity;
+        }
+        if let Some(visible) = update.visible {
+            entry.show(visible);
+        }
+        inner.sort();
+        Ok(true)
+    }
+
+    /// Remove a layer immediately. Hide it first to fade it out.
+    pub fn remove(&self, id: &str) -> bool {
+        let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        let count = inner.entries.len();
+        inner.entries.retain(|entry| entry.layer.id != id);
+        inner.entries.len() != count
+    }
+
+    pub fn clear(&self) {
+        let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        inner.entries.clear();
+    }
+
+    /// All layers from bottom to top.
+    pub fn layers(&self) -> Vec<GraphicLayer> {
+        let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+        inner
+            .entries
+            .iter()
+            .map(|entry| entry.layer.clone())
+            .collect()
+    }
+
+    pub(crate) fn frame_at(&self, pts: i64, fps: u32) -> Vec<LayerFrame> {
+        // Called once per rendered frame: the write lock is only needed to
+        // stamp changes made since the previous frame.
+        {
+            let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+            if !inner.entries.iter().any(|entry| entry.needs_stamp(pts)) {
+                return inner.frames(pts, fps);
+            }
+        }
+
+        let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        for entry in &mut inner.entries {
+            entry.stamp(pts, fps);
+        }
+        inner.frames(pts, fps)
+    }
+
+    /// Shared decoder of an animation layer, started on first use.
+    pub(crate) fn animation(
+        &self,
+        revision: u64,
+        output_width: u32,
+        output_height: u32,
+        fps: u32,
+    ) -> Option<SharedAnimation> {
+        let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        let entry = inner
+            .entries
+            .iter_mut()
+            .find(|entry| entry.revision == revision)?;
+        let GraphicLayerContent::Animation(config) = &entry.layer.content else {
+            return None;
+        };
+
+        if let Some(animation) = &entry.animation
+            && animation.matches(output_width, output_height, fps)
+        {
+            return Some(animation.clone());
+        }
+        let animation = SharedAnimation::spawn(config, output_width, output_height, fps);
+        entry.animation = Some(animation.clone());
+        Some(animation)
+    }
+
+    pub(crate) fn content(&self, revision: u64) -> Option<GraphicLayerContent> {
+        let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+        inner
+            .entries
+            .iter()
+            .find(|entry| entry.revision == revision)
+            .map(|entry| entry.layer.content.clone())
+    }
+}
+
+impl LayersInner {
+    fn sort(&mut self) {
+        self.entries.sort_by_key(|entry| entry.layer.z_index);
+    }
+
+    fn frames(&self, pts: i64, fps: u32) -> Vec<LayerFrame> {
+        self.entries
+            .iter()
+            .map(|entry| LayerFrame {
+                revision: entry.revision,
+                content_pts: entry.content_pts.unwrap_or(pts),
+                opacity: entry.layer.opacity * entry.fade.level(&entry.layer, pts, fps),
+            })
+            .collect()
+    }
+}
+
+impl LayerEntry {
+    fn new(layer: GraphicLayer, revision: u64) -> Self {
+        let visible = layer.visible;
+        let mut entry = Self {
+            layer,
+            revision,
+            content_pts: None,
+            fade: Fade {
+                visible: false,
+                start_pts: None,
+                from: 0.0,
+            },
+            pending: None,
+            animation: None,
+        };
+        entry.show(visible);
+        entry
+    }
+
+    fn show(&mut self, visible: bool) {
+        self.layer.visible = visible;
+        self.pending = (visible != self.fade.visible).then_some(visible);
+    }
+
+    /// A clock behind a stamp means the timeline started over.
+    fn needs_stamp(&self, pts: i64) -> bool {
+        self.pending.is_some()
+            || self.content_pts.is_none_or(|content_pts| content_pts > pts)
+            || self.fade.start_pts.is_some_and(|start_pts| start_pts > pts)
+    }
+
+    fn stamp(&mut self, pts: i64, fps: u32) {
+        if self.content_pts.is_none_or(|content_pts| content_pts > pts) {
+            self.content_pts = Some(pts);
+        }
+        if let Some(visible) = self.pending.take() {
+            if visible && let Some(animation) = &self.animation {
+                animation.restart();
+            }
+            self.fade = Fade {
+                visible,
+                start_pts: Some(pts),
+                from: self.fade.level(&self.layer, pts, fps),
+            };
+        } else if self.fade.start_pts.is_some_and(|start_pts| start_pts > pts) {
+            self.fade = Fade {
+                visible: self.fade.visible,
+                start_pts: Some(pts),
+                from: if self.fade.visible { 1.0 } else { 0.0 },
+            };
+        }
+    }
+}
+
+impl Fade {
+    /// Fades run at a constant rate, so reversing one half way takes half
+    /// of the configured time.
+    fn level(self, layer: &GraphicLayer, pts: i64, fps: u32) -> f64 {
+        let Some(start_pts) = self.start_pts else {
+            return self.from;
+        };
+        let elapsed = (pts - start_pts).max(0) as f64 / f64::from(fps.max(1));
+
+        if self.visible {
+            if layer.fade_in_seconds > 0.0 {
+                (self.from + elapsed / layer.fade_in_seconds).min(1.0)
+            } else {
+                1.0
+            }
+        } else if layer.fade_out_seconds > 0.0 {
+            (self.from - elapsed / layer.fade_out_seconds).max(0.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+fn validate_layer(layer: &GraphicLayer) -> Result<()> {
+    if layer.id.trim().is_empty() {
+        return Err(anyhow!("layer id must not be empty"));
+    }
+    validate_opacity(layer.opacity)?;
+    for (name, seconds) in [
+        ("fade in", layer.fade_in_seconds),
+        ("fade out", layer.fade_out_seconds),
+    ] {
+        if !seconds.is_finite() || seconds < 0.0 {
+            return Err(anyhow!("layer {name} must not be negative"));
+        }
+    }
+    Ok(())
+}
+
+fn validate_opacity(opacity: f64) -> Result<()> {
+    if !opacity.is_finite() || !(0.0..=1.0).contains(&opacity) {
+        return Err(anyhow!("layer opacity must be between 0.0 and 1.0"));
+    }
+    Ok(())
+}
+
+/// Draws the layer stack onto output frames of one clip.
+pub(crate) struct LayerRenderer {
+    layers: GraphicsLayers,
+    placeholders: TextPlaceholders,
+    label: String,
+    output_width: u32,
+    output_height: u32,
+    fps: u32,
+}
+
+/// Rendered layers, kept across clips so each layer is rendered once per
+/// content revision and output size.
+#[derive(Default)]
+pub(crate) struct LayerCache {
+    output: (u32, u32, u32),
+    rendered: Vec<RenderedLayer>,
+}
+
+struct RenderedLayer {
+    revision: u64,
+    /// Clip the text was taken from, for texts showing the file name.
+    label: Option<String>,
+    graphic: Option<Graphic>,
+}
+
+enum Graphic {
+    Image(LogoOverlay),
+    Animation(SharedAnimation),
+    Text(TextOverlay),
+}
+
+impl LayerRenderer {
+    pub(crate) fn new(label: &str, cfg: &OutputConfig) -> Self {
+        Self {
+            layers: cfg.graphics_layers.clone(),
+            placeholders: cfg.text_placeholders.clone(),
+            label: label.to_string(),
+            output_width: cfg.width,
+            output_height: cfg.height,
+            fps: cfg.fps,
+        }
+    }
+
+    /// Blend all layers bottom to top at layer clock `pts`.
+    pub(crate) fn blend(&self, target: &mut frame::Video, pts: i64, cache: &mut LayerCache) {
+        let output = (self.output_width, self.output_height, self.fps);
+        if cache.output != output {
+            cache.output = output;
+            cache.rendered.clear();
+        }
+
+        let frames = self.layers.frame_at(pts, self.fps);
+        cache.rendered.retain(|rendered| {
+            frames
+                .iter()
+                .any(|layer| layer.revision == rendered.revision)
+                && rendered
+                    .label
+                    .as_ref()
+                    .is_none_or(|label| *label == self.label)
+        });
+
+        for layer in &frames {
+            if layer.opacity <= 0.0 {
+                continue;
+            }
+
+            let index = match cache
+                .rendered
+                .iter()
+                .position(|rendered| rendered.revision == layer.revision)
+            {
+                Some(index) => index,
+                None => {
+                    cache.rendered.push(self.render(layer));
+                    cache.rendered.len() - 1
+                }
+            };
+
+            match &mut cache.rendered[index].graphic {
+                Some(Graphic::Image(image)) => blend_logo(target, image, layer.opacity),
+                Some(Graphic::Animation(animation)) => animation.blend(target, layer.opacity),
+                Some(Graphic::Text(text)) => {
+                    text.blend_with_opacity(target, pts, pts, layer.opacity);
+                }
+                None => {}
+            }
+        }
+    }
+
+    fn render(&self, layer: &LayerFrame) -> RenderedLayer {
+        let content = self.layers.content(layer.revision);
+        let label = match &content {
+            Some(GraphicLayerContent::Text(text)) if text.use_filename => Some(self.label.clone()),
+            _ => None,
+        };
+        RenderedLayer {
+            revision: layer.revision,
+            label,
+            graphic: content.and_then(|content| self.render_content(content, layer)),
+        }
+    }
+
+    fn render_content(&self, content: GraphicLayerContent, layer: &LayerFrame) -> Option<Graphic> {
+        let graphic = match content {
+            GraphicLayerContent::Image(image) => {
+                LogoOverlay::load(&image, self.output_width, self.output_height)
+                    .map(|image| Some(Graphic::Image(image)))
+            }
+            GraphicLayerContent::Animation(_) => Ok(self
+                .layers
+                .animation(
+                    layer.revision,
+                    self.output_width,
+                    self.output_height,
+                    self.fps,
+                )
+                .map(Graphic::Animation)),
+            GraphicLayerContent::Text(text) => TextOverlay::load(
+                &text,
+                &self.label,
+                self.output_width,
+                self.output_height,
+                self.fps,
+                layer.content_pts,
+                layer.content_pts,
+                None,
+            )
+            .map(|text| text.map(|text| Graphic::Text(text.with_placeholders(&self.placeholders)))),
+        };
+
+        graphic
+            .map_err(|error| {
+                warn!("failed to render graphic layer: {error:#}");
+                error
+            })
+            .ok()
+            .flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ffmpeg_next::{frame, util::format::pixel::Pixel};
+
+    use super::{GraphicsLayers, LayerCache, LayerRenderer};
+    use crate::utils::config::{
+        GraphicLayer, GraphicLayerContent, GraphicLayerUpdate, OutputConfig, TextConfig,
+    };
+
+    const FPS: u32 = 25;
+
+    fn text_layer(id: &str, z_index: i32) -> GraphicLayer {
+        GraphicLayer {
+            id: id.to_string(),
+            z_index,
+            opacity: 1.0,
+            visible: true,
+            fade_in_seconds: 1.0,
+            fade_out_seconds: 2.0,
+            content: GraphicLayerContent::Text(TextConfig {
+                text: Some(id.to_string()),
+                ..TextConfig::default()
+            }),
+        }
+    }
+
+    fn opacities(layers: &GraphicsLayers, pts: i64) -> Vec<f64> {
+        layers
+            .frame_at(pts, FPS)
+            .iter()
+            .map(|layer| layer.opacity)
+            .collect()
+    }
+
+    #[test]
+    fn layers_are_drawn_by_z_index() {
+        let layers = GraphicsLayers::default();
+        layers.set(text_layer("live", 10)).unwrap();
+        layers.set(text_layer("bug", 0)).unwrap();
+        layers.set(text_layer("lower-third", 5)).unwrap();
+
+        let ids = layers
+            .layers()
+            .into_iter()
+            .map(|layer| layer.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["bug", "lower-third", "live"]);
+
+        layers
+            .update(
+                "bug",
+                GraphicLayerUpdate {
+                    z_index: Some(20),
+                    ..GraphicLayerUpdate::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(layers.layers().last().unwrap().id, "bug");
+    }
+
+    #[test]
+    fn layers_fade_on_the_layer_clock() {
+        let layers = GraphicsLayers::default();
+        layers.set(text_layer("bug", 0)).unwrap();
+
+        assert_eq!(opacities(&layers, 100), [0.0]);
+        assert_eq!(opacities(&layers, 110), [0.4]);
+        assert_eq!(opacities(&layers, 125), [1.0]);
+
+        layers
+            .update(
+                "bug",
+                GraphicLayerUpdate {
+                    visible: Some(false),
+                    ..GraphicLayerUpdate::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(opacities(&layers, 200), [1.0]);
+        assert_eq!(opacities(&layers, 225), [0.5]);
+        assert_eq!(opacities(&layers, 250), [0.0]);
+    }
+
+    #[test]
+    fn reversed_fade_starts_from_the_current_level() {
+        let layers = GraphicsLayers::default();
+        layers.set(text_layer("bug", 0)).unwrap();
+        opacities(&layers, 0);
+
+        layers
+            .update(
+                "bug",
+                GraphicLayerUpdate {
+                    visible: Some(false),
+                    ..GraphicLayerUpdate::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(opacities(&layers, 10), [0.4]);
+        assert_eq!(opacities(&layers, 20), [0.2]);
+    }
+
+    #[test]
+    fn only_content_changes_render_the_layer_again() {
+        let layers = GraphicsLayers::default();
+        layers.set(text_layer("bug", 0)).unwrap();
+        let revision = layers.frame_at(0, FPS)[0].revision;
+
+        layers
+            .update(
+                "bug",
+                GraphicLayerUpdate {
+                    opacity: Some(0.5),
+                    ..GraphicLayerUpdate::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(layers.frame_at(25, FPS)[0].revision, revision);
+        assert_eq!(opacities(&layers, 25), [0.5]);
+
+        let mut changed = text_layer("bug", 0);
+        changed.content = GraphicLayerContent::Text(TextConfig {
+            text: Some("changed".to_string()),
+            ..TextConfig::default()
+        });
+        layers.set(changed).unwrap();
+        let frame = layers.frame_at(30, FPS)[0];
+        assert_ne!(frame.revision, revision);
+        assert_eq!(frame.content_pts, 30);
+        assert_eq!(frame.opacity, 1.0);
+    }
+
+    #[test]
+    fn rejects_invalid_layers_and_unknown_ids() {
+        let layers = GraphicsLayers::default();
+        let mut layer = text_layer("bug", 0);
+        layer.opacity = 1.5;
+        assert!(layers.set(layer).is_err());
+        assert!(layers.set(text_layer(" ", 0)).is_err());
+
+        assert!(
+            !layers
+                .update("missing", GraphicLayerUpdate::default())
+                .unwrap()
+        );
+        assert!(!layers.remove("missing"));
+
+        layers.set(text_layer("bug", 0)).unwrap();
+        assert!(layers.remove("bug"));
+        assert!(layers.frame_at(0, FPS).is_empty());
+    }
+
+    #[test]
+    fn rendered_layers_are_kept_across_clips() {
+        let layers = GraphicsLayers::default();
+        let cfg = OutputConfig::new(64, 36, FPS, 48_000).with_graphics_layers(layers.clone());
+        let mut bug = text_layer("bug", 0);
+        bug.fade_in_seconds = 0.0;
+        layers.set(bug).unwrap();
+        let mut name = text_layer("name", 1);
+        name.fade_in_seconds = 0.0;
+        name.content = GraphicLayerContent::Text(TextConfig {
+            use_filename: true,
+            ..TextConfig::default()
+        });
+        layers.set(name).unwrap();
+
+        let mut cache = LayerCache::default();
+        let mut target = frame::Video::new(Pixel::YUV420P, 64, 36);
+        LayerRenderer::new("/media/first.mp4", &cfg).blend(&mut target, 0, &mut cache);
+        assert_eq!(cache.rendered.len(), 2);
+        cache.rendered[0].graphic = None;
+
+        LayerRenderer::new("/media/second.mp4", &cfg).blend(&mut target, 1, &mut cache);
+
+        // The plain text is not rendered again, the file name is.
+        assert_eq!(cache.rendered.len(), 2);
+        assert!(cache.rendered[0].label.is_none());
+        assert!(cache.rendered[0].graphic.is_none());
+        assert_eq!(
+            cache.rendered[1].label.as_deref(),
+            Some("/media/second.mp4")
+        );
+    }
+}
@@ -118,7 +118,7 @@ impl LogoOverlay {
     }
 }
 
-fn logo_dimensions(
+pub(crate) fn logo_dimensions(
     scale: Option<&str>,
     input_width: u32,
     input_height: u32,
@@ -0,0 +1,235 @@
+use crate::utils::config::RgbaColor;
+
+/// Run of a marked up text with one style.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MarkupSpan {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub color: Option<RgbaColor>,
+    pub size: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Tag {
+    Bold,
+    Italic,
+    Color(RgbaColor),
+    Size(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TagKind {
+    Bold,
+    Italic,
+    Color,
+    Size,
+}
+
+impl Tag {
+    fn kind(self) -> TagKind {
+        match self {
+            Self::Bold => TagKind::Bold,
+            Self::Italic => TagKind::Italic,
+            Self::Color(_) => TagKind::Color,
+            Self::Size(_) => TagKind::Size,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Char(char),
+    Open(Tag),
+    Close(TagKind),
+}
+
+/// Spans of a text with `<b>`, `<i>`, `<color=#rrggbb>` and `<size=N>`
+/// tags and `<br>` line breaks. Tags can be nested, anything in angle
+/// brackets that is no known tag stays in the text as written.
+pub(crate) fn parse_markup(text: &str) -> Vec<MarkupSpan> {
+    let mut spans = Vec::new();
+    let mut open = Vec::<Tag>::new();
+    let mut current = String::new();
+
+    for (_, token) in tokens(text) {
+        match token {
+            Token::Char(character) => current.push(character),
+            Token::Open(tag) => {
+                push_span(&mut spans, &mut current, &open);
+                open.push(tag);
+            }
+            Token::Close(kind) => {
+                push_span(&mut spans, &mut current, &open);
+                if let Some(index) = open.iter().rposition(|tag| tag.kind() == kind) {
+                    open.remove(index);
+                }
+            }
+        }
+    }
+    push_span(&mut spans, &mut current, &open);
+
+    spans
+}
+
+/// Text without the markup.
+pub(crate) fn plain_text(text: &str) -> String {
+    tokens(text)
+        .filter_map(|(_, token)| match token {
+            Token::Char(character) => Some(character),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Start of a marked up text up to the `count`th visible character. Tags
+/// left open by the cut are closed by the end of the text.
+pub(crate) fn revealed_markup(text: &str, count: usize) -> &str {
+    if count == 0 {
+        return "";
+    }
+
+    let mut visible = 0;
+    for (end, token) in tokens(text) {
+        if matches!(token, Token::Char(_)) {
+            visible += 1;
+            if visible == count {
+                return &text[..end];
+            }
+        }
+    }
+
+    text
+}
+
+fn push_span(spans: &mut Vec<MarkupSpan>, text: &mut String, open: &[Tag]) {
+    if text.is_empty() {
+        return;
+    }
+
+    let mut span = MarkupSpan {
+        text: std::mem::take(text),
+        bold: false,
+        italic: false,
+        color: None,
+        size: None,
+    };
+    for tag in open {
+        match *tag {
+            Tag::Bold => span.bold = true,
+            Tag::Italic => span.italic = true,
+            Tag::Color(color) => span.color = Some(color),
+            Tag::Size(size) => span.size = Some(size),
+        }
+    }
+    spans.push(span);
+}
+
+/// Tokens of `text`, each with the byte offset of its end.
+fn tokens(text: &str) -> impl Iterator<Item = (usize, Token)> + '_ {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let rest = &text[offset..];
+        let character = rest.chars().next()?;
+        let (token, length) = if character == '<'
+            && let Some(end) = rest.find('>')
+            && let Some(token) = tag(&rest[1..end])
+        {
+            (token, end + 1)
+        } else {
+            (Token::Char(character), character.len_utf8())
+        };
+        offset += length;
+
+        Some((offset, token))
+    })
+}
+
+fn tag(inner: &str) -> Option<Token> {
+    let inner = inner.trim();
+    if let Some(name) = inner.strip_prefix('/') {
+        return match name.trim() {
+            "b" => Some(Token::Close(TagKind::Bold)),
+            "i" => Some(Token::Close(TagKind::Italic)),
+            "color" => Some(Token::Close(TagKind::Color)),
+            "size" => Some(Token::Close(TagKind::Size)),
+            _ => None,
+        };
+    }
+
+    let (name, value) = match inner.split_once('=') {
+        Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+        None => (inner.trim_end_matches('/').trim_end(), None),
+    };
+    match (name, value) {
+        ("b", None) => Some(Token::Open(Tag::Bold)),
+        ("i", None) => Some(Token::Open(Tag::Italic)),
+        ("br", None) => Some(Token::Char('\n')),
+        ("color", Some(value)) => hex_color(value).map(|color| Token::Open(Tag::Color(color))),
+        ("size", Some(value)) => value
+            .parse::<f32>()
+            .ok()
+            .filter(|size| size.is_finite() && *size > 0.0)
+            .map(|size| Token::Open(Tag::Size(size))),
+        _ => None,
+    }
+}
+
+/// `#rrggbb` or `#rrggbbaa`.
+fn hex_color(value: &str) -> Option<RgbaColor> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+
+    Some(RgbaColor {
+        r: channel(0)?,
+        g: channel(2)?,
+        b: channel(4)?,
+        a: if hex.len() == 8 { channel(6)? } else { 255 },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RgbaColor, parse_markup, plain_text, revealed_markup};
+
+    #[test]
+    fn nested_tags_make_spans() {
+        let spans = parse_markup("<b>NAME</b><br><color=#ff0000><i>title</i> x</color> <a>");
+        let texts = spans
+            .iter()
+            .map(|span| span.text.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(texts, ["NAME", "\n", "title", " x", " <a>"]);
+        assert!(spans[0].bold && !spans[0].italic);
+        assert!(spans[2].italic && !spans[2].bold);
+        assert_eq!(spans[2].color, Some(RgbaColor::opaque(255, 0, 0)));
+        assert_eq!(spans[3].color, spans[2].color);
+        assert!(!spans[3].italic);
+        assert_eq!(spans[4].color, None);
+    }
+
+    #[test]
+    fn invalid_tags_stay_text() {
+        let spans = parse_markup("a < b <size=0>c</size> <size=40>d");
+
+        assert_eq!(spans[0].text, "a < b <size=0>c");
+        assert_eq!(spans[0].size, None);
+        assert_eq!(spans[2].text, "d");
+        assert_eq!(spans[2].size, Some(40.0));
+    }
+
+    #[test]
+    fn reveal_skips_tags() {
+        let text = "<b>Hi</b> <size=30>you</size>";
+
+        assert_eq!(revealed_markup(text, 0), "");
+        assert_eq!(revealed_markup(text, 1), "<b>H");
+        assert_eq!(revealed_markup(text, 4), "<b>Hi</b> <size=30>y");
+        assert_eq!(revealed_markup(text, 20), text);
+        assert_eq!(plain_text(text), "Hi you");
+    }
+}
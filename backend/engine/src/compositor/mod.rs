@@ -1,4 +1,9 @@
+pub mod animation;
 pub mod blend;
+pub mod layers;
 pub mod logo;
+pub mod markup;
 pub mod overlay;
+pub mod pip;
+pub mod template;
 pub mod text;
@@ -0,0 +1,583 @@
+use std::{
+    borrow::Cow,
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use anyhow::{Result, anyhow};
+use ffmpeg_next::{frame, software::scaling, util::format::pixel::Pixel};
+use log::warn;
+
+use crate::{
+    compositor::animation::SharedAnimation,
+    input::generator::{Generator, GeneratorKind, GeneratorSpec, is_generator_source},
+    playout::{SyntheticSource, black_video_frame},
+    utils::{
+        config::{
+            AnimationConfig, OutputConfig, PictureInPictureConfig, PipLayout, PipRect, PipSource,
+        },
+        helper::even,
+    },
+};
+
+/// Generators only deliver the picture here, the rate is never used.
+const GENERATOR_SAMPLE_RATE: u32 = 48_000;
+
+/// Second video source in a window over the program, or around the
+/// squeezed program, that can be changed while the channel is running.
+///
+/// Transitions run on the layer clock, like the fades of graphic layers.
+#[derive(Clone, Default)]
+pub struct PictureInPicture {
+    inner: Arc<Mutex<PipInner>>,
+}
+
+impl fmt::Debug for PictureInPicture {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("PictureInPicture(..)")
+    }
+}
+
+#[derive(Default)]
+struct PipInner {
+    revision: u64,
+    /// Shown configuration, kept until it has moved out.
+    config: Option<PictureInPictureConfig>,
+    visible: bool,
+    /// Rect the running transition started from.
+    from: PipRect,
+    /// Layer clock when the transition started, stamped by the next frame.
+    start_pts: Option<i64>,
+    /// Layer clock and frame rate of the last drawn frame.
+    last_pts: Option<(i64, u32)>,
+    /// Decoder or generator of the source, started by the first frame.
+    feed: Option<RunningFeed>,
+    /// Latest frame of the live ingest.
+    ingest: Option<frame::Video>,
+}
+
+struct RunningFeed {
+    /// `None` when the source could not be opened.
+    feed: Option<Feed>,
+    format: PictureFormat,
+}
+
+enum Feed {
+    Media(SharedAnimation),
+    Generator(Box<Generator>),
+    Ingest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PictureFormat {
+    width: u32,
+    height: u32,
+    fps: u32,
+}
+
+/// Second source state for one output frame.
+pub(crate) struct PipFrame {
+    revision: u64,
+    layout: PipLayout,
+    rect: PipRect,
+    /// Output sized picture of the source, `None` until it delivers one.
+    picture: Option<frame::Video>,
+}
+
+impl PictureInPicture {
+    /// Show the second source, or move the shown one to the new settings.
+    pub fn show(&self, config: PictureInPictureConfig) -> Result<()> {
+        validate_config(&config)?;
+
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.revision = inner.revision.wrapping_add(1);
+        let from = match &inner.config {
+            Some(shown) if shown.layout == config.layout => inner.current_rect(),
+            _ => hidden_rect(&config),
+        };
+        if inner
+            .config
+            .as_ref()
+            .is_none_or(|shown| shown.source != config.source)
+        {
+            inner.feed = None;
+        }
+
+        inner.config = Some(config);
+        inner.visible = true;
+        inner.from = from;
+        inner.start_pts = None;
+        Ok(())
+    }
+
+    /// Move the second source out. Returns `false` when nothing is shown.
+    pub fn hide(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        if !inner.visible {
+            return false;
+        }
+
+        inner.revision = inner.revision.wrapping_add(1);
+        inner.from = inner.current_rect();
+        inner.visible = false;
+        inner.start_pts = None;
+        true
+    }
+
+    /// Shown configuration, `None` while nothing is shown or it moves out.
+    pub fn config(&self) -> Option<PictureInPictureConfig> {
+        let inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.config.clone().filter(|_| inner.visible)
+    }
+
+    /// Whether the live ingest goes into the window instead of replacing
+    /// the program, until the window has moved out.
+    pub(crate) fn takes_ingest(&self) -> bool {
+        let inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner
+            .config
+            .as_ref()
+            .is_some_and(|config| config.source == PipSource::Ingest)
+    }
+
+    pub(crate) fn push_ingest(&self, frame: &frame::Video) {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.ingest = Some(frame.clone());
+    }
+
+    pub(crate) fn clear_ingest(&self) {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.ingest = None;
+    }
+
+    pub(crate) fn frame_at(&self, pts: i64, format: PictureFormat) -> Option<PipFrame> {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.config.as_ref()?;
+
+        // A clock behind the stamp means the timeline started over.
+        if inner.start_pts.is_none_or(|start_pts| start_pts > pts) {
+            inner.start_pts = Some(pts);
+        }
+        inner.last_pts = Some((pts, format.fps));
+
+        let (rect, finished) = inner.rect_at(pts, format.fps);
+        if finished && !inner.visible {
+            inner.config = None;
+            inner.feed = None;
+            inner.ingest = None;
+            return None;
+        }
+
+        Some(PipFrame {
+            revision: inner.revision,
+            layout: inner.config.as_ref()?.layout,
+            rect,
+            picture: inner.picture(format),
+        })
+    }
+}
+
+impl PipInner {
+    /// Rect at layer clock `pts`, and whether the transition has ended.
+    fn rect_at(&self, pts: i64, fps: u32) -> (PipRect, bool) {
+        let Some(config) = &self.config else {
+            return (self.from, true);
+        };
+        let Some(start_pts) = self.start_pts else {
+            return (self.from, false);
+        };
+        let target = if self.visible {
+            config.rect
+        } else {
+            hidden_rect(config)
+        };
+        let elapsed = (pts - start_pts).max(0) as f64 / f64::from(fps.max(1));
+        let progress = if config.transition_seconds > 0.0 {
+            elapsed / config.transition_seconds
+        } else {
+            1.0
+        };
+
+        if progress >= 1.0 {
+            (target, true)
+        } else {
+            (self.from.lerp(target, config.easing.apply(progress)), false)
+        }
+    }
+
+    fn current_rect(&self) -> PipRect {
+        match self.last_pts {
+            Some((pts, fps)) => self.rect_at(pts, fps).0,
+            None => self.from,
+        }
+    }
+
+    fn picture(&mut self, format: PictureFormat) -> Option<frame::Video> {
+        let config = self.config.as_ref()?;
+        if self
+            .feed
+            .as_ref()
+            .is_none_or(|running| running.format != format)
+        {
+            self.feed = Some(RunningFeed {
+                feed: Feed::open(&config.source, format)
+                    .map_err(|error| warn!("picture-in-picture source is not usable: {error:#}"))
+                    .ok(),
+                format,
+            });
+        }
+
+        match self.feed.as_mut()?.feed.as_mut()? {
+            Feed::Media(animation) => animation.next_picture(),
+            Feed::Generator(generator) => Some(generator.video_frame()),
+            Feed::Ingest => self.ingest.clone(),
+        }
+    }
+}
+
+impl Feed {
+    fn open(source: &PipSource, format: PictureFormat) -> Result<Self> {
+        let PipSource::Media(path) = source else {
+            return Ok(Self::Ingest);
+        };
+
+        if is_generator_source(path) {
+            let cfg = OutputConfig::new(
+                format.width,
+                format.height,
+                format.fps,
+                GENERATOR_SAMPLE_RATE,
+            );
+            return Ok(Self::Generator(Box::new(Generator::new(
+                GeneratorSpec::parse(path)?,
+                &cfg,
+                0.0,
+            ))));
+        }
+
+        // Decoded like an animated layer, scaled to the full output.
+        let config = AnimationConfig {
+            path: path.clone(),
+            scale: Some(format!("{}:{}", format.width, format.height)),
+            position: "0:0".to_string(),
+            looping: true,
+        };
+        Ok(Self::Media(SharedAnimation::spawn(
+            &config,
+            format.width,
+            format.height,
+            format.fps,
+        )))
+    }
+}
+
+/// Rect the source moves in from and out to.
+fn hidden_rect(config: &PictureInPictureConfig) -> PipRect {
+    match config.layout {
+        PipLayout::Window => PipRect {
+            x: config.rect.x + config.rect.width / 2.0,
+            y: config.rect.y + config.rect.height / 2.0,
+            width: 0.0,
+            height: 0.0,
+        },
+        PipLayout::SqueezeBack => PipRect::FULL,
+    }
+}
+
+fn validate_config(config: &PictureInPictureConfig) -> Result<()> {
+    let rect = config.rect;
+    if ![rect.x, rect.y, rect.width, rect.height]
+        .iter()
+        .all(|value| value.is_finite() && (0.0..=1.0).contains(value))
+    {
+        return Err(anyhow!(
+            "picture-in-picture rect values must be between 0.0 and 1.0"
+        ));
+    }
+    if rect.width <= 0.0 || rect.height <= 0.0 {
+        return Err(anyhow!("picture-in-picture rect must not be empty"));
+    }
+    if rect.x + rect.width > 1.0 + f64::EPSILON || rect.y + rect.height > 1.0 + f64::EPSILON {
+        return Err(anyhow!("picture-in-picture rect must lie inside the frame"));
+    }
+    if !config.transition_seconds.is_finite() || config.transition_seconds < 0.0 {
+        return Err(anyhow!(
+            "picture-in-picture transition must not be negative"
+        ));
+    }
+
+    if let PipSource::Media(path) = &config.source {
+        if path.trim().is_empty() {
+            return Err(anyhow!("picture-in-picture source must not be empty"));
+        }
+        if is_generator_source(path) && GeneratorSpec::parse(path)?.kind == GeneratorKind::Countdown
+        {
+            return Err(anyhow!(
+                "the countdown generator needs a clip duration and cannot be a second source"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Draws the second source onto output frames. Runs before the logo and
+/// the texts, so they stay on top of it.
+pub(crate) struct PipRenderer {
+    pip: PictureInPicture,
+    format: PictureFormat,
+    /// Revision that failed to draw, skipped until the next change.
+    failed: Option<u64>,
+    source_scaler: Scaler,
+    program_scaler: Scaler,
+}
+
+impl PipRenderer {
+    pub(crate) fn new(cfg: &OutputConfig) -> Self {
+        Self {
+            pip: cfg.picture_in_picture.clone(),
+            format: PictureFormat {
+                width: cfg.width,
+                height: cfg.height,
+                fps: cfg.fps,
+            },
+            failed: None,
+            source_scaler: Scaler::default(),
+            program_scaler: Scaler::default(),
+        }
+    }
+
+    /// Draw the second source at layer clock `pts`.
+    pub(crate) fn apply(&mut self, target: &mut frame::Video, pts: i64) {
+        let Some(pip) = self.pip.frame_at(pts, self.format) else {
+            return;
+        };
+        if self.failed == Some(pip.revision) {
+            return;
+        }
+
+        if let Err(error) = self.draw(target, &pip) {
+            warn!("picture-in-picture is not shown: {error:#}");
+            self.failed = Some(pip.revision);
+        }
+    }
+
+    fn draw(&mut self, target: &mut frame::Video, pip: &PipFrame) -> Result<()> {
+        let (width, height) = (self.format.width, self.format.height);
+        let Some((x, y, rect_width, rect_height)) = pixel_rect(pip.rect, width, height) else {
+            return Ok(());
+        };
+
+        match pip.layout {
+            PipLayout::Window => {
+                if let Some(picture) = &pip.picture {
+                    let window = self.source_scaler.scale(picture, rect_width, rect_height)?;
+                    paste(target, &window, x, y);
+                }
+            }
+            PipLayout::SqueezeBack => {
+                if (x, y, rect_width, rect_height) == (0, 0, width, height) {
+                    return Ok(());
+                }
+
+                let program = self
+                    .program_scaler
+                    .scale(target, rect_width, rect_height)?
+                    .into_owned();
+                match &pip.picture {
+                    Some(picture) => {
+                        let background = self.source_scaler.scale(picture, width, height)?;
+                        paste(target, &background, 0, 0);
+                    }
+                    None => paste(target, &black_video_frame(width, height), 0, 0),
+                }
+                paste(target, &program, x, y);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Scales pictures to YUV420P and keeps the context while the sizes stay
+/// the same.
+#[derive(Default)]
+struct Scaler {
+    context: Option<(scaling::Context, (Pixel, u32, u32, u32, u32))>,
+}
+
+impl Scaler {
+    fn scale<'a>(
+        &mut self,
+        picture: &'a frame::Video,
+        width: u32,
+        height: u32,
+    ) -> Result<Cow<'a, frame::Video>> {
+        if picture.format() == Pixel::YUV420P
+            && picture.width() == width
+            && picture.height() == height
+        {
+            return Ok(Cow::Borrowed(picture));
+        }
+
+        let key = (
+            picture.format(),
+            picture.width(),
+            picture.height(),
+            width,
+            height,
+        );
+        if self
+            .context
+            .as_ref()
+            .is_none_or(|(_, current)| *current != key)
+        {
+            let context = scaling::Context::get(
+                key.0,
+                key.1,
+                key.2,
+                Pixel::YUV420P,
+                width,
+                height,
+                scaling::flag::Flags::BILINEAR,
+            )?;
+            self.context = Some((context, key));
+        }
+
+        let mut scaled = frame::Video::empty();
+        let (context, _) = self
+            .context
+            .as_mut()
+            .ok_or_else(|| anyhow!("picture-in-picture scaler is missing"))?;
+        context.run(picture, &mut scaled)?;
+        Ok(Cow::Owned(scaled))
+    }
+}
+
+/// Even pixel position and size of `rect`, `None` while it is too small to
+/// draw.
+fn pixel_rect(rect: PipRect, width: u32, height: u32) -> Option<(u32, u32, u32, u32)> {
+    let pixels =
+        |value: f64, size: u32| even((value.clamp(0.0, 1.0) * f64::from(size)).round() as u32);
+    let x = pixels(rect.x, width);
+    let y = pixels(rect.y, height);
+    let rect_width = pixels(rect.width, width).min(even(width.saturating_sub(x)));
+    let rect_height = pixels(rect.height, height).min(even(height.saturating_sub(y)));
+
+    (rect_width >= 2 && rect_height >= 2).then_some((x, y, rect_width, rect_height))
+}
+
+/// Copy a YUV420P picture into `target` at an even position, cut at the
+/// frame edges.
+fn paste(target: &mut frame::Video, source: &frame::Video, x: u32, y: u32) {
+    let width = source.width().min(target.width().saturating_sub(x));
+    let height = source.height().min(target.height().saturating_sub(y));
+
+    for plane in 0..3 {
+        let shift = u32::from(plane > 0);
+        let plane_x = (x >> shift) as usize;
+        let plane_y = (y >> shift) as usize;
+        let plane_width = (width >> shift) as usize;
+        let plane_height = (height >> shift) as usize;
+        let source_stride = source.stride(plane);
+        let target_stride = target.stride(plane);
+        let source_data = source.data(plane);
+        let target_data = target.data_mut(plane);
+
+        for row in 0..plane_height {
+            let from = row * source_stride;
+            let to = (plane_y + row) * target_stride + plane_x;
+            target_data[to..to + plane_width]
+                .copy_from_slice(&source_data[from..from + plane_width]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PictureFormat, PictureInPicture, pixel_rect};
+    use crate::utils::config::{
+        MotionEasing, PictureInPictureConfig, PipLayout, PipRect, PipSource,
+    };
+
+    const FORMAT: PictureFormat = PictureFormat {
+        width: 1920,
+        height: 1080,
+        fps: 25,
+    };
+
+    fn window(layout: PipLayout) -> PictureInPictureConfig {
+        PictureInPictureConfig {
+            source: PipSource::Ingest,
+            layout,
+            rect: PipRect {
+                x: 0.25,
+                y: 0.5,
+                width: 0.5,
+                height: 0.4,
+            },
+            transition_seconds: 1.0,
+            easing: MotionEasing::Linear,
+        }
+    }
+
+    fn rect_at(pip: &PictureInPicture, pts: i64) -> Option<PipRect> {
+        pip.frame_at(pts, FORMAT).map(|frame| frame.rect)
+    }
+
+    #[test]
+    fn window_grows_from_its_center_and_moves_out() {
+        let pip = PictureInPicture::default();
+        pip.show(window(PipLayout::Window)).unwrap();
+        assert!(pip.takes_ingest());
+
+        let start = rect_at(&pip, 100).unwrap();
+        assert_eq!((start.x, start.width), (0.5, 0.0));
+        let half = rect_at(&pip, 112).unwrap();
+        assert!((half.width - 0.24).abs() < 1e-9);
+        assert_eq!(rect_at(&pip, 125), Some(window(PipLayout::Window).rect));
+
+        assert!(pip.hide());
+        assert!(!pip.hide());
+        assert_eq!(pip.config(), None);
+        assert!(pip.takes_ingest());
+        assert_eq!(rect_at(&pip, 130), Some(window(PipLayout::Window).rect));
+        assert_eq!(rect_at(&pip, 155), None);
+        assert!(!pip.takes_ingest());
+    }
+
+    #[test]
+    fn squeeze_back_starts_from_the_full_frame() {
+        let pip = PictureInPicture::default();
+        pip.show(window(PipLayout::SqueezeBack)).unwrap();
+
+        assert_eq!(rect_at(&pip, 20), Some(PipRect::FULL));
+        assert_ne!(rect_at(&pip, 30), Some(PipRect::FULL));
+        // A clock behind the start restarts the transition.
+        assert_eq!(rect_at(&pip, 5), Some(PipRect::FULL));
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected() {
+        let pip = PictureInPicture::default();
+        let mut config = window(PipLayout::Window);
+        config.rect.width = 0.8;
+        assert!(pip.show(config).is_err());
+
+        let mut config = window(PipLayout::Window);
+        config.source = PipSource::Media("generator://countdown".to_string());
+        assert!(pip.show(config).is_err());
+        assert_eq!(pip.config(), None);
+    }
+
+    #[test]
+    fn pixel_rects_are_even_and_inside_the_frame() {
+        let rect = PipRect {
+            x: 0.651,
+            y: 0.05,
+            width: 0.35,
+            height: 0.3,
+        };
+
+        assert_eq!(pixel_rect(rect, 1920, 1080), Some((1250, 54, 670, 324)));
+        assert_eq!(pixel_rect(PipRect { width: 0.0, ..rect }, 1920, 1080), None);
+    }
+}
@@ -0,0 +1,274 @@
+use std::{
+    fmt::Write,
+    sync::{Arc, PoisonError, RwLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{
+    DateTime, Timelike,
+    format::{Item, StrftimeItems},
+};
+use chrono_tz::Tz;
+
+/// Playlist values for the placeholders in overlay texts.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlaceholderValues {
+    pub title: String,
+    pub next_title: String,
+    /// Start of the next clip in seconds since the Unix epoch.
+    pub next_start: Option<f64>,
+    /// Channel timezone; `None` is UTC.
+    pub timezone: Option<Tz>,
+}
+
+/// Shared placeholder values, updated by the caller before each clip and
+/// read by every text overlay while it is rendered.
+#[derive(Debug, Clone, Default)]
+pub struct TextPlaceholders {
+    inner: Arc<RwLock<Arc<PlaceholderValues>>>,
+}
+
+impl TextPlaceholders {
+    pub fn set(&self, values: PlaceholderValues) {
+        *self.inner.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(values);
+    }
+
+    pub fn values(&self) -> Arc<PlaceholderValues> {
+        self.inner
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Title,
+    NextTitle,
+    NextStart(String),
+    Clock(String),
+    /// Time of day in seconds to count down to.
+    Countdown(u32),
+    Remaining,
+}
+
+/// Overlay text with placeholders like `{title}` or `{time:%H:%M}`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TextTemplate {
+    segments: Vec<Segment>,
+}
+
+impl TextTemplate {
+    /// Template for `text`, `None` when it contains no known placeholder.
+    /// Unknown placeholders are kept as written, `{{` and `}}` give
+    /// literal braces.
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut rest = text;
+
+        while let Some(index) = rest.find(['{', '}']) {
+            literal.push_str(&rest[..index]);
+            let tail = &rest[index..];
+            if let Some(after) = tail.strip_prefix("{{").or_else(|| tail.strip_prefix("}}")) {
+                literal.push_str(&tail[..1]);
+                rest = after;
+                continue;
+            }
+            if tail.starts_with('{')
+                && let Some(end) = tail.find('}')
+                && let Some(segment) = placeholder(&tail[1..end])
+            {
+                if !literal.is_empty() {
+                    segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                }
+                segments.push(segment);
+                rest = &tail[end + 1..];
+                continue;
+            }
+            literal.push_str(&tail[..1]);
+            rest = &tail[1..];
+        }
+
+        if segments.is_empty() {
+            return None;
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Some(Self { segments })
+    }
+
+    /// Writes the text with all placeholders resolved at `now`, in seconds
+    /// since the Unix epoch, to the cleared `text`. The timezone offset is
+    /// taken at `now`, so the clock follows daylight saving time.
+    pub(crate) fn render(&self, text: &mut String, values: &PlaceholderValues, now: f64) {
+        let timezone = values.timezone.unwrap_or(Tz::UTC);
+        text.clear();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => text.push_str(literal),
+                Segment::Title => text.push_str(&values.title),
+                Segment::NextTitle => text.push_str(&values.next_title),
+                Segment::NextStart(format) => {
+                    if let Some(start) = values.next_start {
+                        format_time(text, format, start, timezone);
+                    }
+                }
+                Segment::Clock(format) => format_time(text, format, now, timezone),
+                Segment::Countdown(target) => {
+                    let of_day = local_time(now, timezone)
+                        .map_or(0, |local| local.num_seconds_from_midnight());
+                    format_duration(text, f64::from((*target + 86_400 - of_day) % 86_400));
+                }
+                Segment::Remaining => {
+                    if let Some(start) = values.next_start {
+                        format_duration(text, start - now);
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn unix_now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64())
+}
+
+fn placeholder(inner: &str) -> Option<Segment> {
+    let (name, argument) = match inner.split_once(':') {
+        Some((name, argument)) => (name, Some(argument)),
+        None => (inner, None),
+    };
+    // A format chrono can't read leaves the placeholder as written.
+    let format = |default: &str| {
+        let format = argument.unwrap_or(default);
+        StrftimeItems::new(format)
+            .all(|item| item != Item::Error)
+            .then(|| format.to_string())
+    };
+
+    match name.trim() {
+        "title" => Some(Segment::Title),
+        "next_title" => Some(Segment::NextTitle),
+        "next_start" => format("%H:%M").map(Segment::NextStart),
+        "time" => format("%H:%M").map(Segment::Clock),
+        "date" => format("%Y-%m-%d").map(Segment::Clock),
+        "countdown" => argument.and_then(time_of_day).map(Segment::Countdown),
+        "remaining" => Some(Segment::Remaining),
+        _ => None,
+    }
+}
+
+/// Seconds of day for `HH:MM` or `HH:MM:SS`.
+fn time_of_day(value: &str) -> Option<u32> {
+    let parts = value
+        .trim()
+        .split(':')
+        .map(|part| part.parse::<u32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let (hours, minutes, seconds) = match parts[..] {
+        [hours, minutes] => (hours, minutes, 0),
+        [hours, minutes, seconds] => (hours, minutes, seconds),
+        _ => return None,
+    };
+
+    (hours < 24 && minutes < 60 && seconds < 60).then_some(hours * 3600 + minutes * 60 + seconds)
+}
+
+fn local_time(unix: f64, timezone: Tz) -> Option<DateTime<Tz>> {
+    DateTime::from_timestamp(unix.floor() as i64, 0).map(|utc| utc.with_timezone(&timezone))
+}
+
+fn format_time(text: &mut String, format: &str, unix: f64, timezone: Tz) {
+    if let Some(local) = local_time(unix, timezone) {
+        let _ = write!(text, "{}", local.format(format));
+    }
+}
+
+/// `MM:SS` below one hour, `H:MM:SS` above, rounded up so a countdown
+/// shows zero only when the target is reached.
+fn format_duration(text: &mut String, seconds: f64) {
+    let total = seconds.max(0.0).ceil() as u64;
+    let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+    let _ = if hours > 0 {
+        write!(text, "{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        write!(text, "{minutes:02}:{seconds:02}")
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::Europe::Berlin;
+
+    use super::{PlaceholderValues, TextTemplate};
+
+    // 2023-11-14 22:13:20 UTC
+    const NOW: f64 = 1_700_000_000.0;
+
+    fn values() -> PlaceholderValues {
+        PlaceholderValues {
+            title: "News".to_string(),
+            next_title: "Weather".to_string(),
+            next_start: Some(NOW + 95.0),
+            timezone: Some(Berlin),
+        }
+    }
+
+    fn render(template: &TextTemplate, now: f64) -> String {
+        let mut text = String::new();
+        template.render(&mut text, &values(), now);
+        text
+    }
+
+    #[test]
+    fn plain_text_is_not_a_template() {
+        assert_eq!(TextTemplate::parse("Live {unknown} text"), None);
+        assert_eq!(TextTemplate::parse("No placeholders"), None);
+    }
+
+    #[test]
+    fn placeholders_are_resolved() {
+        let template =
+            TextTemplate::parse("Up next: {next_title} at {next_start} ({remaining}) {{x}}")
+                .unwrap();
+
+        assert_eq!(
+            render(&template, NOW),
+            "Up next: Weather at 23:14 (01:35) {x}"
+        );
+    }
+
+    #[test]
+    fn clock_uses_channel_timezone() {
+        let template = TextTemplate::parse("{date} {time:%H:%M:%S} {date:%d.%m.%y}").unwrap();
+
+        assert_eq!(render(&template, NOW), "2023-11-14 23:13:20 14.11.23");
+    }
+
+    #[test]
+    fn countdown_targets_next_time_of_day() {
+        let template = TextTemplate::parse("{countdown:23:15} {countdown:23:00}").unwrap();
+
+        assert_eq!(render(&template, NOW), "01:40 23:46:40");
+    }
+
+    #[test]
+    fn clock_follows_daylight_saving_time() {
+        let template = TextTemplate::parse("{time} {countdown:15:00}").unwrap();
+
+        // 2023-07-01 12:00:00 UTC is 14:00 in Berlin summer time.
+        assert_eq!(render(&template, 1_688_212_800.0), "14:00 1:00:00");
+    }
+
+    #[test]
+    fn unreadable_format_is_shown_as_written() {
+        assert_eq!(TextTemplate::parse("{time:%Q}"), None);
+    }
+}
@@ -1,21 +1,33 @@
 use std::{
     collections::BTreeSet,
+    mem,
     path::Path,
-    sync::{Mutex, OnceLock, PoisonError},
+    sync::{Arc, Mutex, OnceLock, PoisonError},
 };
 
 use anyhow::{Context, Result, anyhow};
-use cosmic_text::{Attrs, Buffer, Color, Family, FontSystem, Metrics, Shaping, SwashCache, Weight};
+use cosmic_text::{
+    Attrs, Buffer, Color, Family, FontSystem, Metrics, Shaping, Style, SwashCache, Weight,
+    fontdb::{Database, ID, Source},
+};
 use ffmpeg_next::{frame, util::format::pixel::Pixel};
+use log::debug;
 use regex::Regex;
 
 #[cfg(feature = "desktop-base")]
 use cosmic_text::{Align, Wrap};
 
 use crate::{
-    compositor::overlay::{OverlayFrame, blend_overlay, chroma_alpha},
+    compositor::{
+        markup::{MarkupSpan, parse_markup, plain_text, revealed_markup},
+        overlay::{OverlayFrame, blend_overlay, chroma_alpha},
+        template::{PlaceholderValues, TextPlaceholders, TextTemplate, unix_now},
+    },
     utils::{
-        config::{RgbaColor, TextConfig, TextPosition, TextScroll, TextWeight},
+        config::{
+            RgbaColor, TextAnimation, TextConfig, TextOutline, TextPosition, TextReveal,
+            TextScroll, TextShadow, TextSlide, TextWeight,
+        },
         helper::even,
     },
 };
@@ -56,6 +68,76 @@ pub(crate) fn available_font_families() -> Vec<String> {
         .collect()
 }
 
+/// Load the faces of a font file, so texts can use its families.
+pub(crate) fn load_font_file(path: &Path) -> Result<Vec<String>> {
+    let mut renderer = renderer().lock().unwrap_or_else(PoisonError::into_inner);
+    remove_faces(&mut renderer.font_system, path);
+    renderer
+        .font_system
+        .db_mut()
+        .load_font_file(path)
+        .with_context(|| format!("failed to read font file {}", path.display()))?;
+
+    let families = font_file_families(&renderer.font_system, path);
+    if families.is_empty() {
+        return Err(anyhow!("{} contains no usable font", path.display()));
+    }
+    Ok(families)
+}
+
+/// Families in font data, without loading it. Fails when the data holds
+/// no usable font.
+pub(crate) fn font_data_families(data: &[u8]) -> Result<Vec<String>> {
+    let mut database = Database::new();
+    database.load_font_data(data.to_vec());
+
+    let families = database
+        .faces()
+        .filter_map(|face| face.families.first().map(|(family, _)| family.clone()))
+        .collect::<BTreeSet<_>>();
+    if families.is_empty() {
+        return Err(anyhow!("no usable font"));
+    }
+    Ok(families.into_iter().collect())
+}
+
+/// Remove the faces loaded from a font file.
+pub(crate) fn unload_font_file(path: &Path) {
+    let mut renderer = renderer().lock().unwrap_or_else(PoisonError::into_inner);
+    remove_faces(&mut renderer.font_system, path);
+}
+
+/// Families of the faces loaded from a font file.
+pub(crate) fn loaded_font_families(path: &Path) -> Vec<String> {
+    let renderer = renderer().lock().unwrap_or_else(PoisonError::into_inner);
+    font_file_families(&renderer.font_system, path)
+}
+
+fn file_faces(font_system: &FontSystem, path: &Path) -> Vec<ID> {
+    font_system
+        .db()
+        .faces()
+        .filter(|face| matches!(&face.source, Source::File(file) if file == path))
+        .map(|face| face.id)
+        .collect()
+}
+
+fn font_file_families(font_system: &FontSystem, path: &Path) -> Vec<String> {
+    file_faces(font_system, path)
+        .into_iter()
+        .filter_map(|id| font_system.db().face(id))
+        .filter_map(|face| face.families.first().map(|(family, _)| family.clone()))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn remove_faces(font_system: &mut FontSystem, path: &Path) {
+    for id in file_faces(font_system, path) {
+        font_system.db_mut().remove_face(id);
+    }
+}
+
 #[cfg(feature = "desktop-base")]
 pub(crate) struct TextBitmap {
     pub pixels: Vec<u8>,
@@ -186,15 +268,38 @@ pub struct TextOverlay {
     overlay: OverlayFrame,
     scroll: TextScroll,
     scroll_repeat: i32,
+    animation: TextAnimation,
     fade_in_frames: i64,
     fade_out_frames: i64,
+    slide_frames: i64,
     fade_start_pts: i64,
     scroll_start_pts: i64,
     end_pts: Option<i64>,
     base_x: i32,
     base_y: i32,
     output_width: u32,
+    output_height: u32,
     fps: u32,
+    live: Option<LiveText>,
+}
+
+/// Text that is rendered again while it is on air: a template with
+/// placeholders, or a typewriter reveal.
+struct LiveText {
+    template: Option<TextTemplate>,
+    config: TextConfig,
+    placeholders: TextPlaceholders,
+    /// Values and whole second the template was last resolved for. The
+    /// text only changes with them, so it is resolved once per second.
+    resolved: Option<(Arc<PlaceholderValues>, i64)>,
+    /// Buffer the template is resolved into, compared with `text`.
+    resolved_text: String,
+    /// Full text, with resolved placeholders.
+    text: String,
+    /// Size of the full text, the revealed part is placed like it.
+    full_size: Option<(u32, u32)>,
+    /// Part of the text that is on screen.
+    shown: String,
 }
 
 impl TextOverlay {
@@ -212,14 +317,31 @@ impl TextOverlay {
         let Some(text) = overlay_text(config, media_path) else {
             return Ok(None);
         };
-        if text.trim().is_empty() {
+        if is_blank(config, &text) {
             return Ok(None);
         }
 
+        // A template or a revealed text starts with the full text and is
+        // replaced on the first blend.
         let overlay = render_text_overlay(config, &text, output_width, output_height)?;
         let base_x = text_position(config.position_x, output_width, overlay.width);
         let base_y = text_position(config.position_y, output_height, overlay.height);
 
+        let template = (!config.use_filename)
+            .then(|| TextTemplate::parse(&text))
+            .flatten();
+        let live =
+            (template.is_some() || config.animation.reveal != TextReveal::None).then(|| LiveText {
+                template,
+                config: config.clone(),
+                placeholders: TextPlaceholders::default(),
+                resolved: None,
+                resolved_text: String::new(),
+                text: text.clone(),
+                full_size: Some((overlay.width, overlay.height)),
+                shown: text,
+            });
+
         let chroma_alpha = chroma_alpha(&overlay.frame, overlay.width, overlay.height);
 
         Ok(Some(Self {
@@ -234,30 +356,126 @@ impl TextOverlay {
             },
             scroll: config.scroll,
             scroll_repeat: config.scroll_repeat,
+            animation: config.animation,
             fade_in_frames: seconds_to_frames(config.fade_in_seconds, fps),
             fade_out_frames: seconds_to_frames(config.fade_out_seconds, fps),
+            slide_frames: seconds_to_frames(config.animation.slide_seconds, fps),
             fade_start_pts,
             scroll_start_pts,
             end_pts,
             base_x,
             base_y,
             output_width,
+            output_height,
             fps,
+            live,
         }))
     }
 
+    /// Take the values for the placeholders of the text from `placeholders`.
+    pub(crate) fn with_placeholders(mut self, placeholders: &TextPlaceholders) -> Self {
+        if let Some(live) = &mut self.live {
+            live.placeholders = placeholders.clone();
+        }
+        self
+    }
+
     pub fn blend(&mut self, target: &mut frame::Video, pts: i64, scroll_pts: i64) {
-        let opacity = self.opacity_at(pts);
-        if opacity <= 0.0 {
+        self.blend_with_opacity(target, pts, scroll_pts, 1.0);
+    }
+
+    /// Blend with the own fades multiplied by `opacity_factor`.
+    pub(crate) fn blend_with_opacity(
+        &mut self,
+        target: &mut frame::Video,
+        pts: i64,
+        scroll_pts: i64,
+        opacity_factor: f64,
+    ) {
+        let opacity = self.opacity_at(pts) * opacity_factor;
+        if opacity <= 0.0 || !self.update_live_text(pts) {
             return;
         }
 
-        let x = self.x_at(scroll_pts);
+        let (x, y) = self.position_at(pts, scroll_pts);
         self.overlay.x = even_signed(x);
-        self.overlay.y = even_signed(self.base_y);
+        self.overlay.y = even_signed(y);
         blend_overlay(target, self.overlay.as_ref(), opacity);
     }
 
+    /// Render the text again when the part on screen changed. Returns
+    /// `false` while nothing is to be shown.
+    fn update_live_text(&mut self, pts: i64) -> bool {
+        let Some(live) = &mut self.live else {
+            return true;
+        };
+        if let Some(template) = &live.template {
+            let values = live.placeholders.values();
+            let second = unix_now().floor() as i64;
+            if live
+                .resolved
+                .as_ref()
+                .is_none_or(|(resolved, at)| !Arc::ptr_eq(resolved, &values) || *at != second)
+            {
+                template.render(&mut live.resolved_text, &values, second as f64);
+                if live.resolved_text != live.text {
+                    mem::swap(&mut live.resolved_text, &mut live.text);
+                    live.full_size = None;
+                }
+                live.resolved = Some((values, second));
+            }
+        }
+        let shown = match live.config.animation.reveal {
+            TextReveal::None => live.text.as_str(),
+            TextReveal::Typewriter {
+                characters_per_second,
+            } => {
+                let seconds =
+                    (pts - self.fade_start_pts).max(0) as f64 / f64::from(self.fps.max(1));
+                let count = (seconds * characters_per_second).floor() as usize;
+                if live.config.markup {
+                    revealed_markup(&live.text, count)
+                } else {
+                    revealed(&live.text, count)
+                }
+            }
+        };
+        if shown == live.shown {
+            return !is_blank(&live.config, shown);
+        }
+        live.shown = shown.to_string();
+        if is_blank(&live.config, &live.shown) {
+            return false;
+        }
+
+        let render = |text: &str| {
+            render_text_overlay(&live.config, text, self.output_width, self.output_height)
+                .map_err(|error| debug!("failed to render live text: {error:#}"))
+                .ok()
+        };
+        let Some(rendered) = render(&live.shown) else {
+            return true;
+        };
+        let (width, height) = if live.shown == live.text {
+            (rendered.width, rendered.height)
+        } else if let Some(size) = live.full_size {
+            size
+        } else {
+            render(&live.text).map_or((rendered.width, rendered.height), |full| {
+                (full.width, full.height)
+            })
+        };
+        live.full_size = Some((width, height));
+
+        self.base_x = text_position(live.config.position_x, self.output_width, width);
+        self.base_y = text_position(live.config.position_y, self.output_height, height);
+        self.overlay.chroma_alpha = chroma_alpha(&rendered.frame, rendered.width, rendered.height);
+        self.overlay.frame = rendered.frame;
+        self.overlay.width = rendered.width;
+        self.overlay.height = rendered.height;
+        true
+    }
+
     pub fn dimensions(&self) -> (u32, u32) {
         (self.overlay.width, self.overlay.height)
     }
@@ -277,26 +495,76 @@ impl TextOverlay {
         opacity.clamp(0.0, 1.0)
     }
 
-    fn x_at(&self, pts: i64) -> i32 {
-        let elapsed = (pts - self.scroll_start_pts).max(0);
-        match self.scroll {
-            TextScroll::None => self.base_x,
-            TextScroll::LeftToRight { pixels_per_second } => {
-                let offset = self.scroll_offset(elapsed, pixels_per_second);
-                -i32::try_from(self.overlay.width).unwrap_or(i32::MAX)
-                    + i32::try_from(offset).unwrap_or(i32::MAX)
+    fn position_at(&self, pts: i64, scroll_pts: i64) -> (i32, i32) {
+        let elapsed = (scroll_pts - self.scroll_start_pts).max(0);
+        let width = i32::try_from(self.overlay.width).unwrap_or(i32::MAX);
+        let height = i32::try_from(self.overlay.height).unwrap_or(i32::MAX);
+        let offset = i32::try_from(self.scroll_offset(elapsed)).unwrap_or(i32::MAX);
+        let (mut x, mut y) = match self.scroll {
+            TextScroll::None => (self.base_x, self.base_y),
+            TextScroll::LeftToRight { .. } => (-width + offset, self.base_y),
+            TextScroll::RightToLeft { .. } => (
+                i32::try_from(self.output_width).unwrap_or(i32::MAX) - offset,
+                self.base_y,
+            ),
+            TextScroll::TopToBottom { .. } => (self.base_x, -height + offset),
+            TextScroll::BottomToTop { .. } => (
+                self.base_x,
+                i32::try_from(self.output_height).unwrap_or(i32::MAX) - offset,
+            ),
+        };
+
+        if self.slide_frames > 0 {
+            let slide_in = (pts - self.fade_start_pts) as f64 / self.slide_frames as f64;
+            let slide_out = self.end_pts.map_or(1.0, |end_pts| {
+                (end_pts - pts) as f64 / self.slide_frames as f64
+            });
+            for (edge, progress) in [
+                (self.animation.slide_in, slide_in),
+                (self.animation.slide_out, slide_out),
+            ] {
+                let progress = self.animation.easing.apply(progress);
+                (x, y) = slide_position(edge, (x, y), self.dimensions(), self.output(), progress);
             }
-            TextScroll::RightToLeft { pixels_per_second } => {
-                let offset = self.scroll_offset(elapsed, pixels_per_second);
-                i32::try_from(self.output_width).unwrap_or(i32::MAX)
-                    - i32::try_from(offset).unwrap_or(i32::MAX)
+        }
+
+        (x, y)
+    }
+
+    fn output(&self) -> (u32, u32) {
+        (self.output_width, self.output_height)
+    }
+
+    /// Whether another text can take over at `scroll_pts` without a jump:
+    /// the text does not move, its crawl is over or starts a new pass.
+    pub(crate) fn at_pass_start(&self, scroll_pts: i64) -> bool {
+        scroll_speed(self.scroll).is_none_or(|pixels_per_second| {
+            pass_start(
+                scroll_pts - self.scroll_start_pts,
+                pixels_per_second,
+                self.fps,
+                self.scroll_travel(),
+                self.scroll_repeat,
+            )
+        })
+    }
+
+    /// Distance of one pass: across the screen until the text has left it.
+    fn scroll_travel(&self) -> i64 {
+        match self.scroll {
+            TextScroll::TopToBottom { .. } | TextScroll::BottomToTop { .. } => {
+                i64::from(self.output_height) + i64::from(self.overlay.height)
             }
+            _ => i64::from(self.output_width) + i64::from(self.overlay.width),
         }
     }
 
-    fn scroll_offset(&self, elapsed: i64, pixels_per_second: u32) -> i64 {
+    fn scroll_offset(&self, elapsed: i64) -> i64 {
+        let Some(pixels_per_second) = scroll_speed(self.scroll) else {
+            return 0;
+        };
         let offset = elapsed * i64::from(pixels_per_second) / i64::from(self.fps.max(1));
-        let travel = i64::from(self.output_width) + i64::from(self.overlay.width);
+        let travel = self.scroll_travel();
         if travel <= 0 {
             return offset;
         }
@@ -314,6 +582,68 @@ impl TextOverlay {
     }
 }
 
+/// Whether the crawl at frame `elapsed` has just begun, starts a new pass
+/// of `travel` pixels or is over after `repeat` repeats.
+fn pass_start(elapsed: i64, pixels_per_second: u32, fps: u32, travel: i64, repeat: i32) -> bool {
+    if travel <= 0 || elapsed <= 0 {
+        return true;
+    }
+
+    let offset_at = |elapsed: i64| elapsed * i64::from(pixels_per_second) / i64::from(fps.max(1));
+    let offset = offset_at(elapsed);
+    if repeat >= 0 && offset >= travel.saturating_mul(i64::from(repeat) + 1) {
+        return true;
+    }
+
+    offset / travel != offset_at(elapsed - 1) / travel
+}
+
+fn scroll_speed(scroll: TextScroll) -> Option<u32> {
+    match scroll {
+        TextScroll::None => None,
+        TextScroll::LeftToRight { pixels_per_second }
+        | TextScroll::RightToLeft { pixels_per_second }
+        | TextScroll::TopToBottom { pixels_per_second }
+        | TextScroll::BottomToTop { pixels_per_second } => Some(pixels_per_second),
+    }
+}
+
+/// The first `count` characters of `text`.
+fn revealed(text: &str, count: usize) -> &str {
+    text.char_indices()
+        .nth(count)
+        .map_or(text, |(index, _)| &text[..index])
+}
+
+/// `true` when `text` has nothing to show.
+fn is_blank(config: &TextConfig, text: &str) -> bool {
+    if config.markup {
+        plain_text(text).trim().is_empty()
+    } else {
+        text.trim().is_empty()
+    }
+}
+
+/// Position between off screen beyond `edge` at `progress` 0 and
+/// `position` at 1.
+fn slide_position(
+    edge: TextSlide,
+    (x, y): (i32, i32),
+    (width, height): (u32, u32),
+    (output_width, output_height): (u32, u32),
+    progress: f64,
+) -> (i32, i32) {
+    let away = 1.0 - progress.clamp(0.0, 1.0);
+    let towards = |from: i32, to: i64| from + ((to - i64::from(from)) as f64 * away).round() as i32;
+    match edge {
+        TextSlide::None => (x, y),
+        TextSlide::Left => (towards(x, -i64::from(width)), y),
+        TextSlide::Right => (towards(x, i64::from(output_width)), y),
+        TextSlide::Top => (x, towards(y, -i64::from(height))),
+        TextSlide::Bottom => (x, towards(y, i64::from(output_height))),
+    }
+}
+
 struct RenderedText {
     frame: frame::Video,
     width: u32,
@@ -353,16 +683,19 @@ fn render_text_overlay(
         .background
         .map(|background| background.padding)
         .unwrap_or(0);
-    let render_width = render_width(config, text, output_width, padding);
-    let text_width = render_width
-        .saturating_sub(padding.saturating_mul(2))
-        .max(2);
-    let text_height = output_height
-        .saturating_sub(padding.saturating_mul(2))
-        .max(2);
-    let line_height = (config.font_size + config.line_spacing.max(0.0))
-        .ceil()
-        .max(config.font_size);
+    let margin = effect_margin(config);
+    let inset = padding.saturating_add(margin);
+    let spans = config.markup.then(|| parse_markup(text));
+    let estimated_width = match &spans {
+        Some(spans) => spans
+            .iter()
+            .map(|span| span.text.chars().count() as f32 * span.size.unwrap_or(config.font_size))
+            .sum(),
+        None => text.chars().count() as f32 * config.font_size,
+    } * 0.75;
+    let render_width = render_width(config, estimated_width, output_width, inset);
+    let text_width = render_width.saturating_sub(inset.saturating_mul(2)).max(2);
+    let text_height = output_height.saturating_sub(inset.saturating_mul(2)).max(2);
 
     let mut rgba = vec![0_u8; (render_width as usize) * (output_height as usize) * 4];
     let mut renderer = renderer().lock().unwrap_or_else(PoisonError::into_inner);
@@ -370,7 +703,7 @@ fn render_text_overlay(
         font_system,
         swash_cache,
     } = &mut *renderer;
-    let metrics = Metrics::new(config.font_size, line_height);
+    let metrics = text_metrics(config, config.font_size);
     let mut buffer = Buffer::new(font_system, metrics);
     buffer.set_size(Some(text_width as f32), Some(text_height as f32));
 
@@ -383,7 +716,17 @@ fn render_text_overlay(
         TextWeight::Semibold => Weight::SEMIBOLD,
         TextWeight::Bold => Weight::BOLD,
     });
-    buffer.set_text(text, &attrs, Shaping::Advanced, None);
+    match &spans {
+        Some(spans) => buffer.set_rich_text(
+            spans
+                .iter()
+                .map(|span| (span.text.as_str(), span_attrs(config, &attrs, span))),
+            &attrs,
+            Shaping::Advanced,
+            None,
+        ),
+        None => buffer.set_text(text, &attrs, Shaping::Advanced, None),
+    }
     buffer.shape_until_scroll(font_system, false);
 
     let text_color = Color::rgba(
@@ -400,8 +743,8 @@ fn render_text_overlay(
         |x, y, width, height, color| {
             let color = rgba_color(color);
             let Some(dest) = PixelRect::new(
-                x + i32::try_from(padding).unwrap_or(0),
-                y + i32::try_from(padding).unwrap_or(0),
+                x + i32::try_from(inset).unwrap_or(0),
+                y + i32::try_from(inset).unwrap_or(0),
                 width,
                 height,
                 render_width,
@@ -422,6 +765,23 @@ fn render_text_overlay(
     let Some(mut bounds) = bounds.finish() else {
         return Err(anyhow!("text overlay produced no visible pixels"));
     };
+    if margin > 0 {
+        bounds.expand(
+            margin as usize,
+            render_width as usize,
+            output_height as usize,
+        );
+        draw_effects(
+            &mut rgba,
+            render_width as usize,
+            &bounds,
+            config.outline,
+            config.shadow,
+        );
+        let mut visible = Bounds::default();
+        visible.include_alpha(&rgba, render_width as usize, &bounds);
+        bounds = visible.finish().unwrap_or(bounds);
+    }
     bounds.expand(
         padding as usize,
         render_width as usize,
@@ -465,17 +825,183 @@ fn render_text_overlay(
     })
 }
 
-fn render_width(config: &TextConfig, text: &str, output_width: u32, padding: u32) -> u32 {
+fn render_width(config: &TextConfig, text_width: f32, output_width: u32, padding: u32) -> u32 {
     if matches!(config.scroll, TextScroll::None) {
         return output_width.max(2);
     }
 
-    let estimated_text_width = (text.chars().count() as f32 * config.font_size * 0.75).ceil();
-    let estimated = estimated_text_width as u32 + padding.saturating_mul(2);
+    let estimated = text_width.ceil() as u32 + padding.saturating_mul(2);
     let max_width = output_width.saturating_mul(8).max(output_width);
     even(estimated.max(output_width).min(max_width)).max(2)
 }
 
+fn text_metrics(config: &TextConfig, font_size: f32) -> Metrics {
+    let line_height = (font_size + config.line_spacing.max(0.0))
+        .ceil()
+        .max(font_size);
+    Metrics::new(font_size, line_height)
+}
+
+fn span_attrs<'a>(config: &TextConfig, base: &Attrs<'a>, span: &MarkupSpan) -> Attrs<'a> {
+    let mut attrs = base.clone();
+    if span.bold {
+        attrs = attrs.weight(Weight::BOLD);
+    }
+    if span.italic {
+        attrs = attrs.style(Style::Italic);
+    }
+    if let Some(color) = span.color {
+        let alpha = u16::from(color.a) * u16::from(config.text_color.a) / 255;
+        attrs = attrs.color(Color::rgba(color.r, color.g, color.b, alpha as u8));
+    }
+    if let Some(size) = span.size {
+        attrs = attrs.metrics(text_metrics(config, size));
+    }
+    attrs
+}
+
+/// Room the outline and the shadow need around the glyphs.
+fn effect_margin(config: &TextConfig) -> u32 {
+    let outline = config.outline.map_or(0, |outline| outline.width);
+    let shadow = config.shadow.map_or(0, |shadow| {
+        shadow
+            .offset_x
+            .unsigned_abs()
+            .max(shadow.offset_y.unsigned_abs())
+            .saturating_add(shadow.blur)
+    });
+    outline.saturating_add(shadow)
+}
+
+/// Put the shadow and the outline under the glyphs in `region`.
+fn draw_effects(
+    rgba: &mut [u8],
+    width: usize,
+    region: &ResolvedBounds,
+    outline: Option<TextOutline>,
+    shadow: Option<TextShadow>,
+) {
+    let (region_width, region_height) = (region.width, region.height);
+    let index = |x: usize, y: usize| ((region.y + y) * width + region.x + x) * 4;
+    let glyphs = (0..region_width * region_height)
+        .map(|i| rgba[index(i % region_width, i / region_width) + 3])
+        .collect::<Vec<_>>();
+    let outlined =
+        outline.map(|outline| dilate(&glyphs, region_width, region_height, outline.width as usize));
+
+    let mut layer = vec![0_u8; glyphs.len() * 4];
+    if let Some(shadow) = shadow {
+        let source = outlined.as_deref().unwrap_or(&glyphs);
+        let shifted = shift(
+            source,
+            region_width,
+            region_height,
+            shadow.offset_x,
+            shadow.offset_y,
+        );
+        let blurred = box_blur(&shifted, region_width, region_height, shadow.blur as usize);
+        paint(&mut layer, &blurred, shadow.color);
+    }
+    if let (Some(outline), Some(mask)) = (outline, &outlined) {
+        paint(&mut layer, mask, outline.color);
+    }
+
+    for y in 0..region_height {
+        for x in 0..region_width {
+            let idx = index(x, y);
+            let glyph = RgbaColor {
+                r: rgba[idx],
+                g: rgba[idx + 1],
+                b: rgba[idx + 2],
+                a: rgba[idx + 3],
+            };
+            let pixel = &mut layer[(y * region_width + x) * 4..][..4];
+            alpha_composite(pixel, glyph);
+            rgba[idx..idx + 4].copy_from_slice(pixel);
+        }
+    }
+}
+
+/// Largest alpha within `radius` of each pixel.
+fn dilate(mask: &[u8], width: usize, height: usize, radius: usize) -> Vec<u8> {
+    let reach = radius as isize;
+    let offsets = (-reach..=reach)
+        .flat_map(|dy| (-reach..=reach).map(move |dx| (dx, dy)))
+        .filter(|(dx, dy)| dx * dx + dy * dy <= reach * reach + reach)
+        .collect::<Vec<_>>();
+    let mut dilated = mask.to_vec();
+    for y in 0..height {
+        for x in 0..width {
+            let alpha = mask[y * width + x];
+            if alpha == 0 {
+                continue;
+            }
+            for (dx, dy) in &offsets {
+                let (Some(tx), Some(ty)) = (x.checked_add_signed(*dx), y.checked_add_signed(*dy))
+                else {
+                    continue;
+                };
+                if tx < width && ty < height {
+                    let target = &mut dilated[ty * width + tx];
+                    *target = (*target).max(alpha);
+                }
+            }
+        }
+    }
+    dilated
+}
+
+fn shift(mask: &[u8], width: usize, height: usize, dx: i32, dy: i32) -> Vec<u8> {
+    let mut shifted = vec![0_u8; mask.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let (Some(tx), Some(ty)) = (
+                x.checked_add_signed(dx as isize),
+                y.checked_add_signed(dy as isize),
+            ) else {
+                continue;
+            };
+            if tx < width && ty < height {
+                shifted[ty * width + tx] = mask[y * width + x];
+            }
+        }
+    }
+    shifted
+}
+
+/// Box blur, horizontal and then vertical.
+fn box_blur(mask: &[u8], width: usize, height: usize, radius: usize) -> Vec<u8> {
+    if radius == 0 {
+        return mask.to_vec();
+    }
+
+    let pass = |source: &[u8], length: usize, lines: usize, at: &dyn Fn(usize, usize) -> usize| {
+        let mut blurred = vec![0_u8; source.len()];
+        for line in 0..lines {
+            for position in 0..length {
+                let from = position.saturating_sub(radius);
+                let to = (position + radius).min(length - 1);
+                let sum = (from..=to)
+                    .map(|p| u32::from(source[at(line, p)]))
+                    .sum::<u32>();
+                blurred[at(line, position)] = (sum / (2 * radius as u32 + 1)) as u8;
+            }
+        }
+        blurred
+    };
+    let horizontal = pass(mask, width, height, &|y, x| y * width + x);
+    pass(&horizontal, height, width, &|x, y| y * width + x)
+}
+
+fn paint(layer: &mut [u8], mask: &[u8], color: RgbaColor) {
+    for (pixel, alpha) in layer.chunks_exact_mut(4).zip(mask) {
+        if *alpha > 0 {
+            let a = (u16::from(*alpha) * u16::from(color.a) / 255) as u8;
+            alpha_composite(pixel, RgbaColor { a, ..color });
+        }
+    }
+}
+
 fn rgba_to_yuva420p(rgba: &[u8], width: u32, height: u32) -> Result<frame::Video> {
     let mut frame = frame::Video::new(Pixel::YUVA420P, width, height);
     let width = width as usize;
@@ -631,6 +1157,17 @@ impl Bounds {
         self.y_max = self.y_max.max(y + height);
     }
 
+    /// Include every pixel in `region` that is not transparent.
+    fn include_alpha(&mut self, rgba: &[u8], width: usize, region: &ResolvedBounds) {
+        for y in region.y..region.y + region.height {
+            for x in region.x..region.x + region.width {
+                if rgba[(y * width + x) * 4 + 3] > 0 {
+                    self.include(x, y, 1, 1);
+                }
+            }
+        }
+    }
+
     fn finish(self) -> Option<ResolvedBounds> {
         let x = self.x_min?;
         let y = self.y_min?;
@@ -662,3 +1199,76 @@ impl ResolvedBounds {
         self.height = bottom.saturating_sub(y).max(1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{TextSlide, box_blur, dilate, pass_start, revealed, shift, slide_position};
+
+    #[test]
+    fn reveal_counts_characters() {
+        assert_eq!(revealed("Grüße", 0), "");
+        assert_eq!(revealed("Grüße", 3), "Grü");
+        assert_eq!(revealed("Grüße", 9), "Grüße");
+    }
+
+    #[test]
+    fn slide_moves_from_the_edge_to_the_position() {
+        let output = (1920, 1080);
+        let size = (400, 60);
+
+        assert_eq!(
+            slide_position(TextSlide::Left, (100, 900), size, output, 0.0),
+            (-400, 900)
+        );
+        assert_eq!(
+            slide_position(TextSlide::Left, (100, 900), size, output, 0.5),
+            (-150, 900)
+        );
+        assert_eq!(
+            slide_position(TextSlide::Bottom, (100, 900), size, output, 0.0),
+            (100, 1080)
+        );
+        assert_eq!(
+            slide_position(TextSlide::Right, (100, 900), size, output, 1.0),
+            (100, 900)
+        );
+    }
+
+    #[test]
+    fn outline_and_shadow_masks() {
+        let mut dot = vec![0_u8; 25];
+        dot[12] = 200;
+
+        let outline = dilate(&dot, 5, 5, 2);
+        assert_eq!(outline[2], 200);
+        assert_eq!(outline[7], 200);
+        assert_eq!(outline[0], 0);
+        assert_eq!(outline.iter().filter(|alpha| **alpha > 0).count(), 21);
+
+        let shadow = shift(&dot, 5, 5, 2, -1);
+        assert_eq!(shadow[9], 200);
+        assert_eq!(shadow.iter().filter(|alpha| **alpha > 0).count(), 1);
+
+        let blurred = box_blur(&dot, 5, 5, 1);
+        assert_eq!(blurred[6], 22);
+        assert_eq!(blurred[12], 22);
+        assert_eq!(blurred[0], 0);
+    }
+
+    #[test]
+    fn crawl_passes_start_once_the_text_has_left() {
+        // 100 px per second at 25 fps over a travel of 400 px: a pass
+        // takes 100 frames.
+        assert!(pass_start(0, 100, 25, 400, -1));
+        assert!(!pass_start(1, 100, 25, 400, -1));
+        assert!(!pass_start(99, 100, 25, 400, -1));
+        assert!(pass_start(100, 100, 25, 400, -1));
+        assert!(!pass_start(101, 100, 25, 400, -1));
+        assert!(pass_start(200, 100, 25, 400, -1));
+
+        // With one repeat, the crawl is over after two passes.
+        assert!(!pass_start(150, 100, 25, 400, 1));
+        assert!(pass_start(200, 100, 25, 400, 1));
+        assert!(pass_start(250, 100, 25, 400, 1));
+    }
+}
@@ -0,0 +1,473 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Result, anyhow};
+use ffmpeg_next::frame;
+use log::info;
+
+/// Playback speed factor of the live input while its delay is rebuilt: the
+/// feed runs 4% slow, so the delay grows by about 2.3 seconds a minute.
+pub(crate) const DELAY_REBUILD_STRETCH: f64 = 1.04;
+
+/// Broadcast delay of the live ingest, like a profanity delay unit. The
+/// feed reaches the program [`LiveDelay::set_delay`] later than it comes
+/// in; [`LiveDelay::dump`] throws the buffered part away, and the delay
+/// builds up again while the feed plays slightly slow. The feed is held as
+/// compressed packets before it is decoded, so a long delay takes a few
+/// megabytes at any picture size.
+#[derive(Clone, Default)]
+pub struct LiveDelay {
+    inner: Arc<Mutex<DelayInner>>,
+}
+
+impl fmt::Debug for LiveDelay {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("LiveDelay(..)")
+    }
+}
+
+#[derive(Default)]
+struct DelayInner {
+    target: Duration,
+    /// Dumps asked for so far; every line follows the count.
+    dumps: u64,
+    current: Duration,
+    rebuilding: bool,
+    /// Lines that hold the program feed; they report the delay.
+    lines_on_air: usize,
+}
+
+/// Delay of the live ingest as the program has it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiveDelayStatus {
+    /// Configured delay.
+    pub target: Duration,
+    /// Delay the program has right now, less than `target` after a dump.
+    pub current: Duration,
+    pub rebuilding: bool,
+}
+
+impl LiveDelay {
+    /// Sets the delay, zero switches it off. A longer delay is built up
+    /// like after a dump, a shorter one skips the difference.
+    pub fn set_delay(&self, delay: Duration) {
+        let mut inner = self.lock();
+        inner.target = delay;
+        if inner.lines_on_air == 0 {
+            inner.current = delay;
+        }
+    }
+
+    pub fn status(&self) -> LiveDelayStatus {
+        let inner = self.lock();
+        LiveDelayStatus {
+            target: inner.target,
+            current: inner.current,
+            rebuilding: inner.rebuilding,
+        }
+    }
+
+    /// Skips the buffered part of the feed, the program continues with
+    /// what comes in now.
+    pub fn dump(&self) -> Result<()> {
+        let mut inner = self.lock();
+        if inner.target.is_zero() {
+            return Err(anyhow!("the live delay is off"));
+        }
+        inner.dumps += 1;
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, DelayInner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// What [`DelayLine::update`] did to the feed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DelayChange {
+    /// Packets were dropped; decoding starts over at the next keyframe.
+    pub(crate) skipped: bool,
+    /// The playback speed changed, see [`DelayLine::stretch`].
+    pub(crate) retimed: bool,
+}
+
+/// Holds the packets of one live connection for the delay.
+pub(crate) struct DelayLine<T> {
+    delay: LiveDelay,
+    /// Packets with their arrival.
+    queue: VecDeque<(Instant, T)>,
+    /// Packets were dropped up to the end of the queue; the next ones wait
+    /// for a packet decoding can start from.
+    resync: bool,
+    on_air: bool,
+    target: Duration,
+    /// Delay, or where the rebuild started from.
+    hold: Duration,
+    rebuild_since: Option<Instant>,
+    dumps: u64,
+}
+
+impl<T> DelayLine<T> {
+    pub(crate) fn new(delay: LiveDelay) -> Self {
+        let (target, dumps) = {
+            let inner = delay.lock();
+            (inner.target, inner.dumps)
+        };
+
+        Self {
+            delay,
+            queue: VecDeque::new(),
+            resync: false,
+            on_air: false,
+            target,
+            hold: target,
+            rebuild_since: None,
+            dumps,
+        }
+    }
+
+    /// Without delay, packets go straight through.
+    pub(crate) fn is_off(&self) -> bool {
+        self.target.is_zero() && self.queue.is_empty() && !self.resync
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Queues a packet that came in `now`. After a skip, packets are
+    /// dropped until one decoding can start from, see `is_start`.
+    pub(crate) fn push(&mut self, packet: T, now: Instant, is_start: impl Fn(&T) -> bool) {
+        if self.resync && !is_start(&packet) {
+            return;
+        }
+        self.resync = false;
+        self.queue.push_back((now, packet));
+    }
+
+    /// Next packet whose delay is over.
+    pub(crate) fn pop(&mut self, now: Instant) -> Option<T> {
+        let hold = self.hold(now);
+        let (arrived, _) = self.queue.front()?;
+        if now.saturating_duration_since(*arrived) < hold {
+            return None;
+        }
+
+        self.queue.pop_front().map(|(_, packet)| packet)
+    }
+
+    /// Playback speed factor for the packets that come out.
+    pub(crate) fn stretch(&self) -> f64 {
+        if self.rebuild_since.is_some() {
+            DELAY_REBUILD_STRETCH
+        } else {
+            1.0
+        }
+    }
+
+    /// Follows the delay setting and dumps. Off air, a delay is there at
+    /// once, as nobody hears it build up, and a dump has nothing to skip.
+    pub(crate) fn update(
+        &mut self,
+        now: Instant,
+        on_air: bool,
+        is_start: impl Fn(&T) -> bool,
+    ) -> DelayChange {
+        let (target, dumps) = {
+            let inner = self.delay.lock();
+            (inner.target, inner.dumps)
+        };
+        let stretch = self.stretch();
+        let mut skipped = false;
+
+        if dumps != self.dumps {
+            self.dumps = dumps;
+            if on_air {
+                let dumped = self.hold(now);
+                skipped |= self.drop_older(now, Duration::ZERO, &is_start);
+                self.hold = Duration::ZERO;
+                self.rebuild_since = Some(now);
+                info!(
+                    "live delay dumped {:.1} s; rebuilding to {:.1} s",
+                    dumped.as_secs_f64(),
+                    target.as_secs_f64()
+                );
+            }
+        }
+
+        if target != self.target {
+            let hold = self.hold(now);
+            self.target = target;
+            if target < hold {
+                skipped |= self.drop_older(now, target, &is_start);
+                self.hold = target;
+                self.rebuild_since = None;
+            } else if target > hold {
+                self.hold = hold;
+                self.rebuild_since = Some(now);
+            }
+        }
+
+        if let Some(since) = self.rebuild_since
+            && (!on_air || self.hold(now) >= self.target)
+        {
+            self.hold = self.target;
+            self.rebuild_since = None;
+            if on_air {
+                info!(
+                    "live delay rebuilt to {:.1} s after {:.0} s",
+                    self.target.as_secs_f64(),
+                    now.saturating_duration_since(since).as_secs_f64()
+                );
+            }
+        }
+
+        let mut inner = self.delay.lock();
+        if on_air != self.on_air {
+            self.on_air = on_air;
+            if on_air {
+                inner.lines_on_air += 1;
+            } else {
+                inner.lines_on_air -= 1;
+            }
+        }
+        if on_air || inner.lines_on_air == 0 {
+            inner.current = self.hold(now);
+            inner.rebuilding = self.rebuild_since.is_some();
+        }
+
+        DelayChange {
+            skipped,
+            retimed: stretch != self.stretch(),
+        }
+    }
+
+    fn hold(&self, now: Instant) -> Duration {
+        let Some(since) = self.rebuild_since else {
+            return self.hold;
+        };
+        let grown = now
+            .saturating_duration_since(since)
+            .mul_f64(1.0 - 1.0 / DELAY_REBUILD_STRETCH);
+
+        (self.hold + grown).min(self.target)
+    }
+
+    /// Drops the packets that have waited `keep` or longer, and the ones
+    /// after them up to the next start, so decoding picks up cleanly.
+    fn drop_older(&mut self, now: Instant, keep: Duration, is_start: impl Fn(&T) -> bool) -> bool {
+        let before = self.queue.len();
+        while self
+            .queue
+            .front()
+            .is_some_and(|(arrived, _)| now.saturating_duration_since(*arrived) >= keep)
+        {
+            self.queue.pop_front();
+        }
+        if self.queue.len() == before {
+            return false;
+        }
+
+        while self
+            .queue
+            .front()
+            .is_some_and(|(_, packet)| !is_start(packet))
+        {
+            self.queue.pop_front();
+        }
+        self.resync = self.queue.is_empty();
+        true
+    }
+}
+
+impl<T> Drop for DelayLine<T> {
+    fn drop(&mut self) {
+        if !self.on_air {
+            return;
+        }
+        let mut inner = self.delay.lock();
+        inner.lines_on_air -= 1;
+        if inner.lines_on_air == 0 {
+            inner.current = inner.target;
+            inner.rebuilding = false;
+        }
+    }
+}
+
+/// Resamples planar float audio to `factor` times its length, which lowers
+/// the pitch a little, like tape running slow.
+pub(crate) fn stretch_audio(frame: &frame::Audio, factor: f64) -> frame::Audio {
+    let samples = frame.samples();
+    if samples == 0 {
+        return frame.clone();
+    }
+
+    let stretched = (samples as f64 * factor).ceil() as usize;
+    let mut output = frame::Audio::new(frame.format(), stretched, frame.channel_layout());
+    output.set_rate(frame.rate());
+    output.set_pts(frame.pts());
+    for plane in 0..frame.planes() {
+        let input = frame.plane::<f32>(plane);
+        for (index, sample) in output.plane_mut::<f32>(plane).iter_mut().enumerate() {
+            let position = index as f64 / factor;
+            let left = (position as usize).min(samples - 1);
+            let right = (left + 1).min(samples - 1);
+            let fraction = (position - left as f64).clamp(0.0, 1.0) as f32;
+            *sample = input[left] + (input[right] - input[left]) * fraction;
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{DELAY_REBUILD_STRETCH, DelayChange, DelayLine, LiveDelay};
+
+    fn is_start(packet: &&str) -> bool {
+        packet.starts_with("key")
+    }
+
+    #[test]
+    fn holds_packets_for_the_delay() {
+        let delay = LiveDelay::default();
+        delay.set_delay(Duration::from_secs(7));
+        let mut line = DelayLine::new(delay);
+        let start = Instant::now();
+
+        line.push("key", start, is_start);
+        line.push("frame", start + Duration::from_secs(1), is_start);
+        assert_eq!(line.update(start, true, is_start), DelayChange::default());
+        assert_eq!(line.pop(start + Duration::from_secs(6)), None);
+        assert_eq!(line.pop(start + Duration::from_secs(7)), Some("key"));
+        assert_eq!(line.pop(start + Duration::from_secs(7)), None);
+        assert_eq!(line.pop(start + Duration::from_secs(8)), Some("frame"));
+        assert!(line.is_empty());
+    }
+
+    #[test]
+    fn holds_thirty_seconds_of_1080p50() {
+        let delay = LiveDelay::default();
+        delay.set_delay(Duration::from_secs(30));
+        let mut line = DelayLine::new(delay.clone());
+        let start = Instant::now();
+        // Picture packets with a keyframe every second.
+        let frame = Duration::from_millis(20);
+        let is_key = |packet: &u32| packet.is_multiple_of(50);
+
+        for packet in 0..1_500 {
+            let now = start + frame * packet;
+            assert_eq!(line.update(now, true, is_key), DelayChange::default());
+            line.push(packet, now, is_key);
+            assert_eq!(line.pop(now), None);
+        }
+        assert_eq!(delay.status().current, Duration::from_secs(30));
+
+        let mut played = vec![];
+        for packet in 0..1_500 {
+            let now = start + Duration::from_secs(30) + frame * packet;
+            while let Some(packet) = line.pop(now) {
+                played.push(packet);
+            }
+        }
+        assert_eq!(played, (0..1_500).collect::<Vec<_>>());
+        assert!(line.is_empty());
+    }
+
+    #[test]
+    fn dump_skips_to_a_keyframe_and_rebuilds_slowly() {
+        let delay = LiveDelay::default();
+        delay.set_delay(Duration::from_secs(10));
+        let mut line = DelayLine::new(delay.clone());
+        let start = Instant::now();
+
+        line.push("key", start, is_start);
+        line.push("frame", start, is_start);
+        line.update(start, true, is_start);
+        delay.dump().unwrap();
+        let change = line.update(start, true, is_start);
+        assert!(change.skipped && change.retimed);
+        assert_eq!(line.stretch(), DELAY_REBUILD_STRETCH);
+        assert!(line.is_empty());
+        assert_eq!(delay.status().current, Duration::ZERO);
+        assert!(delay.status().rebuilding);
+
+        line.push("frame", start, is_start);
+        assert!(line.is_empty());
+        line.push("key 2", start, is_start);
+        line.push("frame", start, is_start);
+        assert_eq!(line.pop(start), Some("key 2"));
+        assert_eq!(line.pop(start), Some("frame"));
+
+        let minute = start + Duration::from_secs(60);
+        assert_eq!(line.update(minute, true, is_start), DelayChange::default());
+        let current = delay.status().current.as_secs_f64();
+        assert!((2.2..2.4).contains(&current), "{current}");
+
+        let rebuilt = start + Duration::from_secs(300);
+        assert!(line.update(rebuilt, true, is_start).retimed);
+        assert_eq!(line.stretch(), 1.0);
+        assert_eq!(delay.status().current, Duration::from_secs(10));
+        assert!(!delay.status().rebuilding);
+    }
+
+    #[test]
+    fn off_air_the_delay_is_there_at_once() {
+        let delay = LiveDelay::default();
+        let mut line = DelayLine::<&str>::new(delay.clone());
+        assert!(delay.dump().is_err());
+        assert!(line.is_off());
+
+        delay.set_delay(Duration::from_secs(20));
+        let start = Instant::now();
+        assert_eq!(line.update(start, false, is_start), DelayChange::default());
+        assert_eq!(delay.status().current, Duration::from_secs(20));
+        assert!(!line.is_off());
+
+        line.push("key", start, is_start);
+        delay.dump().unwrap();
+        assert_eq!(line.update(start, false, is_start), DelayChange::default());
+        assert_eq!(line.pop(start + Duration::from_secs(20)), Some("key"));
+    }
+
+    #[test]
+    fn status_follows_the_line_on_air() {
+        let delay = LiveDelay::default();
+        delay.set_delay(Duration::from_secs(10));
+        let mut line = DelayLine::<&str>::new(delay.clone());
+        let start = Instant::now();
+
+        line.update(start, true, is_start);
+        delay.dump().unwrap();
+        line.update(start, true, is_start);
+        assert!(delay.status().rebuilding);
+
+        drop(line);
+        assert_eq!(delay.status().current, Duration::from_secs(10));
+        assert!(!delay.status().rebuilding);
+    }
+
+    #[test]
+    fn shorter_delay_drops_the_difference() {
+        let delay = LiveDelay::default();
+        delay.set_delay(Duration::from_secs(30));
+        let mut line = DelayLine::new(delay.clone());
+        let start = Instant::now();
+
+        line.push("key", start, is_start);
+        line.push("frame", start + Duration::from_secs(24), is_start);
+        line.push("key 2", start + Duration::from_secs(25), is_start);
+        delay.set_delay(Duration::from_secs(7));
+        let now = start + Duration::from_secs(29);
+        assert!(line.update(now, true, is_start).skipped);
+        assert_eq!(line.pop(now), None);
+        assert_eq!(line.pop(start + Duration::from_secs(32)), Some("key 2"));
+    }
+}
@@ -0,0 +1,348 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use ffmpeg_next::{
+    format::{Sample, sample::Type as SampleType},
+    frame,
+    util::format::pixel::Pixel,
+};
+
+/// An endpoint that sends no frame for this long counts as dropped, before
+/// the live watchdog restarts it.
+const FAILOVER_STALL: Duration = Duration::from_millis(500);
+const DEFAULT_SWITCH_AFTER: Duration = Duration::from_secs(5);
+/// Luma up to 10% above video black counts as black, like FFmpeg's
+/// `blackdetect`.
+const BLACK_LUMA: u8 = 16 + 22;
+const BLACK_PICTURE_RATIO: f64 = 0.98;
+/// Every n-th pixel of every n-th row is checked for black.
+const BLACK_SAMPLE_STEP: usize = 8;
+/// -60 dBFS.
+const SILENCE_PEAK: f32 = 0.001;
+
+/// Called with the endpoint that went on air, `None` when none is left.
+#[derive(Clone)]
+pub struct IngestSwitchCallback(Arc<dyn Fn(Option<usize>) + Send + Sync>);
+
+impl IngestSwitchCallback {
+    pub fn new(callback: impl Fn(Option<usize>) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+}
+
+impl fmt::Debug for IngestSwitchCallback {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("IngestSwitchCallback(..)")
+    }
+}
+
+/// Failover between several ingest endpoints. The first endpoint is the
+/// primary; when the one on air drops or is black or silent for
+/// `switch_after`, the next healthy one takes over. A higher endpoint goes
+/// back on air once it was healthy for `switch_after` again.
+#[derive(Clone, Default)]
+pub struct IngestFailover {
+    inner: Arc<Mutex<FailoverInner>>,
+}
+
+impl fmt::Debug for IngestFailover {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("IngestFailover(..)")
+    }
+}
+
+#[derive(Default)]
+struct FailoverInner {
+    switch_after: Option<Duration>,
+    endpoints: Vec<EndpointHealth>,
+    on_air: Option<usize>,
+    callback: Option<IngestSwitchCallback>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct EndpointHealth {
+    connected: bool,
+    last_frame_at: Option<Instant>,
+    black_since: Option<Instant>,
+    silent_since: Option<Instant>,
+    healthy_since: Option<Instant>,
+}
+
+impl IngestFailover {
+    pub fn set_switch_after(&self, switch_after: Duration) {
+        self.lock().switch_after = Some(switch_after);
+    }
+
+    pub fn set_callback(&self, callback: IngestSwitchCallback) {
+        self.lock().callback = Some(callback);
+    }
+
+    /// Endpoint on air, by its position in the URL list.
+    pub fn on_air(&self) -> Option<usize> {
+        self.lock().on_air
+    }
+
+    pub(crate) fn register(&self, endpoints: usize) {
+        let mut inner = self.lock();
+        inner.endpoints = vec![EndpointHealth::default(); endpoints];
+        inner.on_air = None;
+    }
+
+    pub(crate) fn feed_started(&self, index: usize) {
+        self.update(index, Instant::now(), |endpoint, now| {
+            *endpoint = EndpointHealth {
+                connected: true,
+                last_frame_at: Some(now),
+                ..EndpointHealth::default()
+            };
+        });
+    }
+
+    pub(crate) fn feed_ended(&self, index: usize) {
+        self.update(index, Instant::now(), |endpoint, _| {
+            *endpoint = EndpointHealth::default();
+        });
+    }
+
+    /// Notes a picture of the endpoint and returns whether it is on air.
+    pub(crate) fn observe_video(&self, index: usize, picture: &frame::Video) -> bool {
+        let black = is_black(picture);
+        self.update(index, Instant::now(), |endpoint, now| {
+            endpoint.last_frame_at = Some(now);
+            endpoint.black_since = black.then(|| endpoint.black_since.unwrap_or(now));
+        }) == Some(index)
+    }
+
+    pub(crate) fn observe_audio(&self, index: usize, samples: &frame::Audio) {
+        let silent = is_silent(samples);
+        self.update(index, Instant::now(), |endpoint, now| {
+            endpoint.silent_since = silent.then(|| endpoint.silent_since.unwrap_or(now));
+        });
+    }
+
+    fn update(
+        &self,
+        index: usize,
+        now: Instant,
+        change: impl FnOnce(&mut EndpointHealth, Instant),
+    ) -> Option<usize> {
+        let (on_air, callback) = {
+            let mut inner = self.lock();
+            if let Some(endpoint) = inner.endpoints.get_mut(index) {
+                change(endpoint, now);
+            }
+            let previous = inner.on_air;
+            inner.select(now);
+            let callback = (inner.on_air != previous)
+                .then(|| inner.callback.clone())
+                .flatten();
+            (inner.on_air, callback)
+        };
+        if let Some(callback) = callback {
+            (callback.0)(on_air);
+        }
+
+        on_air
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, FailoverInner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl FailoverInner {
+    fn select(&mut self, now: Instant) {
+        let switch_after = self.switch_after.unwrap_or(DEFAULT_SWITCH_AFTER);
+        for endpoint in &mut self.endpoints {
+            if endpoint.is_healthy(now, switch_after) {
+                endpoint.healthy_since.get_or_insert(now);
+            } else {
+                endpoint.healthy_since = None;
+            }
+        }
+
+        let healthy = |index: usize| self.endpoints[index].healthy_since.is_some();
+        let recovered = |index: usize| {
+            self.endpoints[index]
+                .healthy_since
+                .is_some_and(|since| now.duration_since(since) >= switch_after)
+        };
+
+        self.on_air = match self.on_air.filter(|index| *index < self.endpoints.len()) {
+            Some(current) if healthy(current) => Some(
+                (0..current)
+                    .find(|index| recovered(*index))
+                    .unwrap_or(current),
+            ),
+            current => (0..self.endpoints.len())
+                .find(|index| healthy(*index))
+                // Better a black picture than none.
+                .or_else(|| current.filter(|index| self.endpoints[*index].connected))
+                .or_else(|| {
+                    (0..self.endpoints.len()).find(|index| self.endpoints[*index].connected)
+                }),
+        };
+    }
+}
+
+impl EndpointHealth {
+    fn is_healthy(&self, now: Instant, switch_after: Duration) -> bool {
+        let lasts = |since: Option<Instant>| {
+            since.is_some_and(|since| now.duration_since(since) >= switch_after)
+        };
+
+        self.connected
+            && self
+                .last_frame_at
+                .is_some_and(|last| now.duration_since(last) < FAILOVER_STALL)
+            && !lasts(self.black_since)
+            && !lasts(self.silent_since)
+    }
+}
+
+/// Whether the first plane of the picture is its luma, as in the YUV
+/// formats the live input delivers.
+pub(crate) fn has_luma(picture: &frame::Video) -> bool {
+    matches!(
+        picture.format(),
+        Pixel::YUV420P
+            | Pixel::YUV422P
+            | Pixel::YUV444P
+            | Pixel::YUVJ420P
+            | Pixel::YUVJ422P
+            | Pixel::YUVJ444P
+            | Pixel::NV12
+    ) && picture.width() > 0
+        && picture.height() > 0
+}
+
+/// Whether nearly the whole picture is black. Only YUV pictures are
+/// checked.
+pub(crate) fn is_black(picture: &frame::Video) -> bool {
+    if !has_luma(picture) {
+        return false;
+    }
+
+    let stride = picture.stride(0);
+    let luma = picture.data(0);
+    let width = picture.width() as usize;
+    let mut pixels = 0;
+    let mut black = 0;
+    for row in (0..picture.height() as usize).step_by(BLACK_SAMPLE_STEP) {
+        let Some(line) = luma.get(row * stride..row * stride + width) else {
+            break;
+        };
+        for value in line.iter().step_by(BLACK_SAMPLE_STEP) {
+            pixels += 1;
+            if *value <= BLACK_LUMA {
+                black += 1;
+            }
+        }
+    }
+
+    pixels > 0 && f64::from(black) / f64::from(pixels) >= BLACK_PICTURE_RATIO
+}
+
+/// Whether the samples stay below -60 dBFS. Only planar float samples, as
+/// the live input delivers them, are checked.
+pub(crate) fn is_silent(samples: &frame::Audio) -> bool {
+    if samples.samples() == 0 || samples.format() != Sample::F32(SampleType::Planar) {
+        return false;
+    }
+
+    (0..samples.planes()).all(|plane| {
+        samples
+            .plane::<f32>(plane)
+            .iter()
+            .all(|sample| sample.abs() < SILENCE_PEAK)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use ffmpeg_next::{
+        format::{Sample, sample::Type as SampleType},
+        frame,
+        util::{channel_layout::ChannelLayout, format::pixel::Pixel},
+    };
+
+    use super::{IngestFailover, is_black, is_silent};
+
+    fn connected(failover: &IngestFailover, index: usize, now: Instant) {
+        let mut inner = failover.lock();
+        let endpoint = &mut inner.endpoints[index];
+        endpoint.connected = true;
+        endpoint.last_frame_at = Some(now);
+    }
+
+    fn select(failover: &IngestFailover, now: Instant) -> Option<usize> {
+        let mut inner = failover.lock();
+        inner.select(now);
+        inner.on_air
+    }
+
+    #[test]
+    fn failover_switches_to_the_backup_and_back() {
+        let failover = IngestFailover::default();
+        failover.set_switch_after(Duration::from_secs(5));
+        failover.register(2);
+        let start = Instant::now();
+        assert_eq!(select(&failover, start), None);
+
+        connected(&failover, 0, start);
+        connected(&failover, 1, start);
+        assert_eq!(select(&failover, start), Some(0));
+
+        // The primary goes black, the backup takes over after five seconds.
+        failover.lock().endpoints[0].black_since = Some(start);
+        let later = start + Duration::from_secs(5);
+        connected(&failover, 0, later);
+        connected(&failover, 1, later);
+        assert_eq!(select(&failover, later), Some(1));
+
+        // It has to be healthy for five seconds before it goes back on air.
+        failover.lock().endpoints[0].black_since = None;
+        assert_eq!(select(&failover, later), Some(1));
+        let recovered = later + Duration::from_secs(5);
+        connected(&failover, 0, recovered);
+        connected(&failover, 1, recovered);
+        assert_eq!(select(&failover, recovered), Some(0));
+
+        // A primary without frames is dropped right away.
+        let stalled = recovered + Duration::from_secs(1);
+        connected(&failover, 1, stalled);
+        assert_eq!(select(&failover, stalled), Some(1));
+
+        failover.feed_ended(1);
+        failover.feed_ended(0);
+        assert_eq!(failover.on_air(), None);
+    }
+
+    #[test]
+    fn black_pictures_are_detected() {
+        let mut picture = frame::Video::new(Pixel::YUV420P, 64, 36);
+        picture.data_mut(0).fill(16);
+        assert!(is_black(&picture));
+
+        picture.data_mut(0).fill(120);
+        assert!(!is_black(&picture));
+    }
+
+    #[test]
+    fn silence_is_detected() {
+        let mut samples =
+            frame::Audio::new(Sample::F32(SampleType::Planar), 4, ChannelLayout::STEREO);
+        for plane in 0..samples.planes() {
+            samples.plane_mut::<f32>(plane).fill(0.0001);
+        }
+        assert!(is_silent(&samples));
+
+        samples.plane_mut::<f32>(1)[2] = 0.5;
+        assert!(!is_silent(&samples));
+    }
+}
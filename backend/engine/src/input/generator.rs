@@ -0,0 +1,509 @@
+use std::{f64::consts::TAU, time::SystemTime};
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Timelike, Utc};
+use chrono_tz::Tz;
+use ffmpeg_next::{
+    format, frame,
+    util::{channel_layout::ChannelLayout, format::pixel::Pixel, format::sample::Sample},
+};
+use log::warn;
+
+use crate::{
+    compositor::text::TextOverlay,
+    playout::SyntheticSource,
+    utils::{
+        config::{OutputConfig, TextConfig, TextPosition, TextWeight},
+        helper::even,
+        media_info::{AudioStream, MediaProbe, ProbeFormat, VideoStream},
+    },
+};
+
+const SCHEME: &str = "generator://";
+/// -20 dBFS, the usual line-up level.
+const TONE_AMPLITUDE: f32 = 0.1;
+/// The countdown beep lasts a tenth of a second.
+const BEEP_SECONDS_DIVISOR: i64 = 10;
+
+type Yuv = (u8, u8, u8);
+
+// BT.601 limited range values for 75% color bars.
+const WHITE_75: Yuv = (180, 128, 128);
+const YELLOW: Yuv = (162, 44, 142);
+const CYAN: Yuv = (131, 156, 44);
+const GREEN: Yuv = (112, 72, 58);
+const MAGENTA: Yuv = (84, 184, 198);
+const RED: Yuv = (65, 100, 212);
+const BLUE: Yuv = (35, 212, 114);
+const BLACK: Yuv = (16, 128, 128);
+const WHITE: Yuv = (235, 128, 128);
+const MINUS_I: Yuv = (40, 152, 110);
+const PLUS_Q: Yuv = (39, 167, 142);
+const SUB_BLACK: Yuv = (7, 128, 128);
+const SUPER_BLACK: Yuv = (25, 128, 128);
+const COUNTDOWN_BACKGROUND: Yuv = (48, 128, 128);
+const CLOCK_BACKGROUND: Yuv = BLACK;
+
+/// Pattern drawn by a `generator://` source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GeneratorKind {
+    SmpteBars,
+    Black,
+    Countdown,
+    /// Time of day in the channel timezone.
+    Clock,
+    /// The channel's fallback slate.
+    Slate,
+}
+
+/// Parsed `generator://<kind>?<options>` source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct GeneratorSpec {
+    pub(crate) kind: GeneratorKind,
+    /// Tone frequency in Hz; silence when unset.
+    pub(crate) tone: Option<u32>,
+}
+
+/// Whether `path` addresses a source rendered by the engine itself.
+pub fn is_generator_source(path: &str) -> bool {
+    path.get(..SCHEME.len())
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case(SCHEME))
+}
+
+/// Checks a `generator://` source the same way playout parses it, so a typo
+/// shows up before the clip goes on air.
+pub fn validate_generator_source(path: &str) -> Result<()> {
+    GeneratorSpec::parse(path).map(|_| ())
+}
+
+impl GeneratorSpec {
+    pub(crate) fn parse(path: &str) -> Result<Self> {
+        if !is_generator_source(path) {
+            return Err(anyhow!("{path} is not a generator source"));
+        }
+
+        let rest = &path[SCHEME.len()..];
+        let (name, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let kind = match name.trim_end_matches('/').to_ascii_lowercase().as_str() {
+            "smpte-bars" | "bars" => GeneratorKind::SmpteBars,
+            "black" => GeneratorKind::Black,
+            "countdown" => GeneratorKind::Countdown,
+            "clock" => GeneratorKind::Clock,
+            "slate" => GeneratorKind::Slate,
+            other => return Err(anyhow!("unknown generator '{other}' in {path}")),
+        };
+
+        let mut tone = None;
+        for (key, value) in query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+        {
+            match key {
+                "tone" => {
+                    let frequency = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|frequency| (1..=20_000).contains(frequency))
+                        .ok_or_else(|| {
+                            anyhow!("tone must be a frequency from 1 to 20000 Hz in {path}")
+                        })?;
+                    tone = Some(frequency);
+                }
+                other => return Err(anyhow!("unknown generator option '{other}' in {path}")),
+            }
+        }
+
+        if kind == GeneratorKind::Slate && tone.is_some() {
+            return Err(anyhow!(
+                "the slate takes its tone from the channel settings in {path}"
+            ));
+        }
+
+        Ok(Self { kind, tone })
+    }
+
+    /// Probe result for a generator source. It has no duration of its own;
+    /// the playlist node decides how long it runs.
+    pub(crate) fn probe(&self) -> MediaProbe {
+        MediaProbe {
+            format: ProbeFormat {
+                duration: None,
+                nb_streams: 2,
+                size: None,
+                bit_rate: None,
+            },
+            audio: vec![AudioStream {
+                channels: Some(2),
+                codec_name: Some("generator".to_string()),
+                duration: None,
+                sample_rate: None,
+            }],
+            video: vec![VideoStream {
+                aspect_ratio: None,
+                bit_rate: None,
+                codec_name: Some("generator".to_string()),
+                duration: None,
+                field_order: None,
+                frame_rate: "0/0".to_string(),
+                height: None,
+                nb_frames: None,
+                width: None,
+            }],
+        }
+    }
+}
+
+/// Renders a generator source at the output size for a fixed duration.
+pub(crate) struct Generator {
+    spec: GeneratorSpec,
+    picture: frame::Video,
+    /// Number currently shown by the countdown or clock and its rendered
+    /// text.
+    label: Option<(i64, Option<TextOverlay>)>,
+    /// Channel timezone of the clock.
+    timezone: Tz,
+    width: u32,
+    height: u32,
+    fps: u32,
+    sample_rate: u32,
+    total_frames: i64,
+    total_samples: i64,
+    frames: i64,
+    samples: i64,
+}
+
+impl Generator {
+    pub(crate) fn new(spec: GeneratorSpec, cfg: &OutputConfig, duration: f64) -> Self {
+        let picture = match spec.kind {
+            GeneratorKind::SmpteBars => smpte_bars_frame(cfg.width, cfg.height),
+            // The slate is drawn by `Slate`, this is never on air.
+            GeneratorKind::Black | GeneratorKind::Slate => {
+                solid_frame(cfg.width, cfg.height, BLACK)
+            }
+            GeneratorKind::Countdown => solid_frame(cfg.width, cfg.height, COUNTDOWN_BACKGROUND),
+            GeneratorKind::Clock => solid_frame(cfg.width, cfg.height, CLOCK_BACKGROUND),
+        };
+        let fps = cfg.fps.max(1);
+        let sample_rate = cfg.sample_rate.max(1);
+
+        Self {
+            spec,
+            picture,
+            label: None,
+            timezone: cfg.text_placeholders.values().timezone.unwrap_or(Tz::UTC),
+            width: cfg.width,
+            height: cfg.height,
+            fps,
+            sample_rate,
+            total_frames: (duration * f64::from(fps)).round() as i64,
+            total_samples: (duration * f64::from(sample_rate)).round() as i64,
+            frames: 0,
+            samples: 0,
+        }
+    }
+
+    /// Text for `seconds`, rendered again only when the number changes.
+    fn label_overlay(&mut self, seconds: i64) -> Option<&mut TextOverlay> {
+        if self
+            .label
+            .as_ref()
+            .is_none_or(|(shown, _)| *shown != seconds)
+        {
+            let text = match self.spec.kind {
+                GeneratorKind::Clock => clock_label(seconds),
+                _ => countdown_label(seconds),
+            };
+            let config = TextConfig {
+                text: Some(text),
+                font_size: (self.height as f32 / 3.0).max(16.0),
+                font_weight: TextWeight::Bold,
+                position_x: TextPosition::Center,
+                position_y: TextPosition::Center,
+                ..TextConfig::default()
+            };
+            let overlay = TextOverlay::load(
+                &config,
+                "generator",
+                self.width,
+                self.height,
+                self.fps,
+                0,
+                0,
+                None,
+            )
+            .map_err(|error| warn!("generator text is not usable: {error:#}"))
+            .ok()
+            .flatten();
+            self.label = Some((seconds, overlay));
+        }
+
+        self.label
+            .as_mut()
+            .and_then(|(_, overlay)| overlay.as_mut())
+    }
+
+    fn tone_at(&self, index: i64, frequency: u32) -> f32 {
+        if self.spec.kind == GeneratorKind::Countdown {
+            // Beep at the start of every second, counted back from the end.
+            let remaining = (self.total_samples - index).max(0);
+            let rate = i64::from(self.sample_rate);
+            let into_second = (rate - remaining % rate) % rate;
+            if into_second >= rate / BEEP_SECONDS_DIVISOR {
+                return 0.0;
+            }
+        }
+        tone_sample(index, self.sample_rate, frequency)
+    }
+}
+
+impl SyntheticSource for Generator {
+    fn video_frame(&mut self) -> frame::Video {
+        let mut frame = self.picture.clone();
+        let seconds = match self.spec.kind {
+            GeneratorKind::Countdown => {
+                let fps = i64::from(self.fps);
+                let remaining = (self.total_frames - self.frames).max(1);
+                Some((remaining + fps - 1) / fps)
+            }
+            GeneratorKind::Clock => Some(clock_seconds(SystemTime::now(), self.timezone)),
+            _ => None,
+        };
+        if let Some(seconds) = seconds {
+            let pts = self.frames;
+            if let Some(overlay) = self.label_overlay(seconds) {
+                overlay.blend(&mut frame, pts, pts);
+            }
+        }
+        self.frames += 1;
+        frame
+    }
+
+    fn audio_frame(&mut self, samples: usize) -> frame::Audio {
+        let mut frame = frame::Audio::new(
+            Sample::F32(format::sample::Type::Planar),
+            samples,
+            ChannelLayout::STEREO,
+        );
+        frame.set_rate(self.sample_rate);
+        for plane in 0..frame.planes() {
+            let data = frame.plane_mut::<f32>(plane);
+            match self.spec.tone {
+                Some(frequency) => {
+                    for (index, sample) in data.iter_mut().enumerate() {
+                        *sample = self.tone_at(self.samples + index as i64, frequency);
+                    }
+                }
+                None => data.fill(0.0),
+            }
+        }
+        self.samples += samples as i64;
+        frame
+    }
+}
+
+/// Sine sample at line-up level for a continuous tone of `frequency` Hz.
+pub(crate) fn tone_sample(index: i64, sample_rate: u32, frequency: u32) -> f32 {
+    let phase = (index % i64::from(sample_rate)) as f64 / f64::from(sample_rate);
+    (TAU * f64::from(frequency) * phase).sin() as f32 * TONE_AMPLITUDE
+}
+
+fn countdown_label(seconds: i64) -> String {
+    if seconds >= 60 {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    } else {
+        seconds.to_string()
+    }
+}
+
+/// Seconds of the local day at `now`, with the offset `timezone` has then.
+fn clock_seconds(now: SystemTime, timezone: Tz) -> i64 {
+    i64::from(
+        DateTime::<Utc>::from(now)
+            .with_timezone(&timezone)
+            .num_seconds_from_midnight(),
+    )
+}
+
+fn clock_label(seconds: i64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+fn solid_frame(width: u32, height: u32, color: Yuv) -> frame::Video {
+    let mut frame = frame::Video::new(Pixel::YUV420P, width, height);
+    fill_rect(&mut frame, (0, width), (0, height), color);
+    frame
+}
+
+/// SMPTE color bars: seven 75% bars, the reversed castellation row and the
+/// -I/white/+Q row with a PLUGE in the lower right.
+fn smpte_bars_frame(width: u32, height: u32) -> frame::Video {
+    const TOP: [Yuv; 7] = [WHITE_75, YELLOW, CYAN, GREEN, MAGENTA, RED, BLUE];
+    const MIDDLE: [Yuv; 7] = [BLUE, BLACK, MAGENTA, BLACK, CYAN, BLACK, WHITE_75];
+    // Bottom row segments in 84ths of the width, so the PLUGE fits in bar six.
+    const BOTTOM: [(u32, u32, Yuv); 8] = [
+        (0, 15, MINUS_I),
+        (15, 30, WHITE),
+        (30, 45, PLUS_Q),
+        (45, 60, BLACK),
+        (60, 64, SUB_BLACK),
+        (64, 68, BLACK),
+        (68, 72, SUPER_BLACK),
+        (72, 84, BLACK),
+    ];
+
+    let mut frame = frame::Video::new(Pixel::YUV420P, width, height);
+    let x = |numerator: u32, denominator: u32| even(width * numerator / denominator);
+    let top_end = even(height * 2 / 3);
+    let middle_end = even(height * 3 / 4);
+
+    for (index, color) in (0_u32..).zip(TOP) {
+        fill_rect(
+            &mut frame,
+            (x(index, 7), x(index + 1, 7)),
+            (0, top_end),
+            color,
+        );
+    }
+    for (index, color) in (0_u32..).zip(MIDDLE) {
+        fill_rect(
+            &mut frame,
+            (x(index, 7), x(index + 1, 7)),
+            (top_end, middle_end),
+            color,
+        );
+    }
+    for (start, end, color) in BOTTOM {
+        fill_rect(
+            &mut frame,
+            (x(start, 84), x(end, 84)),
+            (middle_end, height),
+            color,
+        );
+    }
+    frame
+}
+
+fn fill_rect(frame: &mut frame::Video, (x0, x1): (u32, u32), (y0, y1): (u32, u32), color: Yuv) {
+    let (luma, cb, cr) = color;
+    for (plane, value, shift) in [(0, luma, 0), (1, cb, 1), (2, cr, 1)] {
+        let stride = frame.stride(plane);
+        let (x0, x1) = ((x0 >> shift) as usize, (x1 >> shift) as usize);
+        let data = frame.data_mut(plane);
+        for y in (y0 >> shift)..(y1 >> shift) {
+            let start = y as usize * stride;
+            data[start + x0..start + x1].fill(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Generator, GeneratorKind, GeneratorSpec, clock_label, clock_seconds, countdown_label,
+        is_generator_source, smpte_bars_frame, tone_sample, validate_generator_source,
+    };
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use chrono_tz::{Europe::Berlin, Tz};
+
+    use crate::{playout::SyntheticSource, utils::config::OutputConfig};
+
+    #[test]
+    fn parses_kind_and_tone() {
+        assert_eq!(
+            GeneratorSpec::parse("generator://smpte-bars?tone=1000").unwrap(),
+            GeneratorSpec {
+                kind: GeneratorKind::SmpteBars,
+                tone: Some(1000),
+            }
+        );
+        assert_eq!(
+            GeneratorSpec::parse("Generator://countdown").unwrap(),
+            GeneratorSpec {
+                kind: GeneratorKind::Countdown,
+                tone: None,
+            }
+        );
+        assert_eq!(
+            GeneratorSpec::parse("generator://slate").unwrap().kind,
+            GeneratorKind::Slate
+        );
+        assert_eq!(
+            GeneratorSpec::parse("generator://clock?tone=440").unwrap(),
+            GeneratorSpec {
+                kind: GeneratorKind::Clock,
+                tone: Some(440),
+            }
+        );
+        assert!(GeneratorSpec::parse("generator://slate?tone=1000").is_err());
+        assert!(GeneratorSpec::parse("generator://plasma").is_err());
+        assert!(GeneratorSpec::parse("generator://black?tone=loud").is_err());
+        assert!(GeneratorSpec::parse("generator://black?volume=1").is_err());
+        assert!(!is_generator_source("/media/generator.mp4"));
+    }
+
+    #[test]
+    fn validation_reports_typos() {
+        assert!(validate_generator_source("generator://smpte-bars").is_ok());
+        assert!(validate_generator_source("generator://countdown?tone=1000").is_ok());
+
+        let error = validate_generator_source("generator://smpte-barz").unwrap_err();
+        assert!(error.to_string().contains("unknown generator 'smpte-barz'"));
+        assert!(validate_generator_source("generator://countdown?to=12:00").is_err());
+        assert!(validate_generator_source("generator://countdown?tone=0").is_err());
+    }
+
+    #[test]
+    fn tone_is_a_continuous_sine() {
+        assert_eq!(tone_sample(0, 48_000, 1_000), 0.0);
+        // A quarter period of 1 kHz at 48 kHz is 12 samples.
+        assert!((tone_sample(12, 48_000, 1_000) - 0.1).abs() < 1e-6);
+        assert!((tone_sample(48_012, 48_000, 1_000) - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn bars_start_with_white_and_end_with_blue() {
+        let frame = smpte_bars_frame(140, 84);
+        let luma = frame.data(0);
+
+        assert_eq!(luma[0], 180);
+        assert_eq!(luma[139], 35);
+        // Bottom row starts with -I.
+        assert_eq!(luma[83 * frame.stride(0)], 40);
+    }
+
+    #[test]
+    fn countdown_beeps_once_per_second_until_the_end() {
+        let cfg = OutputConfig::new(64, 36, 25, 48_000);
+        let spec = GeneratorSpec::parse("generator://countdown?tone=1000").unwrap();
+        let mut generator = Generator::new(spec, &cfg, 2.0);
+
+        let first = generator.audio_frame(9_600);
+        assert!(first.plane::<f32>(0)[..4_800].iter().any(|s| *s != 0.0));
+        let second = generator.audio_frame(9_600);
+        assert!(second.plane::<f32>(0).iter().all(|s| *s == 0.0));
+
+        assert_eq!(countdown_label(9), "9");
+        assert_eq!(countdown_label(75), "1:15");
+    }
+
+    #[test]
+    fn clock_shows_the_time_of_day_in_the_channel_timezone() {
+        let now = UNIX_EPOCH + Duration::from_secs(86_400 * 3 + 23 * 3600 + 59 * 60 + 30);
+
+        assert_eq!(clock_seconds(now, Tz::UTC), 86_370);
+        // One hour east of UTC the day has already turned.
+        assert_eq!(clock_seconds(now, Berlin), 3_570);
+        // 2023-07-01 12:00:00 UTC is summer time, two hours east.
+        let summer = UNIX_EPOCH + Duration::from_secs(1_688_212_800);
+        assert_eq!(clock_seconds(summer, Berlin), 50_400);
+        assert_eq!(clock_label(3_570), "00:59:30");
+        assert_eq!(clock_label(86_370), "23:59:30");
+    }
+}
@@ -23,10 +23,21 @@ use log::{error, info, warn};
 
 use crate::{
     PlaybackControl,
+    analysis::signal::SignalMeter,
     benchmark::{self, BenchHandle, Stage},
-    compositor::logo::LogoOverlay,
+    compositor::{logo::LogoOverlay, pip::PictureInPicture},
+    input::{
+        delay::{DelayLine, LiveDelay, stretch_audio},
+        failover::IngestFailover,
+        publish::{PublishAuthorizer, Publisher},
+        pull::{PullControl, PullSource},
+        srt::{SrtListener, is_srt_url},
+        take::LiveTake,
+    },
     output::FrameOutput,
-    playout::{InputPlaybackOptions, LogoFadePlan, Timeline, play_opened_input},
+    playout::{
+        InputPlaybackOptions, LogoFadePlan, Slate, SyntheticSource, Timeline, play_opened_input,
+    },
     utils::{config::OutputConfig, logging},
 };
 
@@ -37,15 +48,24 @@ const LIVE_WATCHDOG_INTERVAL: Duration = Duration::from_millis(100);
 /// frames. Larger jumps (buggy publisher encoders can leap by hours) re-anchor
 /// the session instead, so the output never gets stuck writing filler.
 const MAX_LIVE_GAP_SECONDS: f64 = 5.0;
+/// Gaps shorter than this repeat the last live frame; the slate only covers
+/// real dropouts, so a late packet doesn't flash it on air.
+const LIVE_GAP_SLATE_AFTER: Duration = Duration::from_millis(500);
 /// The live channel carries decoded raw frames (several MB each for video);
 /// it must be bounded so a stalled consumer cannot exhaust memory.
 const LIVE_CHANNEL_SECONDS: usize = 2;
 const LIVE_SEND_RETRY_INTERVAL: Duration = Duration::from_millis(10);
 const LIVE_BACKPRESSURE_LOG_INTERVAL: Duration = Duration::from_secs(1);
+const LIVE_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// Failed pulls back off up to this delay, so an offline partner isn't
+/// hammered with connection attempts.
+const PULL_MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+/// Time a reader gets to exit after its abort before it counts as stuck.
+const LIVE_WORKER_EXIT_GRACE: Duration = Duration::from_millis(500);
 
 /// Number of RTMP reader threads that outlived their `abort` signal and are
 /// being reaped in the background. Exposed only via log messages for now;
-/// see the usage in `run_rtmp_listener` for context.
+/// see the usage in `run_live_source` for context.
 static STUCK_LIVE_WORKERS: AtomicU64 = AtomicU64::new(0);
 
 pub struct LiveReceiver {
@@ -70,9 +90,53 @@ pub struct LiveReceiver {
     video_pts: i64,
     audio_pts: i64,
     source_has_audio: bool,
+    publisher: Option<String>,
+    /// Broadcast delay the reader threads hold the feed for.
+    delay: LiveDelay,
+    /// Playback speed factor of the feed while its delay is rebuilt.
+    stretch: f64,
+    /// Output settings for slate gap filling; `None` freezes the last frame.
+    slate_cfg: Option<OutputConfig>,
+    /// Slate opened by the first long gap and kept for the next ones.
+    slate: Option<Slate>,
+    /// End of the scheduled live block; the session is cut off there.
+    block_end: Option<Instant>,
+    /// Block length that starts counting with the first live frame.
+    block_length: Option<Duration>,
+    /// Session cut off by its block end. It stays off air until the
+    /// publisher reconnects or the next block starts.
+    released_session: ReleasedSession,
     benchmark: Arc<Mutex<Option<BenchHandle>>>,
 }
 
+/// Sessions of the endpoints of one receiver.
+#[derive(Clone, Default)]
+struct LiveSessions {
+    /// Last session ID given out; IDs stay unique across the endpoints.
+    last_id: Arc<AtomicU64>,
+    released: ReleasedSession,
+}
+
+/// Session the receiver cut off, shared with the senders so its recording
+/// stops with it. Session IDs start at 1, so 0 is none.
+#[derive(Clone, Default)]
+struct ReleasedSession(Arc<AtomicU64>);
+
+impl ReleasedSession {
+    fn get(&self) -> Option<u64> {
+        let session_id = self.0.load(Ordering::Relaxed);
+        (session_id != 0).then_some(session_id)
+    }
+
+    fn set(&self, session_id: Option<u64>) {
+        self.0.store(session_id.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    fn is(&self, session_id: u64) -> bool {
+        self.get() == Some(session_id)
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct LiveEnded;
 
@@ -85,24 +149,142 @@ impl fmt::Display for LiveEnded {
 impl Error for LiveEnded {}
 
 enum LiveEvent {
-    Started { session_id: u64, has_audio: bool },
+    Started {
+        session_id: u64,
+        has_audio: bool,
+        /// Publisher the listener let on, for the logs.
+        publisher: Option<String>,
+    },
     Video(u64, frame::Video),
     Audio(u64, frame::Audio),
+    /// The feed plays at this speed factor from here on, see
+    /// [`DelayLine::stretch`].
+    Retimed(u64, f64),
     Ended(u64),
 }
 
-pub fn spawn_rtmp_listener(url: String, cfg: OutputConfig) -> LiveReceiver {
+/// Where the live ingest comes from: a listener chosen by the URL scheme,
+/// or a pull from a remote URL.
+enum LiveSource {
+    Rtmp(String),
+    Srt(SrtListener),
+    Pull(PullSource),
+}
+
+impl LiveSource {
+    fn listener(url: &str) -> Result<Self> {
+        if is_srt_url(url) {
+            return Ok(Self::Srt(SrtListener::parse(url)?));
+        }
+
+        Ok(Self::Rtmp(url.to_string()))
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Rtmp(_) => "RTMP listener",
+            Self::Srt(_) => "SRT listener",
+            Self::Pull(_) => "pull ingest",
+        }
+    }
+
+    /// Input label without secrets like the SRT passphrase.
+    fn label(&self) -> &str {
+        match self {
+            Self::Rtmp(url) => url,
+            Self::Srt(srt) => srt.address(),
+            Self::Pull(pull) => pull.label(),
+        }
+    }
+
+    fn is_disarmed(&self) -> bool {
+        matches!(self, Self::Pull(pull) if pull.is_disarmed())
+    }
+
+    fn next_retry_delay(&self, delay: Duration) -> Duration {
+        match self {
+            Self::Pull(_) => (delay * 2).min(PULL_MAX_RETRY_DELAY),
+            _ => LIVE_RETRY_DELAY,
+        }
+    }
+
+    /// Opens the input, together with the name of the publisher a listener
+    /// let on.
+    fn open(
+        &self,
+        authorizer: Option<&PublishAuthorizer>,
+        abort: Arc<AtomicBool>,
+        listener_abort: Arc<AtomicBool>,
+    ) -> Result<(format::context::Input, Option<String>)> {
+        match self {
+            Self::Rtmp(url) => open_rtmp_listener(url, authorizer, abort, listener_abort),
+            Self::Srt(srt) => srt.open(authorizer, abort, listener_abort),
+            Self::Pull(pull) => Ok((pull.open(abort, listener_abort)?, None)),
+        }
+    }
+}
+
+/// Starts the ingest server for an `rtmp://` or `srt://` listen address.
+pub fn spawn_live_listener(url: &str, cfg: OutputConfig) -> Result<LiveReceiver> {
+    Ok(spawn_live_sources(vec![LiveSource::listener(url)?], cfg))
+}
+
+/// Connects to a remote stream while `control` is armed and reconnects
+/// when it fails or ends.
+pub fn spawn_live_pull(url: &str, cfg: OutputConfig, control: PullControl) -> Result<LiveReceiver> {
+    Ok(spawn_live_sources(
+        vec![LiveSource::Pull(PullSource::new(url, control)?)],
+        cfg,
+    ))
+}
+
+/// Starts one ingest per URL, listeners or pulls while `pull` is armed.
+/// The URLs are in priority order; with more than one, the config's
+/// [`IngestFailover`] decides which is on air.
+pub fn spawn_live_failover(
+    urls: &[String],
+    cfg: OutputConfig,
+    pull: Option<PullControl>,
+) -> Result<LiveReceiver> {
+    if urls.is_empty() {
+        return Err(anyhow::anyhow!("live ingest needs a URL"));
+    }
+    let sources = urls
+        .iter()
+        .map(|url| match &pull {
+            Some(control) => PullSource::new(url, control.clone()).map(LiveSource::Pull),
+            None => LiveSource::listener(url),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(spawn_live_sources(sources, cfg))
+}
+
+fn spawn_live_sources(sources: Vec<LiveSource>, cfg: OutputConfig) -> LiveReceiver {
     let fps = cfg.fps;
     let sample_rate = cfg.sample_rate;
     let capacity = live_channel_capacity(cfg.fps);
     let (tx, rx) = mpsc::sync_channel(capacity);
     let abort = Arc::new(AtomicBool::new(false));
     let benchmark = Arc::new(Mutex::new(None));
-    thread::spawn({
-        let abort = Arc::clone(&abort);
-        let benchmark = Arc::clone(&benchmark);
-        move || run_rtmp_listener(url, cfg, tx, abort, benchmark)
-    });
+    let slate_cfg = cfg.slate.is_some().then(|| cfg.clone());
+    let delay = cfg.live_delay.clone();
+    let failover = sources.len() > 1;
+    if failover {
+        cfg.ingest_failover.register(sources.len());
+    }
+    let sessions = LiveSessions::default();
+    for (index, source) in sources.into_iter().enumerate() {
+        thread::spawn({
+            let cfg = cfg.clone();
+            let tx = tx.clone();
+            let sessions = sessions.clone();
+            let abort = Arc::clone(&abort);
+            let benchmark = Arc::clone(&benchmark);
+            let endpoint = failover.then_some(index);
+            move || run_live_source(source, endpoint, cfg, tx, sessions, abort, benchmark)
+        });
+    }
 
     LiveReceiver {
         rx,
@@ -126,6 +308,14 @@ pub fn spawn_rtmp_listener(url: String, cfg: OutputConfig) -> LiveReceiver {
         video_pts: 0,
         audio_pts: 0,
         source_has_audio: false,
+        publisher: None,
+        delay,
+        stretch: 1.0,
+        slate_cfg,
+        slate: None,
+        block_end: None,
+        block_length: None,
+        released_session: sessions.released,
         benchmark,
     }
 }
@@ -137,6 +327,25 @@ impl Drop for LiveReceiver {
 }
 
 impl LiveReceiver {
+    /// Limits live sessions to a scheduled block of `length`. With
+    /// `from_first_frame` the block starts when the feed shows up instead
+    /// of now.
+    pub(crate) fn start_block(&mut self, length: Duration, from_first_frame: bool) {
+        self.released_session.set(None);
+        if from_first_frame {
+            self.block_end = None;
+            self.block_length = Some(length);
+        } else {
+            self.block_end = Some(Instant::now() + length);
+            self.block_length = None;
+        }
+    }
+
+    pub(crate) fn end_block(&mut self) {
+        self.block_end = None;
+        self.block_length = None;
+    }
+
     pub(crate) fn set_benchmark(&self, benchmark: Option<BenchHandle>) {
         *self
             .benchmark
@@ -162,8 +371,31 @@ impl<'a, O: FrameOutput> LiveOverrideOutput<'a, O> {
                 Ok(LiveEvent::Started {
                     session_id,
                     has_audio,
+                    publisher,
+                }) if self.live.active => {
+                    // A failover endpoint took over; the program stays live
+                    // and the new feed is anchored on its first picture.
+                    info!(
+                        "live input switched to another ingest endpoint{}",
+                        from_publisher(publisher.as_deref())
+                    );
+                    self.live.publisher = publisher;
+                    self.live.session_id = session_id;
+                    self.live.stretch = 1.0;
+                    self.live.session_output_start_seconds = None;
+                    self.live.session_source_start_seconds = None;
+                    self.live.pending_audio.clear();
+                    self.live.last_media_at = Some(Instant::now());
+                    self.live.source_has_audio = has_audio;
+                }
+                Ok(LiveEvent::Started {
+                    session_id,
+                    has_audio,
+                    publisher,
                 }) => {
                     self.live.session_id = session_id;
+                    self.live.publisher = publisher;
+                    self.live.stretch = 1.0;
                     self.live.session_output_start_seconds = None;
                     self.live.session_source_start_seconds = None;
                     self.live.pending_audio.clear();
@@ -175,12 +407,24 @@ impl<'a, O: FrameOutput> LiveOverrideOutput<'a, O> {
                     info!("live input connected; waiting for first video frame");
                 }
                 Ok(LiveEvent::Video(session_id, frame)) => {
-                    if session_id == self.live.session_id {
-                        if !self.live.active {
-                            info!("first live video frame received; switching to RTMP live");
+                    if session_id == self.live.session_id
+                        && !self.live.released_session.is(session_id)
+                    {
+                        let starts = !self.live.active;
+                        if starts {
+                            info!(
+                                "first live video frame received{}; switching to live input",
+                                from_publisher(self.live.publisher.as_deref())
+                            );
+                            if let Some(length) = self.live.block_length.take() {
+                                self.live.block_end = Some(Instant::now() + length);
+                            }
                             self.live.active = true;
                             self.live.connecting = false;
                             self.live.connecting_since = None;
+                        }
+                        // A session starts, or a failover endpoint took over.
+                        if starts || self.live.session_source_start_seconds.is_none() {
                             self.start_live_session(video_seconds(
                                 self.live.fps,
                                 frame.pts().unwrap_or(0),
@@ -193,17 +437,33 @@ impl<'a, O: FrameOutput> LiveOverrideOutput<'a, O> {
                     }
                 }
                 Ok(LiveEvent::Audio(session_id, frame)) => {
-                    if session_id == self.live.session_id {
+                    if session_id == self.live.session_id
+                        && !self.live.released_session.is(session_id)
+                    {
                         received_event = true;
                         self.live.last_media_at = Some(Instant::now());
-                        if self.live.active {
+                        if self.live.active && self.live.session_source_start_seconds.is_some() {
                             self.encode_live_audio_frame(frame)?;
                         } else {
                             self.live.pending_audio.push(frame);
                         }
                     }
                 }
+                Ok(LiveEvent::Retimed(session_id, stretch)) => {
+                    if session_id == self.live.session_id {
+                        // The feed changed its speed; it is anchored again on
+                        // its next picture, like after a failover switch.
+                        self.live.stretch = stretch;
+                        self.live.session_output_start_seconds = None;
+                        self.live.session_source_start_seconds = None;
+                        self.live.pending_audio.clear();
+                    }
+                }
                 Ok(LiveEvent::Ended(session_id)) => {
+                    // A session held off air after its block can be taken again.
+                    if self.live.released_session.is(session_id) {
+                        self.live.released_session.set(None);
+                    }
                     if session_id == self.live.session_id {
                         info!("live input ended; switching back to file playback");
                         if self.live.active {
@@ -262,6 +522,23 @@ impl<'a, O: FrameOutput> LiveOverrideOutput<'a, O> {
                 self.live.connecting_since = None;
                 self.live.pending_audio.clear();
             }
+            if self.live.active
+                && self
+                    .live
+                    .block_end
+                    .is_some_and(|block_end| Instant::now() >= block_end)
+            {
+                info!("live block ended; switching back to file playback");
+                self.fill_live_gap_since_last_media()?;
+                self.align_live_pts_to_common_time();
+                self.prepare_file_resume();
+                self.live.released_session.set(Some(self.live.session_id));
+                self.live.returned_to_file = true;
+                self.live.active = false;
+                self.live.connecting = false;
+                self.live.connecting_since = None;
+                self.live.pending_audio.clear();
+            }
         }
         if self.live.returned_to_file {
             self.live.returned_to_file = false;
@@ -272,13 +549,15 @@ impl<'a, O: FrameOutput> LiveOverrideOutput<'a, O> {
 
     /// Gives up on a connect attempt that never produced a video frame
     /// (e.g. an audio-only publisher), so buffered audio cannot pile up
-    /// indefinitely while file playback continues.
+    /// indefinitely while file playback continues. The first frame comes
+    /// the broadcast delay later.
     fn expire_stalled_connect(&mut self) {
+        let timeout = LIVE_STARTUP_TIMEOUT + self.live.delay.status().target;
         if self.live.connecting
             && self
                 .live
                 .connecting_since
-                .is_some_and(|since| since.elapsed() >= LIVE_STARTUP_TIMEOUT)
+                .is_some_and(|since| since.elapsed() >= timeout)
         {
             info!("live input produced no video frame; staying on file playback");
             self.live.connecting = false;
@@ -299,8 +578,36 @@ impl<'a, O: FrameOutput> LiveOverrideOutput<'a, O> {
         // pumping for a while (e.g. between clips) it can be arbitrarily
         // large. Cap it so the output never gets stuck writing filler.
         let duration = duration.min(Duration::from_secs_f64(MAX_LIVE_GAP_SECONDS));
+        // Short gaps hold the last frame, unless there is none to hold yet.
+        let mut slate = None;
+        if duration >= LIVE_GAP_SLATE_AFTER || self.live.last_video_frame.is_none() {
+            slate = self
+                .live
+                .slate
+                .take()
+                .or_else(|| self.live.slate_cfg.as_ref().map(Slate::new));
+        }
+        let result = self.write_live_gap(duration, slate.as_mut());
+        if slate.is_some() {
+            self.live.slate = slate;
+        }
+        result?;
+
+        self.live.last_media_at = Some(Instant::now());
+        Ok(())
+    }
+
+    fn write_live_gap(&mut self, duration: Duration, mut slate: Option<&mut Slate>) -> Result<()> {
         let video_frames = (duration.as_secs_f64() * f64::from(self.live.fps)).ceil() as i64;
-        if let Some(last_video_frame) = self.live.last_video_frame.clone() {
+        if let Some(slate) = slate.as_deref_mut() {
+            for _ in 0..video_frames {
+                let mut frame = slate.video_frame();
+                frame.set_pts(Some(self.live.video_pts));
+                self.output.encode_video(&frame)?;
+                self.remember_video_frame(frame, self.live.video_pts);
+                self.live.video_pts += 1;
+            }
+        } else if let Some(last_video_frame) = self.live.last_video_frame.clone() {
             for _ in 0..video_frames {
                 let mut frame = last_video_frame.clone();
                 frame.set_pts(Some(self.live.video_pts));
@@ -317,24 +624,29 @@ impl<'a, O: FrameOutput> LiveOverrideOutput<'a, O> {
         let frame_size = self.output.audio_frame_size().max(1);
         while remaining_samples > 0 {
             let samples = remaining_samples.min(frame_size);
-            let mut frame = frame::Audio::new(
-                Sample::F32(SampleType::Planar),
-                samples,
-                ChannelLayout::STEREO,
-            );
-            frame.set_rate(self.live.sample_rate);
-            frame.set_pts(Some(self.live.audio_pts));
-            for channel in 0..2 {
-                for sample in frame.plane_mut::<f32>(channel) {
-                    *sample = 0.0;
+            let mut frame = match slate.as_deref_mut() {
+                Some(slate) => slate.audio_frame(samples),
+                None => {
+                    let mut frame = frame::Audio::new(
+                        Sample::F32(SampleType::Planar),
+                        samples,
+                        ChannelLayout::STEREO,
+                    );
+                    frame.set_rate(self.live.sample_rate);
+                    for channel in 0..2 {
+                        for sample in frame.plane_mut::<f32>(channel) {
+                            *sample = 0.0;
+                        }
+                    }
+                    frame
                 }
-            }
+            };
+            frame.set_pts(Some(self.live.audio_pts));
             self.output.encode_audio(&frame)?;
             self.remember_audio_frame_end(self.live.audio_pts + samples as i64);
             remaining_samples -= samples;
         }
 
-        self.live.last_media_at = Some(Instant::now());
         Ok(())
     }
 
@@ -428,7 +740,7 @@ impl<'a, O: FrameOutput> LiveOverrideOutput<'a, O> {
             .live
             .session_source_start_seconds
             .unwrap_or(source_seconds);
-        output_start + (source_seconds - source_start)
+        output_start + (source_seconds - source_start) * self.live.stretch
     }
 
     fn encode_live_video_frame(&mut self, mut frame: frame::Video) -> Result<()> {
@@ -466,6 +778,9 @@ impl<'a, O: FrameOutput> LiveOverrideOutput<'a, O> {
     }
 
     fn encode_live_audio_frame(&mut self, mut frame: frame::Audio) -> Result<()> {
+        if self.live.stretch != 1.0 {
+            frame = stretch_audio(&frame, self.live.stretch);
+        }
         let samples = frame.samples() as i64;
         let source_pts = frame.pts().unwrap_or(0);
         let source_seconds = audio_seconds(self.live.sample_rate, source_pts);
@@ -677,6 +992,29 @@ struct LiveFrameSender {
     frame_seen: Arc<AtomicBool>,
     abort: Arc<AtomicBool>,
     listener_abort: Arc<AtomicBool>,
+    pip: PictureInPicture,
+    /// Frames go to the picture-in-picture window instead of the program.
+    in_pip: bool,
+    take: LiveTake,
+    /// A manual take keeps the frames off air.
+    held: bool,
+    failover: Option<FailoverEndpoint>,
+    /// Whether the pictures go to the program, for the recording.
+    on_air: Arc<AtomicBool>,
+    /// Session the receiver cut off; its pictures don't reach the program.
+    released: ReleasedSession,
+    signal: SignalMeter,
+    /// A failed signal keeps the frames off air.
+    signal_held: bool,
+}
+
+/// Endpoint of a failover ingest. Only the one on air reaches the program.
+struct FailoverEndpoint {
+    failover: IngestFailover,
+    index: usize,
+    has_audio: bool,
+    publisher: Option<String>,
+    on_air: bool,
 }
 
 impl LiveFrameSender {
@@ -684,9 +1022,7 @@ impl LiveFrameSender {
     /// the RTMP reader instead of dropping frames, but the retry loop keeps
     /// checking abort flags so shutdown/restart cannot hang on a blocked send.
     fn send_frame(&mut self, event: LiveEvent) -> Result<()> {
-        self.frame_seen.store(true, Ordering::Relaxed);
-        self.last_frame_ms
-            .store(monotonic_millis(), Ordering::Relaxed);
+        self.mark_frame_seen();
         benchmark::measure(Stage::LiveQueue, || {
             send_live_event(
                 &self.tx,
@@ -697,6 +1033,115 @@ impl LiveFrameSender {
             )
         })
     }
+
+    fn mark_frame_seen(&self) {
+        self.frame_seen.store(true, Ordering::Relaxed);
+        self.last_frame_ms
+            .store(monotonic_millis(), Ordering::Relaxed);
+    }
+
+    /// Hand the ingest to the picture-in-picture window while it asks for
+    /// it. The program leaves the live session then, and takes it up again
+    /// with the next frame once the window is gone.
+    fn route_to_pip(&mut self) -> Result<bool> {
+        let in_pip = self.pip.takes_ingest();
+        if in_pip && !self.in_pip {
+            info!("live input moved to the picture-in-picture window");
+            send_live_event(
+                &self.tx,
+                LiveEvent::Ended(self.session_id),
+                Some(&self.abort),
+                &self.listener_abort,
+                "live end",
+            )?;
+        } else if !in_pip && self.in_pip {
+            info!("live input left the picture-in-picture window");
+            self.pip.clear_ingest();
+        }
+        self.in_pip = in_pip;
+        Ok(in_pip)
+    }
+
+    /// Sends the picture where it goes instead of the program, and returns
+    /// whether it goes on air.
+    fn route_off_air(&mut self, frame: &frame::Video) -> Result<bool> {
+        if !self.select_endpoint(frame)? {
+            return Ok(false);
+        }
+        self.take.push_preview(frame);
+        if self.hold_for_signal()? {
+            return Ok(false);
+        }
+        if self.route_to_pip()? {
+            self.pip.push_ingest(frame);
+            return Ok(false);
+        }
+        Ok(!self.hold_off_air()?)
+    }
+
+    /// With failover, an endpoint starts its session when it goes on air;
+    /// the receiver takes that as a switch when another one was on air.
+    fn select_endpoint(&mut self, frame: &frame::Video) -> Result<bool> {
+        let Some(endpoint) = self.failover.as_mut() else {
+            return Ok(true);
+        };
+
+        let on_air = endpoint.failover.observe_video(endpoint.index, frame);
+        if on_air && !endpoint.on_air {
+            info!("ingest endpoint {} on air", endpoint.index + 1);
+            send_live_event(
+                &self.tx,
+                LiveEvent::Started {
+                    session_id: self.session_id,
+                    has_audio: endpoint.has_audio,
+                    publisher: endpoint.publisher.clone(),
+                },
+                Some(&self.abort),
+                &self.listener_abort,
+                "live start",
+            )?;
+        }
+        endpoint.on_air = on_air;
+        Ok(on_air)
+    }
+
+    /// Keep a feed whose signal failed off air; the program returns to the
+    /// playlist until the signal recovers.
+    fn hold_for_signal(&mut self) -> Result<bool> {
+        let held = self.signal.failed();
+        if held && !self.signal_held {
+            send_live_event(
+                &self.tx,
+                LiveEvent::Ended(self.session_id),
+                Some(&self.abort),
+                &self.listener_abort,
+                "live end",
+            )?;
+        }
+        self.signal_held = held;
+        Ok(held)
+    }
+
+    /// Keep the feed off air until it is taken. Holding it again, like after
+    /// a release, ends the live session so the program returns to the
+    /// playlist.
+    fn hold_off_air(&mut self) -> Result<bool> {
+        let held = self.take.holds_feed();
+        if held && !self.held {
+            info!("live input armed; waiting for a take");
+            send_live_event(
+                &self.tx,
+                LiveEvent::Ended(self.session_id),
+                Some(&self.abort),
+                &self.listener_abort,
+                "live end",
+            )?;
+        } else if !held && self.held {
+            info!("live input taken on air");
+        }
+        self.held = held;
+        Ok(held)
+    }
 }
 
 impl FrameOutput for LiveFrameSender {
@@ -705,36 +1150,116 @@ impl FrameOutput for LiveFrameSender {
     }
 
     fn encode_video(&mut self, frame: &frame::Video) -> Result<()> {
+        self.signal.observe_video(frame);
+        let on_air = self.route_off_air(frame)?;
+        self.on_air.store(
+            on_air && !self.released.is(self.session_id),
+            Ordering::Relaxed,
+        );
+        if !on_air {
+            self.mark_frame_seen();
+            return Ok(());
+        }
         self.send_frame(LiveEvent::Video(self.session_id, frame.clone()))
             .context("failed to send live video frame")
     }
 
     fn encode_audio(&mut self, frame: &frame::Audio) -> Result<()> {
+        self.signal.observe_audio(frame);
+        let off_air = self.failover.as_ref().is_some_and(|endpoint| {
+            endpoint.failover.observe_audio(endpoint.index, frame);
+            !endpoint.on_air
+        });
+        // The window shows the picture only, the program keeps its sound.
+        if off_air || self.signal_held || self.in_pip || self.held {
+            self.mark_frame_seen();
+            return Ok(());
+        }
         self.send_frame(LiveEvent::Audio(self.session_id, frame.clone()))
             .context("failed to send live audio frame")
     }
+
+    fn composites_overlays(&self) -> bool {
+        !self.pip.takes_ingest()
+    }
+}
+
+/// Broadcast delay of one live connection. Its reader thread holds the
+/// compressed packets before they are decoded.
+pub(crate) struct LiveFeedDelay {
+    delay: LiveDelay,
+    tx: SyncSender<LiveEvent>,
+    session_id: u64,
+    on_air: Arc<AtomicBool>,
+    abort: Arc<AtomicBool>,
+    listener_abort: Arc<AtomicBool>,
+    last_frame_ms: Arc<AtomicU64>,
+    frame_seen: Arc<AtomicBool>,
+}
+
+impl LiveFeedDelay {
+    pub(crate) fn line<T>(&self) -> DelayLine<T> {
+        DelayLine::new(self.delay.clone())
+    }
+
+    /// Whether the pictures of the feed go to the program.
+    pub(crate) fn on_air(&self) -> bool {
+        self.on_air.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn is_aborted(&self) -> bool {
+        self.abort.load(Ordering::Relaxed) || self.listener_abort.load(Ordering::Relaxed)
+    }
+
+    /// Packets that wait for the delay keep the connection alive, as no
+    /// frame is decoded from them yet.
+    pub(crate) fn packet_arrived(&self) {
+        self.frame_seen.store(true, Ordering::Relaxed);
+        self.last_frame_ms
+            .store(monotonic_millis(), Ordering::Relaxed);
+    }
+
+    /// Tells the receiver that the feed plays at `stretch` from its next
+    /// frame on.
+    pub(crate) fn retime(&self, stretch: f64) -> Result<()> {
+        send_live_event(
+            &self.tx,
+            LiveEvent::Retimed(self.session_id, stretch),
+            Some(&self.abort),
+            &self.listener_abort,
+            "live retime",
+        )
+    }
 }
 
-fn run_rtmp_listener(
-    url: String,
+fn run_live_source(
+    source: LiveSource,
+    endpoint: Option<usize>,
     cfg: OutputConfig,
     tx: SyncSender<LiveEvent>,
+    sessions: LiveSessions,
     listener_abort: Arc<AtomicBool>,
     benchmark: Arc<Mutex<Option<BenchHandle>>>,
 ) {
-    let mut session_id = 0;
+    let mut retry_delay = LIVE_RETRY_DELAY;
 
     while !listener_abort.load(Ordering::Relaxed) {
         let abort = Arc::new(AtomicBool::new(false));
         match logging::with_ingest_logs(cfg.channel_id, || {
-            open_rtmp_listener(&url, Arc::clone(&abort), Arc::clone(&listener_abort))
+            source.open(
+                cfg.publish_authorizer.as_ref(),
+                Arc::clone(&abort),
+                Arc::clone(&listener_abort),
+            )
         }) {
-            Ok(ictx) => {
+            Ok((ictx, publisher)) => {
                 if listener_abort.load(Ordering::Relaxed) {
                     abort.store(true, Ordering::Relaxed);
                     return;
                 }
-                session_id += 1;
+                retry_delay = LIVE_RETRY_DELAY;
+                let session_id = sessions.last_id.fetch_add(1, Ordering::Relaxed) + 1;
+                let has_audio = ictx.streams().best(media::Type::Audio).is_some();
                 let last_frame_ms = Arc::new(AtomicU64::new(monotonic_millis()));
                 let frame_seen = Arc::new(AtomicBool::new(false));
                 let watchdog = spawn_live_watchdog(
@@ -743,24 +1268,41 @@ fn run_rtmp_listener(
                     Arc::clone(&abort),
                 );
 
-                if send_live_event(
-                    &tx,
-                    LiveEvent::Started {
-                        session_id,
-                        has_audio: ictx.streams().best(media::Type::Audio).is_some(),
-                    },
-                    Some(&abort),
-                    &listener_abort,
-                    "live start",
-                )
-                .is_err()
-                {
+                let started = match endpoint {
+                    Some(index) => {
+                        cfg.ingest_failover.feed_started(index);
+                        Ok(())
+                    }
+                    None => send_live_event(
+                        &tx,
+                        LiveEvent::Started {
+                            session_id,
+                            has_audio,
+                            publisher: publisher.clone(),
+                        },
+                        Some(&abort),
+                        &listener_abort,
+                        "live start",
+                    ),
+                };
+                if started.is_err() {
                     abort.store(true, Ordering::Relaxed);
                     let _ = watchdog.join();
                     return;
                 }
 
                 let (done_tx, done_rx) = mpsc::sync_channel(1);
+                let on_air = Arc::new(AtomicBool::new(false));
+                let delay = LiveFeedDelay {
+                    delay: cfg.live_delay.clone(),
+                    tx: tx.clone(),
+                    session_id,
+                    on_air: Arc::clone(&on_air),
+                    abort: Arc::clone(&abort),
+                    listener_abort: Arc::clone(&listener_abort),
+                    last_frame_ms: Arc::clone(&last_frame_ms),
+                    frame_seen: Arc::clone(&frame_seen),
+                };
                 let mut output = LiveFrameSender {
                     tx: tx.clone(),
                     session_id,
@@ -768,9 +1310,25 @@ fn run_rtmp_listener(
                     frame_seen,
                     abort: Arc::clone(&abort),
                     listener_abort: Arc::clone(&listener_abort),
+                    pip: cfg.picture_in_picture.clone(),
+                    in_pip: false,
+                    take: cfg.live_take.clone(),
+                    held: false,
+                    failover: endpoint.map(|index| FailoverEndpoint {
+                        failover: cfg.ingest_failover.clone(),
+                        index,
+                        has_audio,
+                        publisher,
+                        on_air: false,
+                    }),
+                    on_air: Arc::clone(&on_air),
+                    released: sessions.released.clone(),
+                    signal: cfg.signal_monitor.meter(endpoint.unwrap_or(0)),
+                    signal_held: false,
                 };
+                cfg.live_take.feed_started();
 
-                let worker_url = url.clone();
+                let worker_url = source.label().to_string();
                 let worker_cfg = cfg.clone();
                 let worker_benchmark = benchmark
                     .lock()
@@ -783,6 +1341,8 @@ fn run_rtmp_listener(
                     let mut timeline = Timeline::new();
                     let playback_control = PlaybackControl::default();
                     let logo_fade_plan = LogoFadePlan::none(timeline.video_pts(), &worker_cfg);
+                    let mut recording = worker_cfg.live_recorder.session(&ictx, on_air);
+                    let mut packets = output.signal.packet_meter(&ictx);
                     let result = logging::with_ingest_logs(worker_cfg.channel_id, || {
                         play_opened_input(
                             &worker_url,
@@ -795,7 +1355,13 @@ fn run_rtmp_listener(
                                 duration_seconds: None,
                                 subtitles_media_path: None,
                                 logo_fade_plan,
+                                image: None,
+                                branding: None,
+                                events: None,
                                 playback_control: &playback_control,
+                                recording: recording.as_mut(),
+                                packets: Some(&mut packets),
+                                delay: Some(&delay),
                             },
                         )
                     });
@@ -818,10 +1384,32 @@ fn run_rtmp_listener(
                             break;
                         }
                     }
+                    if source.is_disarmed() {
+                        info!("pull ingest disarmed; dropping the connection");
+                        break;
+                    }
                 }
 
                 abort.store(true, Ordering::Relaxed);
+                if !worker_finished {
+                    worker_finished = !matches!(
+                        done_rx.recv_timeout(LIVE_WORKER_EXIT_GRACE),
+                        Err(RecvTimeoutError::Timeout)
+                    );
+                }
                 let _ = watchdog.join();
+                let was_on_air =
+                    endpoint.is_none_or(|index| cfg.ingest_failover.on_air() == Some(index));
+                if let Some(index) = endpoint {
+                    cfg.ingest_failover.feed_ended(index);
+                }
+                // A backup that took over keeps the program live.
+                let taken_over =
+                    was_on_air && endpoint.is_some() && cfg.ingest_failover.on_air().is_some();
+                if was_on_air {
+                    cfg.picture_in_picture.clear_ingest();
+                }
+                cfg.live_take.feed_ended();
                 if worker_finished {
                     let _ = worker.join();
                 } else {
@@ -843,15 +1431,19 @@ fn run_rtmp_listener(
                     });
                 }
 
-                info!("Restart ingest server after live input ended");
-                if send_live_event(
-                    &tx,
-                    LiveEvent::Ended(session_id),
-                    None,
-                    &listener_abort,
-                    "live end",
-                )
-                .is_err()
+                match source {
+                    LiveSource::Pull(_) => info!("Reconnect pull ingest after live input ended"),
+                    _ => info!("Restart ingest server after live input ended"),
+                }
+                if !taken_over
+                    && send_live_event(
+                        &tx,
+                        LiveEvent::Ended(session_id),
+                        None,
+                        &listener_abort,
+                        "live end",
+                    )
+                    .is_err()
                 {
                     return;
                 }
@@ -861,13 +1453,28 @@ fn run_rtmp_listener(
                 if listener_abort.load(Ordering::Relaxed) {
                     return;
                 }
-                error!("RTMP listener failed: {error:#}; retrying");
-                thread::sleep(Duration::from_secs(1));
+                if source.is_disarmed() {
+                    continue;
+                }
+                error!(
+                    "{} failed: {error:#}; retrying in {} s",
+                    source.name(),
+                    retry_delay.as_secs()
+                );
+                sleep_unless_aborted(retry_delay, &listener_abort);
+                retry_delay = source.next_retry_delay(retry_delay);
             }
         }
     }
 }
 
+fn sleep_unless_aborted(duration: Duration, listener_abort: &AtomicBool) {
+    let until = Instant::now() + duration;
+    while !listener_abort.load(Ordering::Relaxed) && Instant::now() < until {
+        thread::sleep(LIVE_WATCHDOG_INTERVAL);
+    }
+}
+
 fn live_channel_capacity(fps: u32) -> usize {
     (fps as usize).saturating_mul(LIVE_CHANNEL_SECONDS).max(1)
 }
@@ -943,9 +1550,10 @@ fn spawn_live_watchdog(
 
 fn open_rtmp_listener(
     url: &str,
+    authorizer: Option<&PublishAuthorizer>,
     abort: Arc<AtomicBool>,
     listener_abort: Arc<AtomicBool>,
-) -> Result<format::context::Input> {
+) -> Result<(format::context::Input, Option<String>)> {
     let mut options = Dictionary::new();
     options.set("listen", "1");
     options.set("timeout", "0");
@@ -965,15 +1573,33 @@ fn open_rtmp_listener(
     )
     .with_context(|| format!("failed to listen for RTMP input at {url}"))?;
 
-    if let Some((actual_key, expected_key)) = logging::take_unexpected_rtmp_stream() {
+    let unexpected = logging::take_unexpected_rtmp_stream();
+    // ffmpeg-next does not expose protocol-private AVOptions. Limit the raw
+    // context access to this fallback lookup of FFmpeg's RTMP playpath.
+    let context = unsafe { input.as_ptr().cast_mut() };
+
+    if let Some(authorizer) = authorizer {
+        // FFmpeg lets a publisher with another key on and only warns, so
+        // the warning holds the key the publisher sent.
+        let key = unexpected
+            .map(|(actual_key, _)| actual_key)
+            .or_else(|| unsafe { rtmp_context_option(context, "rtmp_playpath") });
+        let publisher = authorizer.authorize(&Publisher {
+            protocol: "rtmp",
+            listener: url.to_string(),
+            key,
+            expected_key: rtmp_stream_key(url),
+        })?;
+
+        return Ok((input, publisher));
+    }
+
+    if let Some((actual_key, expected_key)) = unexpected {
         anyhow::bail!(
             "incoming RTMP stream key {actual_key:?} does not match configured key {expected_key:?}"
         );
     }
 
-    // ffmpeg-next does not expose protocol-private AVOptions. Limit the raw
-    // context access to this fallback validation of FFmpeg's RTMP playpath.
-    let context = unsafe { input.as_ptr().cast_mut() };
     if let Some(expected_key) = rtmp_stream_key(url)
         && let Some(actual_key) = unsafe { rtmp_context_option(context, "rtmp_playpath") }
         && actual_key != expected_key
@@ -983,7 +1609,14 @@ fn open_rtmp_listener(
         );
     }
 
-    Ok(input)
+    Ok((input, None))
+}
+
+/// ` from <publisher>` for log lines, when a listener authorized one.
+fn from_publisher(publisher: Option<&str>) -> String {
+    publisher
+        .map(|publisher| format!(" from {publisher}"))
+        .unwrap_or_default()
 }
 
 fn rtmp_stream_key(url: &str) -> Option<String> {
@@ -1049,6 +1682,8 @@ fn monotonic_millis() -> u64 {
 #[cfg(test)]
 mod tests {
     use std::{
+        net::TcpListener,
+        path::Path,
         sync::{
             Arc, Mutex,
             atomic::{AtomicBool, AtomicU64, Ordering},
@@ -1059,13 +1694,30 @@ mod tests {
     };
 
     use anyhow::Result;
-    use ffmpeg_next::frame;
+    use ffmpeg_next::{frame, util::format::pixel::Pixel};
 
     use super::{
-        LiveEvent, LiveFrameSender, LiveOverrideOutput, LiveReceiver, live_channel_capacity,
-        resume_pts,
+        LIVE_GAP_SLATE_AFTER, LIVE_RETRY_DELAY, LiveEnded, LiveEvent, LiveFeedDelay,
+        LiveFrameSender, LiveOverrideOutput, LiveReceiver, LiveTake, PictureInPicture,
+        live_channel_capacity, resume_pts, spawn_live_pull,
+    };
+    use crate::{
+        PlaybackControl,
+        analysis::signal::SignalMonitor,
+        input::{
+            delay::{DELAY_REBUILD_STRETCH, LiveDelay},
+            pull::PullControl,
+        },
+        output::FrameOutput,
+        playout::{InputPlaybackOptions, LogoFadePlan, Timeline, play_opened_input},
+        utils::{
+            config::{
+                MotionEasing, OutputConfig, PictureInPictureConfig, PipLayout, PipRect, PipSource,
+                SlateConfig,
+            },
+            helper::open_media_input,
+        },
     };
-    use crate::output::FrameOutput;
 
     #[derive(Default)]
     struct CountingOutput {
@@ -1119,6 +1771,14 @@ mod tests {
             video_pts: 0,
             audio_pts: 0,
             source_has_audio: false,
+            publisher: None,
+            delay: LiveDelay::default(),
+            stretch: 1.0,
+            slate_cfg: None,
+            slate: None,
+            block_end: None,
+            block_length: None,
+            released_session: ReleasedSession::default(),
             benchmark: Arc::new(Mutex::new(None)),
         }
     }
@@ -1181,6 +1841,7 @@ mod tests {
         tx.try_send(LiveEvent::Started {
             session_id: 1,
             has_audio: true,
+            publisher: None,
         })
         .unwrap();
         let frame_seen = Arc::new(AtomicBool::new(false));
@@ -1202,6 +1863,15 @@ mod tests {
                     frame_seen,
                     abort,
                     listener_abort,
+                    pip: PictureInPicture::default(),
+                    in_pip: false,
+                    take: LiveTake::default(),
+                    held: false,
+                    failover: None,
+                    on_air: Arc::new(AtomicBool::new(false)),
+                    released: ReleasedSession::default(),
+                    signal: SignalMonitor::default().meter(0),
+                    signal_held: false,
                 };
                 sender
                     .send_frame(LiveEvent::Video(1, frame::Video::empty()))
@@ -1253,12 +1923,157 @@ mod tests {
         assert_eq!(live.last_audio_output_end_pts, Some(96_000));
     }
 
+    #[test]
+    fn rebuilding_delay_plays_the_feed_slower() {
+        let (tx, rx) = mpsc::sync_channel(8);
+        let mut live = test_live_receiver(rx);
+        live.active = true;
+        live.session_id = 1;
+        live.source_has_audio = true;
+        live.session_output_start_seconds = Some(0.0);
+        live.session_source_start_seconds = Some(0.0);
+        live.video_pts = 25;
+        live.last_video_output_pts = Some(24);
+        live.last_video_frame = Some(frame::Video::empty());
+        let mut output = CountingOutput::default();
+
+        tx.send(LiveEvent::Retimed(2, 2.0)).unwrap();
+        tx.send(LiveEvent::Retimed(1, DELAY_REBUILD_STRETCH))
+            .unwrap();
+        for pts in [100, 125] {
+            let mut frame = frame::Video::empty();
+            frame.set_pts(Some(pts));
+            tx.send(LiveEvent::Video(1, frame)).unwrap();
+        }
+        LiveOverrideOutput::new(&mut output, &mut live)
+            .pump_live()
+            .unwrap();
+
+        assert_eq!(live.stretch, DELAY_REBUILD_STRETCH);
+        // Anchored again on its next picture, a second of the feed fills
+        // 1.04 seconds of the program.
+        assert!(
+            live.last_video_output_pts
+                .is_some_and(|pts| (51..=52).contains(&pts)),
+            "{:?}",
+            live.last_video_output_pts
+        );
+    }
+
+    /// Plays a test clip as a live feed behind `delay`, and counts the
+    /// video and audio frames that come out.
+    fn play_delayed(delay: Duration) -> (usize, usize) {
+        ffmpeg_next::init().ok();
+        let cfg = OutputConfig::new(320, 240, 25, 48_000);
+        let live_delay = LiveDelay::default();
+        live_delay.set_delay(delay);
+        let (tx, _rx) = mpsc::sync_channel(4);
+        let feed = LiveFeedDelay {
+            delay: live_delay,
+            tx,
+            session_id: 1,
+            on_air: Arc::new(AtomicBool::new(false)),
+            abort: Arc::new(AtomicBool::new(false)),
+            listener_abort: Arc::new(AtomicBool::new(false)),
+            last_frame_ms: Arc::new(AtomicU64::new(0)),
+            frame_seen: Arc::new(AtomicBool::new(false)),
+        };
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../tests/assets/storage/media_mix/short_video.mp4");
+        let ictx = open_media_input(&path.to_string_lossy()).unwrap();
+        let mut timeline = Timeline::new();
+        let mut output = CountingOutput::default();
+
+        play_opened_input(
+            "delayed feed",
+            ictx,
+            &cfg,
+            &mut timeline,
+            &mut output,
+            InputPlaybackOptions {
+                seek_seconds: None,
+                duration_seconds: None,
+                subtitles_media_path: None,
+                logo_fade_plan: LogoFadePlan::none(0, &cfg),
+                image: None,
+                branding: None,
+                events: None,
+                playback_control: &PlaybackControl::default(),
+                recording: None,
+                packets: None,
+                delay: Some(&feed),
+            },
+        )
+        .unwrap();
+
+        (output.video_frames, output.audio_frames)
+    }
+
+    #[test]
+    fn delayed_feed_plays_out_in_full() {
+        let direct = play_delayed(Duration::ZERO);
+        let started = Instant::now();
+        let delayed = play_delayed(Duration::from_millis(500));
+
+        assert!(started.elapsed() >= Duration::from_millis(500));
+        assert!(direct.0 > 0);
+        assert_eq!(delayed, direct);
+    }
+
+    #[test]
+    fn live_gap_shows_slate_before_any_live_frame() {
+        ffmpeg_next::init().ok();
+        let (_tx, rx) = mpsc::sync_channel(1);
+        let mut live = test_live_receiver(rx);
+        live.slate_cfg = Some(OutputConfig::new(64, 36, 25, 48_000).with_slate(Some(
+            SlateConfig {
+                tone: true,
+                ..SlateConfig::default()
+            },
+        )));
+        let mut output = CountingOutput::default();
+
+        LiveOverrideOutput::new(&mut output, &mut live)
+            .fill_live_gap(Duration::from_millis(200))
+            .unwrap();
+
+        assert_eq!(output.video_frames, 5);
+        assert_eq!(live.video_pts, 5);
+        assert_eq!(live.audio_pts, 9_600);
+        assert!(live.slate.is_some());
+    }
+
+    #[test]
+    fn short_live_gap_holds_the_last_frame() {
+        ffmpeg_next::init().ok();
+        let (_tx, rx) = mpsc::sync_channel(1);
+        let mut live = test_live_receiver(rx);
+        live.slate_cfg =
+            Some(OutputConfig::new(64, 36, 25, 48_000).with_slate(Some(SlateConfig::default())));
+        live.last_video_frame = Some(frame::Video::new(Pixel::YUV420P, 64, 36));
+        let mut output = CountingOutput::default();
+
+        LiveOverrideOutput::new(&mut output, &mut live)
+            .fill_live_gap(Duration::from_millis(80))
+            .unwrap();
+
+        assert_eq!(output.video_frames, 2);
+        assert_eq!(live.audio_pts, 3_840);
+        assert!(live.slate.is_none());
+
+        LiveOverrideOutput::new(&mut output, &mut live)
+            .fill_live_gap(LIVE_GAP_SLATE_AFTER)
+            .unwrap();
+        assert!(live.slate.is_some());
+    }
+
     #[test]
     fn live_frame_sender_stops_waiting_when_aborted() {
         let (tx, _rx) = mpsc::sync_channel(1);
         tx.try_send(LiveEvent::Started {
             session_id: 1,
             has_audio: true,
+            publisher: None,
         })
         .unwrap();
         let abort = Arc::new(AtomicBool::new(true));
@@ -1269,6 +2084,15 @@ mod tests {
             frame_seen: Arc::new(AtomicBool::new(false)),
             abort,
             listener_abort: Arc::new(AtomicBool::new(false)),
+            pip: PictureInPicture::default(),
+            in_pip: false,
+            take: LiveTake::default(),
+            held: false,
+            failover: None,
+            on_air: Arc::new(AtomicBool::new(false)),
+            released: ReleasedSession::default(),
+            signal: SignalMonitor::default().meter(0),
+            signal_held: false,
         };
 
         assert!(
@@ -1289,6 +2113,15 @@ mod tests {
             frame_seen: Arc::new(AtomicBool::new(false)),
             abort: Arc::new(AtomicBool::new(false)),
             listener_abort: Arc::new(AtomicBool::new(false)),
+            pip: PictureInPicture::default(),
+            in_pip: false,
+            take: LiveTake::default(),
+            held: false,
+            failover: None,
+            on_air: Arc::new(AtomicBool::new(false)),
+            released: ReleasedSession::default(),
+            signal: SignalMonitor::default().meter(0),
+            signal_held: false,
         };
 
         assert!(
@@ -1319,4 +2152,225 @@ mod tests {
         assert_eq!(output.video_frames, 0);
         assert_eq!(output.audio_frames, 0);
     }
+
+    #[test]
+    fn failover_start_keeps_the_program_live() {
+        ffmpeg_next::init().ok();
+        let (tx, rx) = mpsc::sync_channel(live_channel_capacity(25));
+        tx.send(LiveEvent::Started {
+            session_id: 2,
+            has_audio: false,
+            publisher: None,
+        })
+        .unwrap();
+        tx.send(LiveEvent::Video(2, frame::Video::empty())).unwrap();
+        let mut live = test_live_receiver(rx);
+        live.session_id = 1;
+        live.active = true;
+        live.session_output_start_seconds = Some(0.0);
+        live.session_source_start_seconds = Some(0.0);
+        live.last_media_at = Some(Instant::now());
+        let mut output = CountingOutput::default();
+
+        let received_event = LiveOverrideOutput::new(&mut output, &mut live)
+            .pump_live()
+            .unwrap();
+
+        assert!(received_event);
+        assert!(live.active);
+        assert!(!live.returned_to_file);
+        assert_eq!(live.session_id, 2);
+        assert!(live.session_source_start_seconds.is_some());
+        assert_eq!(output.video_frames, 1);
+    }
+
+    #[test]
+    fn block_end_releases_the_live_session() {
+        ffmpeg_next::init().ok();
+        let (tx, rx) = mpsc::sync_channel(live_channel_capacity(25));
+        let mut live = test_live_receiver(rx);
+        live.session_id = 1;
+        live.active = true;
+        live.last_media_at = Some(Instant::now());
+        live.start_block(Duration::ZERO, false);
+        let mut output = CountingOutput::default();
+
+        let error = LiveOverrideOutput::new(&mut output, &mut live)
+            .wait_for_file_playback()
+            .unwrap_err();
+
+        assert!(error.downcast_ref::<LiveEnded>().is_some());
+        assert!(!live.active);
+        assert_eq!(live.released_session.get(), Some(1));
+
+        // The publisher keeps sending, but stays off air after its block.
+        tx.send(LiveEvent::Video(1, frame::Video::empty())).unwrap();
+        LiveOverrideOutput::new(&mut output, &mut live)
+            .pump_live()
+            .unwrap();
+        assert!(!live.active);
+    }
+
+    #[test]
+    fn live_frame_sender_moves_frames_to_the_pip_window() {
+        let (tx, rx) = mpsc::sync_channel(4);
+        let pip = PictureInPicture::default();
+        pip.show(PictureInPictureConfig {
+            source: PipSource::Ingest,
+            layout: PipLayout::Window,
+            rect: PipRect::FULL,
+            transition_seconds: 0.0,
+            easing: MotionEasing::Linear,
+        })
+        .unwrap();
+        let mut sender = LiveFrameSender {
+            tx,
+            session_id: 1,
+            last_frame_ms: Arc::new(AtomicU64::new(0)),
+            frame_seen: Arc::new(AtomicBool::new(false)),
+            abort: Arc::new(AtomicBool::new(false)),
+            listener_abort: Arc::new(AtomicBool::new(false)),
+            pip: pip.clone(),
+            in_pip: false,
+            take: LiveTake::default(),
+            held: false,
+            failover: None,
+            on_air: Arc::new(AtomicBool::new(false)),
+            released: ReleasedSession::default(),
+            signal: SignalMonitor::default().meter(0),
+            signal_held: false,
+        };
+
+        let picture = frame::Video::new(Pixel::YUV420P, 4, 4);
+
+        assert!(!sender.composites_overlays());
+        sender.encode_video(&picture).unwrap();
+        sender.encode_audio(&frame::Audio::empty()).unwrap();
+        sender.encode_video(&picture).unwrap();
+        assert!(matches!(rx.try_recv(), Ok(LiveEvent::Ended(1))));
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+    }
+
+    #[test]
+    fn live_frame_sender_holds_the_feed_until_it_is_taken() {
+        let (tx, rx) = mpsc::sync_channel(4);
+        let take = LiveTake::default();
+        take.set_manual(true);
+        take.feed_started();
+        let mut sender = LiveFrameSender {
+            tx,
+            session_id: 1,
+            last_frame_ms: Arc::new(AtomicU64::new(0)),
+            frame_seen: Arc::new(AtomicBool::new(false)),
+            abort: Arc::new(AtomicBool::new(false)),
+            listener_abort: Arc::new(AtomicBool::new(false)),
+            pip: PictureInPicture::default(),
+            in_pip: false,
+            take: take.clone(),
+            held: false,
+            failover: None,
+            on_air: Arc::new(AtomicBool::new(false)),
+            released: ReleasedSession::default(),
+            signal: SignalMonitor::default().meter(0),
+            signal_held: false,
+        };
+
+        let picture = frame::Video::new(Pixel::YUV420P, 4, 4);
+
+        sender.encode_video(&picture).unwrap();
+        sender.encode_audio(&frame::Audio::empty()).unwrap();
+        assert!(matches!(rx.try_recv(), Ok(LiveEvent::Ended(1))));
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+        assert!(!sender.on_air.load(Ordering::Relaxed));
+
+        take.take().unwrap();
+        sender.encode_video(&picture).unwrap();
+        assert!(matches!(rx.try_recv(), Ok(LiveEvent::Video(1, _))));
+        // Only what goes on air is recorded.
+        assert!(sender.on_air.load(Ordering::Relaxed));
+
+        assert!(take.release());
+        sender.encode_video(&picture).unwrap();
+        assert!(matches!(rx.try_recv(), Ok(LiveEvent::Ended(1))));
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+        assert!(!sender.on_air.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn released_session_is_not_recorded() {
+        let (tx, rx) = mpsc::sync_channel(4);
+        let released = ReleasedSession::default();
+        let mut sender = LiveFrameSender {
+            tx,
+            session_id: 1,
+            last_frame_ms: Arc::new(AtomicU64::new(0)),
+            frame_seen: Arc::new(AtomicBool::new(false)),
+            abort: Arc::new(AtomicBool::new(false)),
+            listener_abort: Arc::new(AtomicBool::new(false)),
+            pip: PictureInPicture::default(),
+            in_pip: false,
+            take: LiveTake::default(),
+            held: false,
+            failover: None,
+            on_air: Arc::new(AtomicBool::new(false)),
+            released: released.clone(),
+            signal: SignalMonitor::default().meter(0),
+            signal_held: false,
+        };
+
+        let picture = frame::Video::new(Pixel::YUV420P, 4, 4);
+
+        sender.encode_video(&picture).unwrap();
+        assert!(sender.on_air.load(Ordering::Relaxed));
+
+        // The block ended; the receiver drops the frames from now on.
+        released.set(Some(1));
+        sender.encode_video(&picture).unwrap();
+        assert!(!sender.on_air.load(Ordering::Relaxed));
+
+        // The next block takes the session again.
+        released.set(None);
+        sender.encode_video(&picture).unwrap();
+        assert!(sender.on_air.load(Ordering::Relaxed));
+        assert_eq!(rx.try_iter().count(), 3);
+    }
+
+    #[test]
+    fn failed_pulls_back_off() {
+        ffmpeg_next::init().ok();
+        // Stand-in for the remote: it takes each connection and drops it
+        // right away, so every pull attempt fails.
+        let remote = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("tcp://{}", remote.local_addr().unwrap());
+        let (accepted_tx, accepted_rx) = mpsc::channel();
+        thread::spawn(move || {
+            for connection in remote.incoming() {
+                if connection.is_err() || accepted_tx.send(Instant::now()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let receiver = spawn_live_pull(
+            &url,
+            OutputConfig::new(320, 240, 25, 48_000),
+            PullControl::new(true),
+        )
+        .unwrap();
+        let attempts = (0..3)
+            .map(|_| accepted_rx.recv_timeout(Duration::from_secs(10)).unwrap())
+            .collect::<Vec<_>>();
+        drop(receiver);
+
+        let first = attempts[1] - attempts[0];
+        let second = attempts[2] - attempts[1];
+        assert!(
+            first >= LIVE_RETRY_DELAY && first < LIVE_RETRY_DELAY * 2,
+            "first retry after {first:?}"
+        );
+        assert!(
+            second >= LIVE_RETRY_DELAY * 2 && second < LIVE_RETRY_DELAY * 4,
+            "second retry after {second:?}"
+        );
+    }
 }
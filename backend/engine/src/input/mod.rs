@@ -1 +1,9 @@
+pub(crate) mod delay;
+pub(crate) mod failover;
+pub(crate) mod generator;
 pub mod live;
+pub(crate) mod publish;
+pub(crate) mod pull;
+pub(crate) mod record;
+pub(crate) mod srt;
+pub(crate) mod take;
@@ -0,0 +1,47 @@
+use std::{fmt, sync::Arc};
+
+/// A caller that connected to an ingest listener and wants to publish.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Publisher {
+    /// `rtmp` or `srt`.
+    pub protocol: &'static str,
+    /// Listen address without secrets.
+    pub listener: String,
+    /// RTMP stream key or SRT stream id resource the caller sent.
+    pub key: Option<String>,
+    /// Key of the ingest URL, the only one listeners take without an
+    /// authorizer.
+    pub expected_key: Option<String>,
+}
+
+type Authorize = dyn Fn(&Publisher) -> Result<Option<String>, String> + Send + Sync;
+
+/// Decides whether a publisher may go on, instead of the listener's own
+/// comparison with the key of the ingest URL. `Ok` can carry a name logged
+/// for the publisher, like the label of its stream key; `Err` the reason it
+/// was turned away.
+#[derive(Clone)]
+pub struct PublishAuthorizer(Arc<Authorize>);
+
+impl PublishAuthorizer {
+    pub fn new(
+        authorize: impl Fn(&Publisher) -> Result<Option<String>, String> + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(authorize))
+    }
+
+    pub(crate) fn authorize(&self, publisher: &Publisher) -> anyhow::Result<Option<String>> {
+        (self.0)(publisher).map_err(|reason| {
+            anyhow::anyhow!(
+                "{} publisher rejected: {reason}",
+                publisher.protocol.to_uppercase()
+            )
+        })
+    }
+}
+
+impl fmt::Debug for PublishAuthorizer {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("PublishAuthorizer(..)")
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context, Result, anyhow};
+use ffmpeg_next::{Dictionary, format};
+
+use crate::utils::logging;
+
+const PULL_SCHEMES: &[&str] = &[
+    "rtmp://", "rtmps://", "srt://", "http://", "https://", "udp://", "rtp://", "tcp://",
+];
+/// A remote that accepts the connection but sends nothing fails after this.
+const PULL_READ_TIMEOUT: Duration = Duration::from_secs(5);
+const PULL_ARM_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Whether the engine can pull a live ingest from `url`.
+pub fn is_pull_url(url: &str) -> bool {
+    PULL_SCHEMES.iter().any(|scheme| {
+        url.get(..scheme.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme))
+    })
+}
+
+/// Arms and disarms a pull ingest. A disarmed pull drops its connection and
+/// waits until it is armed again.
+#[derive(Debug, Clone)]
+pub struct PullControl {
+    armed: Arc<AtomicBool>,
+}
+
+impl PullControl {
+    pub fn new(armed: bool) -> Self {
+        Self {
+            armed: Arc::new(AtomicBool::new(armed)),
+        }
+    }
+
+    pub fn set_armed(&self, armed: bool) {
+        self.armed.store(armed, Ordering::Relaxed);
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.armed.load(Ordering::Relaxed)
+    }
+}
+
+/// Live input the engine connects to, like a partner's RTMP server, an SRT
+/// caller, an HLS playlist or a UDP multicast.
+pub(crate) struct PullSource {
+    url: String,
+    control: PullControl,
+}
+
+impl PullSource {
+    pub(crate) fn new(url: &str, control: PullControl) -> Result<Self> {
+        if !is_pull_url(url) {
+            return Err(anyhow!(
+                "pull ingest needs an rtmp, rtmps, srt, http(s), udp, rtp or tcp URL"
+            ));
+        }
+
+        Ok(Self {
+            url: url.to_string(),
+            control,
+        })
+    }
+
+    /// URL without its query, which may carry tokens or a passphrase.
+    pub(crate) fn label(&self) -> &str {
+        self.url
+            .split_once('?')
+            .map_or(self.url.as_str(), |(address, _)| address)
+    }
+
+    pub(crate) fn is_disarmed(&self) -> bool {
+        !self.control.is_armed()
+    }
+
+    /// Waits until the pull is armed and connects to the remote.
+    pub(crate) fn open(
+        &self,
+        abort: Arc<AtomicBool>,
+        listener_abort: Arc<AtomicBool>,
+    ) -> Result<format::context::Input> {
+        while self.is_disarmed() {
+            if listener_abort.load(Ordering::Relaxed) {
+                return Err(anyhow!("pull ingest stopped"));
+            }
+            thread::sleep(PULL_ARM_POLL_INTERVAL);
+        }
+
+        let mut options = Dictionary::new();
+        options.set("rw_timeout", &PULL_READ_TIMEOUT.as_micros().to_string());
+        let control = self.control.clone();
+
+        format::input_with_interrupt_and_dictionary(
+            &self.url,
+            move || {
+                let interrupted = abort.load(Ordering::Relaxed)
+                    || listener_abort.load(Ordering::Relaxed)
+                    || !control.is_armed();
+                if interrupted {
+                    logging::mark_ingest_interrupted();
+                }
+                interrupted
+            },
+            options,
+        )
+        .with_context(|| format!("failed to pull live input from {}", self.label()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PullControl, PullSource};
+
+    #[test]
+    fn pull_sources_need_a_stream_url() {
+        let control = PullControl::new(false);
+        let source = PullSource::new(
+            "srt://partner.example:9000?passphrase=secret",
+            control.clone(),
+        )
+        .unwrap();
+
+        assert_eq!(source.label(), "srt://partner.example:9000");
+        assert!(source.is_disarmed());
+        control.set_armed(true);
+        assert!(!source.is_disarmed());
+
+        assert!(PullSource::new("https://partner.example/live.m3u8", control.clone()).is_ok());
+        assert!(PullSource::new("udp://239.0.0.1:1234", control.clone()).is_ok());
+        assert!(PullSource::new("/media/clip.mp4", control.clone()).is_err());
+        assert!(PullSource::new("generator://black", control).is_err());
+    }
+}
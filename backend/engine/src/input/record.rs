@@ -0,0 +1,442 @@
+use std::{
+    fmt,
+    path::PathBuf,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use ffmpeg_next::{Dictionary, Packet, Rational, codec, format, media};
+use log::{error, info, warn};
+
+use crate::input::pull::{PullControl, PullSource};
+
+const STREAM_RETRY_DELAY: Duration = Duration::from_secs(1);
+const STREAM_MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+const STREAM_STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Names the file of the next recording; `None` skips it.
+#[derive(Clone)]
+pub struct RecordingPath(Arc<dyn Fn() -> Option<PathBuf> + Send + Sync>);
+
+impl RecordingPath {
+    pub fn new(path: impl Fn() -> Option<PathBuf> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(path))
+    }
+}
+
+impl fmt::Debug for RecordingPath {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("RecordingPath(..)")
+    }
+}
+
+/// Called with the file of a finished recording.
+#[derive(Clone)]
+pub struct RecordingCallback(Arc<dyn Fn(PathBuf) + Send + Sync>);
+
+impl RecordingCallback {
+    pub fn new(callback: impl Fn(PathBuf) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+}
+
+impl fmt::Debug for RecordingCallback {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("RecordingCallback(..)")
+    }
+}
+
+/// Records the live input while it is on air. The packets are copied as
+/// they come in, into a Matroska file per stretch on air.
+#[derive(Clone, Default)]
+pub struct LiveRecorder {
+    inner: Arc<Mutex<RecorderInner>>,
+}
+
+impl fmt::Debug for LiveRecorder {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("LiveRecorder(..)")
+    }
+}
+
+#[derive(Default)]
+struct RecorderInner {
+    path: Option<RecordingPath>,
+    callback: Option<RecordingCallback>,
+}
+
+impl LiveRecorder {
+    /// Records with `path` naming the files; `None` stops recording with
+    /// the next stretch on air.
+    pub fn set_path(&self, path: Option<RecordingPath>) {
+        self.lock().path = path;
+    }
+
+    pub fn set_callback(&self, callback: RecordingCallback) {
+        self.lock().callback = Some(callback);
+    }
+
+    /// Recorder for one connection, `None` without a path.
+    pub(crate) fn session(
+        &self,
+        ictx: &format::context::Input,
+        on_air: Arc<AtomicBool>,
+    ) -> Option<SessionRecorder> {
+        self.lock().path.as_ref()?;
+        let video = ictx.streams().best(media::Type::Video);
+        let audio = ictx.streams().best(media::Type::Audio);
+        let streams = [video.as_ref(), audio.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(|stream| RecordedStream {
+                index: stream.index(),
+                parameters: stream.parameters().clone(),
+                time_base: stream.time_base(),
+            })
+            .collect::<Vec<_>>();
+        if streams.is_empty() {
+            return None;
+        }
+
+        Some(SessionRecorder {
+            recorder: self.clone(),
+            on_air,
+            video_index: video.map(|stream| stream.index()),
+            streams,
+            file: None,
+            failed: false,
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RecorderInner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Records the remote stream at `url` until `stop` is set, without putting
+/// it on air. A dropped connection, or one without audio or video, is tried
+/// again, and every connection gets its own file from `path`, which goes to
+/// `callback` when it ends.
+pub fn record_stream(
+    url: &str,
+    path: RecordingPath,
+    callback: RecordingCallback,
+    stop: Arc<AtomicBool>,
+) -> Result<()> {
+    let source = PullSource::new(url, PullControl::new(true))?;
+    let recorder = LiveRecorder::default();
+    recorder.set_path(Some(path));
+    recorder.set_callback(callback);
+    let on_air = Arc::new(AtomicBool::new(true));
+    let mut retry_delay = STREAM_RETRY_DELAY;
+
+    while !stop.load(Ordering::Relaxed) {
+        match source.open(Arc::new(AtomicBool::new(false)), Arc::clone(&stop)) {
+            Ok(mut ictx) => match recorder.session(&ictx, Arc::clone(&on_air)) {
+                Some(mut session) => {
+                    retry_delay = STREAM_RETRY_DELAY;
+                    info!("recording stream {}", source.label());
+                    for (stream, packet) in ictx.packets() {
+                        if stop.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        session.write(&stream, &packet);
+                    }
+                }
+                // A remote that is still starting up may answer without
+                // streams; it is tried again like a dropped connection.
+                None => warn!("{} has no audio or video yet", source.label()),
+            },
+            Err(error) if !stop.load(Ordering::Relaxed) => warn!("{error:#}"),
+            Err(_) => break,
+        }
+
+        let mut waited = Duration::ZERO;
+        while waited < retry_delay && !stop.load(Ordering::Relaxed) {
+            thread::sleep(STREAM_STOP_POLL_INTERVAL);
+            waited += STREAM_STOP_POLL_INTERVAL;
+        }
+        retry_delay = (retry_delay * 2).min(STREAM_MAX_RETRY_DELAY);
+    }
+
+    Ok(())
+}
+
+struct RecordedStream {
+    index: usize,
+    parameters: codec::Parameters,
+    time_base: Rational,
+}
+
+struct RecordingFile {
+    path: PathBuf,
+    octx: format::context::Output,
+    /// Output stream and input time base, by input stream index.
+    outputs: Vec<(usize, usize, Rational)>,
+}
+
+/// Copies the packets of one live connection while it is on air. A file
+/// starts on a key frame and ends when the input goes off air or ends.
+pub(crate) struct SessionRecorder {
+    recorder: LiveRecorder,
+    on_air: Arc<AtomicBool>,
+    video_index: Option<usize>,
+    streams: Vec<RecordedStream>,
+    file: Option<RecordingFile>,
+    /// A broken recording is not retried for the rest of the connection.
+    failed: bool,
+}
+
+impl SessionRecorder {
+    pub(crate) fn write(&mut self, stream: &format::stream::Stream, packet: &Packet) {
+        if self.failed {
+            return;
+        }
+        if !self.on_air.load(Ordering::Relaxed) {
+            self.finish();
+            return;
+        }
+
+        if self.file.is_none() {
+            let starts = match self.video_index {
+                Some(index) => stream.index() == index && packet.is_key(),
+                None => true,
+            };
+            if !starts {
+                return;
+            }
+            match self.open() {
+                Ok(file) => self.file = file,
+                Err(error) => {
+                    error!("live recording failed: {error:#}");
+                    self.failed = true;
+                    return;
+                }
+            }
+        }
+
+        if let Some(file) = self.file.as_mut()
+            && let Err(error) = file.write(stream.index(), packet)
+        {
+            error!(
+                "live recording of {} failed: {error:#}",
+                file.path.display()
+            );
+            self.failed = true;
+            self.finish();
+        }
+    }
+
+    fn open(&self) -> Result<Option<RecordingFile>> {
+        let path = self.recorder.lock().path.clone();
+        let Some(path) = path.and_then(|path| (path.0)()) else {
+            return Ok(None);
+        };
+
+        let mut octx = format::output_as(&path, "matroska")
+            .with_context(|| format!("failed to create {}", path.display()))?;
+        let mut outputs = Vec::with_capacity(self.streams.len());
+        for input in &self.streams {
+            let mut parameters = input.parameters.clone();
+            // The tag of the input container may not fit Matroska.
+            unsafe {
+                (*parameters.as_mut_ptr()).codec_tag = 0;
+            }
+            let mut stream = octx.add_stream(parameters.id())?;
+            stream.set_parameters(parameters);
+            outputs.push((input.index, stream.index(), input.time_base));
+        }
+        let mut options = Dictionary::new();
+        options.set("avoid_negative_ts", "make_zero");
+        octx.write_header_with(options)
+            .with_context(|| format!("failed to start {}", path.display()))?;
+        info!("recording live input to {}", path.display());
+
+        Ok(Some(RecordingFile {
+            path,
+            octx,
+            outputs,
+        }))
+    }
+
+    /// Closes the file and hands it on.
+    fn finish(&mut self) {
+        let Some(mut file) = self.file.take() else {
+            return;
+        };
+        match file.octx.write_trailer() {
+            Ok(()) => info!("live recording {} finished", file.path.display()),
+            Err(error) => warn!(
+                "live recording {} is not finished cleanly: {error}",
+                file.path.display()
+            ),
+        }
+
+        let callback = self.recorder.lock().callback.clone();
+        if let Some(callback) = callback {
+            (callback.0)(file.path);
+        }
+    }
+}
+
+impl Drop for SessionRecorder {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+impl RecordingFile {
+    fn write(&mut self, input_index: usize, packet: &Packet) -> Result<()> {
+        let Some(&(_, output_index, input_time_base)) = self
+            .outputs
+            .iter()
+            .find(|(index, _, _)| *index == input_index)
+        else {
+            return Ok(());
+        };
+        let output_time_base = self
+            .octx
+            .stream(output_index)
+            .context("recording stream is missing")?
+            .time_base();
+
+        let mut packet = packet.clone();
+        packet.rescale_ts(input_time_base, output_time_base);
+        packet.set_position(-1);
+        packet.set_stream(output_index);
+        packet.write_interleaved(&mut self.octx)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::Write,
+        net::TcpListener,
+        path::Path,
+        sync::{atomic::AtomicUsize, mpsc},
+        time::Instant,
+    };
+
+    use super::*;
+
+    fn video_packets(path: &Path) -> usize {
+        let mut ictx = format::input(path).unwrap();
+        let video = ictx.streams().best(media::Type::Video).unwrap().index();
+        ictx.packets()
+            .filter(|(stream, _)| stream.index() == video)
+            .count()
+    }
+
+    #[test]
+    fn session_records_every_stretch_on_air() {
+        ffmpeg_next::init().ok();
+        let dir = std::env::temp_dir().join(format!("live_recording_test_{}", std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        let source = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../tests/assets/storage/media_mix/short_video.mp4");
+
+        let recorder = LiveRecorder::default();
+        let files = AtomicUsize::new(0);
+        recorder.set_path(Some(RecordingPath::new({
+            let dir = dir.clone();
+            move || Some(dir.join(format!("{}.mkv", files.fetch_add(1, Ordering::Relaxed))))
+        })));
+        let finished = Arc::new(Mutex::new(Vec::new()));
+        recorder.set_callback(RecordingCallback::new({
+            let finished = Arc::clone(&finished);
+            move |path| finished.lock().unwrap().push(path)
+        }));
+
+        let mut ictx = format::input(&source).unwrap();
+        let on_air = Arc::new(AtomicBool::new(true));
+        let mut session = recorder.session(&ictx, Arc::clone(&on_air)).unwrap();
+        let video = ictx.streams().best(media::Type::Video).unwrap().index();
+        let packets = ictx.packets().collect::<Vec<_>>();
+        let key_frames = packets
+            .iter()
+            .enumerate()
+            .filter(|(_, (stream, packet))| stream.index() == video && packet.is_key())
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        assert!(key_frames.len() > 2, "the fixture needs three key frames");
+
+        // Off air from the second key frame to the third one.
+        let off_air = key_frames[1]..key_frames[2];
+        for (index, (stream, packet)) in packets.iter().enumerate() {
+            on_air.store(!off_air.contains(&index), Ordering::Relaxed);
+            session.write(stream, packet);
+            if index == off_air.start {
+                assert_eq!(finished.lock().unwrap().len(), 1);
+            }
+        }
+        assert_eq!(finished.lock().unwrap().len(), 1);
+        drop(session);
+
+        let finished = finished.lock().unwrap().clone();
+        assert_eq!(finished, [dir.join("0.mkv"), dir.join("1.mkv")]);
+        let before = packets[..off_air.start]
+            .iter()
+            .filter(|(stream, _)| stream.index() == video)
+            .count();
+        let after = packets[off_air.end..]
+            .iter()
+            .filter(|(stream, _)| stream.index() == video)
+            .count();
+        assert_eq!(video_packets(&finished[0]), before);
+        assert_eq!(video_packets(&finished[1]), after);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn stream_without_media_is_tried_again() {
+        ffmpeg_next::init().ok();
+        // Stand-in for a remote that answers with subtitles only, like a
+        // server whose program has not started yet.
+        let remote = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("tcp://{}", remote.local_addr().unwrap());
+        let (accepted_tx, accepted_rx) = mpsc::channel();
+        thread::spawn(move || {
+            for connection in remote.incoming() {
+                let Ok(mut connection) = connection else {
+                    break;
+                };
+                connection
+                    .write_all(b"1\n00:00:00,000 --> 00:00:01,000\nSoon\n\n")
+                    .ok();
+                if accepted_tx.send(Instant::now()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let recording = thread::spawn({
+            let stop = Arc::clone(&stop);
+            move || {
+                record_stream(
+                    &url,
+                    RecordingPath::new(|| None),
+                    RecordingCallback::new(|_| {}),
+                    stop,
+                )
+            }
+        });
+        let attempts = (0..2)
+            .map(|_| accepted_rx.recv_timeout(Duration::from_secs(10)).unwrap())
+            .collect::<Vec<_>>();
+        stop.store(true, Ordering::Relaxed);
+
+        assert!(attempts[1] - attempts[0] >= STREAM_RETRY_DELAY);
+        assert!(recording.join().unwrap().is_ok());
+    }
+}
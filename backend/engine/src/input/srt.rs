@@ -0,0 +1,228 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use anyhow::{Context, Result, anyhow};
+use ffmpeg_next::{Dictionary, format};
+
+use crate::{
+    input::publish::{PublishAuthorizer, Publisher},
+    utils::logging,
+};
+
+const SCHEME: &str = "srt://";
+/// Passphrase length SRT accepts for AES encryption.
+const PASSPHRASE_LENGTH: std::ops::RangeInclusive<usize> = 10..=79;
+const MAX_STREAM_ID_LENGTH: usize = 512;
+const MAX_LATENCY_MS: u32 = 60_000;
+
+/// SRT listener ingest, `srt://<host>:<port>?passphrase=..&latency=..&streamid=..`.
+///
+/// `latency` is in milliseconds. With `streamid`, callers that send another
+/// stream id are turned away, so each channel only takes its own feed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrtListener {
+    address: String,
+    passphrase: Option<String>,
+    latency_ms: Option<u32>,
+    stream_id: Option<String>,
+}
+
+/// Whether `url` addresses an SRT ingest.
+pub fn is_srt_url(url: &str) -> bool {
+    url.get(..SCHEME.len())
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case(SCHEME))
+}
+
+impl SrtListener {
+    pub fn parse(url: &str) -> Result<Self> {
+        if !is_srt_url(url) {
+            return Err(anyhow!("{url} is not an SRT address"));
+        }
+
+        let rest = &url[SCHEME.len()..];
+        let (authority, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let authority = authority.trim_end_matches('/');
+        if authority.is_empty() || authority.contains('/') {
+            return Err(anyhow!("SRT ingest needs srt://<host>:<port>"));
+        }
+
+        let mut listener = Self {
+            address: format!("{SCHEME}{authority}"),
+            passphrase: None,
+            latency_ms: None,
+            stream_id: None,
+        };
+        for (key, value) in query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+        {
+            match key {
+                "passphrase" => {
+                    if !PASSPHRASE_LENGTH.contains(&value.len()) {
+                        return Err(anyhow!(
+                            "SRT passphrase must have {} to {} characters",
+                            PASSPHRASE_LENGTH.start(),
+                            PASSPHRASE_LENGTH.end()
+                        ));
+                    }
+                    listener.passphrase = Some(value.to_string());
+                }
+                "latency" => {
+                    let latency = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|latency| *latency <= MAX_LATENCY_MS)
+                        .ok_or_else(|| {
+                            anyhow!("SRT latency must be 0 to {MAX_LATENCY_MS} milliseconds")
+                        })?;
+                    listener.latency_ms = Some(latency);
+                }
+                "streamid" => {
+                    if value.is_empty() || value.len() > MAX_STREAM_ID_LENGTH {
+                        return Err(anyhow!(
+                            "SRT stream id must have 1 to {MAX_STREAM_ID_LENGTH} characters"
+                        ));
+                    }
+                    listener.stream_id = Some(value.to_string());
+                }
+                "mode" if value == "listener" => {}
+                other => return Err(anyhow!("unknown SRT ingest option '{other}'")),
+            }
+        }
+
+        Ok(listener)
+    }
+
+    /// Address without the passphrase, for logs and input labels.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Waits for the next caller. FFmpeg's listener takes any caller, so the
+    /// stream id is checked after the connection is up, by the authorizer
+    /// when there is one.
+    pub(crate) fn open(
+        &self,
+        authorizer: Option<&PublishAuthorizer>,
+        abort: Arc<AtomicBool>,
+        listener_abort: Arc<AtomicBool>,
+    ) -> Result<(format::context::Input, Option<String>)> {
+        let mut options = Dictionary::new();
+        options.set("mode", "listener");
+        if let Some(passphrase) = &self.passphrase {
+            options.set("passphrase", passphrase);
+        }
+        if let Some(latency_ms) = self.latency_ms {
+            // FFmpeg takes SRT latency in microseconds.
+            options.set("latency", &(u64::from(latency_ms) * 1000).to_string());
+        }
+        logging::clear_accepted_srt_stream_id();
+
+        let input = format::input_with_interrupt_and_dictionary(
+            &self.address,
+            move || {
+                let interrupted =
+                    abort.load(Ordering::Relaxed) || listener_abort.load(Ordering::Relaxed);
+                if interrupted {
+                    logging::mark_ingest_interrupted();
+                }
+                interrupted
+            },
+            options,
+        )
+        .with_context(|| format!("failed to listen for SRT input at {}", self.address))?;
+
+        if let Some(authorizer) = authorizer {
+            let key = logging::take_accepted_srt_stream_id()
+                .map(|stream_id| stream_resource(&stream_id).to_string());
+            let publisher = authorizer.authorize(&Publisher {
+                protocol: "srt",
+                listener: self.address.clone(),
+                key,
+                expected_key: self
+                    .stream_id
+                    .as_deref()
+                    .map(|stream_id| stream_resource(stream_id).to_string()),
+            })?;
+
+            return Ok((input, publisher));
+        }
+
+        if let Some(expected) = &self.stream_id {
+            match logging::take_accepted_srt_stream_id() {
+                Some(actual) if stream_id_matches(&actual, expected) => {}
+                Some(actual) => {
+                    anyhow::bail!(
+                        "incoming SRT stream id {actual:?} does not match configured id {expected:?}"
+                    );
+                }
+                // A caller that can't be checked is not taken.
+                None => anyhow::bail!(
+                    "incoming SRT caller has no readable stream id, configured id is {expected:?}; FFmpeg before 6.1 doesn't report it"
+                ),
+            }
+        }
+
+        Ok((input, None))
+    }
+}
+
+/// Compares stream ids, or only their resource (`r=`) when the caller uses
+/// the `#!::r=<resource>,m=publish` access control syntax.
+fn stream_id_matches(actual: &str, expected: &str) -> bool {
+    stream_resource(actual) == stream_resource(expected)
+}
+
+fn stream_resource(stream_id: &str) -> &str {
+    let Some(fields) = stream_id.strip_prefix("#!::") else {
+        return stream_id;
+    };
+
+    fields
+        .split(',')
+        .find_map(|field| field.strip_prefix("r="))
+        .unwrap_or(stream_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SrtListener, is_srt_url, stream_id_matches};
+
+    #[test]
+    fn parses_listener_options() {
+        let listener = SrtListener::parse(
+            "srt://0.0.0.0:9000?passphrase=reporter-secret&latency=800&streamid=field1",
+        )
+        .unwrap();
+
+        assert_eq!(listener.address(), "srt://0.0.0.0:9000");
+        assert_eq!(listener.passphrase.as_deref(), Some("reporter-secret"));
+        assert_eq!(listener.latency_ms, Some(800));
+        assert_eq!(listener.stream_id.as_deref(), Some("field1"));
+        assert!(is_srt_url("SRT://0.0.0.0:9000"));
+        assert!(!is_srt_url("rtmp://0.0.0.0:1936/live/stream"));
+    }
+
+    #[test]
+    fn rejects_invalid_listener_options() {
+        assert!(SrtListener::parse("srt://0.0.0.0:9000?passphrase=short").is_err());
+        assert!(SrtListener::parse("srt://0.0.0.0:9000?latency=fast").is_err());
+        assert!(SrtListener::parse("srt://0.0.0.0:9000?latency=120000").is_err());
+        assert!(SrtListener::parse("srt://0.0.0.0:9000?streamid=").is_err());
+        assert!(SrtListener::parse("srt://0.0.0.0:9000?mode=caller").is_err());
+        assert!(SrtListener::parse("srt://0.0.0.0:9000/live").is_err());
+        assert!(SrtListener::parse("srt://").is_err());
+    }
+
+    #[test]
+    fn stream_ids_match_plainly_or_by_resource() {
+        assert!(stream_id_matches("field1", "field1"));
+        assert!(stream_id_matches("#!::r=field1,m=publish", "field1"));
+        assert!(stream_id_matches("#!::u=anna,r=field1", "#!::r=field1"));
+        assert!(!stream_id_matches("field2", "field1"));
+        assert!(!stream_id_matches("#!::r=field2,m=publish", "field1"));
+    }
+}
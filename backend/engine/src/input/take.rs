@@ -0,0 +1,254 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result, anyhow};
+use ffmpeg_next::{Packet, Rational, codec, frame, software::scaling, util::format::pixel::Pixel};
+
+use crate::utils::helper::even;
+
+/// The preview picture is refreshed at most this often.
+const PREVIEW_INTERVAL: Duration = Duration::from_secs(1);
+const PREVIEW_WIDTH: u32 = 320;
+
+/// Whether the live ingest goes on air by itself or waits for a take.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiveTakeState {
+    /// A feed goes on air as soon as it arrives.
+    Automatic,
+    /// No feed is connected.
+    Idle,
+    /// A feed is connected and previewed, but off air.
+    Armed,
+    OnAir,
+}
+
+/// Manual take of the live ingest. In manual mode an incoming feed is
+/// received and previewed, but only goes on air with [`LiveTake::take`];
+/// [`LiveTake::release`] returns to the playlist.
+#[derive(Clone, Default)]
+pub struct LiveTake {
+    inner: Arc<Mutex<TakeInner>>,
+}
+
+impl fmt::Debug for LiveTake {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("LiveTake(..)")
+    }
+}
+
+#[derive(Default)]
+struct TakeInner {
+    manual: bool,
+    /// Connected feeds; a failover ingest has one per endpoint.
+    feeds: usize,
+    on_air: bool,
+    /// A live event of the playlist has the feed on air.
+    scheduled: bool,
+    /// Latest feed picture, scaled down to the preview size.
+    preview: Option<frame::Video>,
+    preview_at: Option<Instant>,
+}
+
+impl LiveTake {
+    pub fn set_manual(&self, manual: bool) {
+        let mut inner = self.lock();
+        inner.manual = manual;
+        if !manual {
+            inner.on_air = false;
+        }
+    }
+
+    pub fn state(&self) -> LiveTakeState {
+        let inner = self.lock();
+        if !inner.manual {
+            LiveTakeState::Automatic
+        } else if inner.feeds == 0 {
+            LiveTakeState::Idle
+        } else if inner.on_air || inner.scheduled {
+            LiveTakeState::OnAir
+        } else {
+            LiveTakeState::Armed
+        }
+    }
+
+    /// Puts the connected feed on air.
+    pub fn take(&self) -> Result<()> {
+        let mut inner = self.lock();
+        if !inner.manual {
+            return Err(anyhow!("the live ingest is not in manual mode"));
+        }
+        if inner.feeds == 0 {
+            return Err(anyhow!("no live feed is connected"));
+        }
+        inner.on_air = true;
+        Ok(())
+    }
+
+    /// Takes the feed off air again. Returns `false` when it wasn't on air.
+    pub fn release(&self) -> bool {
+        let mut inner = self.lock();
+        let was_on_air = inner.manual && inner.on_air;
+        inner.on_air = false;
+        was_on_air
+    }
+
+    /// Latest picture of the connected feed as JPEG, `None` without a feed.
+    pub fn preview_jpeg(&self) -> Result<Option<Vec<u8>>> {
+        let Some(picture) = self.lock().preview.clone() else {
+            return Ok(None);
+        };
+
+        encode_jpeg(&picture).map(Some)
+    }
+
+    /// Whether a manual take keeps the feed off air.
+    pub(crate) fn holds_feed(&self) -> bool {
+        let inner = self.lock();
+        inner.manual && !inner.on_air && !inner.scheduled
+    }
+
+    pub(crate) fn set_scheduled(&self, scheduled: bool) {
+        self.lock().scheduled = scheduled;
+    }
+
+    pub(crate) fn feed_started(&self) {
+        self.lock().feeds += 1;
+    }
+
+    /// A new feed waits for its own take, a failover to a backup doesn't.
+    pub(crate) fn feed_ended(&self) {
+        let mut inner = self.lock();
+        inner.feeds = inner.feeds.saturating_sub(1);
+        if inner.feeds > 0 {
+            return;
+        }
+        inner.on_air = false;
+        inner.preview = None;
+        inner.preview_at = None;
+    }
+
+    pub(crate) fn push_preview(&self, picture: &frame::Video) {
+        let mut inner = self.lock();
+        if inner
+            .preview_at
+            .is_some_and(|preview_at| preview_at.elapsed() < PREVIEW_INTERVAL)
+        {
+            return;
+        }
+
+        inner.preview_at = Some(Instant::now());
+        drop(inner);
+        match scale_preview(picture) {
+            Ok(preview) => self.lock().preview = Some(preview),
+            Err(error) => log::debug!("live preview not updated: {error:#}"),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TakeInner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Scales the picture down for the preview. It runs once per interval, so
+/// the scaler isn't kept.
+fn scale_preview(picture: &frame::Video) -> Result<frame::Video> {
+    if picture.width() == 0 || picture.height() == 0 {
+        return Err(anyhow!("live picture is empty"));
+    }
+
+    let (width, height) = preview_size(picture.width(), picture.height());
+    let mut scaler = scaling::Context::get(
+        picture.format(),
+        picture.width(),
+        picture.height(),
+        Pixel::YUVJ420P,
+        width,
+        height,
+        scaling::flag::Flags::BILINEAR,
+    )?;
+    let mut preview = frame::Video::empty();
+    scaler.run(picture, &mut preview)?;
+    Ok(preview)
+}
+
+/// Preview size with the picture's aspect ratio, at most `PREVIEW_WIDTH`
+/// wide.
+fn preview_size(width: u32, height: u32) -> (u32, u32) {
+    if width <= PREVIEW_WIDTH {
+        return (even(width).max(2), even(height).max(2));
+    }
+
+    let height = u64::from(height) * u64::from(PREVIEW_WIDTH) / u64::from(width);
+    (PREVIEW_WIDTH, even(height as u32).max(2))
+}
+
+fn encode_jpeg(picture: &frame::Video) -> Result<Vec<u8>> {
+    let jpeg = codec::encoder::find(codec::Id::MJPEG).context("JPEG encoder not found")?;
+    let mut context = codec::context::Context::new_with_codec(jpeg)
+        .encoder()
+        .video()?;
+    context.set_width(picture.width());
+    context.set_height(picture.height());
+    context.set_format(Pixel::YUVJ420P);
+    context.set_time_base(Rational(1, 1));
+    let mut encoder = context.open_as(jpeg)?;
+
+    let mut picture = picture.clone();
+    picture.set_pts(Some(0));
+    encoder.send_frame(&picture)?;
+    encoder.send_eof()?;
+    let mut packet = Packet::empty();
+    encoder.receive_packet(&mut packet)?;
+
+    Ok(packet.data().unwrap_or_default().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LiveTake, LiveTakeState, preview_size};
+
+    #[test]
+    fn manual_take_waits_for_a_feed_and_the_take() {
+        let take = LiveTake::default();
+        assert_eq!(take.state(), LiveTakeState::Automatic);
+        assert!(!take.holds_feed());
+        assert!(take.take().is_err());
+
+        take.set_manual(true);
+        assert_eq!(take.state(), LiveTakeState::Idle);
+        assert!(take.take().is_err());
+
+        take.feed_started();
+        assert_eq!(take.state(), LiveTakeState::Armed);
+        assert!(take.holds_feed());
+        take.take().unwrap();
+        assert_eq!(take.state(), LiveTakeState::OnAir);
+        assert!(!take.holds_feed());
+
+        assert!(take.release());
+        assert!(!take.release());
+        assert!(take.holds_feed());
+
+        // A new feed needs a new take.
+        take.take().unwrap();
+        take.feed_ended();
+        take.feed_started();
+        assert_eq!(take.state(), LiveTakeState::Armed);
+
+        // A live event puts the feed on air without a take.
+        take.set_scheduled(true);
+        assert!(!take.holds_feed());
+        take.set_scheduled(false);
+        assert!(take.holds_feed());
+    }
+
+    #[test]
+    fn preview_keeps_the_aspect_ratio() {
+        assert_eq!(preview_size(1920, 1080), (320, 180));
+        assert_eq!(preview_size(720, 576), (320, 256));
+        assert_eq!(preview_size(160, 90), (160, 90));
+    }
+}
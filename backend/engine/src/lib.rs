@@ -1,4 +1,6 @@
 use std::{
+    mem,
+    path::Path,
     sync::{
         Arc,
         atomic::{AtomicBool, Ordering},
@@ -26,23 +28,45 @@ mod output;
 mod playout;
 mod utils;
 
-pub use analysis::audio_level::{AudioLevel, AudioLevelCallback};
+pub use analysis::{
+    audio_level::{AudioLevel, AudioLevelCallback},
+    signal::{SignalMonitor, SignalReport},
+};
 pub use audio_mixer::AudioEffectsControl;
+pub use compositor::{
+    layers::GraphicsLayers,
+    pip::PictureInPicture,
+    template::{PlaceholderValues, TextPlaceholders},
+};
 use input::live::{LiveEnded, LiveOverrideOutput};
-pub use input::live::{LiveReceiver, spawn_rtmp_listener};
+pub use input::{
+    delay::{LiveDelay, LiveDelayStatus},
+    failover::{IngestFailover, IngestSwitchCallback},
+    generator::{is_generator_source, validate_generator_source},
+    live::{LiveReceiver, spawn_live_failover, spawn_live_listener, spawn_live_pull},
+    publish::{PublishAuthorizer, Publisher},
+    pull::{PullControl, is_pull_url},
+    record::{LiveRecorder, RecordingCallback, RecordingPath, record_stream},
+    srt::{SrtListener, is_srt_url},
+    take::{LiveTake, LiveTakeState},
+};
 #[cfg(all(feature = "desktop-base", feature = "tokio"))]
 pub use output::desktop::thread::run_on_main_thread as run_desktop_on_main_thread;
-pub use output::resolved_variant_playlist_path;
+pub use output::{ClipMetadata, resolved_variant_playlist_path};
 use output::{FrameOutput, Output, PlaybackStopped};
 use playout::{PlaybackRestart, PlaybackSkipped, Timeline, play_clip, write_fallback};
 pub use utils::{
     clock,
     config::{
-        DesktopControlCallback, DesktopControlCommand, HlsSubtitle, HlsVariant, LogLevel,
-        LogoConfig, OutputConfig, OutputSize, RgbaColor, StreamType, TextBackgroundConfig,
-        TextConfig, TextOverlayState, TextPosition, TextScroll, TextWeight, VideoOptionChoice,
+        AnimationConfig, BrandingIcon, ClipBranding, ClipEvent, ClipEventAction, ClipEventCallback,
+        ClipOffset, DesktopControlCallback, DesktopControlCommand, GraphicLayer,
+        GraphicLayerContent, GraphicLayerUpdate, HlsSubtitle, HlsVariant, ImageMotion,
+        ImageOptions, LogLevel, LogoConfig, MotionEasing, MotionRect, OutputConfig, OutputSize,
+        PictureInPictureConfig, PipLayout, PipRect, PipSource, RgbaColor, SlateConfig, StreamType,
+        TextAnimation, TextBackgroundConfig, TextConfig, TextOutline, TextOverlayState,
+        TextPosition, TextReveal, TextScroll, TextShadow, TextSlide, TextWeight, VideoOptionChoice,
         VideoOptionKind, VideoOptionSpec, VideoOptionVisibility, VideoOptions,
-        audio_codec_uses_bitrate, validate_video_options, video_codec_uses_bitrate,
+        audio_codec_uses_bitrate, icecast_muxer, validate_video_options, video_codec_uses_bitrate,
         video_option_defaults, video_option_specs,
     },
     ffmpeg_capabilities::{
@@ -61,6 +85,26 @@ pub fn available_font_families() -> Vec<String> {
     compositor::text::available_font_families()
 }
 
+/// Load a font file for all texts, returns the families it contains.
+/// Loading a file again replaces its faces.
+pub fn load_font_file(path: &Path) -> Result<Vec<String>> {
+    compositor::text::load_font_file(path)
+}
+
+/// Families in font data, to check an upload before it replaces a font.
+pub fn font_data_families(data: &[u8]) -> Result<Vec<String>> {
+    compositor::text::font_data_families(data)
+}
+
+pub fn unload_font_file(path: &Path) {
+    compositor::text::unload_font_file(path);
+}
+
+/// Families of a loaded font file, empty when it is not loaded.
+pub fn loaded_font_families(path: &Path) -> Vec<String> {
+    compositor::text::loaded_font_families(path)
+}
+
 /// Sets the report interval for the optional `processing-bench` feature.
 /// Has no effect when that feature is disabled.
 pub fn set_processing_bench_interval(interval: Duration) {
@@ -82,12 +126,31 @@ pub struct LogoFade {
     pub fade_out: bool,
 }
 
+/// Scheduled live event: the live feed is on air for `duration_seconds`, and
+/// `standby` plays while the feed is absent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiveBlock {
+    /// Stream pulled for the block; `None` takes the ingest started with
+    /// `start_live`.
+    pub url: Option<String>,
+    /// Source played while the feed is absent, like `generator://slate`.
+    pub standby: String,
+    pub duration_seconds: f64,
+    /// Count the duration from the first live frame instead of the block
+    /// start. `standby` plays for at most `duration_seconds` while waiting.
+    pub wait: bool,
+}
+
 pub struct Playout {
     config: OutputConfig,
     output: Output,
     timeline: Timeline,
     fallback_duration: f64,
     playback_control: PlaybackControl,
+    clip_metadata: Option<ClipMetadata>,
+    image_options: Option<ImageOptions>,
+    clip_branding: Option<ClipBranding>,
+    clip_events: Vec<ClipEvent>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -148,6 +211,9 @@ struct PlayOptions<'a> {
     duration_seconds: Option<f64>,
     subtitles_media_path: Option<&'a str>,
     logo_fade: LogoFade,
+    image: Option<&'a ImageOptions>,
+    branding: Option<&'a ClipBranding>,
+    events: &'a [ClipEvent],
 }
 
 #[cfg(feature = "tokio")]
@@ -329,24 +395,92 @@ impl AsyncPlayout {
         result.await.context("playout worker stopped during play")?
     }
 
-    pub async fn start_rtmp_live(
+    /// Queue in-band metadata for the next `play_*` call.
+    pub fn set_clip_metadata(&self, metadata: ClipMetadata) -> Result<()> {
+        self.commands
+            .send(AsyncCommand::SetClipMetadata(metadata))
+            .map_err(|_| anyhow!("playout worker stopped"))
+    }
+
+    /// Queue still-image settings for the next `play_*` call.
+    pub fn set_image_options(&self, options: ImageOptions) -> Result<()> {
+        self.commands
+            .send(AsyncCommand::SetImageOptions(options))
+            .map_err(|_| anyhow!("playout worker stopped"))
+    }
+
+    /// Queue branding for the next `play_*` call.
+    pub fn set_clip_branding(&self, branding: ClipBranding) -> Result<()> {
+        self.commands
+            .send(AsyncCommand::SetClipBranding(branding))
+            .map_err(|_| anyhow!("playout worker stopped"))
+    }
+
+    /// Queue secondary events for the next `play_*` call.
+    pub fn set_clip_events(&self, events: Vec<ClipEvent>) -> Result<()> {
+        self.commands
+            .send(AsyncCommand::SetClipEvents(events))
+            .map_err(|_| anyhow!("playout worker stopped"))
+    }
+
+    /// Start the RTMP or SRT ingest server for `url`.
+    pub async fn start_live(&self, url: impl Into<String>, config: OutputConfig) -> Result<()> {
+        self.send_start_live(vec![url.into()], config, None).await
+    }
+
+    /// Pull the live ingest from `url` while `control` is armed.
+    pub async fn start_live_pull(
         &self,
         url: impl Into<String>,
         config: OutputConfig,
+        control: PullControl,
+    ) -> Result<()> {
+        self.send_start_live(vec![url.into()], config, Some(control))
+            .await
+    }
+
+    /// Start the live ingest on several endpoints in priority order, pulled
+    /// while `pull` is armed or listening without it. The config's
+    /// `ingest_failover` switches between them.
+    pub async fn start_live_failover(
+        &self,
+        urls: Vec<String>,
+        config: OutputConfig,
+        pull: Option<PullControl>,
+    ) -> Result<()> {
+        self.send_start_live(urls, config, pull).await
+    }
+
+    /// Play a scheduled live block. It ends with `LiveEnded` when the feed
+    /// was on air, and the caller goes on from the current time.
+    pub async fn play_live_block(&self, block: LiveBlock) -> Result<ClipResult> {
+        let (response, result) = oneshot::channel();
+        self.commands
+            .send(AsyncCommand::PlayLiveBlock { block, response })
+            .map_err(|_| anyhow!("playout worker stopped"))?;
+
+        result.await.context("playout worker stopped during play")?
+    }
+
+    async fn send_start_live(
+        &self,
+        urls: Vec<String>,
+        config: OutputConfig,
+        pull: Option<PullControl>,
     ) -> Result<()> {
-        let url = url.into();
         let (response, result) = oneshot::channel();
         self.commands
-            .send(AsyncCommand::StartRtmpLive {
-                url,
+            .send(AsyncCommand::StartLive {
+                urls,
                 config: Box::new(config),
+                pull,
                 response,
             })
             .map_err(|_| anyhow!("playout worker stopped"))?;
 
         result
             .await
-            .context("playout worker stopped while starting RTMP live")?
+            .context("playout worker stopped while starting live ingest")?
     }
 
     pub async fn finish(self) -> Result<()> {
@@ -383,9 +517,18 @@ enum AsyncCommand {
         playout_rate: f64,
         response: oneshot::Sender<Result<ClipResult>>,
     },
-    StartRtmpLive {
-        url: String,
+    PlayLiveBlock {
+        block: LiveBlock,
+        response: oneshot::Sender<Result<ClipResult>>,
+    },
+    SetClipMetadata(ClipMetadata),
+    SetImageOptions(ImageOptions),
+    SetClipBranding(ClipBranding),
+    SetClipEvents(Vec<ClipEvent>),
+    StartLive {
+        urls: Vec<String>,
         config: Box<OutputConfig>,
+        pull: Option<PullControl>,
         response: oneshot::Sender<Result<()>>,
     },
     Finish {
@@ -423,16 +566,28 @@ fn run_async_playout_worker(mut playout: Playout, commands: mpsc::Receiver<Async
                 // worker alive so that command can explicitly release the
                 // window and its WGPU resources before process shutdown.
             }
-            AsyncCommand::StartRtmpLive {
-                url,
+            AsyncCommand::PlayLiveBlock { block, response } => {
+                let _ = response.send(playout.play_live_block(&block, &mut live));
+            }
+            AsyncCommand::SetClipMetadata(metadata) => playout.set_clip_metadata(metadata),
+            AsyncCommand::SetImageOptions(options) => playout.set_image_options(options),
+            AsyncCommand::SetClipBranding(branding) => playout.set_clip_branding(branding),
+            AsyncCommand::SetClipEvents(events) => playout.set_clip_events(events),
+            AsyncCommand::StartLive {
+                urls,
                 config,
+                pull,
                 response,
-            } => {
-                let receiver = spawn_rtmp_listener(url, *config);
-                receiver.set_benchmark(benchmark::current());
-                live = Some(receiver);
-                let _ = response.send(Ok(()));
-            }
+            } => match spawn_live_failover(&urls, *config, pull) {
+                Ok(receiver) => {
+                    receiver.set_benchmark(benchmark::current());
+                    live = Some(receiver);
+                    let _ = response.send(Ok(()));
+                }
+                Err(error) => {
+                    let _ = response.send(Err(error));
+                }
+            },
             AsyncCommand::Finish { response } => {
                 let _ = response.send(playout.finish());
                 break;
@@ -539,9 +694,33 @@ impl Playout {
             timeline: Timeline::new(),
             fallback_duration,
             playback_control: PlaybackControl::default(),
+            clip_metadata: None,
+            image_options: None,
+            clip_branding: None,
+            clip_events: Vec::new(),
         }
     }
 
+    /// Metadata announced in the output stream when the next clip starts.
+    pub fn set_clip_metadata(&mut self, metadata: ClipMetadata) {
+        self.clip_metadata = Some(metadata);
+    }
+
+    /// Pan/zoom and audio bed for the next clip; they apply to that clip only.
+    pub fn set_image_options(&mut self, options: ImageOptions) {
+        self.image_options = Some(options);
+    }
+
+    /// Logo changes and extra icons for the next clip.
+    pub fn set_clip_branding(&mut self, branding: ClipBranding) {
+        self.clip_branding = Some(branding);
+    }
+
+    /// Secondary events of the next clip.
+    pub fn set_clip_events(&mut self, events: Vec<ClipEvent>) {
+        self.clip_events = events;
+    }
+
     pub fn play(&mut self, path: &str) -> Result<ClipResult> {
         self.play_with_seek(path, None)
     }
@@ -593,6 +772,63 @@ impl Playout {
         )
     }
 
+    /// Plays `block.standby` while the block's live feed stays on air until
+    /// the block ends. `ingest` serves blocks without their own URL.
+    pub fn play_live_block(
+        &mut self,
+        block: &LiveBlock,
+        ingest: &mut Option<LiveReceiver>,
+    ) -> Result<ClipResult> {
+        if !block.duration_seconds.is_finite() || block.duration_seconds <= 0.0 {
+            return Err(anyhow!("live block needs a positive duration"));
+        }
+
+        // A block whose feed can't be pulled still fills its slot.
+        let mut pull = block.url.as_deref().and_then(|url| {
+            // The block is its own take, so its feed doesn't wait for one.
+            let config = self.config.clone().with_live_take(LiveTake::default());
+            spawn_live_pull(url, config, PullControl::new(true))
+                .map_err(|error| {
+                    log::error!(channel = self.config.channel_id;
+                        "live block plays its standby only: {error:#}"
+                    );
+                })
+                .ok()
+        });
+        if let Some(receiver) = pull.as_ref() {
+            receiver.set_benchmark(benchmark::current());
+        }
+        let live = if block.url.is_some() {
+            &mut pull
+        } else {
+            ingest
+        };
+        if let Some(receiver) = live.as_mut() {
+            receiver.start_block(Duration::from_secs_f64(block.duration_seconds), block.wait);
+        }
+        if block.url.is_none() {
+            self.config.live_take.set_scheduled(true);
+        }
+
+        let result = self.play_timed_with_live(
+            &block.standby,
+            None,
+            Some(block.duration_seconds),
+            None,
+            LogoFade::default(),
+            1.0,
+            live,
+        );
+        if let Some(receiver) = live.as_mut() {
+            receiver.end_block();
+        }
+        if block.url.is_none() {
+            self.config.live_take.set_scheduled(false);
+        }
+
+        result
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn play_timed_with_live(
         &mut self,
@@ -605,14 +841,26 @@ impl Playout {
         live: &mut Option<LiveReceiver>,
     ) -> Result<ClipResult> {
         let subtitles_media_path = subtitles_media_path.map(str::to_string);
+        let image = self.image_options.take();
+        let branding = self.clip_branding.take();
+        let events = mem::take(&mut self.clip_events);
         self.output.set_playout_rate(playout_rate);
+        if let Some(metadata) = self.clip_metadata.take() {
+            let output_ms = self.timeline.video_pts() * 1_000 / i64::from(self.config.fps);
+            // Metadata is informational; a rejected packet must not cost the clip.
+            if let Err(error) = self.output.write_clip_metadata(&metadata, output_ms) {
+                log::warn!(channel = self.config.channel_id;
+                    "failed to write clip metadata: {error:#}"
+                );
+            }
+        }
 
         #[cfg(feature = "desktop-base")]
         if self.output.is_desktop() {
             let config = self.config.clone();
             let fallback_duration = self.fallback_duration;
             let playback_control = self.playback_control.clone();
-            let mut timeline = self.timeline;
+            let mut timeline = self.timeline.take();
             let path = path.to_string();
             let mut live_for_worker = live.take();
             let benchmark = benchmark::start(config.channel_id);
@@ -634,6 +882,9 @@ impl Playout {
                             duration_seconds,
                             subtitles_media_path: subtitles_media_path.as_deref(),
                             logo_fade,
+                            image: image.as_ref(),
+                            branding: branding.as_ref(),
+                            events: &events,
                         },
                     )
                 } else {
@@ -649,6 +900,9 @@ impl Playout {
                             duration_seconds,
                             subtitles_media_path: subtitles_media_path.as_deref(),
                             logo_fade,
+                            image: image.as_ref(),
+                            branding: branding.as_ref(),
+                            events: &events,
                         },
                     )
                 };
@@ -682,6 +936,9 @@ impl Playout {
                     duration_seconds,
                     subtitles_media_path: subtitles_media_path.as_deref(),
                     logo_fade,
+                    image: image.as_ref(),
+                    branding: branding.as_ref(),
+                    events: &events,
                 },
             )
         } else {
@@ -697,6 +954,9 @@ impl Playout {
                     duration_seconds,
                     subtitles_media_path: subtitles_media_path.as_deref(),
                     logo_fade,
+                    image: image.as_ref(),
+                    branding: branding.as_ref(),
+                    events: &events,
                 },
             )
         }
@@ -739,6 +999,9 @@ fn play_to_output<O: FrameOutput>(
         options.duration_seconds,
         options.subtitles_media_path,
         options.logo_fade,
+        options.image,
+        options.branding,
+        options.events,
         playback_control,
     ) {
         Ok(()) => Ok(ClipResult::Played),
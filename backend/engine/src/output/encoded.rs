@@ -11,7 +11,11 @@ use ffmpeg::{
 };
 use ffmpeg_next as ffmpeg;
 
-use super::{hls, vtt};
+use super::{
+    hls,
+    metadata::{ClipMetadata, MetadataKind},
+    vtt,
+};
 use crate::{
     HlsHealth,
     analysis::audio_level::AudioLevelMeter,
@@ -20,8 +24,8 @@ use crate::{
     clock::PlayoutClock,
     utils::{
         config::{
-            HlsSubtitle, HlsVariant, OutputConfig, audio_codec_uses_bitrate,
-            video_codec_uses_bitrate,
+            HlsSubtitle, HlsVariant, OutputConfig, StreamType, audio_codec_uses_bitrate,
+            icecast_content_type, video_codec_uses_bitrate,
         },
         helper::{is_network_url, network_io_options},
     },
@@ -33,6 +37,7 @@ pub(super) struct EncodedOutput {
     audio_streams: Vec<AudioOutputStream>,
     subtitle_streams: Vec<SubtitleOutputStream>,
     vtt_subtitles: bool,
+    metadata_stream: Option<MetadataOutputStream>,
     audio_effects: AudioEffectChain,
     audio_level_meter: AudioLevelMeter,
     audio_buffer: [VecDeque<f32>; 2],
@@ -173,6 +178,11 @@ struct SubtitleOutputStream {
     stream_index: usize,
 }
 
+struct MetadataOutputStream {
+    stream_index: usize,
+    kind: MetadataKind,
+}
+
 impl EncodedOutput {
     pub(super) fn open(
         path: &str,
@@ -267,6 +277,9 @@ impl EncodedOutput {
             // preopening the output path because that truncates a standalone
             // playlist before `append_list` can resume it.
             EncodedFormat::Hls { .. } => hls::output_context(&hls_output_path)?,
+            EncodedFormat::Stream { ref muxer } if cfg.stream_type == StreamType::Icecast => {
+                format::output_as_with(path, muxer, icecast_io_options(cfg))?
+            }
             EncodedFormat::Stream { ref muxer } if is_network_url(path) => {
                 format::output_as_with(path, muxer, network_io_options())?
             }
@@ -291,20 +304,35 @@ impl EncodedOutput {
 
         for index in 0..stream_count {
             let variant = hls_variants.get(index);
-            video_streams.push(open_video_stream(
-                &mut octx,
-                cfg,
-                output_format.clone(),
-                global_header,
-                variant,
-            )?);
+            if !cfg.audio_only {
+                video_streams.push(open_video_stream(
+                    &mut octx,
+                    cfg,
+                    output_format.clone(),
+                    global_header,
+                    variant,
+                )?);
+            }
             audio_streams.push(open_audio_stream(&mut octx, cfg, global_header, variant)?);
         }
         if vtt_subtitles {
             subtitle_streams.push(open_subtitle_stream(&mut octx)?);
         }
+        // In-band clip metadata is opt-in, since some players and ingest
+        // servers reject data streams. `var_stream_map` has no slot for
+        // them, so variant HLS outputs always go without.
+        let metadata_stream = if !cfg.clip_metadata || uses_var_stream_map {
+            None
+        } else {
+            MetadataKind::for_muxer(octx.format().name())
+                .map(|kind| open_metadata_stream(&mut octx, kind))
+                .transpose()?
+        };
 
         match output_format {
+            EncodedFormat::Auto | EncodedFormat::Stream { .. } if metadata_stream.is_some() => {
+                reject_unused_options(octx.write_header_with(metadata_mux_options())?)?;
+            }
             EncodedFormat::Auto | EncodedFormat::Stream { .. } => {
                 octx.write_header()?;
             }
@@ -331,6 +359,11 @@ impl EncodedOutput {
                 if let Some(start_number) = hls_start_number {
                     options.set("start_number", &start_number.to_string());
                 }
+                if metadata_stream.is_some() {
+                    for (key, value) in metadata_mux_options().iter() {
+                        options.set(key, value);
+                    }
+                }
                 if uses_var_stream_map {
                     options.set("master_pl_name", "master.m3u8");
                     options.set(
@@ -348,6 +381,7 @@ impl EncodedOutput {
             audio_streams,
             subtitle_streams,
             vtt_subtitles,
+            metadata_stream,
             audio_effects: AudioEffectChain::new(cfg.audio_effects.clone(), cfg.sample_rate),
             audio_level_meter: AudioLevelMeter::new(
                 cfg.sample_rate,
@@ -481,6 +515,30 @@ impl EncodedOutput {
         Ok(())
     }
 
+    pub(super) fn write_clip_metadata(
+        &mut self,
+        metadata: &ClipMetadata,
+        output_ms: i64,
+    ) -> Result<()> {
+        let Some(stream) = &self.metadata_stream else {
+            return Ok(());
+        };
+        let stream_index = stream.stream_index;
+        let stream_time_base = self
+            .octx
+            .stream(stream_index)
+            .context("metadata output stream is missing")?
+            .time_base();
+
+        let mut packet = Packet::copy(&stream.kind.payload(metadata));
+        packet.set_pts(Some(output_ms));
+        packet.set_dts(Some(output_ms));
+        packet.set_stream(stream_index);
+        packet.rescale_ts(Rational(1, 1_000), stream_time_base);
+        packet.write_interleaved(&mut self.octx)?;
+        Ok(())
+    }
+
     fn write_complete_audio_frames(&mut self) -> Result<()> {
         let frame_size = self.audio_frame_size();
         if frame_size == 0 {
@@ -688,6 +746,14 @@ fn reject_unused_options(options: ffmpeg::Dictionary<'_>) -> Result<()> {
     }
 }
 
+/// The icecast protocol sends the MIME type with the source request; Icecast
+/// rejects or mislabels the mount when it does not match the container.
+fn icecast_io_options(cfg: &OutputConfig) -> ffmpeg::Dictionary<'static> {
+    let mut options = network_io_options();
+    options.set("content_type", icecast_content_type(&cfg.audio_codec));
+    options
+}
+
 fn hls_resume_playlist_paths(
     path: &str,
     hls_playlist_path: &str,
@@ -1076,11 +1142,40 @@ fn open_subtitle_stream(octx: &mut format::context::Output) -> Result<SubtitleOu
     })
 }
 
+fn open_metadata_stream(
+    octx: &mut format::context::Output,
+    kind: MetadataKind,
+) -> Result<MetadataOutputStream> {
+    let codec_id = match kind {
+        MetadataKind::FlvText => codec::Id::TEXT,
+        MetadataKind::Id3 => codec::Id::TIMED_ID3,
+    };
+    let mut stream = octx.add_stream(codec_id)?;
+    stream.set_time_base(Rational(1, 1_000));
+    let mut parameters = codec::Parameters::new();
+    parameters.set_medium(ffmpeg::media::Type::Data);
+    parameters.set_id(codec_id);
+    stream.set_parameters(parameters);
+    Ok(MetadataOutputStream {
+        stream_index: stream.index(),
+        kind,
+    })
+}
+
+/// The metadata stream is sparse. Without a short interleave window the
+/// muxer would hold back audio and video for up to ten seconds waiting for
+/// the next clip change.
+fn metadata_mux_options() -> ffmpeg::Dictionary<'static> {
+    let mut options = ffmpeg::Dictionary::new();
+    options.set("max_interleave_delta", "500000");
+    options
+}
+
 #[cfg(test)]
 mod open_tests {
     use super::*;
     use crate::utils::{
-        config::{HlsSubtitle, OutputConfig},
+        config::{HlsSubtitle, OutputConfig, icecast_muxer},
         ffmpeg_capabilities::ffmpeg_capabilities,
     };
     use std::fs;
@@ -1337,6 +1432,143 @@ mod open_tests {
         fs::remove_dir_all(&dir).ok();
     }
 
+    #[test]
+    fn mpegts_output_carries_id3_clip_metadata() {
+        ffmpeg::init().ok();
+        let dir = std::env::temp_dir().join(format!("stream_id3_test_{}", std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("stream.ts");
+        let format = EncodedFormat::Stream {
+            muxer: "mpegts".to_string(),
+        };
+        let plain = EncodedOutput::open(
+            path.to_str().unwrap(),
+            &OutputConfig::new(320, 240, 25, 44100),
+            format.clone(),
+        )
+        .unwrap();
+        assert!(plain.metadata_stream.is_none());
+        plain.finish().unwrap();
+
+        let mut output = EncodedOutput::open(
+            path.to_str().unwrap(),
+            &OutputConfig::new(320, 240, 25, 44100).with_clip_metadata(true),
+            format,
+        )
+        .unwrap();
+
+        assert_eq!(
+            output.metadata_stream.as_ref().map(|stream| stream.kind),
+            Some(MetadataKind::Id3)
+        );
+        output
+            .write_clip_metadata(&ClipMetadata::new("News").with_category("live"), 0)
+            .unwrap();
+        output.finish().unwrap();
+
+        let content = fs::read(&path).unwrap();
+        assert!(content.windows(4).any(|window| window == b"TIT2"));
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn hls_output_with_clip_metadata_writes_playable_segments() {
+        ffmpeg::init().ok();
+        let dir = std::env::temp_dir().join(format!("hls_id3_test_{}", std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("stream.m3u8");
+        let cfg = OutputConfig::new(320, 240, 25, 44100).with_clip_metadata(true);
+        let mut output = EncodedOutput::open(
+            path.to_str().unwrap(),
+            &cfg,
+            EncodedFormat::Hls {
+                variants: vec![],
+                subtitle: None,
+                segment_seconds: 1,
+                list_size: 60,
+            },
+        )
+        .unwrap();
+        assert!(output.metadata_stream.is_some());
+
+        for index in 0..75 {
+            if index % 25 == 0 {
+                output
+                    .write_clip_metadata(&ClipMetadata::new(format!("Clip {index}")), index * 40)
+                    .unwrap();
+            }
+            let mut video = frame::Video::new(Pixel::YUV420P, 320, 240);
+            video.set_pts(Some(index));
+            output.encode_video(&video).unwrap();
+            let mut audio = frame::Audio::new(
+                Sample::F32(ffmpeg::format::sample::Type::Planar),
+                output.audio_frame_size(),
+                ChannelLayout::STEREO,
+            );
+            audio.set_rate(44100);
+            audio.set_pts(Some(index * output.audio_frame_size() as i64));
+            for channel in 0..2 {
+                audio.plane_mut::<f32>(channel).fill(0.0);
+            }
+            output.encode_audio(&audio).unwrap();
+        }
+        output.finish().unwrap();
+
+        let playlist = fs::read_to_string(&path).unwrap();
+        let segments: Vec<_> = playlist
+            .lines()
+            .filter(|line| line.ends_with(".ts"))
+            .collect();
+        assert!(segments.len() >= 2, "{playlist}");
+        let mut tagged = false;
+
+        for segment in segments {
+            let segment = dir.join(segment);
+            let ictx = format::input(&segment).unwrap();
+            let mediums: Vec<_> = ictx
+                .streams()
+                .map(|stream| stream.parameters().medium())
+                .collect();
+            assert!(mediums.contains(&ffmpeg::media::Type::Video), "{segment:?}");
+            assert!(mediums.contains(&ffmpeg::media::Type::Audio), "{segment:?}");
+            tagged |= fs::read(&segment)
+                .unwrap()
+                .windows(4)
+                .any(|window| window == b"TIT2");
+        }
+        assert!(tagged, "no segment carries the clip title");
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn audio_only_output_has_no_video_stream() {
+        ffmpeg::init().ok();
+        let dir = std::env::temp_dir().join(format!("audio_only_test_{}", std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("radio.aac");
+        let cfg = OutputConfig::new(320, 240, 25, 44100).with_audio_only(true);
+        let mut output = EncodedOutput::open(
+            path.to_str().unwrap(),
+            &cfg,
+            EncodedFormat::Stream {
+                muxer: icecast_muxer(&cfg.audio_codec).to_string(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(output.octx.streams().count(), 1);
+        assert!(output.video_streams.is_empty());
+        output
+            .encode_video(&frame::Video::new(Pixel::YUV420P, 320, 240))
+            .unwrap();
+        output.finish().unwrap();
+        assert!(path.exists(), "expected radio.aac to exist");
+        fs::remove_dir_all(&dir).ok();
+    }
+
     #[test]
     fn libfdk_aac_sample_format_is_converted_when_available() {
         ffmpeg::init().ok();
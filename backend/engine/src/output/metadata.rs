@@ -0,0 +1,132 @@
+/// Program information announced in-band when a clip starts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClipMetadata {
+    pub title: String,
+    pub category: Option<String>,
+}
+
+impl ClipMetadata {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            category: None,
+        }
+    }
+
+    pub fn with_category(mut self, category: impl Into<String>) -> Self {
+        let category = category.into();
+        self.category = (!category.trim().is_empty()).then_some(category);
+        self
+    }
+}
+
+/// How the clip metadata is carried by the output container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum MetadataKind {
+    /// `onTextData` script tags, written by the FLV muxer for text data streams.
+    FlvText,
+    /// ID3 timed metadata, as consumed by HLS players from MPEG-TS segments.
+    Id3,
+}
+
+impl MetadataKind {
+    pub(super) fn for_muxer(name: &str) -> Option<Self> {
+        match name {
+            "flv" => Some(Self::FlvText),
+            "mpegts" | "hls" => Some(Self::Id3),
+            _ => None,
+        }
+    }
+
+    pub(super) fn payload(self, metadata: &ClipMetadata) -> Vec<u8> {
+        match self {
+            Self::FlvText => flv_text(metadata).into_bytes(),
+            Self::Id3 => id3_tag(metadata),
+        }
+    }
+}
+
+/// `onTextData` only carries a single string, so the category is appended
+/// after the title.
+fn flv_text(metadata: &ClipMetadata) -> String {
+    match &metadata.category {
+        Some(category) => format!("{} | {category}", metadata.title),
+        None => metadata.title.clone(),
+    }
+}
+
+/// Build an ID3v2.4 tag with the title as `TIT2` and the category as a
+/// `TXXX:CATEGORY` frame.
+fn id3_tag(metadata: &ClipMetadata) -> Vec<u8> {
+    let mut frames = id3_text_frame(b"TIT2", metadata.title.as_bytes());
+    if let Some(category) = &metadata.category {
+        let mut value = b"CATEGORY\0".to_vec();
+        value.extend_from_slice(category.as_bytes());
+        frames.extend(id3_text_frame(b"TXXX", &value));
+    }
+
+    let mut tag = b"ID3\x04\x00\x00".to_vec();
+    tag.extend(syncsafe(frames.len()));
+    tag.extend(frames);
+    tag
+}
+
+fn id3_text_frame(id: &[u8; 4], text: &[u8]) -> Vec<u8> {
+    // One byte for the UTF-8 text encoding marker.
+    let mut frame = id.to_vec();
+    frame.extend(syncsafe(text.len() + 1));
+    frame.extend([0, 0, 3]);
+    frame.extend_from_slice(text);
+    frame
+}
+
+fn syncsafe(size: usize) -> [u8; 4] {
+    let size = size.min(0x0fff_ffff) as u32;
+    [
+        ((size >> 21) & 0x7f) as u8,
+        ((size >> 14) & 0x7f) as u8,
+        ((size >> 7) & 0x7f) as u8,
+        (size & 0x7f) as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClipMetadata, MetadataKind, syncsafe};
+
+    #[test]
+    fn syncsafe_sizes_skip_the_high_bit() {
+        assert_eq!(syncsafe(127), [0, 0, 0, 127]);
+        assert_eq!(syncsafe(128), [0, 0, 1, 0]);
+        assert_eq!(syncsafe(300), [0, 0, 2, 44]);
+    }
+
+    #[test]
+    fn id3_tag_contains_title_and_category_frames() {
+        let metadata = ClipMetadata::new("News").with_category("live");
+        let tag = MetadataKind::Id3.payload(&metadata);
+
+        assert_eq!(&tag[..6], b"ID3\x04\x00\x00");
+        assert_eq!(syncsafe(tag.len() - 10), tag[6..10]);
+        assert_eq!(&tag[10..14], b"TIT2");
+        assert_eq!(&tag[14..18], [0, 0, 0, 5]);
+        assert_eq!(&tag[20..25], b"\x03News");
+        assert_eq!(&tag[25..29], b"TXXX");
+        assert!(tag.ends_with(b"\x03CATEGORY\0live"));
+    }
+
+    #[test]
+    fn flv_text_appends_category() {
+        let metadata = ClipMetadata::new("News");
+        assert_eq!(MetadataKind::FlvText.payload(&metadata), b"News");
+        assert_eq!(
+            MetadataKind::FlvText.payload(&metadata.with_category("live")),
+            b"News | live"
+        );
+        assert_eq!(
+            MetadataKind::for_muxer("mp4"),
+            None,
+            "containers without timed metadata are skipped"
+        );
+    }
+}
@@ -4,9 +4,11 @@ use std::{error::Error, fmt};
 pub(crate) mod desktop;
 mod encoded;
 mod hls;
+mod metadata;
 mod vtt;
 
 pub use hls::resolved_variant_playlist_path;
+pub use metadata::ClipMetadata;
 
 use anyhow::Result;
 #[cfg(feature = "desktop-base")]
@@ -56,6 +58,11 @@ pub(crate) trait FrameOutput {
     fn benchmarks_logo_overlay(&self) -> bool {
         true
     }
+    /// Frames that leave the program, like a live ingest shown in the
+    /// picture-in-picture window, are passed on without overlays.
+    fn composites_overlays(&self) -> bool {
+        true
+    }
     fn set_video_end(&mut self, _video_end_pts: Option<i64>) -> Result<()> {
         Ok(())
     }
@@ -109,7 +116,10 @@ impl Output {
                 path,
                 cfg,
                 EncodedFormat::Stream {
-                    muxer: cfg.stream_type.muxer(&cfg.stream_format).to_string(),
+                    muxer: cfg
+                        .stream_type
+                        .muxer(&cfg.stream_format, &cfg.audio_codec)
+                        .to_string(),
                 },
             )?)),
         })
@@ -178,6 +188,20 @@ impl Output {
         }
     }
 
+    /// Announce a new clip in-band. Outputs whose container cannot carry
+    /// timed metadata ignore the call.
+    pub(crate) fn write_clip_metadata(
+        &mut self,
+        metadata: &ClipMetadata,
+        output_ms: i64,
+    ) -> Result<()> {
+        match &mut self.kind {
+            OutputKind::Encoded(output) => output.write_clip_metadata(metadata, output_ms),
+            #[cfg(feature = "desktop-base")]
+            OutputKind::Desktop(_) => Ok(()),
+        }
+    }
+
     pub(crate) fn finish(self) -> Result<()> {
         match self.kind {
             OutputKind::Encoded(output) => output.finish(),
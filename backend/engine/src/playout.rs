@@ -1,26 +1,57 @@
-use std::{error::Error, fmt};
+use std::{
+    error::Error,
+    fmt, mem, thread,
+    time::{Duration, Instant},
+};
 
 use anyhow::{Context, Result, anyhow};
 use ffmpeg_next::{
-    Rational, Rescale, codec, format, frame, media,
+    Packet, Rational, Rescale, codec, format, frame, media,
     software::{resampling, scaling},
     util::{channel_layout::ChannelLayout, format::pixel::Pixel, format::sample::Sample},
 };
-use log::{debug, trace};
+use log::{debug, trace, warn};
 
 use crate::{
     LogoFade, PlaybackControl,
+    analysis::signal::PacketMeter,
     benchmark::{self, Stage},
-    compositor::{logo::*, text::TextOverlay},
+    compositor::{
+        layers::{LayerCache, LayerRenderer},
+        logo::*,
+        pip::PipRenderer,
+        template::TextPlaceholders,
+        text::TextOverlay,
+    },
+    input::{
+        generator::{Generator, GeneratorKind, GeneratorSpec, is_generator_source},
+        live::LiveFeedDelay,
+        record::SessionRecorder,
+    },
     output::FrameOutput,
     utils::{
-        config::{OutputConfig, TextOverlayState},
+        config::{
+            ClipBranding, ClipEvent, ImageMotion, ImageOptions, OutputConfig, TextOverlayState,
+        },
         helper::{even, open_media_input},
     },
 };
 
+mod audio_bed;
+mod events;
+mod motion;
+mod slate;
+
+use audio_bed::AudioBed;
+use events::ClipEventRenderer;
+pub(crate) use events::EventPlan;
+use motion::MotionRenderer;
+pub(crate) use slate::Slate;
+
 const LOGO_FADE_SECONDS: f64 = 1.0;
 const MIN_LOOP_REMAINING_SECONDS: f64 = 3.0;
+/// How often a delayed live input that ended looks for its next due packet.
+const DELAY_DRAIN_INTERVAL: Duration = Duration::from_millis(5);
 
 #[derive(Debug)]
 pub(crate) struct PlaybackSkipped;
@@ -44,6 +75,13 @@ impl fmt::Display for PlaybackRestart {
 
 impl Error for PlaybackRestart {}
 
+/// Pictures and sound produced by the engine instead of decoded from media.
+/// Frames are returned without pts.
+pub(crate) trait SyntheticSource {
+    fn video_frame(&mut self) -> frame::Video;
+    fn audio_frame(&mut self, samples: usize) -> frame::Audio;
+}
+
 fn check_playback_control(playback_control: &PlaybackControl) -> Result<()> {
     if playback_control.take_restart() {
         return Err(PlaybackRestart.into());
@@ -54,12 +92,12 @@ fn check_playback_control(playback_control: &PlaybackControl) -> Result<()> {
     Ok(())
 }
 
-#[derive(Clone, Copy)]
 pub(crate) struct Timeline {
     video_pts: i64,
     audio_pts: i64,
     text_pts: i64,
     logo_opacity: f64,
+    layers: LayerCache,
 }
 
 impl Timeline {
@@ -69,6 +107,7 @@ impl Timeline {
             audio_pts: 0,
             text_pts: 0,
             logo_opacity: 1.0,
+            layers: LayerCache::default(),
         }
     }
 
@@ -76,6 +115,15 @@ impl Timeline {
         self.video_pts
     }
 
+    /// Moves the timeline out, for a clip played on another thread. The
+    /// timeline left behind keeps the position but not the rendered layers.
+    pub(crate) fn take(&mut self) -> Self {
+        Self {
+            layers: mem::take(&mut self.layers),
+            ..*self
+        }
+    }
+
     pub(crate) fn finish_logo_fade(&mut self, fade: LogoFade) {
         if fade.fade_out {
             self.logo_opacity = 0.0;
@@ -99,12 +147,25 @@ pub(crate) fn play_clip<O: FrameOutput>(
     duration_seconds: Option<f64>,
     subtitles_media_path: Option<&str>,
     logo_fade: LogoFade,
+    image: Option<&ImageOptions>,
+    branding: Option<&ClipBranding>,
+    events: &[ClipEvent],
     playback_control: &PlaybackControl,
 ) -> Result<()> {
     let logo_fade_plan = LogoFadePlan::new(timeline.video_pts, duration_seconds, cfg, logo_fade);
+    let events = EventPlan::new(events, timeline.video_pts, duration_seconds, cfg);
+    let branding = branding.map(|branding| BrandingPlan::new(branding, timeline.video_pts, cfg));
 
-    let result = if let Some(duration_seconds) = duration_seconds.filter(|duration| *duration > 0.0)
-    {
+    let result = if is_generator_source(path) {
+        play_generator(
+            path,
+            cfg,
+            timeline,
+            output,
+            duration_seconds,
+            playback_control,
+        )
+    } else if let Some(duration_seconds) = duration_seconds.filter(|duration| *duration > 0.0) {
         play_looped_clip(
             path,
             cfg,
@@ -114,6 +175,9 @@ pub(crate) fn play_clip<O: FrameOutput>(
             duration_seconds,
             subtitles_media_path,
             logo_fade_plan,
+            image,
+            branding,
+            events,
             playback_control,
         )
     } else {
@@ -129,7 +193,13 @@ pub(crate) fn play_clip<O: FrameOutput>(
                 duration_seconds,
                 subtitles_media_path,
                 logo_fade_plan,
+                image,
+                branding,
+                events,
                 playback_control,
+                recording: None,
+                packets: None,
+                delay: None,
             },
         )
     };
@@ -141,6 +211,35 @@ pub(crate) fn play_clip<O: FrameOutput>(
     result
 }
 
+/// Renders a `generator://` source for the duration given by the playlist node.
+fn play_generator<O: FrameOutput>(
+    path: &str,
+    cfg: &OutputConfig,
+    timeline: &mut Timeline,
+    output: &mut O,
+    duration_seconds: Option<f64>,
+    playback_control: &PlaybackControl,
+) -> Result<()> {
+    let duration = duration_seconds
+        .filter(|duration| duration.is_finite() && *duration > 0.0)
+        .ok_or_else(|| anyhow!("{path} needs a duration from the playlist"))?;
+    let spec = GeneratorSpec::parse(path)?;
+    if spec.kind == GeneratorKind::Slate {
+        return write_fallback(path, cfg, timeline, output, duration, playback_control);
+    }
+    let mut generator = Generator::new(spec, cfg, duration);
+
+    write_synthetic(
+        path,
+        cfg,
+        timeline,
+        output,
+        duration,
+        &mut generator,
+        playback_control,
+    )
+}
+
 #[allow(clippy::too_many_arguments)]
 fn play_looped_clip<O: FrameOutput>(
     path: &str,
@@ -151,6 +250,9 @@ fn play_looped_clip<O: FrameOutput>(
     duration_seconds: f64,
     subtitles_media_path: Option<&str>,
     logo_fade_plan: LogoFadePlan,
+    image: Option<&ImageOptions>,
+    branding: Option<BrandingPlan<'_>>,
+    events: Option<EventPlan<'_>>,
     playback_control: &PlaybackControl,
 ) -> Result<()> {
     if !duration_seconds.is_finite() {
@@ -178,7 +280,13 @@ fn play_looped_clip<O: FrameOutput>(
                 duration_seconds: Some(remaining),
                 subtitles_media_path: first_iteration.then_some(subtitles_media_path).flatten(),
                 logo_fade_plan,
+                image,
+                branding,
+                events,
                 playback_control,
+                recording: None,
+                packets: None,
+                delay: None,
             },
         )?;
 
@@ -234,7 +342,61 @@ pub(crate) struct InputPlaybackOptions<'a> {
     pub(crate) duration_seconds: Option<f64>,
     pub(crate) subtitles_media_path: Option<&'a str>,
     pub(crate) logo_fade_plan: LogoFadePlan,
+    pub(crate) image: Option<&'a ImageOptions>,
+    pub(crate) branding: Option<BrandingPlan<'a>>,
+    pub(crate) events: Option<EventPlan<'a>>,
     pub(crate) playback_control: &'a PlaybackControl,
+    /// Gets every packet of the input, for live recordings.
+    pub(crate) recording: Option<&'a mut SessionRecorder>,
+    /// Counts every packet of the input, for the live signal report.
+    pub(crate) packets: Option<&'a mut PacketMeter>,
+    /// Holds the packets of a live input for its broadcast delay.
+    pub(crate) delay: Option<&'a LiveFeedDelay>,
+}
+
+/// Clip branding anchored at the timeline position the clip started at, so
+/// icons keep their timing across loop iterations.
+#[derive(Clone, Copy)]
+pub(crate) struct BrandingPlan<'a> {
+    branding: &'a ClipBranding,
+    /// Timeline PTS of the first played frame of the clip.
+    origin_pts: i64,
+    fps: u32,
+}
+
+impl<'a> BrandingPlan<'a> {
+    fn new(branding: &'a ClipBranding, start_pts: i64, cfg: &OutputConfig) -> Self {
+        Self {
+            branding,
+            origin_pts: start_pts,
+            fps: cfg.fps,
+        }
+    }
+
+    fn end_pts(self, seconds: f64) -> i64 {
+        self.origin_pts + (seconds.max(0.0) * f64::from(self.fps)).round() as i64
+    }
+
+    fn icons(self, cfg: &OutputConfig, start_pts: i64) -> Result<Vec<BrandingIconOverlay>> {
+        let mut icons = vec![];
+        for icon in &self.branding.icons {
+            let end_pts = icon.seconds.map(|seconds| self.end_pts(seconds));
+            if end_pts.is_some_and(|end_pts| end_pts <= start_pts) {
+                continue;
+            }
+            icons.push(BrandingIconOverlay {
+                overlay: LogoOverlay::load(&icon.image, cfg.width, cfg.height)?,
+                end_pts,
+            });
+        }
+
+        Ok(icons)
+    }
+}
+
+struct BrandingIconOverlay {
+    overlay: LogoOverlay,
+    end_pts: Option<i64>,
 }
 
 #[derive(Clone, Copy)]
@@ -309,7 +471,7 @@ pub(crate) fn play_opened_input<O: FrameOutput>(
     cfg: &OutputConfig,
     timeline: &mut Timeline,
     output: &mut O,
-    options: InputPlaybackOptions<'_>,
+    mut options: InputPlaybackOptions<'_>,
 ) -> Result<()> {
     let seek_seconds = options.seek_seconds;
     let seek_us = seek_seconds.map(seconds_to_microseconds).unwrap_or(0);
@@ -317,9 +479,27 @@ pub(crate) fn play_opened_input<O: FrameOutput>(
         seek_input(&mut ictx, seek_seconds)?;
     }
 
-    let video_stream = ictx.streams().best(media::Type::Video);
-    let audio_stream = ictx.streams().best(media::Type::Audio);
-    if video_stream.is_none() && audio_stream.is_none() {
+    // Audio-only outputs never look at the picture, so the video stream is
+    // ignored before a decoder is opened for it.
+    let video_stream = if cfg.audio_only {
+        None
+    } else {
+        ictx.streams().best(media::Type::Video)
+    };
+    let mut audio_bed = options
+        .image
+        .and_then(|image| image.audio_bed.as_deref())
+        .and_then(|path| {
+            AudioBed::open(path, cfg)
+                .map_err(|error| warn!("audio bed {path} is not usable: {error:#}"))
+                .ok()
+        });
+    let audio_stream = if audio_bed.is_some() {
+        None
+    } else {
+        ictx.streams().best(media::Type::Audio)
+    };
+    if video_stream.is_none() && audio_stream.is_none() && audio_bed.is_none() {
         return Err(anyhow!("{label} contains no audio or video stream"));
     }
 
@@ -345,6 +525,9 @@ pub(crate) fn play_opened_input<O: FrameOutput>(
     let logo_fade_plan = options
         .logo_fade_plan
         .with_end_pts(video_limit_pts.or(video_end_pts));
+    let events = options
+        .events
+        .map(|events| events.with_end_pts(video_limit_pts.or(video_end_pts)));
 
     let trim_start_us = (seek_us > 0).then_some(seek_us);
     let mut video = match video_stream {
@@ -356,6 +539,9 @@ pub(crate) fn play_opened_input<O: FrameOutput>(
             timeline.video_pts,
             timeline.text_pts,
             video_limit_pts.or(video_end_pts),
+            options.image.and_then(|image| image.motion),
+            options.branding,
+            events,
         )?),
         None => None,
     };
@@ -386,9 +572,60 @@ pub(crate) fn play_opened_input<O: FrameOutput>(
         output.video_finished()?;
     }
 
+    // After a skip in the delay, decoding starts over at a video keyframe.
+    let is_start = |(stream, packet): &(format::stream::Stream<'_>, Packet)| {
+        video_index.is_none_or(|index| stream.index() == index && packet.is_key())
+    };
     let result = (|| -> Result<()> {
-        for (stream, packet) in ictx.packets() {
+        let mut delay_line = options.delay.map(LiveFeedDelay::line);
+        let mut input = ictx.packets();
+        let mut input_ended = false;
+        loop {
             check_playback_control(options.playback_control)?;
+            let mut next = None;
+            if let (Some(delay), Some(line)) = (options.delay, delay_line.as_mut()) {
+                let now = Instant::now();
+                let change = line.update(now, delay.on_air(), is_start);
+                if change.skipped {
+                    if let Some(video) = video.as_mut() {
+                        video.decoder.flush();
+                        video.frame_rate_converter.restart();
+                    }
+                    if let Some(audio) = audio.as_mut() {
+                        audio.decoder.flush();
+                    }
+                }
+                if change.retimed {
+                    delay.retime(line.stretch())?;
+                }
+                if !line.is_off() {
+                    next = line.pop(now);
+                    if next.is_none() {
+                        if input_ended {
+                            if line.is_empty() || delay.is_aborted() {
+                                break;
+                            }
+                            thread::sleep(DELAY_DRAIN_INTERVAL);
+                        } else if let Some(packet) =
+                            read_packet(&mut input, options.packets.as_deref_mut())
+                        {
+                            delay.packet_arrived();
+                            line.push(packet, Instant::now(), is_start);
+                        } else {
+                            input_ended = true;
+                        }
+                        continue;
+                    }
+                }
+            }
+            let Some((stream, packet)) =
+                next.or_else(|| read_packet(&mut input, options.packets.as_deref_mut()))
+            else {
+                break;
+            };
+            if let Some(recording) = options.recording.as_deref_mut() {
+                recording.write(&stream, &packet);
+            }
             if Some(stream.index()) == video_index {
                 if !video_finished && let Some(video) = video.as_mut() {
                     benchmark::measure(Stage::VideoDecode, || video.decoder.send_packet(&packet))?;
@@ -402,6 +639,18 @@ pub(crate) fn play_opened_input<O: FrameOutput>(
                         options.playback_control,
                     )?;
                 }
+                if let Some(audio_bed) = audio_bed.as_mut() {
+                    let target_pts = audio_pts_for_video_pts(cfg, timeline.video_pts);
+                    audio_bed.fill_to(
+                        cfg,
+                        timeline,
+                        output,
+                        &mut decoded_audio_samples,
+                        target_pts,
+                        audio_limit_pts,
+                        options.playback_control,
+                    )?;
+                }
             } else if Some(stream.index()) == audio_index
                 && let Some(audio) = audio.as_mut()
             {
@@ -417,7 +666,8 @@ pub(crate) fn play_opened_input<O: FrameOutput>(
             }
 
             if duration_us.is_some()
-                && video_limit_pts.is_none_or(|limit| timeline.video_pts >= limit)
+                && (cfg.audio_only
+                    || video_limit_pts.is_none_or(|limit| timeline.video_pts >= limit))
                 && audio_limit_pts.is_none_or(|limit| timeline.audio_pts >= limit)
             {
                 break;
@@ -434,6 +684,19 @@ pub(crate) fn play_opened_input<O: FrameOutput>(
             logo_fade_plan,
             options.playback_control,
         )?;
+        if let Some(audio_bed) = audio_bed.as_mut() {
+            let target_pts =
+                audio_limit_pts.unwrap_or_else(|| audio_pts_for_video_pts(cfg, timeline.video_pts));
+            audio_bed.fill_to(
+                cfg,
+                timeline,
+                output,
+                &mut decoded_audio_samples,
+                target_pts,
+                audio_limit_pts,
+                options.playback_control,
+            )?;
+        }
         if let Some(audio) = audio.as_mut() {
             benchmark::measure(Stage::AudioDecode, || audio.decoder.send_eof())?;
             receive_audio_frames(
@@ -481,6 +744,25 @@ pub(crate) fn play_opened_input<O: FrameOutput>(
     result
 }
 
+/// Next packet of the input, counted for the signal report.
+fn read_packet<'a>(
+    input: &mut format::context::input::PacketIter<'a>,
+    meter: Option<&mut PacketMeter>,
+) -> Option<(format::stream::Stream<'a>, Packet)> {
+    let (stream, packet) = input.next()?;
+    if let Some(meter) = meter {
+        meter.observe(&stream, &packet);
+    }
+    Some((stream, packet))
+}
+
+fn audio_pts_for_video_pts(cfg: &OutputConfig, video_pts: i64) -> i64 {
+    div_ceil(
+        i128::from(video_pts) * i128::from(cfg.sample_rate),
+        i128::from(cfg.fps.max(1)),
+    ) as i64
+}
+
 fn seek_input(ictx: &mut format::context::Input, seek_seconds: f64) -> Result<()> {
     if !seek_seconds.is_finite() || seek_seconds < 0.0 {
         return Err(anyhow!("seek position must be a non-negative number"));
@@ -556,9 +838,10 @@ fn repeat_single_video_frame_to_limit<O: FrameOutput>(
     if repeat_frames == 0 {
         return Ok(());
     }
-    let Some(frame) = video.last_output_frame.clone() else {
+    let frame = video.last_output_frame.clone();
+    if frame.is_none() && video.motion.is_none() {
         return Ok(());
-    };
+    }
 
     debug!(
         "holding single decoded video frame for {repeat_frames} frame(s) ({:.6} s)",
@@ -567,7 +850,9 @@ fn repeat_single_video_frame_to_limit<O: FrameOutput>(
 
     while timeline.video_pts < limit_pts {
         check_playback_control(playback_control)?;
-        let mut frame = frame.clone();
+        let Some(mut frame) = video.picture_at(frame.as_ref(), timeline.video_pts) else {
+            return Ok(());
+        };
         apply_overlays(&mut frame, video, timeline, logo_fade_plan, output);
         frame.set_pts(Some(timeline.video_pts));
         output.encode_video(&frame)?;
@@ -649,29 +934,20 @@ fn receive_video_frames<O: FrameOutput>(
             continue;
         }
 
-        let mut scaled = frame::Video::empty();
-        benchmark::measure(Stage::Scale, || video.scaler.run(&raw, &mut scaled))?;
-        let pristine = if video.needs_padding() {
-            let mut padded = black_video_frame(video.output_width, video.output_height);
-            benchmark::measure(Stage::Scale, || {
-                copy_video_frame(
-                    &scaled,
-                    &mut padded,
-                    video.x_offset,
-                    video.y_offset,
-                    video.scaled_width,
-                    video.scaled_height,
-                );
-            });
-            padded
-        } else {
-            scaled
+        // With a motion move the picture is rendered per output frame from
+        // the decoded source instead of scaled once.
+        let pristine = match video.motion.as_mut() {
+            Some(motion) => {
+                benchmark::measure(Stage::Scale, || motion.set_source(&raw))?;
+                None
+            }
+            None => Some(video.scale_to_output(&raw)?),
         };
         // Only the first decoded frame can become the single-frame repeat
         // source (see `repeat_single_video_frame_to_limit`); keeping a copy of
         // every frame would cost a full-frame memcpy per output frame.
         if *decoded_frames == 0 {
-            video.last_output_frame = Some(pristine.clone());
+            video.last_output_frame.clone_from(&pristine);
         }
         for _ in 0..output_frames {
             check_playback_control(playback_control)?;
@@ -683,7 +959,9 @@ fn receive_video_frames<O: FrameOutput>(
             // onto the shared buffer would stack the logo (and scrolling text
             // positions) on top of each other for duplicated frames during
             // frame-rate up-conversion.
-            let mut frame = pristine.clone();
+            let Some(mut frame) = video.picture_at(pristine.as_ref(), timeline.video_pts) else {
+                continue;
+            };
             apply_overlays(&mut frame, video, timeline, logo_fade_plan, output);
             frame.set_pts(Some(timeline.video_pts));
             output.encode_video(&frame)?;
@@ -702,10 +980,19 @@ fn apply_overlays(
     logo_fade_plan: LogoFadePlan,
     output: &mut impl FrameOutput,
 ) {
+    video.events.fire(timeline.video_pts);
+    if !output.composites_overlays() {
+        timeline.text_pts += 1;
+        return;
+    }
+
     let opacity = logo_fade_plan.opacity_at(timeline.video_pts, timeline.logo_opacity);
     timeline.logo_opacity = opacity;
 
-    if let Some(logo) = &video.logo {
+    video.pip.apply(frame, timeline.text_pts);
+    if let Some(logo) = &video.logo
+        && video.events.logo_visible(timeline.video_pts) != Some(false)
+    {
         if output.benchmarks_logo_overlay() {
             benchmark::measure_overlay(Stage::LogoOverlay, logo.width, logo.height, || {
                 output.apply_logo_overlay(frame, logo, opacity);
@@ -714,6 +1001,14 @@ fn apply_overlays(
             output.apply_logo_overlay(frame, logo, opacity);
         }
     }
+    for icon in &video.icons {
+        if icon
+            .end_pts
+            .is_none_or(|end_pts| timeline.video_pts < end_pts)
+        {
+            output.apply_logo_overlay(frame, &icon.overlay, 1.0);
+        }
+    }
     if let Some(text) = &mut video.text {
         let (width, height) = text.dimensions();
         benchmark::measure_overlay(Stage::TextStatic, width, height, || {
@@ -727,6 +1022,14 @@ fn apply_overlays(
             text.blend(frame, timeline.video_pts, timeline.text_pts);
         });
     }
+    video
+        .events
+        .blend_texts(frame, timeline.video_pts, timeline.text_pts);
+    benchmark::measure(Stage::GraphicsLayers, || {
+        video
+            .layers
+            .blend(frame, timeline.text_pts, &mut timeline.layers);
+    });
     timeline.text_pts += 1;
 }
 
@@ -860,19 +1163,26 @@ struct VideoDecoder {
     x_offset: u32,
     y_offset: u32,
     logo: Option<LogoOverlay>,
+    icons: Vec<BrandingIconOverlay>,
+    events: ClipEventRenderer,
     text: Option<TextOverlay>,
     runtime_text_state: TextOverlayState,
     runtime_text_revision: u64,
+    placeholders: TextPlaceholders,
     runtime_text: Option<TextOverlay>,
+    layers: LayerRenderer,
+    pip: PipRenderer,
     label: String,
     frame_rate_converter: FrameRateConverter,
     output_fps: u32,
     trim_start_us: Option<i64>,
     last_output_frame: Option<frame::Video>,
     last_composited_frame: Option<frame::Video>,
+    motion: Option<MotionRenderer>,
 }
 
 impl VideoDecoder {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         stream: &format::stream::Stream,
         cfg: &OutputConfig,
@@ -881,6 +1191,9 @@ impl VideoDecoder {
         start_pts: i64,
         scroll_pts: i64,
         end_pts: Option<i64>,
+        motion: Option<ImageMotion>,
+        branding: Option<BrandingPlan<'_>>,
+        events: Option<EventPlan<'_>>,
     ) -> Result<Self> {
         let mut ctx = codec::context::Context::from_parameters(stream.parameters())?;
         ctx.set_threading(codec::threading::Config::kind(
@@ -915,7 +1228,21 @@ impl VideoDecoder {
                 )
             })
             .transpose()?
-            .flatten();
+            .flatten()
+            .map(|text| text.with_placeholders(&cfg.text_placeholders));
+        let motion = motion
+            .zip(end_pts)
+            .map(|(motion, end_pts)| {
+                MotionRenderer::new(
+                    motion,
+                    cfg,
+                    &decoder,
+                    start_pts,
+                    trim_start_us.unwrap_or_default(),
+                    end_pts,
+                )
+            })
+            .transpose()?;
 
         Ok(Self {
             decoder,
@@ -926,11 +1253,17 @@ impl VideoDecoder {
             scaled_height: scale.scaled_height,
             x_offset: scale.x_offset,
             y_offset: scale.y_offset,
-            logo: cfg
-                .logo
-                .as_ref()
+            logo: branding
+                .map_or(cfg.logo.as_ref(), |plan| {
+                    plan.branding.logo(cfg.logo.as_ref())
+                })
                 .map(|logo| LogoOverlay::load(logo, cfg.width, cfg.height))
                 .transpose()?,
+            icons: branding
+                .map(|plan| plan.icons(cfg, start_pts))
+                .transpose()?
+                .unwrap_or_default(),
+            events: ClipEventRenderer::new(events, cfg, label, start_pts, scroll_pts)?,
             text: cfg
                 .text
                 .as_ref()
@@ -940,19 +1273,54 @@ impl VideoDecoder {
                     )
                 })
                 .transpose()?
-                .flatten(),
+                .flatten()
+                .map(|text| text.with_placeholders(&cfg.text_placeholders)),
             runtime_text_state: cfg.text_overlay_state.clone(),
             runtime_text_revision: runtime_text_snapshot.revision,
+            placeholders: cfg.text_placeholders.clone(),
             runtime_text,
+            layers: LayerRenderer::new(label, cfg),
+            pip: PipRenderer::new(cfg),
             label: label.to_string(),
             frame_rate_converter: FrameRateConverter::new(stream.time_base(), cfg.fps),
             output_fps: cfg.fps,
             trim_start_us,
             last_output_frame: None,
             last_composited_frame: None,
+            motion,
         })
     }
 
+    fn scale_to_output(&mut self, raw: &frame::Video) -> Result<frame::Video> {
+        let mut scaled = frame::Video::empty();
+        benchmark::measure(Stage::Scale, || self.scaler.run(raw, &mut scaled))?;
+        if !self.needs_padding() {
+            return Ok(scaled);
+        }
+
+        let mut padded = black_video_frame(self.output_width, self.output_height);
+        benchmark::measure(Stage::Scale, || {
+            copy_video_frame(
+                &scaled,
+                &mut padded,
+                self.x_offset,
+                self.y_offset,
+                self.scaled_width,
+                self.scaled_height,
+            );
+        });
+        Ok(padded)
+    }
+
+    /// Picture for the output frame at `pts`: the motion frame when a move
+    /// is set, otherwise a copy of `pristine`.
+    fn picture_at(&self, pristine: Option<&frame::Video>, pts: i64) -> Option<frame::Video> {
+        match &self.motion {
+            Some(motion) => motion.render(pts),
+            None => pristine.cloned(),
+        }
+    }
+
     fn needs_padding(&self) -> bool {
         self.scaled_width != self.output_width
             || self.scaled_height != self.output_height
@@ -961,7 +1329,16 @@ impl VideoDecoder {
     }
 
     fn update_runtime_text(&mut self, pts: i64, scroll_pts: i64) {
-        let snapshot = self.runtime_text_state.snapshot_at(scroll_pts);
+        let mut snapshot = self.runtime_text_state.snapshot_at(scroll_pts);
+        if snapshot.pending_text
+            && self
+                .runtime_text
+                .as_ref()
+                .is_none_or(|text| text.at_pass_start(scroll_pts))
+        {
+            self.runtime_text_state.apply_text(scroll_pts);
+            snapshot = self.runtime_text_state.snapshot_at(scroll_pts);
+        }
         if snapshot.revision == self.runtime_text_revision {
             return;
         }
@@ -984,6 +1361,7 @@ impl VideoDecoder {
             })
             .ok()
             .flatten()
+            .map(|text| text.with_placeholders(&self.placeholders))
         });
     }
 }
@@ -1052,6 +1430,12 @@ impl FrameRateConverter {
         }
     }
 
+    /// Starts counting from the next frame again, after a skip in the input.
+    fn restart(&mut self) {
+        self.first_timestamp = None;
+        self.next_output_frame = 0;
+    }
+
     fn output_frames(&mut self, timestamp: Option<i64>) -> i64 {
         let Some(timestamp) = timestamp else {
             self.next_output_frame += 1;
@@ -1120,6 +1504,27 @@ pub(crate) fn write_fallback<O: FrameOutput>(
     output: &mut O,
     duration: f64,
     playback_control: &PlaybackControl,
+) -> Result<()> {
+    let mut slate = Slate::new(cfg);
+    write_synthetic(
+        label,
+        cfg,
+        timeline,
+        output,
+        duration,
+        &mut slate,
+        playback_control,
+    )
+}
+
+fn write_synthetic<O: FrameOutput>(
+    label: &str,
+    cfg: &OutputConfig,
+    timeline: &mut Timeline,
+    output: &mut O,
+    duration: f64,
+    source: &mut impl SyntheticSource,
+    playback_control: &PlaybackControl,
 ) -> Result<()> {
     let video_end = timeline.video_pts + (duration * f64::from(cfg.fps)).ceil() as i64;
     let audio_end = timeline.audio_pts + (duration * f64::from(cfg.sample_rate)).ceil() as i64;
@@ -1139,11 +1544,14 @@ pub(crate) fn write_fallback<O: FrameOutput>(
         if timeline.video_pts < video_end
             && (timeline.audio_pts >= audio_end || video_time <= audio_time)
         {
-            write_black_frames(cfg, timeline, output, 1, Some(&mut overlays))?;
+            write_synthetic_frame(cfg, timeline, output, source, &mut overlays)?;
         } else {
             let remaining = (audio_end - timeline.audio_pts) as usize;
             let samples = remaining.min(output.audio_frame_size().max(1));
-            write_silence_frame(cfg, timeline, output, samples)?;
+            let mut frame = source.audio_frame(samples);
+            frame.set_pts(Some(timeline.audio_pts));
+            output.encode_audio(&frame)?;
+            timeline.audio_pts += samples as i64;
         }
     }
 
@@ -1249,13 +1657,14 @@ fn write_black_frames<O: FrameOutput>(
     timeline: &mut Timeline,
     output: &mut O,
     frames: i64,
-    mut overlays: Option<&mut FallbackOverlays>,
 ) -> Result<()> {
+    if cfg.audio_only {
+        timeline.video_pts += frames.max(0);
+        return Ok(());
+    }
+
     for _ in 0..frames {
         let mut black = black_video_frame_for_config(cfg);
-        if let Some(overlays) = overlays.as_mut() {
-            overlays.apply(&mut black, timeline);
-        }
         black.set_pts(Some(timeline.video_pts));
         output.encode_video(&black)?;
         timeline.video_pts += 1;
@@ -1263,11 +1672,34 @@ fn write_black_frames<O: FrameOutput>(
     Ok(())
 }
 
+fn write_synthetic_frame<O: FrameOutput>(
+    cfg: &OutputConfig,
+    timeline: &mut Timeline,
+    output: &mut O,
+    source: &mut impl SyntheticSource,
+    overlays: &mut FallbackOverlays,
+) -> Result<()> {
+    if cfg.audio_only {
+        timeline.video_pts += 1;
+        return Ok(());
+    }
+
+    let mut frame = source.video_frame();
+    overlays.apply(&mut frame, timeline);
+    frame.set_pts(Some(timeline.video_pts));
+    output.encode_video(&frame)?;
+    timeline.video_pts += 1;
+    Ok(())
+}
+
 struct FallbackOverlays {
     text: Option<TextOverlay>,
     runtime_text_state: TextOverlayState,
     runtime_text_revision: u64,
+    placeholders: TextPlaceholders,
     runtime_text: Option<TextOverlay>,
+    layers: LayerRenderer,
+    pip: PipRenderer,
     label: String,
     output_width: u32,
     output_height: u32,
@@ -1300,7 +1732,8 @@ impl FallbackOverlays {
                 )
             })
             .transpose()?
-            .flatten();
+            .flatten()
+            .map(|text| text.with_placeholders(&cfg.text_placeholders));
 
         Ok(Self {
             text: cfg
@@ -1319,10 +1752,14 @@ impl FallbackOverlays {
                     )
                 })
                 .transpose()?
-                .flatten(),
+                .flatten()
+                .map(|text| text.with_placeholders(&cfg.text_placeholders)),
             runtime_text_state: cfg.text_overlay_state.clone(),
             runtime_text_revision: runtime_text_snapshot.revision,
+            placeholders: cfg.text_placeholders.clone(),
             runtime_text,
+            layers: LayerRenderer::new(label, cfg),
+            pip: PipRenderer::new(cfg),
             label: label.to_string(),
             output_width: cfg.width,
             output_height: cfg.height,
@@ -1331,6 +1768,7 @@ impl FallbackOverlays {
     }
 
     fn apply(&mut self, frame: &mut frame::Video, timeline: &mut Timeline) {
+        self.pip.apply(frame, timeline.text_pts);
         if let Some(text) = &mut self.text {
             text.blend(frame, timeline.video_pts, timeline.text_pts);
         }
@@ -1338,11 +1776,22 @@ impl FallbackOverlays {
         if let Some(text) = &mut self.runtime_text {
             text.blend(frame, timeline.video_pts, timeline.text_pts);
         }
+        self.layers
+            .blend(frame, timeline.text_pts, &mut timeline.layers);
         timeline.text_pts += 1;
     }
 
     fn update_runtime_text(&mut self, pts: i64, scroll_pts: i64) {
-        let snapshot = self.runtime_text_state.snapshot_at(scroll_pts);
+        let mut snapshot = self.runtime_text_state.snapshot_at(scroll_pts);
+        if snapshot.pending_text
+            && self
+                .runtime_text
+                .as_ref()
+                .is_none_or(|text| text.at_pass_start(scroll_pts))
+        {
+            self.runtime_text_state.apply_text(scroll_pts);
+            snapshot = self.runtime_text_state.snapshot_at(scroll_pts);
+        }
         if snapshot.revision == self.runtime_text_revision {
             return;
         }
@@ -1365,6 +1814,7 @@ impl FallbackOverlays {
             })
             .ok()
             .flatten()
+            .map(|text| text.with_placeholders(&self.placeholders))
         });
     }
 }
@@ -1376,7 +1826,7 @@ fn write_padding_video_frames<O: FrameOutput>(
     frames: i64,
     last_video_frame: Option<&frame::Video>,
 ) -> Result<()> {
-    if let Some(last_video_frame) = last_video_frame {
+    if let Some(last_video_frame) = last_video_frame.filter(|_| !cfg.audio_only) {
         for _ in 0..frames {
             let mut frame = last_video_frame.clone();
             frame.set_pts(Some(timeline.video_pts));
@@ -1385,7 +1835,7 @@ fn write_padding_video_frames<O: FrameOutput>(
         }
         Ok(())
     } else {
-        write_black_frames(cfg, timeline, output, frames, None)
+        write_black_frames(cfg, timeline, output, frames)
     }
 }
 
@@ -1393,7 +1843,7 @@ fn black_video_frame_for_config(cfg: &OutputConfig) -> frame::Video {
     black_video_frame(cfg.width, cfg.height)
 }
 
-fn black_video_frame(width: u32, height: u32) -> frame::Video {
+pub(crate) fn black_video_frame(width: u32, height: u32) -> frame::Video {
     let mut frame = frame::Video::new(Pixel::YUV420P, width, height);
     fill_plane(&mut frame, 0, 16);
     fill_plane(&mut frame, 1, 128);
@@ -1495,11 +1945,18 @@ mod tests {
     use ffmpeg_next::frame;
 
     use super::{
-        FrameRateConverter, LogoFade, PlaybackControl, Rational, Timeline, fit_dimensions,
-        padding_to_sync, parse_duration_us, play_clip, resample_audio_frame,
+        BrandingPlan, FrameRateConverter, LogoFade, PlaybackControl, Rational, Timeline,
+        fit_dimensions, padding_to_sync, parse_duration_us, play_clip, resample_audio_frame,
         should_play_loop_iteration, single_frame_repeat_frames, synchronize_after_skip,
+        write_fallback,
+    };
+    use crate::{
+        output::FrameOutput,
+        utils::config::{
+            ClipBranding, ImageMotion, ImageOptions, MotionEasing, MotionRect, OutputConfig,
+            SlateConfig,
+        },
     };
-    use crate::{output::FrameOutput, utils::config::OutputConfig};
 
     #[derive(Default)]
     struct RecordingOutput {
@@ -1615,6 +2072,16 @@ mod tests {
         assert!(should_play_loop_iteration(false, 3.0, 0.04));
     }
 
+    #[test]
+    fn branding_icons_count_from_the_first_played_frame() {
+        let cfg = OutputConfig::new(320, 180, 25, 48_000);
+        let branding = ClipBranding::default();
+
+        let plan = BrandingPlan::new(&branding, 100, &cfg);
+        assert_eq!(plan.end_pts(5.0), 225);
+        assert_eq!(plan.end_pts(-1.0), 100);
+    }
+
     #[test]
     fn rounds_both_streams_to_a_shared_boundary() {
         assert_eq!(padding_to_sync(30, 44_101, 30, 44_100).unwrap(), (1, 1_469));
@@ -1677,6 +2144,9 @@ mod tests {
             Some(1.0),
             None,
             LogoFade::default(),
+            None,
+            None,
+            &[],
             &PlaybackControl::default(),
         )
         .unwrap();
@@ -1709,6 +2179,9 @@ mod tests {
             None,
             None,
             LogoFade::default(),
+            None,
+            None,
+            &[],
             &PlaybackControl::default(),
         )
         .unwrap();
@@ -1718,6 +2191,155 @@ mod tests {
         assert_eq!(output.events.last(), Some(&"video_finished"));
     }
 
+    #[test]
+    fn audio_only_output_skips_video_frames() {
+        let cfg = OutputConfig::new(320, 240, 25, 48_000).with_audio_only(true);
+        let mut timeline = Timeline::new();
+        let mut output = RecordingOutput::default();
+
+        play_clip(
+            &media_mix_asset("av_sync.mp4"),
+            &cfg,
+            &mut timeline,
+            &mut output,
+            None,
+            Some(2.0),
+            None,
+            LogoFade::default(),
+            None,
+            None,
+            &[],
+            &PlaybackControl::default(),
+        )
+        .unwrap();
+
+        assert!(output.video_frames.is_empty());
+        assert_eq!(timeline.video_pts, 50);
+        assert_eq!(timeline.audio_pts, 96_000);
+    }
+
+    #[test]
+    fn fallback_loops_slate_clip_for_the_whole_duration() {
+        let cfg = OutputConfig::new(320, 240, 25, 48_000).with_slate(Some(SlateConfig {
+            path: Some(media_mix_asset("short_video.mp4")),
+            text: Some("We'll be right back".to_string()),
+            tone: true,
+        }));
+        let mut timeline = Timeline::new();
+        let mut output = RecordingOutput::default();
+
+        write_fallback(
+            "missing.mp4",
+            &cfg,
+            &mut timeline,
+            &mut output,
+            4.0,
+            &PlaybackControl::default(),
+        )
+        .unwrap();
+
+        assert_eq!(output.video_frames.len(), 100);
+        assert!(
+            output
+                .video_frames
+                .iter()
+                .all(|(width, height, _)| (*width, *height) == (320, 240))
+        );
+        assert_eq!(output.audio_samples, 192_000);
+    }
+
+    #[test]
+    fn still_image_moves_over_an_audio_bed() {
+        let cfg = OutputConfig::new(320, 180, 25, 48_000);
+        let mut timeline = Timeline::new();
+        let mut output = RecordingOutput::default();
+        let image = ImageOptions {
+            motion: Some(ImageMotion {
+                end: MotionRect {
+                    x: 0.25,
+                    y: 0.25,
+                    width: 0.5,
+                    height: 0.5,
+                },
+                easing: MotionEasing::EaseInOut,
+                ..ImageMotion::default()
+            }),
+            audio_bed: Some(media_mix_asset("short_audio.mp4")),
+        };
+
+        play_clip(
+            &media_mix_asset("still.jpg"),
+            &cfg,
+            &mut timeline,
+            &mut output,
+            None,
+            Some(3.0),
+            None,
+            LogoFade::default(),
+            Some(&image),
+            None,
+            &[],
+            &PlaybackControl::default(),
+        )
+        .unwrap();
+
+        assert!(output.video_frames.len() >= 75);
+        assert!(
+            output
+                .video_frames
+                .iter()
+                .all(|(width, height, _)| (*width, *height) == (320, 180))
+        );
+        assert!(
+            (144_000..=146_000).contains(&output.audio_samples),
+            "unexpected three-second audio bed: {} samples",
+            output.audio_samples
+        );
+    }
+
+    #[test]
+    fn generator_source_runs_for_the_node_duration() {
+        let cfg = OutputConfig::new(320, 240, 25, 48_000);
+        let mut timeline = Timeline::new();
+        let mut output = RecordingOutput::default();
+
+        play_clip(
+            "generator://smpte-bars?tone=1000",
+            &cfg,
+            &mut timeline,
+            &mut output,
+            None,
+            Some(2.0),
+            None,
+            LogoFade::default(),
+            None,
+            None,
+            &[],
+            &PlaybackControl::default(),
+        )
+        .unwrap();
+
+        assert_eq!(output.video_frames.len(), 50);
+        assert_eq!(output.audio_samples, 96_000);
+        assert!(
+            play_clip(
+                "generator://countdown",
+                &cfg,
+                &mut timeline,
+                &mut output,
+                None,
+                None,
+                None,
+                LogoFade::default(),
+                None,
+                None,
+                &[],
+                &PlaybackControl::default(),
+            )
+            .is_err()
+        );
+    }
+
     #[test]
     fn fits_four_by_three_into_sixteen_by_nine() {
         assert_eq!(fit_dimensions(1024, 576, 640, 480), (768, 576));
@@ -1746,6 +2368,9 @@ mod tests {
             Some(0.2),
             None,
             LogoFade::default(),
+            None,
+            None,
+            &[],
             &playback_control,
         )
         .unwrap();
@@ -1761,6 +2386,9 @@ mod tests {
             Some(0.2),
             None,
             LogoFade::default(),
+            None,
+            None,
+            &[],
             &playback_control,
         )
         .unwrap();
@@ -0,0 +1,112 @@
+use anyhow::{Result, anyhow};
+use ffmpeg_next::{Packet, format, media};
+use log::debug;
+
+use super::{
+    AudioDecoder, Timeline, check_playback_control, flush_audio_resampler, receive_audio_frames,
+};
+use crate::{
+    PlaybackControl,
+    output::FrameOutput,
+    utils::{config::OutputConfig, helper::open_media_input},
+};
+
+/// Audio from a separate file played under a clip instead of its own audio.
+/// The file starts over when it ends before the clip does.
+pub(super) struct AudioBed {
+    path: String,
+    ictx: format::context::Input,
+    stream_index: usize,
+    audio: AudioDecoder,
+    /// Samples written since the file was last opened.
+    pass_samples: i64,
+}
+
+impl AudioBed {
+    pub(super) fn open(path: &str, cfg: &OutputConfig) -> Result<Self> {
+        let ictx = open_media_input(path)?;
+        let (stream_index, audio) = {
+            let stream = ictx
+                .streams()
+                .best(media::Type::Audio)
+                .ok_or_else(|| anyhow!("audio bed {path} contains no audio stream"))?;
+            (stream.index(), AudioDecoder::new(&stream, cfg, None)?)
+        };
+
+        Ok(Self {
+            path: path.to_string(),
+            ictx,
+            stream_index,
+            audio,
+            pass_samples: 0,
+        })
+    }
+
+    /// Write bed audio until the timeline reaches `target_pts`, never past
+    /// `limit_pts`.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn fill_to<O: FrameOutput>(
+        &mut self,
+        cfg: &OutputConfig,
+        timeline: &mut Timeline,
+        output: &mut O,
+        decoded_samples: &mut i64,
+        target_pts: i64,
+        limit_pts: Option<i64>,
+        playback_control: &PlaybackControl,
+    ) -> Result<()> {
+        while timeline.audio_pts < target_pts
+            && limit_pts.is_none_or(|limit| timeline.audio_pts < limit)
+        {
+            check_playback_control(playback_control)?;
+            let before = timeline.audio_pts;
+
+            if let Some(packet) = self.next_packet() {
+                self.audio.decoder.send_packet(&packet)?;
+                receive_audio_frames(
+                    &mut self.audio,
+                    timeline,
+                    output,
+                    decoded_samples,
+                    limit_pts,
+                    playback_control,
+                )?;
+                self.pass_samples += timeline.audio_pts - before;
+                continue;
+            }
+
+            self.audio.decoder.send_eof()?;
+            receive_audio_frames(
+                &mut self.audio,
+                timeline,
+                output,
+                decoded_samples,
+                limit_pts,
+                playback_control,
+            )?;
+            flush_audio_resampler(
+                &mut self.audio,
+                timeline,
+                output,
+                decoded_samples,
+                limit_pts,
+            )?;
+            if self.pass_samples + timeline.audio_pts - before == 0 {
+                return Err(anyhow!("audio bed {} produced no audio", self.path));
+            }
+
+            debug!("looping audio bed {}", self.path);
+            let path = self.path.clone();
+            *self = Self::open(&path, cfg)?;
+        }
+
+        Ok(())
+    }
+
+    fn next_packet(&mut self) -> Option<Packet> {
+        self.ictx
+            .packets()
+            .find(|(stream, _)| stream.index() == self.stream_index)
+            .map(|(_, packet)| packet)
+    }
+}
@@ -0,0 +1,260 @@
+use anyhow::Result;
+use ffmpeg_next::frame;
+
+use crate::{
+    compositor::text::TextOverlay,
+    utils::config::{ClipEvent, ClipEventAction, ClipEventCallback, ClipOffset, OutputConfig},
+};
+
+/// Secondary events of a clip anchored at the timeline position the clip
+/// started at, so they keep their frames across loop iterations.
+#[derive(Clone, Copy)]
+pub(crate) struct EventPlan<'a> {
+    events: &'a [ClipEvent],
+    origin_pts: i64,
+    end_pts: Option<i64>,
+    fps: u32,
+}
+
+impl<'a> EventPlan<'a> {
+    pub(super) fn new(
+        events: &'a [ClipEvent],
+        start_pts: i64,
+        duration_seconds: Option<f64>,
+        cfg: &OutputConfig,
+    ) -> Option<Self> {
+        if events.is_empty() {
+            return None;
+        }
+        let end_pts = duration_seconds
+            .filter(|duration| duration.is_finite() && *duration > 0.0)
+            .map(|duration| start_pts + (duration * f64::from(cfg.fps)).ceil() as i64);
+
+        Some(Self {
+            events,
+            origin_pts: start_pts,
+            end_pts,
+            fps: cfg.fps,
+        })
+    }
+
+    pub(super) fn with_end_pts(mut self, end_pts: Option<i64>) -> Self {
+        if self.end_pts.is_none() {
+            self.end_pts = end_pts;
+        }
+        self
+    }
+
+    fn frames(self, seconds: f64) -> i64 {
+        (seconds * f64::from(self.fps)).round() as i64
+    }
+
+    /// First frame and end of an event. Events counted from the end need a
+    /// known clip end.
+    fn window(self, event: &ClipEvent) -> Option<Window> {
+        let start_pts = match event.at {
+            ClipOffset::Start(seconds) => self.origin_pts + self.frames(seconds),
+            ClipOffset::End(seconds) => self.end_pts? - self.frames(seconds),
+        };
+        let end_pts = event
+            .duration
+            .map(|duration| start_pts + self.frames(duration.max(0.0)))
+            .or(self.end_pts);
+
+        Some(Window { start_pts, end_pts })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Window {
+    start_pts: i64,
+    end_pts: Option<i64>,
+}
+
+impl Window {
+    fn contains(self, pts: i64) -> bool {
+        pts >= self.start_pts && self.end_pts.is_none_or(|end_pts| pts < end_pts)
+    }
+}
+
+/// Runs the secondary events of a clip frame by frame.
+#[derive(Default)]
+pub(super) struct ClipEventRenderer {
+    texts: Vec<(Window, TextOverlay)>,
+    logo: Vec<(Window, bool)>,
+    callbacks: Vec<(i64, ClipEventCallback)>,
+}
+
+impl ClipEventRenderer {
+    /// Events that ended before `start_pts` are dropped and callbacks before
+    /// it are not called again, e.g. on the next loop of a clip.
+    pub(super) fn new(
+        plan: Option<EventPlan<'_>>,
+        cfg: &OutputConfig,
+        label: &str,
+        start_pts: i64,
+        scroll_pts: i64,
+    ) -> Result<Self> {
+        let mut renderer = Self::default();
+        let Some(plan) = plan else {
+            return Ok(renderer);
+        };
+
+        for event in plan.events {
+            let Some(window) = plan.window(event) else {
+                continue;
+            };
+            if window.end_pts.is_some_and(|end_pts| end_pts <= start_pts) {
+                continue;
+            }
+            match &event.action {
+                ClipEventAction::Text(text) => {
+                    let overlay = TextOverlay::load(
+                        text,
+                        label,
+                        cfg.width,
+                        cfg.height,
+                        cfg.fps,
+                        window.start_pts,
+                        window.start_pts - start_pts + scroll_pts,
+                        window.end_pts,
+                    )?;
+                    if let Some(overlay) = overlay {
+                        renderer
+                            .texts
+                            .push((window, overlay.with_placeholders(&cfg.text_placeholders)));
+                    }
+                }
+                ClipEventAction::Logo { visible } => renderer.logo.push((window, *visible)),
+                ClipEventAction::Callback(callback) if window.start_pts >= start_pts => {
+                    renderer
+                        .callbacks
+                        .push((window.start_pts, callback.clone()));
+                }
+                ClipEventAction::Callback(_) => {}
+            }
+        }
+
+        Ok(renderer)
+    }
+
+    /// Calls the callbacks due at `pts`.
+    pub(super) fn fire(&mut self, pts: i64) {
+        self.callbacks.retain(|(start_pts, callback)| {
+            if *start_pts > pts {
+                return true;
+            }
+            callback.invoke();
+            false
+        });
+    }
+
+    /// Logo visibility set by the last event active at `pts`.
+    pub(super) fn logo_visible(&self, pts: i64) -> Option<bool> {
+        self.logo
+            .iter()
+            .rev()
+            .find(|(window, _)| window.contains(pts))
+            .map(|(_, visible)| *visible)
+    }
+
+    pub(super) fn blend_texts(&mut self, frame: &mut frame::Video, pts: i64, scroll_pts: i64) {
+        for (window, text) in &mut self.texts {
+            if window.contains(pts) {
+                text.blend(frame, pts, scroll_pts);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::{ClipEventRenderer, EventPlan, Window};
+    use crate::utils::config::{
+        ClipEvent, ClipEventAction, ClipEventCallback, ClipOffset, OutputConfig,
+    };
+
+    fn logo(at: ClipOffset, duration: Option<f64>, visible: bool) -> ClipEvent {
+        ClipEvent {
+            at,
+            duration,
+            action: ClipEventAction::Logo { visible },
+        }
+    }
+
+    #[test]
+    fn events_count_from_the_start_or_the_end_of_the_clip() {
+        let cfg = OutputConfig::new(320, 180, 25, 48_000);
+        let events = [
+            logo(ClipOffset::Start(5.0), Some(10.0), true),
+            logo(ClipOffset::End(3.0), None, false),
+        ];
+        let plan = EventPlan::new(&events, 100, Some(60.0), &cfg).unwrap();
+
+        assert_eq!(
+            plan.window(&events[0]),
+            Some(Window {
+                start_pts: 225,
+                end_pts: Some(475)
+            })
+        );
+        assert_eq!(
+            plan.window(&events[1]),
+            Some(Window {
+                start_pts: 1525,
+                end_pts: Some(1600)
+            })
+        );
+
+        let open_ended = EventPlan::new(&events, 100, None, &cfg).unwrap();
+        assert_eq!(open_ended.window(&events[1]), None);
+        assert!(EventPlan::new(&[], 100, None, &cfg).is_none());
+    }
+
+    #[test]
+    fn logo_follows_the_last_active_event() {
+        let cfg = OutputConfig::new(320, 180, 25, 48_000);
+        let events = [
+            logo(ClipOffset::Start(0.0), None, false),
+            logo(ClipOffset::Start(2.0), Some(1.0), true),
+        ];
+        let plan = EventPlan::new(&events, 0, Some(10.0), &cfg);
+        let renderer = ClipEventRenderer::new(plan, &cfg, "clip.mp4", 0, 0).unwrap();
+
+        assert_eq!(renderer.logo_visible(10), Some(false));
+        assert_eq!(renderer.logo_visible(60), Some(true));
+        assert_eq!(renderer.logo_visible(75), Some(false));
+        assert_eq!(renderer.logo_visible(250), None);
+    }
+
+    #[test]
+    fn callbacks_fire_once_and_not_again_on_the_next_loop() {
+        let cfg = OutputConfig::new(320, 180, 25, 48_000);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let events = [ClipEvent {
+            at: ClipOffset::Start(1.0),
+            duration: None,
+            action: ClipEventAction::Callback(ClipEventCallback::new(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })),
+        }];
+        let plan = EventPlan::new(&events, 0, Some(10.0), &cfg);
+
+        let mut first = ClipEventRenderer::new(plan, &cfg, "clip.mp4", 0, 0).unwrap();
+        first.fire(24);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        first.fire(25);
+        first.fire(26);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let mut looped = ClipEventRenderer::new(plan, &cfg, "clip.mp4", 100, 100).unwrap();
+        looped.fire(150);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
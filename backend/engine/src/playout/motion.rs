@@ -0,0 +1,297 @@
+use anyhow::Result;
+use ffmpeg_next::{codec, frame, software::scaling, util::format::pixel::Pixel};
+
+use crate::utils::{
+    config::{ImageMotion, MotionRect, OutputConfig},
+    helper::even,
+};
+
+/// The source is kept at up to twice the output size: enough to zoom in
+/// without visible softening while keeping per-frame sampling cheap.
+const WORKING_SCALE: f64 = 2.0;
+const MIN_REGION: f64 = 0.01;
+
+/// Source region in working-picture pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Region {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+/// Renders the Ken Burns move of an image clip frame by frame.
+pub(super) struct MotionRenderer {
+    motion: ImageMotion,
+    scaler: scaling::Context,
+    source: Option<frame::Video>,
+    output_width: u32,
+    output_height: u32,
+    /// Output pts the whole clip would start at; earlier than the first
+    /// rendered frame when the clip resumes part way in.
+    start_pts: i64,
+    end_pts: i64,
+}
+
+impl MotionRenderer {
+    /// Move for a clip that is first shown at `start_pts` after skipping
+    /// `seek_us` of it, and ends at `end_pts`.
+    pub(super) fn new(
+        motion: ImageMotion,
+        cfg: &OutputConfig,
+        decoder: &codec::decoder::Video,
+        start_pts: i64,
+        seek_us: i64,
+        end_pts: i64,
+    ) -> Result<Self> {
+        let (width, height) =
+            working_size(decoder.width(), decoder.height(), cfg.width, cfg.height);
+        let scaler = scaling::Context::get(
+            decoder.format(),
+            decoder.width(),
+            decoder.height(),
+            Pixel::YUV420P,
+            width,
+            height,
+            scaling::flag::Flags::AREA,
+        )?;
+
+        Ok(Self {
+            motion,
+            scaler,
+            source: None,
+            output_width: cfg.width,
+            output_height: cfg.height,
+            start_pts: start_pts - seek_frames(seek_us, cfg.fps),
+            end_pts,
+        })
+    }
+
+    /// Replace the picture the move is rendered from.
+    pub(super) fn set_source(&mut self, decoded: &frame::Video) -> Result<()> {
+        let mut source = frame::Video::empty();
+        self.scaler.run(decoded, &mut source)?;
+        self.source = Some(source);
+        Ok(())
+    }
+
+    /// Output frame at `pts`, without pts; `None` before the first picture.
+    pub(super) fn render(&self, pts: i64) -> Option<frame::Video> {
+        let source = self.source.as_ref()?;
+        let progress = self
+            .motion
+            .easing
+            .apply(move_progress(self.start_pts, self.end_pts, pts));
+        let region = fit_region(
+            lerp_rect(self.motion.start, self.motion.end, progress),
+            source.width(),
+            source.height(),
+            self.output_width,
+            self.output_height,
+        );
+
+        let mut frame = frame::Video::new(Pixel::YUV420P, self.output_width, self.output_height);
+        for plane in 0..3 {
+            sample_plane(source, &mut frame, plane, region);
+        }
+        Some(frame)
+    }
+}
+
+/// Output frames in `seek_us`.
+fn seek_frames(seek_us: i64, fps: u32) -> i64 {
+    (i128::from(seek_us.max(0)) * i128::from(fps) / 1_000_000) as i64
+}
+
+/// Share of the move done at `pts`, reaching 1 on the last frame.
+fn move_progress(start_pts: i64, end_pts: i64, pts: i64) -> f64 {
+    let span = (end_pts - start_pts - 1).max(1);
+    (pts - start_pts) as f64 / span as f64
+}
+
+fn working_size(
+    source_width: u32,
+    source_height: u32,
+    output_width: u32,
+    output_height: u32,
+) -> (u32, u32) {
+    let source_width = source_width.max(1);
+    let source_height = source_height.max(1);
+    let factor = (WORKING_SCALE
+        * (f64::from(output_width) / f64::from(source_width))
+            .max(f64::from(output_height) / f64::from(source_height)))
+    .min(1.0);
+
+    (
+        even((f64::from(source_width) * factor).round() as u32).max(2),
+        even((f64::from(source_height) * factor).round() as u32).max(2),
+    )
+}
+
+fn lerp_rect(start: MotionRect, end: MotionRect, progress: f64) -> MotionRect {
+    let lerp = |from: f64, to: f64| from + (to - from) * progress;
+    MotionRect {
+        x: lerp(start.x, end.x),
+        y: lerp(start.y, end.y),
+        width: lerp(start.width, end.width),
+        height: lerp(start.height, end.height),
+    }
+}
+
+/// Crop `rect` around its center to the output aspect ratio and keep it
+/// inside the picture.
+fn fit_region(
+    rect: MotionRect,
+    source_width: u32,
+    source_height: u32,
+    output_width: u32,
+    output_height: u32,
+) -> Region {
+    let source_width = f64::from(source_width);
+    let source_height = f64::from(source_height);
+    let center_x = (rect.x + rect.width / 2.0) * source_width;
+    let center_y = (rect.y + rect.height / 2.0) * source_height;
+    let mut width = rect.width.clamp(MIN_REGION, 1.0) * source_width;
+    let mut height = rect.height.clamp(MIN_REGION, 1.0) * source_height;
+    let aspect = f64::from(output_width) / f64::from(output_height.max(1));
+
+    if width / height > aspect {
+        width = height * aspect;
+    } else {
+        height = width / aspect;
+    }
+
+    Region {
+        x: (center_x - width / 2.0).clamp(0.0, source_width - width),
+        y: (center_y - height / 2.0).clamp(0.0, source_height - height),
+        width,
+        height,
+    }
+}
+
+/// Bilinear resample of `region` into one plane of `target`.
+fn sample_plane(source: &frame::Video, target: &mut frame::Video, plane: usize, region: Region) {
+    let shift = u32::from(plane > 0);
+    let scale = f64::from(1_u32 << shift);
+    let source_width = (source.width() >> shift) as usize;
+    let source_height = (source.height() >> shift) as usize;
+    let target_width = (target.width() >> shift) as usize;
+    let target_height = (target.height() >> shift) as usize;
+    let step_x = region.width / scale / target_width as f64;
+    let step_y = region.height / scale / target_height as f64;
+
+    let columns = (0..target_width)
+        .map(|x| {
+            taps(
+                region.x / scale + (x as f64 + 0.5) * step_x - 0.5,
+                source_width,
+            )
+        })
+        .collect::<Vec<_>>();
+    let source_stride = source.stride(plane);
+    let source_data = source.data(plane);
+    let target_stride = target.stride(plane);
+    let target_data = target.data_mut(plane);
+
+    for y in 0..target_height {
+        let (y0, y1, fy) = taps(
+            region.y / scale + (y as f64 + 0.5) * step_y - 0.5,
+            source_height,
+        );
+        let top = &source_data[y0 * source_stride..];
+        let bottom = &source_data[y1 * source_stride..];
+        let row = &mut target_data[y * target_stride..y * target_stride + target_width];
+        for (value, &(x0, x1, fx)) in row.iter_mut().zip(&columns) {
+            let upper = lerp_sample(top[x0], top[x1], fx);
+            let lower = lerp_sample(bottom[x0], bottom[x1], fx);
+            *value = (upper + (lower - upper) * fy).round() as u8;
+        }
+    }
+}
+
+fn taps(position: f64, size: usize) -> (usize, usize, f32) {
+    let position = position.clamp(0.0, size.saturating_sub(1) as f64);
+    let index = position.floor();
+    let first = index as usize;
+    (
+        first,
+        (first + 1).min(size.saturating_sub(1)),
+        (position - index) as f32,
+    )
+}
+
+fn lerp_sample(from: u8, to: u8, fraction: f32) -> f32 {
+    f32::from(from) + (f32::from(to) - f32::from(from)) * fraction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Region, fit_region, lerp_rect, move_progress, seek_frames, working_size};
+    use crate::utils::config::{MotionEasing, MotionRect};
+
+    #[test]
+    fn full_image_is_cropped_to_the_output_aspect() {
+        let region = fit_region(MotionRect::default(), 1600, 1200, 1920, 1080);
+
+        assert_eq!(region.width, 1600.0);
+        assert!((region.height - 900.0).abs() < 1e-9);
+        assert!((region.y - 150.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn region_near_the_edge_stays_inside_the_picture() {
+        let rect = MotionRect {
+            x: 0.8,
+            y: 0.8,
+            width: 0.4,
+            height: 0.4,
+        };
+        let region = fit_region(rect, 1000, 1000, 100, 100);
+
+        assert_eq!(
+            region,
+            Region {
+                x: 600.0,
+                y: 600.0,
+                width: 400.0,
+                height: 400.0,
+            }
+        );
+    }
+
+    #[test]
+    fn eased_motion_moves_slower_at_the_ends() {
+        let end = MotionRect {
+            x: 0.5,
+            y: 0.5,
+            width: 0.5,
+            height: 0.5,
+        };
+        let linear = lerp_rect(MotionRect::default(), end, MotionEasing::Linear.apply(0.25));
+        let eased = lerp_rect(
+            MotionRect::default(),
+            end,
+            MotionEasing::EaseInOut.apply(0.25),
+        );
+
+        assert_eq!(linear.x, 0.125);
+        assert!(eased.x < linear.x);
+        assert_eq!(MotionEasing::EaseOut.apply(1.0), 1.0);
+    }
+
+    #[test]
+    fn working_picture_is_at_most_twice_the_output() {
+        assert_eq!(working_size(6000, 4000, 1920, 1080), (3840, 2560));
+        assert_eq!(working_size(800, 600, 1920, 1080), (800, 600));
+    }
+
+    #[test]
+    fn resumed_move_continues_where_the_clip_was_left() {
+        // A 10 s image at 25 fps resumed after 4 s starts output at pts 500.
+        let start_pts = 500 - seek_frames(4_000_000, 25);
+
+        assert_eq!(start_pts, 400);
+        assert_eq!(move_progress(start_pts, 650, 500), 100.0 / 249.0);
+        assert_eq!(move_progress(start_pts, 650, 649), 1.0);
+    }
+}
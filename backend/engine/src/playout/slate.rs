@@ -0,0 +1,362 @@
+use anyhow::{Context, Result, anyhow};
+use ffmpeg_next::{
+    Rational, Rescale, codec, format, frame, media,
+    software::scaling,
+    util::{channel_layout::ChannelLayout, format::pixel::Pixel, format::sample::Sample},
+};
+use log::warn;
+
+use super::{SyntheticSource, VideoScale, black_video_frame, copy_video_frame};
+use crate::{
+    compositor::text::TextOverlay,
+    input::generator::tone_sample,
+    utils::{
+        config::{OutputConfig, RgbaColor, TextBackgroundConfig, TextConfig, TextPosition},
+        helper::open_media_input,
+    },
+};
+
+const TONE_FREQUENCY: u32 = 1_000;
+const MICROSECONDS: Rational = Rational(1, 1_000_000);
+
+/// Picture, text and sound written while no real media is available.
+///
+/// Without a configured slate this produces black frames and silence, so
+/// callers can use it unconditionally.
+pub(crate) struct Slate {
+    picture: Option<SlatePicture>,
+    black: frame::Video,
+    text: Option<TextOverlay>,
+    tone: bool,
+    fps: u32,
+    sample_rate: u32,
+    frames: i64,
+    samples: i64,
+}
+
+impl Slate {
+    pub(crate) fn new(cfg: &OutputConfig) -> Self {
+        let slate = cfg.slate.as_ref();
+        let picture = slate
+            .and_then(|slate| slate.path.as_deref())
+            .filter(|path| !path.trim().is_empty() && !cfg.audio_only)
+            .and_then(|path| {
+                SlatePicture::open(path, cfg)
+                    .map_err(|error| warn!("slate {path} is not usable: {error:#}"))
+                    .ok()
+            });
+        let text = slate
+            .and_then(|slate| slate.text.as_deref())
+            .filter(|_| !cfg.audio_only)
+            .and_then(|text| {
+                TextOverlay::load(
+                    &slate_text_config(text, cfg.height),
+                    "slate",
+                    cfg.width,
+                    cfg.height,
+                    cfg.fps,
+                    0,
+                    0,
+                    None,
+                )
+                .map_err(|error| warn!("slate text is not usable: {error:#}"))
+                .ok()
+                .flatten()
+                .map(|text| text.with_placeholders(&cfg.text_placeholders))
+            });
+
+        Self {
+            picture,
+            black: black_video_frame(cfg.width, cfg.height),
+            text,
+            tone: slate.is_some_and(|slate| slate.tone),
+            fps: cfg.fps.max(1),
+            sample_rate: cfg.sample_rate.max(1),
+            frames: 0,
+            samples: 0,
+        }
+    }
+}
+
+impl SyntheticSource for Slate {
+    fn video_frame(&mut self) -> frame::Video {
+        let elapsed_us = self.frames * 1_000_000 / i64::from(self.fps);
+        let picture = self
+            .picture
+            .as_mut()
+            .map(|picture| picture.frame_at(elapsed_us).map(frame::Video::clone));
+        let mut frame = match picture {
+            Some(Ok(frame)) => frame,
+            Some(Err(error)) => {
+                warn!("slate playback failed, continuing with black: {error:#}");
+                self.picture = None;
+                self.black.clone()
+            }
+            None => self.black.clone(),
+        };
+        if let Some(text) = &mut self.text {
+            text.blend(&mut frame, self.frames, self.frames);
+        }
+        self.frames += 1;
+        frame
+    }
+
+    fn audio_frame(&mut self, samples: usize) -> frame::Audio {
+        let mut frame = frame::Audio::new(
+            Sample::F32(format::sample::Type::Planar),
+            samples,
+            ChannelLayout::STEREO,
+        );
+        frame.set_rate(self.sample_rate);
+        for plane in 0..frame.planes() {
+            let data = frame.plane_mut::<f32>(plane);
+            if self.tone {
+                for (index, sample) in data.iter_mut().enumerate() {
+                    *sample = tone_sample(
+                        self.samples + index as i64,
+                        self.sample_rate,
+                        TONE_FREQUENCY,
+                    );
+                }
+            } else {
+                data.fill(0.0);
+            }
+        }
+        self.samples += samples as i64;
+        frame
+    }
+}
+
+fn slate_text_config(text: &str, output_height: u32) -> TextConfig {
+    TextConfig {
+        text: Some(text.to_string()),
+        font_size: (output_height as f32 / 14.0).max(16.0),
+        position_x: TextPosition::Center,
+        position_y: TextPosition::Center,
+        background: Some(TextBackgroundConfig {
+            color: RgbaColor {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 160,
+            },
+            padding: 24,
+        }),
+        ..TextConfig::default()
+    }
+}
+
+/// Still image or looping clip, decoded on demand and scaled to the output.
+struct SlatePicture {
+    ictx: format::context::Input,
+    stream_index: usize,
+    time_base: Rational,
+    frame_us: i64,
+    decoder: codec::decoder::Video,
+    scaler: scaling::Context,
+    scale: VideoScale,
+    current: frame::Video,
+    next: Option<(i64, frame::Video)>,
+    pass_start_us: Option<i64>,
+    loop_offset_us: i64,
+    last_us: i64,
+    pass_frames: u64,
+    draining: bool,
+    still: bool,
+}
+
+impl SlatePicture {
+    fn open(path: &str, cfg: &OutputConfig) -> Result<Self> {
+        let ictx = open_media_input(path)?;
+        let stream = ictx
+            .streams()
+            .best(media::Type::Video)
+            .ok_or_else(|| anyhow!("{path} contains no video/image stream"))?;
+        let stream_index = stream.index();
+        let time_base = stream.time_base();
+        let frame_rate = stream.avg_frame_rate();
+        let frame_us = if frame_rate.numerator() > 0 && frame_rate.denominator() > 0 {
+            i64::from(frame_rate.denominator()) * 1_000_000 / i64::from(frame_rate.numerator())
+        } else {
+            1_000_000 / i64::from(cfg.fps.max(1))
+        };
+        let ctx = codec::context::Context::from_parameters(stream.parameters())?;
+        let decoder = ctx.decoder().video()?;
+        let scale = VideoScale::new(decoder.width(), decoder.height(), cfg);
+        let scaler = scaling::Context::get(
+            decoder.format(),
+            decoder.width(),
+            decoder.height(),
+            Pixel::YUV420P,
+            scale.scaled_width,
+            scale.scaled_height,
+            scaling::flag::Flags::BILINEAR,
+        )?;
+
+        let mut picture = Self {
+            ictx,
+            stream_index,
+            time_base,
+            frame_us,
+            decoder,
+            scaler,
+            scale,
+            current: black_video_frame(cfg.width, cfg.height),
+            next: None,
+            pass_start_us: None,
+            loop_offset_us: 0,
+            last_us: 0,
+            pass_frames: 0,
+            draining: false,
+            still: false,
+        };
+        let (_, first) = picture
+            .decode_next()?
+            .context("slate contains no decodable picture")?;
+        picture.current = first;
+        picture.next = picture.decode_next()?;
+        Ok(picture)
+    }
+
+    fn frame_at(&mut self, elapsed_us: i64) -> Result<&frame::Video> {
+        while let Some((next_us, _)) = &self.next
+            && *next_us <= elapsed_us
+        {
+            if let Some((_, frame)) = self.next.take() {
+                self.current = frame;
+            }
+            self.next = self.decode_next()?;
+        }
+        Ok(&self.current)
+    }
+
+    /// Decode the next picture with its time on the looping slate timeline.
+    /// Returns `None` once the source turned out to be a single still image.
+    fn decode_next(&mut self) -> Result<Option<(i64, frame::Video)>> {
+        if self.still {
+            return Ok(None);
+        }
+
+        let mut decoded = frame::Video::empty();
+        loop {
+            if self.decoder.receive_frame(&mut decoded).is_ok() {
+                return self.scaled(&decoded).map(Some);
+            }
+            if self.draining {
+                if self.pass_frames <= 1 {
+                    self.still = true;
+                    return Ok(None);
+                }
+                self.restart()?;
+                continue;
+            }
+
+            match self.next_packet() {
+                Some(packet) => self.decoder.send_packet(&packet)?,
+                None => {
+                    self.decoder.send_eof()?;
+                    self.draining = true;
+                }
+            }
+        }
+    }
+
+    fn next_packet(&mut self) -> Option<ffmpeg_next::Packet> {
+        self.ictx
+            .packets()
+            .find(|(stream, _)| stream.index() == self.stream_index)
+            .map(|(_, packet)| packet)
+    }
+
+    fn restart(&mut self) -> Result<()> {
+        self.loop_offset_us = self.last_us + self.frame_us;
+        self.pass_start_us = None;
+        self.pass_frames = 0;
+        self.draining = false;
+        self.ictx
+            .seek(0, ..)
+            .context("failed to rewind slate clip")?;
+        self.decoder.flush();
+        Ok(())
+    }
+
+    fn scaled(&mut self, decoded: &frame::Video) -> Result<(i64, frame::Video)> {
+        let source_us = decoded
+            .timestamp()
+            .or_else(|| decoded.pts())
+            .map_or(0, |pts| pts.rescale(self.time_base, MICROSECONDS));
+        let pass_start_us = *self.pass_start_us.get_or_insert(source_us);
+        let slate_us = self.loop_offset_us + (source_us - pass_start_us).max(0);
+        self.last_us = slate_us;
+        self.pass_frames += 1;
+
+        let mut scaled = frame::Video::empty();
+        self.scaler.run(decoded, &mut scaled)?;
+        let mut padded = black_video_frame(self.scale.output_width, self.scale.output_height);
+        copy_video_frame(
+            &scaled,
+            &mut padded,
+            self.scale.x_offset,
+            self.scale.y_offset,
+            self.scale.scaled_width,
+            self.scale.scaled_height,
+        );
+        Ok((slate_us, padded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Slate, SlatePicture};
+    use crate::{
+        playout::SyntheticSource,
+        utils::config::{OutputConfig, SlateConfig},
+    };
+
+    #[test]
+    fn tone_is_a_continuous_one_kilohertz_sine() {
+        let cfg = OutputConfig::new(64, 36, 25, 48_000).with_slate(Some(SlateConfig {
+            tone: true,
+            ..SlateConfig::default()
+        }));
+        let mut slate = Slate::new(&cfg);
+        let first = slate.audio_frame(24);
+        let second = slate.audio_frame(24);
+
+        assert_eq!(first.plane::<f32>(0)[0], 0.0);
+        // A quarter period of 1 kHz at 48 kHz is 12 samples.
+        assert!((first.plane::<f32>(0)[12] - 0.1).abs() < 1e-6);
+        assert!((second.plane::<f32>(1)[12] + 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn missing_slate_picture_falls_back_to_black() {
+        ffmpeg_next::init().ok();
+        let cfg = OutputConfig::new(64, 36, 25, 48_000).with_slate(Some(SlateConfig {
+            path: Some("/nonexistent/slate.png".to_string()),
+            text: None,
+            tone: true,
+        }));
+        let mut slate = Slate::new(&cfg);
+
+        let frame = slate.video_frame();
+        assert_eq!((frame.width(), frame.height()), (64, 36));
+        assert_eq!(frame.data(0)[0], 16);
+
+        let audio = slate.audio_frame(24);
+        assert!(audio.plane::<f32>(0).iter().any(|sample| *sample != 0.0));
+    }
+
+    #[test]
+    fn still_image_slate_is_decoded_once() {
+        ffmpeg_next::init().ok();
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../tests/assets/storage/media_mix/still.jpg");
+        let cfg = OutputConfig::new(64, 36, 25, 48_000);
+        let mut picture = SlatePicture::open(path.to_str().unwrap(), &cfg).unwrap();
+
+        assert!(picture.still);
+        let frame = picture.frame_at(10_000_000).unwrap();
+        assert_eq!((frame.width(), frame.height()), (64, 36));
+    }
+}
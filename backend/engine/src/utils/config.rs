@@ -7,7 +7,15 @@ use std::{
 
 use ffmpeg_next::{Rational, util::log::Level as FfmpegLevel};
 
-use crate::{AudioEffectsControl, AudioLevelCallback};
+use crate::{
+    AudioEffectsControl, AudioLevelCallback,
+    analysis::signal::SignalMonitor,
+    compositor::{layers::GraphicsLayers, pip::PictureInPicture, template::TextPlaceholders},
+    input::{
+        delay::LiveDelay, failover::IngestFailover, publish::PublishAuthorizer,
+        record::LiveRecorder, take::LiveTake,
+    },
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct HlsVariant {
@@ -179,8 +187,25 @@ pub struct OutputConfig {
     pub logo: Option<LogoConfig>,
     pub text: Option<TextConfig>,
     pub text_overlay_state: TextOverlayState,
+    pub graphics_layers: GraphicsLayers,
+    pub picture_in_picture: PictureInPicture,
+    pub live_take: LiveTake,
+    pub ingest_failover: IngestFailover,
+    pub publish_authorizer: Option<PublishAuthorizer>,
+    pub live_recorder: LiveRecorder,
+    pub live_delay: LiveDelay,
+    pub signal_monitor: SignalMonitor,
+    pub text_placeholders: TextPlaceholders,
+    /// Replaces black frames and silence while a clip fails or live input
+    /// stalls.
+    pub slate: Option<SlateConfig>,
     pub stream_type: StreamType,
     pub stream_format: String,
+    /// Add a timed metadata stream with the title of the clip on air to
+    /// flv, mpegts and HLS outputs.
+    pub clip_metadata: bool,
+    /// Skip video decoding and encoding entirely, e.g. for radio outputs.
+    pub audio_only: bool,
     pub video_codec: String,
     pub video_options: VideoOptions,
     pub audio_codec: String,
@@ -224,19 +249,42 @@ pub enum StreamType {
     Rtmp,
     Srt,
     Udp,
+    Icecast,
     Custom,
 }
 
 impl StreamType {
-    pub fn muxer(self, custom_format: &str) -> &str {
+    pub fn muxer<'a>(self, custom_format: &'a str, audio_codec: &str) -> &'a str {
         match self {
             Self::Rtmp => "flv",
             Self::Srt | Self::Udp => "mpegts",
+            Self::Icecast => icecast_muxer(audio_codec),
             Self::Custom => custom_format,
         }
     }
 }
 
+/// Icecast mounts carry a bare audio elementary stream, so the container
+/// follows the selected audio encoder instead of the stream type.
+pub fn icecast_muxer(audio_codec: &str) -> &'static str {
+    if audio_codec.contains("mp3") {
+        "mp3"
+    } else if audio_codec.contains("opus") || audio_codec.contains("vorbis") {
+        "ogg"
+    } else {
+        "adts"
+    }
+}
+
+/// MIME type announced to the Icecast server for the selected audio encoder.
+pub fn icecast_content_type(audio_codec: &str) -> &'static str {
+    match icecast_muxer(audio_codec) {
+        "mp3" => "audio/mpeg",
+        "ogg" => "audio/ogg",
+        _ => "audio/aac",
+    }
+}
+
 pub type VideoOptions = BTreeMap<String, String>;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -883,6 +931,158 @@ pub struct LogoConfig {
     pub position: String,
 }
 
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SlateConfig {
+    /// Still image or clip scaled to the output size. Clips loop and play
+    /// muted; without a path the slate stays black.
+    pub path: Option<String>,
+    /// Message centered on the slate, e.g. "We'll be right back".
+    pub text: Option<String>,
+    /// Play a 1 kHz reference tone instead of silence.
+    pub tone: bool,
+}
+
+/// Per-clip settings for still images.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImageOptions {
+    /// Pan and zoom across the image for the duration of the clip.
+    pub motion: Option<ImageMotion>,
+    /// Audio played under the clip instead of its own audio. It loops
+    /// when it is shorter than the clip.
+    pub audio_bed: Option<String>,
+}
+
+/// Per-clip changes to the channel logo.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClipBranding {
+    /// Hide the channel logo for the clip, e.g. during ads.
+    pub hide_logo: bool,
+    /// Logo shown instead of the channel logo.
+    pub logo: Option<LogoConfig>,
+    /// Icons shown on top of the logo, like an age rating.
+    pub icons: Vec<BrandingIcon>,
+}
+
+impl ClipBranding {
+    /// Logo for the clip given the channel logo.
+    pub(crate) fn logo<'a>(&'a self, channel: Option<&'a LogoConfig>) -> Option<&'a LogoConfig> {
+        if self.hide_logo {
+            None
+        } else {
+            self.logo.as_ref().or(channel)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrandingIcon {
+    pub image: LogoConfig,
+    /// Seconds from the first played frame the icon stays visible; without
+    /// a limit it stays for the whole clip.
+    pub seconds: Option<f64>,
+}
+
+/// Secondary event of a clip: an action at a frame relative to the clip
+/// start or end.
+#[derive(Debug, Clone)]
+pub struct ClipEvent {
+    pub at: ClipOffset,
+    /// How long a text or logo change lasts; without a duration it lasts until
+    /// the clip ends. Callbacks ignore it.
+    pub duration: Option<f64>,
+    pub action: ClipEventAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClipOffset {
+    /// Seconds after the first played frame of the clip. Negative values lie
+    /// before it, e.g. when playout joins a clip in the middle.
+    Start(f64),
+    /// Seconds before the last frame of the clip.
+    End(f64),
+}
+
+#[derive(Debug, Clone)]
+pub enum ClipEventAction {
+    Text(Box<TextConfig>),
+    Logo {
+        visible: bool,
+    },
+    /// Called once from the playout thread when the frame is composited; it
+    /// should return quickly.
+    Callback(ClipEventCallback),
+}
+
+#[derive(Clone)]
+pub struct ClipEventCallback(Arc<dyn Fn() + Send + Sync>);
+
+impl fmt::Debug for ClipEventCallback {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("ClipEventCallback(..)")
+    }
+}
+
+impl ClipEventCallback {
+    pub fn new(callback: impl Fn() + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+
+    pub fn invoke(&self) {
+        (self.0)();
+    }
+}
+
+/// Ken Burns move from one image region to another.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ImageMotion {
+    pub start: MotionRect,
+    pub end: MotionRect,
+    pub easing: MotionEasing,
+}
+
+/// Image region as fractions of the image size. The region is cropped
+/// around its center to the output aspect ratio before it is shown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotionRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Default for MotionRect {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: 1.0,
+            height: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MotionEasing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl MotionEasing {
+    /// Map linear progress in `0.0..=1.0` to eased progress.
+    pub fn apply(self, progress: f64) -> f64 {
+        let t = progress.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t,
+            Self::EaseOut => t * (2.0 - t),
+            Self::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct TextConfig {
     pub text: Option<String>,
@@ -897,10 +1097,15 @@ pub struct TextConfig {
     pub position_x: TextPosition,
     pub position_y: TextPosition,
     pub background: Option<TextBackgroundConfig>,
+    /// Read `<b>`, `<i>`, `<color>`, `<size>` and `<br>` tags in the text.
+    pub markup: bool,
+    pub outline: Option<TextOutline>,
+    pub shadow: Option<TextShadow>,
     pub scroll: TextScroll,
     pub scroll_repeat: i32,
     pub fade_in_seconds: f64,
     pub fade_out_seconds: f64,
+    pub animation: TextAnimation,
 }
 
 impl Default for TextConfig {
@@ -918,10 +1123,14 @@ impl Default for TextConfig {
             position_x: TextPosition::Pixels(32),
             position_y: TextPosition::Pixels(32),
             background: None,
+            markup: false,
+            outline: None,
+            shadow: None,
             scroll: TextScroll::None,
             scroll_repeat: -1,
             fade_in_seconds: 0.0,
             fade_out_seconds: 0.0,
+            animation: TextAnimation::default(),
         }
     }
 }
@@ -961,11 +1170,69 @@ pub struct TextBackgroundConfig {
     pub padding: u32,
 }
 
+/// Border around the glyphs, `width` pixels wide.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextOutline {
+    pub color: RgbaColor,
+    pub width: u32,
+}
+
+/// Drop shadow of the glyphs and their outline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextShadow {
+    pub color: RgbaColor,
+    pub offset_x: i32,
+    pub offset_y: i32,
+    /// Blur radius in pixels.
+    pub blur: u32,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TextScroll {
     None,
-    LeftToRight { pixels_per_second: u32 },
-    RightToLeft { pixels_per_second: u32 },
+    LeftToRight {
+        pixels_per_second: u32,
+    },
+    RightToLeft {
+        pixels_per_second: u32,
+    },
+    /// Credits roll, the text moves up from below the screen.
+    BottomToTop {
+        pixels_per_second: u32,
+    },
+    TopToBottom {
+        pixels_per_second: u32,
+    },
+}
+
+/// Entrance and exit of a text. Slides start when the text appears and
+/// end with the clip, like the fades.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TextAnimation {
+    pub reveal: TextReveal,
+    pub slide_in: TextSlide,
+    pub slide_out: TextSlide,
+    pub slide_seconds: f64,
+    pub easing: MotionEasing,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum TextReveal {
+    #[default]
+    None,
+    /// Show the text character by character.
+    Typewriter { characters_per_second: f64 },
+}
+
+/// Screen edge a text slides in from or out to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextSlide {
+    #[default]
+    None,
+    Left,
+    Right,
+    Top,
+    Bottom,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -976,22 +1243,77 @@ pub struct TextOverlayState {
 #[derive(Debug, Clone, Default)]
 struct TextOverlayStateInner {
     revision: u64,
+    /// Bumped with every [`TextOverlayState::set`], tells the overlays of
+    /// different senders apart.
+    owner: u64,
     config: Option<TextConfig>,
     start_pts: Option<i64>,
+    /// Text waiting for the crawl of the current one to start a new pass.
+    pending_text: Option<String>,
 }
 
 impl TextOverlayState {
-    pub fn set(&self, config: Option<TextConfig>) {
+    /// Put `config` on air, replacing any overlay.
+    ///
+    /// Returns the owner of the new overlay, for [`Self::set_text`] and
+    /// [`Self::clear_own`].
+    pub fn set(&self, config: Option<TextConfig>) -> u64 {
         let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
         inner.revision = inner.revision.wrapping_add(1);
+        inner.owner = inner.owner.wrapping_add(1);
         inner.config = config;
         inner.start_pts = None;
+        inner.pending_text = None;
+        inner.owner
     }
 
     pub fn clear(&self) {
         self.set(None);
     }
 
+    /// Clear the overlay, unless it was replaced since `owner` set it.
+    pub fn clear_own(&self, owner: u64) {
+        let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        if inner.owner == owner && inner.config.is_some() {
+            inner.revision = inner.revision.wrapping_add(1);
+            inner.config = None;
+            inner.start_pts = None;
+            inner.pending_text = None;
+        }
+    }
+
+    /// Replace the text of the overlay that `owner` set and keep its style.
+    /// A crawling text keeps moving and the new text follows with the next
+    /// pass, a static text changes with the next frame.
+    ///
+    /// Returns `false` when no overlay is set or it was replaced since.
+    pub fn set_text(&self, owner: u64, text: String) -> bool {
+        let mut guard = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        let inner = &mut *guard;
+        let Some(config) = &inner.config else {
+            return false;
+        };
+        if inner.owner != owner {
+            return false;
+        }
+        inner.pending_text =
+            (config.use_filename || config.text.as_deref() != Some(&text)).then_some(text);
+        true
+    }
+
+    /// Put the pending text on air, with its crawl starting at `pts`.
+    pub(crate) fn apply_text(&self, pts: i64) {
+        let mut guard = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        let inner = &mut *guard;
+        let (Some(text), Some(config)) = (inner.pending_text.take(), &mut inner.config) else {
+            return;
+        };
+        config.text = Some(text);
+        config.use_filename = false;
+        inner.revision = inner.revision.wrapping_add(1);
+        inner.start_pts = Some(pts);
+    }
+
     pub(crate) fn snapshot_at(&self, pts: i64) -> TextOverlaySnapshot {
         // Fast path with a read lock: this is called once per rendered frame,
         // the write lock is only needed right after a new config was set.
@@ -1002,6 +1324,7 @@ impl TextOverlayState {
                     revision: inner.revision,
                     config: inner.config.clone(),
                     start_pts: inner.start_pts,
+                    pending_text: inner.pending_text.is_some(),
                 };
             }
         }
@@ -1014,6 +1337,7 @@ impl TextOverlayState {
             revision: inner.revision,
             config: inner.config.clone(),
             start_pts: inner.start_pts,
+            pending_text: inner.pending_text.is_some(),
         }
     }
 }
@@ -1023,6 +1347,114 @@ pub(crate) struct TextOverlaySnapshot {
     pub revision: u64,
     pub config: Option<TextConfig>,
     pub start_pts: Option<i64>,
+    pub pending_text: bool,
+}
+
+/// One entry of the runtime graphics layer stack.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphicLayer {
+    pub id: String,
+    /// Layers are drawn in ascending order, the highest value ends up on top.
+    pub z_index: i32,
+    /// Multiplied with the opacity of the content, 0.0 to 1.0.
+    pub opacity: f64,
+    pub visible: bool,
+    pub fade_in_seconds: f64,
+    pub fade_out_seconds: f64,
+    pub content: GraphicLayerContent,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphicLayerContent {
+    /// Logo or any other still image, scaled and placed like the channel logo.
+    Image(LogoConfig),
+    /// Animated image or alpha video, decoded in the background.
+    Animation(AnimationConfig),
+    Text(TextConfig),
+}
+
+/// GIF, APNG, WebM with VP8/VP9 alpha, ProRes 4444 or any other video. The
+/// animation starts over whenever its layer is shown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationConfig {
+    pub path: String,
+    pub scale: Option<String>,
+    pub position: String,
+    /// Loop forever instead of holding the last frame.
+    pub looping: bool,
+}
+
+/// Properties that can change without rendering the layer again.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GraphicLayerUpdate {
+    pub z_index: Option<i32>,
+    pub opacity: Option<f64>,
+    pub visible: Option<bool>,
+}
+
+/// Second picture shown together with the program.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PictureInPictureConfig {
+    pub source: PipSource,
+    pub layout: PipLayout,
+    /// Window of the second source, or of the program in squeeze-back.
+    pub rect: PipRect,
+    /// Time to move in, out or to a changed rect.
+    pub transition_seconds: f64,
+    pub easing: MotionEasing,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipSource {
+    /// Media file, stream URL or `generator://` source. Files loop.
+    Media(String),
+    /// The live ingest of the channel. While it is shown here it does not
+    /// replace the program.
+    Ingest,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PipLayout {
+    /// The second source in a window over the program.
+    #[default]
+    Window,
+    /// The program shrinks into the rect and the second source fills the
+    /// rest of the frame.
+    SqueezeBack,
+}
+
+/// Output region as fractions of the output size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PipRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Default for PipRect {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+impl PipRect {
+    pub const FULL: Self = Self {
+        x: 0.0,
+        y: 0.0,
+        width: 1.0,
+        height: 1.0,
+    };
+
+    pub(crate) fn lerp(self, to: Self, progress: f64) -> Self {
+        let mix = |from: f64, to: f64| from + (to - from) * progress;
+        Self {
+            x: mix(self.x, to.x),
+            y: mix(self.y, to.y),
+            width: mix(self.width, to.width),
+            height: mix(self.height, to.height),
+        }
+    }
 }
 
 impl OutputConfig {
@@ -1041,8 +1473,20 @@ impl OutputConfig {
             logo: None,
             text: None,
             text_overlay_state: TextOverlayState::default(),
+            graphics_layers: GraphicsLayers::default(),
+            picture_in_picture: PictureInPicture::default(),
+            live_take: LiveTake::default(),
+            ingest_failover: IngestFailover::default(),
+            publish_authorizer: None,
+            live_recorder: LiveRecorder::default(),
+            live_delay: LiveDelay::default(),
+            signal_monitor: SignalMonitor::default(),
+            text_placeholders: TextPlaceholders::default(),
             stream_type: StreamType::Rtmp,
             stream_format: String::new(),
+            clip_metadata: false,
+            audio_only: false,
+            slate: None,
             video_codec: "libx264".to_string(),
             video_options: video_option_defaults("libx264"),
             audio_codec: "aac".to_string(),
@@ -1095,6 +1539,56 @@ impl OutputConfig {
         self
     }
 
+    pub fn with_graphics_layers(mut self, graphics_layers: GraphicsLayers) -> Self {
+        self.graphics_layers = graphics_layers;
+        self
+    }
+
+    pub fn with_picture_in_picture(mut self, picture_in_picture: PictureInPicture) -> Self {
+        self.picture_in_picture = picture_in_picture;
+        self
+    }
+
+    pub fn with_live_take(mut self, live_take: LiveTake) -> Self {
+        self.live_take = live_take;
+        self
+    }
+
+    pub fn with_ingest_failover(mut self, ingest_failover: IngestFailover) -> Self {
+        self.ingest_failover = ingest_failover;
+        self
+    }
+
+    pub fn with_publish_authorizer(mut self, authorizer: Option<PublishAuthorizer>) -> Self {
+        self.publish_authorizer = authorizer;
+        self
+    }
+
+    pub fn with_live_recorder(mut self, live_recorder: LiveRecorder) -> Self {
+        self.live_recorder = live_recorder;
+        self
+    }
+
+    pub fn with_live_delay(mut self, live_delay: LiveDelay) -> Self {
+        self.live_delay = live_delay;
+        self
+    }
+
+    pub fn with_signal_monitor(mut self, signal_monitor: SignalMonitor) -> Self {
+        self.signal_monitor = signal_monitor;
+        self
+    }
+
+    pub fn with_text_placeholders(mut self, text_placeholders: TextPlaceholders) -> Self {
+        self.text_placeholders = text_placeholders;
+        self
+    }
+
+    pub fn with_slate(mut self, slate: Option<SlateConfig>) -> Self {
+        self.slate = slate;
+        self
+    }
+
     pub fn with_stream_type(mut self, stream_type: StreamType) -> Self {
         self.stream_type = stream_type;
         self
@@ -1105,6 +1599,16 @@ impl OutputConfig {
         self
     }
 
+    pub fn with_clip_metadata(mut self, clip_metadata: bool) -> Self {
+        self.clip_metadata = clip_metadata;
+        self
+    }
+
+    pub fn with_audio_only(mut self, audio_only: bool) -> Self {
+        self.audio_only = audio_only;
+        self
+    }
+
     pub fn with_encoding(
         mut self,
         video_codec: String,
@@ -1187,10 +1691,84 @@ impl FromStr for OutputSize {
 #[cfg(test)]
 mod tests {
     use super::{
-        OutputSize, audio_codec_uses_bitrate, validate_video_options, video_codec_uses_bitrate,
+        ClipBranding, LogoConfig, OutputSize, TextConfig, TextOverlayState,
+        audio_codec_uses_bitrate, validate_video_options, video_codec_uses_bitrate,
         video_option_defaults,
     };
 
+    #[test]
+    fn text_update_waits_until_applied() {
+        let state = TextOverlayState::default();
+        assert!(!state.set_text(0, "first".to_string()));
+
+        let owner = state.set(Some(TextConfig {
+            text: Some("first".to_string()),
+            ..TextConfig::default()
+        }));
+        let started = state.snapshot_at(10);
+        assert!(state.set_text(owner, "first".to_string()));
+        assert!(!state.snapshot_at(11).pending_text);
+
+        assert!(state.set_text(owner, "second".to_string()));
+        let pending = state.snapshot_at(12);
+        assert!(pending.pending_text);
+        assert_eq!(pending.revision, started.revision);
+
+        state.apply_text(40);
+        let applied = state.snapshot_at(41);
+        assert!(!applied.pending_text);
+        assert_ne!(applied.revision, started.revision);
+        assert_eq!(applied.start_pts, Some(40));
+        assert_eq!(applied.config.unwrap().text.as_deref(), Some("second"));
+    }
+
+    #[test]
+    fn text_of_another_sender_is_kept() {
+        let state = TextOverlayState::default();
+        let text = |text: &str| {
+            Some(TextConfig {
+                text: Some(text.to_string()),
+                ..TextConfig::default()
+            })
+        };
+
+        let ticker = state.set(text("headlines"));
+        let message = state.set(text("message"));
+        assert!(!state.set_text(ticker, "more headlines".to_string()));
+
+        state.clear_own(ticker);
+        let snapshot = state.snapshot_at(0);
+        assert!(!snapshot.pending_text);
+        assert_eq!(snapshot.config.unwrap().text.as_deref(), Some("message"));
+
+        state.clear_own(message);
+        assert!(state.snapshot_at(1).config.is_none());
+    }
+
+    #[test]
+    fn clip_branding_replaces_or_hides_the_channel_logo() {
+        let logo = |path: &str| LogoConfig {
+            path: path.to_string(),
+            scale: None,
+            opacity: 1.0,
+            position: "W-w-12:12".to_string(),
+        };
+        let channel = logo("logo.png");
+        let kids = ClipBranding {
+            logo: Some(logo("kids.png")),
+            ..ClipBranding::default()
+        };
+
+        assert_eq!(ClipBranding::default().logo(Some(&channel)), Some(&channel));
+        assert_eq!(kids.logo(Some(&channel)).unwrap().path, "kids.png");
+        assert_eq!(kids.logo(None).unwrap().path, "kids.png");
+        let hidden = ClipBranding {
+            hide_logo: true,
+            ..kids
+        };
+        assert_eq!(hidden.logo(Some(&channel)), None);
+    }
+
     #[test]
     fn parses_output_size_with_colon() {
         let size = "1280:720".parse::<OutputSize>().unwrap();
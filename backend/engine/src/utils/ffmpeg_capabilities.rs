@@ -6,6 +6,8 @@ use std::{
 
 use ffmpeg_next::{codec, ffi, format::Pixel, media};
 
+use crate::utils::config::icecast_muxer;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FfmpegCapabilities {
     pub features: FfmpegFeatureSet,
@@ -108,6 +110,7 @@ pub enum FfmpegOutputTarget {
     Rtmp,
     Srt,
     Udp,
+    Icecast,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -225,6 +228,9 @@ fn output_muxer_supports_codec(
     if matches!(target, FfmpegOutputTarget::Srt | FfmpegOutputTarget::Udp) {
         return mpegts_output_supports_codec(codec);
     }
+    if target == FfmpegOutputTarget::Icecast {
+        return icecast_output_supports_codec(codec);
+    }
 
     muxer_supports_codec(muxer_name, codec.codec_id_raw)
 }
@@ -234,6 +240,7 @@ fn muxer_for_target(target: FfmpegOutputTarget) -> Option<&'static str> {
         FfmpegOutputTarget::Hls => Some("hls"),
         FfmpegOutputTarget::Rtmp => Some("flv"),
         FfmpegOutputTarget::Srt | FfmpegOutputTarget::Udp => Some("mpegts"),
+        FfmpegOutputTarget::Icecast => Some("mp3"),
     }
 }
 
@@ -254,6 +261,14 @@ fn output_supports_codec(target: FfmpegOutputTarget, codec: &FfmpegCodec) -> boo
     }
 }
 
+/// Icecast carries audio only; each supported codec maps to the container
+/// picked by [`icecast_muxer`].
+fn icecast_output_supports_codec(codec: &FfmpegCodec) -> bool {
+    codec.media_type == FfmpegMediaType::Audio
+        && matches!(codec.codec_id.as_str(), "mp3" | "aac" | "opus")
+        && muxer_supports_codec(icecast_muxer(&codec.codec_id), codec.codec_id_raw)
+}
+
 fn mpegts_output_supports_codec(codec: &FfmpegCodec) -> bool {
     match codec.media_type {
         FfmpegMediaType::Audio => matches!(
@@ -451,6 +466,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn icecast_target_offers_audio_codecs_only() {
+        let capabilities = FfmpegCapabilities::detect();
+        let icecast_audio = capabilities.audio_codecs_for(FfmpegOutputTarget::Icecast);
+
+        assert!(
+            capabilities
+                .video_codecs_for(FfmpegOutputTarget::Icecast)
+                .is_empty()
+        );
+        assert!(
+            icecast_audio
+                .iter()
+                .all(|codec| matches!(codec.codec_id.as_str(), "mp3" | "aac" | "opus")),
+            "Icecast audio codecs should only include MP3/AAC/Opus encoders: {icecast_audio:#?}"
+        );
+        assert!(
+            icecast_audio.iter().any(|codec| codec.codec_id == "aac"),
+            "Icecast audio codecs should include AAC encoders: {icecast_audio:#?}"
+        );
+    }
+
     #[test]
     fn cached_capabilities_are_available() {
         let capabilities = ffmpeg_capabilities();
@@ -33,9 +33,12 @@ const SKIPPED_FFMPEG_LOG_MESSAGES: &[&str] = &[
     r"Error parsing Opus packet header.",
 ];
 const LOG_DEDUP_FLUSH_THRESHOLD: usize = 100;
+/// Verbose line FFmpeg's SRT listener writes for each caller.
+const SRT_ACCEPT_FORMAT: &[u8] = b"accept streamid [";
 
 thread_local! {
     static UNEXPECTED_RTMP_STREAM: RefCell<Option<(String, String)>> = const { RefCell::new(None) };
+    static ACCEPTED_SRT_STREAM_ID: RefCell<Option<String>> = const { RefCell::new(None) };
     static INGEST_LOG_CONTEXT: RefCell<Option<i32>> = const { RefCell::new(None) };
     static INGEST_INTERRUPTED: Cell<bool> = const { Cell::new(false) };
 }
@@ -118,6 +121,16 @@ pub(crate) fn take_unexpected_rtmp_stream() -> Option<(String, String)> {
     UNEXPECTED_RTMP_STREAM.with(|stream| stream.borrow_mut().take())
 }
 
+pub(crate) fn clear_accepted_srt_stream_id() {
+    ACCEPTED_SRT_STREAM_ID.with(|stream_id| {
+        *stream_id.borrow_mut() = None;
+    });
+}
+
+pub(crate) fn take_accepted_srt_stream_id() -> Option<String> {
+    ACCEPTED_SRT_STREAM_ID.with(|stream_id| stream_id.borrow_mut().take())
+}
+
 fn configured_level() -> c_int {
     INGEST_LOG_CONTEXT.with(|context| {
         if context.borrow().is_some() {
@@ -154,7 +167,14 @@ unsafe extern "C" fn log_callback(
     fmt: *const c_char,
     vl: FfmpegVaList,
 ) {
-    if level > unsafe { ffi::av_log_get_level() } || level > configured_level() {
+    // The SRT caller's stream id is only logged verbosely, read it at any
+    // log level.
+    let srt_accept = !fmt.is_null()
+        && unsafe { CStr::from_ptr(fmt) }
+            .to_bytes()
+            .starts_with(SRT_ACCEPT_FORMAT);
+    let filtered = level > unsafe { ffi::av_log_get_level() } || level > configured_level();
+    if filtered && !srt_accept {
         return;
     }
 
@@ -179,7 +199,10 @@ unsafe extern "C" fn log_callback(
         .to_string_lossy()
         .trim()
         .to_owned();
-    if message.is_empty() {
+    if srt_accept {
+        remember_accepted_srt_stream_id(&message);
+    }
+    if filtered || message.is_empty() {
         return;
     }
 
@@ -324,6 +347,20 @@ fn remember_unexpected_rtmp_stream(message: &str) {
     });
 }
 
+fn remember_accepted_srt_stream_id(message: &str) {
+    let Some(stream_id) = message
+        .split_once("accept streamid [")
+        .and_then(|(_, rest)| rest.rsplit_once("], length"))
+        .map(|(stream_id, _)| stream_id)
+    else {
+        return;
+    };
+
+    ACCEPTED_SRT_STREAM_ID.with(|accepted| {
+        *accepted.borrow_mut() = Some(stream_id.to_string());
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use ffmpeg_next::{ffi, util::log::Level as FfmpegLevel};
@@ -413,6 +450,18 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn remembers_the_stream_id_of_an_srt_caller() {
+        super::clear_accepted_srt_stream_id();
+        super::remember_accepted_srt_stream_id("accept streamid [#!::r=field1], length 12");
+
+        assert_eq!(
+            super::take_accepted_srt_stream_id(),
+            Some("#!::r=field1".to_string())
+        );
+        assert_eq!(super::take_accepted_srt_stream_id(), None);
+    }
+
     #[cfg(target_os = "windows")]
     #[test]
     fn windows_callback_receives_and_formats_ffmpeg_logs() {
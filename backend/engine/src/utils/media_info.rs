@@ -6,7 +6,10 @@ use ffmpeg_next::{
 };
 use log::{error, info};
 
-use crate::utils::helper::open_media_input;
+use crate::{
+    input::generator::{GeneratorSpec, is_generator_source},
+    utils::helper::open_media_input,
+};
 
 const SILENCE_SAMPLE_RATE: u32 = 48_000;
 const SILENCE_CHANNEL_LAYOUT: ChannelLayout = ChannelLayout::STEREO;
@@ -104,6 +107,10 @@ pub fn probe_media_info(path: &str) -> Result<MediaInfo> {
 }
 
 pub fn probe_media(path: &str) -> Result<MediaProbe> {
+    if is_generator_source(path) {
+        return GeneratorSpec::parse(path).map(|spec| spec.probe());
+    }
+
     let ictx = open_media_input(path)?;
     let format = ProbeFormat {
         duration: (ictx.duration() > 0).then_some(ictx.duration() as f64 / 1_000_000.0),
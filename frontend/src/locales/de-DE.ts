@@ -57,6 +57,11 @@ export default {
         reset: 'Playout-Zustand zurücksetzen',
         restart: 'Playout neu starten',
         next: 'Zum nächsten Clip springen',
+        signalBlack: 'schwarz {sec}s',
+        signalFrozen: 'eingefroren {sec}s',
+        signalSilent: 'still {sec}s',
+        signalClipping: 'übersteuert {percent}%',
+        signalDropped: '{count} verlorene Frames',
     },
     player: {
         start: 'Start',
@@ -209,10 +214,35 @@ export default {
         processingOverrideFilter: 'Achtung: Diese Option überschreibt alle Standardfilter, d.h. es findet keine automatische Formatkorrektur mehr statt, der Befehl muss wie folgt aufgebaut sein: -filter_complex [0:v]fps=25,scale=1280:-1[vout];[0:a:0]volume=0.5[aout] -map [vout] -map [aout]',
         processingVTTEnable: 'VTT kann nur im HLS-Modus verwendet werden und nur, wenn *.vtt-Dateien mit demselben Namen wie die Videodatei vorhanden sind.',
         processingVTTDummy: 'Ein Platzhalter wird benötigt, wenn keine vtt-Datei vorhanden ist.',
+        processingSlateEnable: 'Slate aktivieren',
+        processingSlate: 'Wird statt Schwarzbild und Stille gezeigt, wenn ein Clip fehlschlägt, der Live-Eingang hängt oder kein Füller verfügbar ist. Standbild oder Clip aus dem Speicher; Clips laufen in Schleife ohne Ton.',
+        processingSlateText: 'Slate-Text',
+        processingSlateTone: 'Slate-Ton',
+        processingSlateToneHelp: 'Einen 1-kHz-Ton statt Stille ausgeben.',
         apply: 'Anwenden',
         volumeApplied: 'Lautstärke angewendet.',
         volumeApplyFailed: 'Lautstärke konnte nicht angewendet werden.',
         ingestHelp: `Starte einen Server für einen Ingest-Stream. Dieser Stream wird den normalen Stream überschreiben, bis er beendet ist. Es gibt nur einen sehr einfachen Authentifizierungsmechanismus, der überprüft, ob der Streamname korrekt ist.`,
+        ingestPull: 'Abrufen',
+        ingestPullHelp: "Mit der Input-URL verbinden, z. B. dem RTMP-, SRT-, HLS- oder UDP-Stream eines Partners, statt auf einen Sender zu warten.",
+        ingestSchedule: 'Zeitplan',
+        ingestScheduleHelp: 'Tageszeiten, zu denen abgerufen wird, durch Kommas getrennt. Leer lassen, um immer abzurufen.',
+        ingestManual: 'Manuelle Übernahme',
+        ingestManualHelp: 'Ein eingehender Stream wird bereitgehalten und kann vorab angesehen werden, geht aber erst mit einem Take-Befehl auf Sendung.',
+        ingestBackups: 'Backup-URLs',
+        ingestBackupsHelp: 'Weitere Endpunkte nach Priorität, einer pro Zeile. Sie warten oder rufen ab wie die Input-URL und übernehmen, wenn der Endpunkt auf Sendung ausfällt.',
+        ingestFailover: 'Umschalten (Sekunden)',
+        ingestFailoverHelp: 'Wie lange der Endpunkt auf Sendung schwarz oder stumm sein darf, bevor ein Backup übernimmt. Ein wieder funktionierender Endpunkt geht nach derselben Zeit zurück auf Sendung.',
+        ingestOnPublish: 'On-Publish-URL',
+        ingestOnPublishHelp: 'Wird per POST gefragt, ob ein Sender auf Sendung darf, wenn sein Schlüssel keiner der Stream-Schlüssel des Kanals ist. Jede 2xx-Antwort lässt ihn zu.',
+        ingestRecord: 'Aufnehmen',
+        ingestRecordHelp: 'Der Stream wird aufgenommen, solange er auf Sendung ist, eine Datei pro Sendestrecke, benannt nach ihrer Startzeit.',
+        ingestRecordFolder: 'Aufnahmeordner',
+        ingestRecordFolderHelp: 'Ordner im Kanalspeicher. Fertige Aufnahmen können wie jeder andere Clip abgespielt und vom Playlist-Generator verwendet werden.',
+        ingestDelay: 'Sendeverzögerung (Sek.)',
+        ingestDelayHelp: 'Der Stream geht so viel später auf Sendung, höchstens 60 Sekunden, 0 ist aus. Ein Dump überspringt, was noch in der Verzögerung ist; sie baut sich danach wieder auf, während der Stream etwas langsamer läuft.',
+        ingestSignalFailover: 'Signal-Failover (Sek.)',
+        ingestSignalFailoverHelp: 'Der Stream geht aus der Sendung und die Playlist läuft weiter, wenn er so lange schwarz, eingefroren oder still ist; er kommt zurück, wenn er wieder so lange in Ordnung ist. 0 zeigt das Signal nur im Player an.',
         ingestCustomFilter: 'Wende einen benutzerdefinierten Filter auf den Ingest-Stream auf dieselbe Weise wie im Abschnitt Verarbeitung an.',
         playlistHelp: 'Playlist-Verwaltung.',
         playlistDayStart: 'Zu welcher Zeit die Playlist starten soll; lasse es leer, wenn die Playlist immer von Anfang an starten soll.',
@@ -57,6 +57,11 @@ export default {
         reset: 'Reset playout state',
         restart: 'Restart playout',
         next: 'Jump to next clip',
+        signalBlack: 'black {sec}s',
+        signalFrozen: 'frozen {sec}s',
+        signalSilent: 'silent {sec}s',
+        signalClipping: 'clipping {percent}%',
+        signalDropped: '{count} dropped frames',
     },
     player: {
         start: 'Start',
@@ -209,10 +214,35 @@ export default {
         processingOverrideFilter: 'Attention: This option overwrites all standard filters, i.e. automatic format correction no longer takes place, the command must be structured as follows: -filter_complex [0:v]fps=25,scale=1280:-1[vout];[0:a:0]volume=0.5[aout] -map [vout] -map [aout]',
         processingVTTEnable: 'VTT can only be used in HLS mode and only if there are *.vtt files with the same name as the video file.',
         processingVTTDummy: 'A placeholder is needed if there is no vtt file.',
+        processingSlateEnable: 'Enable slate',
+        processingSlate: 'Shown instead of black frames and silence when a clip fails, the live input stalls or no filler is available. Still image or clip from the storage; clips loop without sound.',
+        processingSlateText: 'Slate text',
+        processingSlateTone: 'Slate tone',
+        processingSlateToneHelp: 'Play a 1 kHz tone instead of silence.',
         apply: 'Apply',
         volumeApplied: 'Volume applied.',
         volumeApplyFailed: 'Failed to apply volume.',
         ingestHelp: `Run a server for an ingest stream. This stream will override the normal streaming until it is finished. There is only a very simple authentication mechanism, which checks if the stream name is correct.`,
+        ingestPull: 'Pull',
+        ingestPullHelp: "Connect to the input URL, like a partner's RTMP, SRT, HLS or UDP stream, instead of waiting for a publisher.",
+        ingestSchedule: 'Schedule',
+        ingestScheduleHelp: 'Times of day the pull is active, separated by commas. Leave it empty to keep it active.',
+        ingestManual: 'Manual take',
+        ingestManualHelp: 'An incoming feed is armed and can be previewed, but only goes on air with a take command.',
+        ingestBackups: 'Backup URLs',
+        ingestBackupsHelp: 'Further endpoints in priority order, one per line. They listen or pull like the input URL and take over when the endpoint on air drops.',
+        ingestFailover: 'Failover (seconds)',
+        ingestFailoverHelp: 'How long the endpoint on air may be black or silent before a backup takes over. A recovered endpoint goes back on air after the same time.',
+        ingestOnPublish: 'On publish URL',
+        ingestOnPublishHelp: 'Asked with a POST whether a publisher may go on air, when its key is not one of the channel stream keys. Any 2xx answer lets it on.',
+        ingestRecord: 'Record',
+        ingestRecordHelp: 'The feed is recorded while it is on air, one file per stretch on air, named by its start time.',
+        ingestRecordFolder: 'Record folder',
+        ingestRecordFolderHelp: 'Folder in the channel storage. Finished recordings can be played and used by the playlist generator like any other clip.',
+        ingestDelay: 'Broadcast delay (sec)',
+        ingestDelayHelp: 'The feed goes on air this much later, up to 60 seconds, 0 is off. A dump skips what is still in the delay; it then builds up again while the feed plays slightly slow.',
+        ingestSignalFailover: 'Signal failover (sec)',
+        ingestSignalFailoverHelp: 'The feed goes off air and the playlist continues when it is black, frozen or silent this long; it comes back when it is fine for as long. 0 only reports the signal in the player.',
         ingestCustomFilter: 'Apply a custom filter to the Ingest stream in the same way as in the Processing section.',
         playlistHelp: 'Playlist handling.',
         playlistDayStart: 'At what time the playlist should start; leave it blank if the playlist should always start at the beginning.',
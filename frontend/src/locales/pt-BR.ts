@@ -57,6 +57,11 @@ export default {
         reset: 'Redefinir Estado do Playout',
         restart: 'Reiniciar Serviço do Playout',
         next: 'Ir para o próximo Clipe',
+        signalBlack: 'preto {sec}s',
+        signalFrozen: 'congelado {sec}s',
+        signalSilent: 'silêncio {sec}s',
+        signalClipping: 'saturação {percent}%',
+        signalDropped: '{count} quadros perdidos',
     },
     player: {
         start: 'Horário',
@@ -209,10 +214,35 @@ export default {
         processingOverrideFilter: 'Attention: This option overwrites all standard filters, i.e. automatic format correction no longer takes place, the command must be structured as follows: -filter_complex [0:v]fps=25,scale=1280:-1[vout];[0:a:0]volume=0.5[aout] -map [vout] -map [aout]',
         processingVTTEnable: 'VTT só pode ser usado no modo HLS e apenas se houver arquivos *.vtt com o mesmo nome do arquivo de vídeo.',
         processingVTTDummy: 'Um espaço reservado é necessário se não houver arquivo vtt.',
+        processingSlateEnable: 'Ativar slate',
+        processingSlate: 'Exibido no lugar de quadros pretos e silêncio quando um clipe falha, a entrada ao vivo trava ou não há filler disponível. Imagem estática ou clipe do armazenamento; clipes repetem sem som.',
+        processingSlateText: 'Texto do slate',
+        processingSlateTone: 'Tom do slate',
+        processingSlateToneHelp: 'Reproduzir um tom de 1 kHz em vez de silêncio.',
         apply: 'Aplicar',
         volumeApplied: 'Volume aplicado.',
         volumeApplyFailed: 'Falha ao aplicar o volume.',
         ingestHelp: `Execute um servidor para um fluxo de ingestão. Este fluxo substituirá o streaming normal até que termine. Há apenas um mecanismo de autenticação simples que verifica se o nome do fluxo está correto.`,
+        ingestPull: 'Puxar',
+        ingestPullHelp: "Conectar à URL de entrada, como um fluxo RTMP, SRT, HLS ou UDP de um parceiro, em vez de esperar por um publicador.",
+        ingestSchedule: 'Agenda',
+        ingestScheduleHelp: 'Horários do dia em que a conexão fica ativa, separados por vírgulas. Deixe vazio para mantê-la sempre ativa.',
+        ingestManual: 'Entrada manual',
+        ingestManualHelp: 'Um sinal recebido fica armado e pode ser pré-visualizado, mas só vai ao ar com um comando de take.',
+        ingestBackups: 'URLs de reserva',
+        ingestBackupsHelp: 'Outros pontos de entrada por ordem de prioridade, um por linha. Eles escutam ou conectam como a URL de entrada e assumem quando o ponto no ar cai.',
+        ingestFailover: 'Troca (segundos)',
+        ingestFailoverHelp: 'Quanto tempo o ponto no ar pode ficar preto ou mudo antes que uma reserva assuma. Um ponto recuperado volta ao ar após o mesmo tempo.',
+        ingestOnPublish: 'URL de on publish',
+        ingestOnPublishHelp: 'Consultada com um POST se um transmissor pode ir ao ar, quando sua chave não é nenhuma das chaves de transmissão do canal. Qualquer resposta 2xx o permite.',
+        ingestRecord: 'Gravar',
+        ingestRecordHelp: 'O sinal é gravado enquanto está no ar, um arquivo por trecho no ar, nomeado pelo horário de início.',
+        ingestRecordFolder: 'Pasta de gravação',
+        ingestRecordFolderHelp: 'Pasta no armazenamento do canal. Gravações finalizadas podem ser reproduzidas e usadas pelo gerador de playlist como qualquer outro clipe.',
+        ingestDelay: 'Atraso de transmissão (seg)',
+        ingestDelayHelp: 'O sinal vai ao ar com este atraso, até 60 segundos, 0 desliga. Um dump descarta o que ainda está no atraso; ele volta a se formar enquanto o sinal toca um pouco mais lento.',
+        ingestSignalFailover: 'Failover de sinal (seg)',
+        ingestSignalFailoverHelp: 'O sinal sai do ar e a playlist continua quando fica preto, congelado ou em silêncio por este tempo; ele volta quando estiver bom pelo mesmo tempo. 0 apenas mostra o sinal no player.',
         ingestCustomFilter: 'Aplique um filtro personalizado ao fluxo de ingestão da mesma forma que na seção de Processamento.',
         playlistHelp: 'Gerenciamento de playlist.',
         playlistDayStart: 'A que horas a playlist deve começar; deixe em branco se a playlist sempre começar do início.',
@@ -57,6 +57,11 @@ export default {
         reset: 'Сбросить Playout State',
         restart: 'Перезапустить Playout',
         next: 'Переключить на следующий файл',
+        signalBlack: 'black {sec}s',
+        signalFrozen: 'frozen {sec}s',
+        signalSilent: 'silent {sec}s',
+        signalClipping: 'clipping {percent}%',
+        signalDropped: '{count} dropped frames',
     },
     player: {
         start: 'Начало',
@@ -216,10 +221,35 @@ export default {
         processingVTTEnable:
             'VTT can only be used in HLS mode and only if there are *.vtt files with the same name as the video file.',
         processingVTTDummy: 'A placeholder is needed if there is no vtt file.',
+        processingSlateEnable: 'Включить заставку',
+        processingSlate: 'Показывается вместо чёрного кадра и тишины, если клип не воспроизводится, живой вход завис или нет заполнителя. Изображение или клип из хранилища; клипы повторяются без звука.',
+        processingSlateText: 'Текст заставки',
+        processingSlateTone: 'Тон заставки',
+        processingSlateToneHelp: 'Воспроизводить тон 1 кГц вместо тишины.',
         apply: 'Применить',
         volumeApplied: 'Громкость применена.',
         volumeApplyFailed: 'Не удалось применить громкость.',
         ingestHelp: `Run a server for an ingest stream. This stream will override the normal streaming until it is finished. There is only a very simple authentication mechanism, which checks if the stream name is correct.`,
+        ingestPull: 'Pull',
+        ingestPullHelp: "Connect to the input URL, like a partner's RTMP, SRT, HLS or UDP stream, instead of waiting for a publisher.",
+        ingestSchedule: 'Schedule',
+        ingestScheduleHelp: 'Times of day the pull is active, separated by commas. Leave it empty to keep it active.',
+        ingestManual: 'Manual take',
+        ingestManualHelp: 'An incoming feed is armed and can be previewed, but only goes on air with a take command.',
+        ingestBackups: 'Backup URLs',
+        ingestBackupsHelp: 'Further endpoints in priority order, one per line. They listen or pull like the input URL and take over when the endpoint on air drops.',
+        ingestFailover: 'Failover (seconds)',
+        ingestFailoverHelp: 'How long the endpoint on air may be black or silent before a backup takes over. A recovered endpoint goes back on air after the same time.',
+        ingestOnPublish: 'On publish URL',
+        ingestOnPublishHelp: 'Asked with a POST whether a publisher may go on air, when its key is not one of the channel stream keys. Any 2xx answer lets it on.',
+        ingestRecord: 'Record',
+        ingestRecordHelp: 'The feed is recorded while it is on air, one file per stretch on air, named by its start time.',
+        ingestRecordFolder: 'Record folder',
+        ingestRecordFolderHelp: 'Folder in the channel storage. Finished recordings can be played and used by the playlist generator like any other clip.',
+        ingestDelay: 'Broadcast delay (sec)',
+        ingestDelayHelp: 'The feed goes on air this much later, up to 60 seconds, 0 is off. A dump skips what is still in the delay; it then builds up again while the feed plays slightly slow.',
+        ingestSignalFailover: 'Signal failover (sec)',
+        ingestSignalFailoverHelp: 'The feed goes off air and the playlist continues when it is black, frozen or silent this long; it comes back when it is fine for as long. 0 only reports the signal in the player.',
         ingestCustomFilter: 'Apply a custom filter to the Ingest stream in the same way as in the Processing section.',
         playlistHelp: 'Playlist handling.',
         playlistDayStart:
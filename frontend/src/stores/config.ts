@@ -23,6 +23,7 @@ export const useConfig = defineStore('config', {
             rtmp: { video: [], audio: [] },
             srt: { video: [], audio: [] },
             udp: { video: [], audio: [] },
+            icecast: { video: [], audio: [] },
             custom: { video: [], audio: [] },
         } as PlayoutCodecOptions,
         currentUser: 0,
@@ -28,6 +28,7 @@ export const usePlaylist = defineStore('playlist', {
         currentIndex: 0,
         ingestRuns: false,
         audioLevel: null as AudioLevel | null,
+        ingestSignal: null as IngestSignal | null,
         elapsedSec: 0,
         shift: 0,
         playoutIsRunning: false,
@@ -98,6 +99,7 @@ export const usePlaylist = defineStore('playlist', {
             this.elapsedSec = item.elapsed
             this.ingestRuns = item.ingest
             this.audioLevel = item.audio ?? null
+            this.ingestSignal = item.signal ?? null
             this.shift = item.shift
 
             this.progressValue = (this.elapsedSec * 100) / this.current.out
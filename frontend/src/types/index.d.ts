@@ -31,6 +31,7 @@ declare global {
         hls_segment_duration: number | null
         hls_list_size: number | null
         desktop_fullscreen: boolean
+        clip_metadata: boolean
         width: number
         height: number
         fps: number
@@ -81,6 +82,7 @@ declare global {
         rtmp: OutputCodecOptions
         srt: OutputCodecOptions
         udp: OutputCodecOptions
+        icecast: OutputCodecOptions
         custom: OutputCodecOptions
     }
 
@@ -104,12 +106,29 @@ declare global {
         background_opacity: number
         background_padding: number
         opacity: number
-        scroll_direction: 'none' | 'left_to_right' | 'right_to_left'
+        scroll_direction: 'none' | 'left_to_right' | 'right_to_left' | 'bottom_to_top' | 'top_to_bottom'
         scroll_speed: number
         scroll_repeat: number
         fade_in_seconds: number
         fade_out_seconds: number
-    }
+        reveal: 'none' | 'typewriter'
+        reveal_speed: number
+        slide_in: TextSlide
+        slide_out: TextSlide
+        slide_seconds: number
+        easing: 'linear' | 'ease_in' | 'ease_out' | 'ease_in_out'
+        markup: boolean
+        outline_width: number
+        outline_color: string
+        shadow_enabled: boolean
+        shadow_color: string
+        shadow_opacity: number
+        shadow_offset_x: number
+        shadow_offset_y: number
+        shadow_blur: number
+    }
+
+    type TextSlide = 'none' | 'left' | 'right' | 'top' | 'bottom'
 
     interface Token {
         access: string
@@ -240,6 +259,7 @@ declare global {
         media: PlaylistItem
         index: number
         audio?: AudioLevel
+        signal?: IngestSignal
         ingest: boolean
         mode: string
         elapsed: number
@@ -252,6 +272,16 @@ declare global {
         peak_db: number
     }
 
+    interface IngestSignal {
+        black: number
+        frozen: number
+        silent: number
+        clipping: number
+        dropped_frames: number
+        bitrate: number
+        failed: boolean
+    }
+
     interface SplitTime {
         id: number
         val: number
@@ -2,13 +2,60 @@
 
 export type General = { stop_threshold: number, };
 
-export type Ingest = { enable: boolean, ingest_url: string, };
+export type Ingest = { enable: boolean, ingest_url: string, 
+/**
+ * Connect to `ingest_url` instead of listening on it.
+ */
+pull: boolean, 
+/**
+ * Times of day a pull is armed, like `08:00-09:30, 18:00-19:00`. Empty
+ * keeps it armed.
+ */
+schedule: string, 
+/**
+ * An incoming feed waits off air until it is taken.
+ */
+manual: boolean, 
+/**
+ * Backup endpoints in priority order, separated by commas or lines.
+ * They are listeners or pulls like `ingest_url`.
+ */
+backup_urls: string, 
+/**
+ * Seconds the endpoint on air may be black or silent before a backup
+ * takes over, and a recovered endpoint has to be fine before it goes
+ * back on air.
+ */
+failover_seconds: number, 
+/**
+ * URL that is asked whether a publisher may go on air. Empty only
+ * checks the channel's stream keys.
+ */
+on_publish: string, 
+/**
+ * Record the ingest while it is on air.
+ */
+record: boolean, 
+/**
+ * Folder in the channel storage the recordings go to.
+ */
+record_folder: string, 
+/**
+ * Broadcast delay in seconds, 0 is off. What is still in the delay can
+ * be dumped before it goes on air.
+ */
+delay: number, 
+/**
+ * Seconds a feed may be black, frozen or silent before the program
+ * returns to the playlist, 0 only reports the signal.
+ */
+signal_failover: number, };
 
 export type Logging = { ffmpeg_level: string, ingest_level: string, detect_silence: boolean, ignore_lines: Array<string>, };
 
 export type Mail = { show: boolean, subject: string, recipient: string, mail_level: string, interval: bigint, };
 
-export type Output = { id: number, mode: OutputMode, stream_url: string, stream_type: StreamType, stream_format: string, hls_playlist_name: string, hls_segment_duration: number, hls_list_size: number, desktop_fullscreen: boolean, width: number, height: number, fps: number, video_codec: string, video_options: { [key in string]: string }, audio_codec: string, audio_bitrate: number, 
+export type Output = { id: number, mode: OutputMode, stream_url: string, stream_type: StreamType, stream_format: string, hls_playlist_name: string, hls_segment_duration: number, hls_list_size: number, desktop_fullscreen: boolean, clip_metadata: boolean, width: number, height: number, fps: number, video_codec: string, video_options: { [key in string]: string }, audio_codec: string, audio_bitrate: number, 
 /**
  * Adaptive HLS renditions, one per entry, each formatted as
  * `NAME:WIDTHxHEIGHT:VIDEO_BITRATE[:AUDIO_BITRATE]` (e.g.
@@ -31,11 +78,11 @@ export type PlayoutConfig = { general: General, mail: Mail, logging: Logging, pr
 
 export type ProcessMode = "folder" | "playlist";
 
-export type Processing = { mode: ProcessMode, add_logo: boolean, logo: string, logo_scale: string, logo_opacity: number, logo_position: string, volume: number, vtt_enable: boolean, vtt_dummy: string | null, vtt_name: string, vtt_language: string, vtt_default: boolean, };
+export type Processing = { mode: ProcessMode, add_logo: boolean, logo: string, logo_scale: string, logo_opacity: number, logo_position: string, volume: number, vtt_enable: boolean, vtt_dummy: string | null, vtt_name: string, vtt_language: string, vtt_default: boolean, slate_enable: boolean, slate: string, slate_text: string, slate_tone: boolean, };
 
 export type Storage = { filler: string, extensions: Array<string>, shuffle: boolean, shared_storage: boolean, };
 
-export type StreamType = "rtmp" | "srt" | "udp" | "custom";
+export type StreamType = "rtmp" | "srt" | "udp" | "icecast" | "custom";
 
 export type Task = { enable: boolean, path: string, };
 
@@ -0,0 +1,4 @@
+ALTER TABLE configurations ADD COLUMN processing_slate_enable INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE configurations ADD COLUMN processing_slate TEXT NOT NULL DEFAULT '';
+ALTER TABLE configurations ADD COLUMN processing_slate_text TEXT NOT NULL DEFAULT '';
+ALTER TABLE configurations ADD COLUMN processing_slate_tone INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,6 @@
+ALTER TABLE text_presets ADD COLUMN reveal TEXT NOT NULL DEFAULT 'none';
+ALTER TABLE text_presets ADD COLUMN reveal_speed REAL NOT NULL DEFAULT 20.0;
+ALTER TABLE text_presets ADD COLUMN slide_in TEXT NOT NULL DEFAULT 'none';
+ALTER TABLE text_presets ADD COLUMN slide_out TEXT NOT NULL DEFAULT 'none';
+ALTER TABLE text_presets ADD COLUMN slide_seconds REAL NOT NULL DEFAULT 0.5;
+ALTER TABLE text_presets ADD COLUMN easing TEXT NOT NULL DEFAULT 'ease_out';
@@ -0,0 +1,9 @@
+ALTER TABLE text_presets ADD COLUMN markup INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE text_presets ADD COLUMN outline_width INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE text_presets ADD COLUMN outline_color TEXT NOT NULL DEFAULT '#000000';
+ALTER TABLE text_presets ADD COLUMN shadow_enabled INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE text_presets ADD COLUMN shadow_color TEXT NOT NULL DEFAULT '#000000';
+ALTER TABLE text_presets ADD COLUMN shadow_opacity REAL NOT NULL DEFAULT 0.6;
+ALTER TABLE text_presets ADD COLUMN shadow_offset_x INTEGER NOT NULL DEFAULT 2;
+ALTER TABLE text_presets ADD COLUMN shadow_offset_y INTEGER NOT NULL DEFAULT 2;
+ALTER TABLE text_presets ADD COLUMN shadow_blur INTEGER NOT NULL DEFAULT 2;
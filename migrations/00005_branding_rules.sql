@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS branding_rules (
+    id INTEGER PRIMARY KEY,
+    channel_id INTEGER NOT NULL DEFAULT 1,
+    name TEXT NOT NULL,
+    category TEXT NOT NULL DEFAULT '',
+    hide_logo INTEGER NOT NULL DEFAULT 0,
+    logo TEXT NOT NULL DEFAULT '',
+    logo_scale TEXT NOT NULL DEFAULT '',
+    logo_opacity REAL NOT NULL DEFAULT 0.7,
+    logo_position TEXT NOT NULL DEFAULT 'W-w-12:12',
+    icon TEXT NOT NULL DEFAULT '',
+    icon_scale TEXT NOT NULL DEFAULT '',
+    icon_opacity REAL NOT NULL DEFAULT 1.0,
+    icon_position TEXT NOT NULL DEFAULT '12:12',
+    icon_seconds REAL NOT NULL DEFAULT 0.0,
+    FOREIGN KEY (channel_id) REFERENCES channels (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    UNIQUE (channel_id, name)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE configurations ADD COLUMN ingest_pull INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE configurations ADD COLUMN ingest_schedule TEXT NOT NULL DEFAULT '';
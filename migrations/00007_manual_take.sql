@@ -0,0 +1 @@
+ALTER TABLE configurations ADD COLUMN ingest_manual INTEGER NOT NULL DEFAULT 0;
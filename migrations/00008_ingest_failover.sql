@@ -0,0 +1,2 @@
+ALTER TABLE configurations ADD COLUMN ingest_backup_urls TEXT NOT NULL DEFAULT '';
+ALTER TABLE configurations ADD COLUMN ingest_failover_seconds REAL NOT NULL DEFAULT 5;
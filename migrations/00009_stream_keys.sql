@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS stream_keys (
+    id INTEGER PRIMARY KEY,
+    channel_id INTEGER NOT NULL DEFAULT 1,
+    label TEXT NOT NULL,
+    key TEXT NOT NULL,
+    expires_at INTEGER,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (channel_id) REFERENCES channels (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    UNIQUE (channel_id, key)
+);
+
+ALTER TABLE configurations ADD COLUMN ingest_on_publish TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,2 @@
+ALTER TABLE configurations ADD COLUMN ingest_record INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE configurations ADD COLUMN ingest_record_folder TEXT NOT NULL DEFAULT 'recordings';
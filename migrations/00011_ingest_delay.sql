@@ -0,0 +1 @@
+ALTER TABLE configurations ADD COLUMN ingest_delay REAL NOT NULL DEFAULT 0;
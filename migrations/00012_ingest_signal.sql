@@ -0,0 +1 @@
+ALTER TABLE configurations ADD COLUMN ingest_signal_failover REAL NOT NULL DEFAULT 0;
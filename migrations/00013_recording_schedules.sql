@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS recording_schedules (
+    id INTEGER PRIMARY KEY,
+    channel_id INTEGER NOT NULL DEFAULT 1,
+    label TEXT NOT NULL,
+    url TEXT NOT NULL,
+    start_time TEXT NOT NULL,
+    end_time TEXT NOT NULL,
+    folder TEXT NOT NULL DEFAULT 'recordings',
+    insert_at TEXT,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (channel_id) REFERENCES channels (id) ON UPDATE CASCADE ON DELETE CASCADE
+);
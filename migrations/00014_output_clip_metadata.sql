@@ -0,0 +1 @@
+ALTER TABLE outputs ADD COLUMN clip_metadata INTEGER NOT NULL DEFAULT 0;
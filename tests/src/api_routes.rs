@@ -7,7 +7,8 @@ use axum::{
     http::{Request, StatusCode},
     routing::{get, post},
 };
-use serde_json::json;
+use protect_axum::GrantsLayer;
+use serde_json::{Value, json};
 use sqlx::{Pool, Sqlite, sqlite::SqlitePoolOptions};
 use tokio::sync::{Mutex, RwLock};
 use tokio_util::sync::CancellationToken;
@@ -17,12 +18,14 @@ use ffplayout::{
     api::{
         auth::{decode_jwt, decode_refresh_jwt, login, logout, refresh},
         file_access::FileAccessState,
+        path::routes,
         state::AppState,
     },
     db::{
         handles, init_globales,
         models::{Role, User},
     },
+    extract,
     player::controller::{ChannelController, ChannelManager},
     sse::{SseAuthState, broadcast::Broadcaster},
     utils::{channels::delete_channel, config::PlayoutConfig, system::SystemStat},
@@ -73,6 +76,71 @@ async fn prepare_config() -> (PlayoutConfig, ChannelManager, Pool<Sqlite>) {
     (config, manager, pool)
 }
 
+/// All API routes with the manager of channel 1, and an access token of the
+/// admin.
+async fn api_app() -> (Router, String, Pool<Sqlite>) {
+    let (_, manager, pool) = prepare_config().await;
+    let controller = Arc::new(RwLock::new(ChannelController::new()));
+    controller.write().await.add(manager.clone());
+    let app_state = AppState {
+        auth_state: Arc::new(SseAuthState::default()),
+        broadcaster: Broadcaster::create(manager.system.clone()),
+        controller,
+        file_access: Arc::new(FileAccessState::default()),
+        mail_queues: Arc::new(Mutex::new(vec![])),
+        pool: pool.clone(),
+        shutdown: CancellationToken::new(),
+        system: manager.system.clone(),
+    };
+
+    // The settings are global; the first test to get here sets them.
+    let _ = init_globales(&pool).await;
+
+    let app = Router::new()
+        .merge(routes())
+        .with_state(app_state)
+        .layer(GrantsLayer::with_extractor(extract));
+
+    let payload = json!({"username": "admin", "password": "admin"});
+    let (status, tokens) = send(&app, "POST", "/auth/login", None, Some(payload)).await;
+    assert!(status.is_success());
+    let access = tokens["access"].as_str().unwrap().to_string();
+
+    (app, access, pool)
+}
+
+/// Sends a request and returns the status with the JSON body, or `Null` when
+/// the body is no JSON.
+async fn send(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    token: Option<&str>,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(token) = token {
+        request = request.header("authorization", format!("Bearer {token}"));
+    }
+    let body = match body {
+        Some(body) => {
+            request = request.header("content-type", "application/json");
+            Body::from(body.to_string())
+        }
+        None => Body::empty(),
+    };
+
+    let res = app
+        .clone()
+        .oneshot(request.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = res.status();
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+
+    (status, serde_json::from_slice(&body).unwrap_or_default())
+}
+
 #[tokio::test]
 async fn test_get() {
     let app = Router::new().route("/", get(StatusCode::OK));
@@ -99,7 +167,8 @@ async fn test_login() {
         system: manager.system.clone(),
     };
 
-    init_globales(&pool).await.unwrap();
+    // The settings are global; the first test to get here sets them.
+    let _ = init_globales(&pool).await;
 
     let app = Router::new()
         .route("/auth/login", post(login))
@@ -308,3 +377,175 @@ async fn deleting_channel_stops_and_removes_manager() {
     assert!(controller.read().await.get(manager.id).is_none());
     assert!(handles::select_channel(&pool, &manager.id).await.is_err());
 }
+
+#[tokio::test]
+async fn stream_keys_are_added_listed_and_deleted() {
+    let (app, token, _pool) = api_app().await;
+    let token = Some(token.as_str());
+
+    let (status, _) = send(&app, "GET", "/api/stream-keys/1", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, generated) = send(
+        &app,
+        "POST",
+        "/api/stream-keys/1",
+        token,
+        Some(json!({"label": "Reporter 1"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(generated["channel_id"], 1);
+    assert_eq!(generated["key"].as_str().unwrap().len(), 32);
+
+    let reporter = json!({"label": "Reporter 2", "key": "reporter-key-2"});
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/api/stream-keys/1",
+        token,
+        Some(reporter.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(&app, "POST", "/api/stream-keys/1", token, Some(reporter)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    for invalid in [
+        json!({"label": " ", "key": "reporter-key-3"}),
+        json!({"label": "Reporter 3", "key": "short"}),
+        json!({"label": "Reporter 3", "key": "reporter key 3"}),
+        json!({"label": "Reporter 3", "key": "reporter-key-3", "expires_at": 1}),
+    ] {
+        let (status, _) = send(&app, "POST", "/api/stream-keys/1", token, Some(invalid)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    let (status, keys) = send(&app, "GET", "/api/stream-keys/1", token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let labels: Vec<_> = keys
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|key| key["label"].as_str().unwrap())
+        .collect();
+    assert_eq!(labels, ["Reporter 1", "Reporter 2"]);
+
+    let uri = format!("/api/stream-keys/1/{}", generated["id"]);
+    let (status, _) = send(&app, "DELETE", &uri, token, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, keys) = send(&app, "GET", "/api/stream-keys/1", token, None).await;
+    assert_eq!(keys.as_array().unwrap().len(), 1);
+    assert_eq!(keys[0]["key"], "reporter-key-2");
+}
+
+#[tokio::test]
+async fn recording_schedules_are_added_validated_and_deleted() {
+    let (app, token, _pool) = api_app().await;
+    let token = Some(token.as_str());
+    let schedule = json!({
+        "label": "Partner News",
+        "url": "https://partner.example/live/index.m3u8",
+        "start_time": "20:00",
+        "end_time": "21:00",
+        "insert_at": "18:00",
+    });
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/api/recording-schedules/1",
+        None,
+        Some(schedule.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, added) = send(
+        &app,
+        "POST",
+        "/api/recording-schedules/1",
+        token,
+        Some(schedule.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(added["channel_id"], 1);
+    assert_eq!(added["folder"], "recordings");
+
+    for (field, invalid) in [
+        ("label", " "),
+        ("url", "/media/partner.mp4"),
+        ("start_time", "25:00"),
+        ("end_time", "20:00"),
+        ("folder", "../partner"),
+        ("insert_at", "noon"),
+    ] {
+        let mut schedule = schedule.clone();
+        schedule[field] = json!(invalid);
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/api/recording-schedules/1",
+            token,
+            Some(schedule),
+        )
+        .await;
+        assert_eq!(
+            status,
+            StatusCode::BAD_REQUEST,
+            "{field} {invalid} was taken"
+        );
+    }
+
+    let (status, schedules) = send(&app, "GET", "/api/recording-schedules/1", token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(schedules.as_array().unwrap().len(), 1);
+    assert_eq!(schedules[0]["label"], "Partner News");
+
+    let uri = format!("/api/recording-schedules/1/{}", added["id"]);
+    let (status, _) = send(&app, "DELETE", &uri, token, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, schedules) = send(&app, "GET", "/api/recording-schedules/1", token, None).await;
+    assert!(schedules.as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn users_cannot_start_a_ticker_from_a_url() {
+    let (app, _, pool) = api_app().await;
+    sqlx::query("UPDATE user SET role_id = 3 WHERE username = 'admin'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let payload = json!({"username": "admin", "password": "admin"});
+    let (_, tokens) = send(&app, "POST", "/auth/login", None, Some(payload)).await;
+    let token = tokens["access"].as_str();
+
+    for source in [
+        "http://127.0.0.1:8080/news.json",
+        "https://example.org/news.rss",
+    ] {
+        let (status, _) = send(
+            &app,
+            "PUT",
+            "/api/control/1/ticker",
+            token,
+            Some(json!({"source": source})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{source} was taken");
+    }
+
+    // Storage files stay open to users; this one is missing.
+    let (status, _) = send(
+        &app,
+        "PUT",
+        "/api/control/1/ticker",
+        token,
+        Some(json!({"source": "news-missing.txt"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}